@@ -1,4 +1,5 @@
-use fm_index::{FMIndex, RLFMIndex, Search, SearchIndex};
+use fm_index::converter::Converter;
+use fm_index::{DNAFMIndex, FMIndex, RLFMIndex, Search, SearchIndex, SmallFMIndex};
 
 use criterion::{criterion_group, criterion_main};
 use criterion::{AxisScale, BatchSize, BenchmarkId, Criterion, PlotConfiguration, Throughput};
@@ -11,6 +12,34 @@ fn prepare_fmindex(len: usize, prob: f64, m: usize) -> (impl SearchIndex<u8>, Ve
     (FMIndex::new(text, converter), patterns)
 }
 
+fn prepare_fmindex_batch(
+    len: usize,
+    prob: f64,
+    m: usize,
+) -> (FMIndex<u8, impl Converter<u8>>, Vec<String>) {
+    let (text, converter) = common::binary_text_set(len, prob);
+    let patterns = common::binary_patterns(m);
+    (FMIndex::new(text, converter), patterns)
+}
+
+fn prepare_fmindex_occ(len: usize, prob: f64, m: usize) -> (impl SearchIndex<u8>, Vec<String>) {
+    let (text, converter) = common::binary_text_set(len, prob);
+    let patterns = common::binary_patterns(m);
+    (FMIndex::new(text, converter).with_occ_table(64), patterns)
+}
+
+fn prepare_dnafmindex(len: usize, prob: f64, m: usize) -> (impl SearchIndex<u8>, Vec<String>) {
+    let (text, converter) = common::binary_text_set(len, prob);
+    let patterns = common::binary_patterns(m);
+    (DNAFMIndex::new(text, converter), patterns)
+}
+
+fn prepare_smallfmindex(len: usize, prob: f64, m: usize) -> (impl SearchIndex<u8>, Vec<String>) {
+    let (text, converter) = common::binary_text_set(len, prob);
+    let patterns = common::binary_patterns(m);
+    (SmallFMIndex::new(text, converter), patterns)
+}
+
 fn prepare_rlfmindex(len: usize, prob: f64, m: usize) -> (impl SearchIndex<u8>, Vec<String>) {
     let (text, converter) = common::binary_text_set(len, prob);
     let patterns = common::binary_patterns(m);
@@ -37,6 +66,54 @@ pub fn bench(c: &mut Criterion) {
             )
         });
 
+        group.bench_with_input(
+            BenchmarkId::new("FMIndexLockstep", prob),
+            prob,
+            |b, &prob| {
+                b.iter_batched(
+                    || prepare_fmindex_batch(n, prob, m),
+                    |(index, patterns)| index.count_many_lockstep(&patterns),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+
+        group.bench_with_input(BenchmarkId::new("FMIndexOcc", prob), prob, |b, &prob| {
+            b.iter_batched(
+                || prepare_fmindex_occ(n, prob, m),
+                |(index, patterns)| {
+                    for pattern in patterns {
+                        index.search(pattern).count();
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_with_input(BenchmarkId::new("DNAFMIndex", prob), prob, |b, &prob| {
+            b.iter_batched(
+                || prepare_dnafmindex(n, prob, m),
+                |(index, patterns)| {
+                    for pattern in patterns {
+                        index.search(pattern).count();
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_with_input(BenchmarkId::new("SmallFMIndex", prob), prob, |b, &prob| {
+            b.iter_batched(
+                || prepare_smallfmindex(n, prob, m),
+                |(index, patterns)| {
+                    for pattern in patterns {
+                        index.search(pattern).count();
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+
         group.bench_with_input(BenchmarkId::new("RLFMIndex", prob), prob, |b, &prob| {
             b.iter_batched(
                 || prepare_rlfmindex(n, prob, m),
@@ -0,0 +1,69 @@
+/*
+ * C API of the fm-index crate, built with the `capi` feature:
+ *
+ *     cargo build --release --features capi
+ *
+ * which produces the shared library libfm_index in target/release.
+ *
+ * An index is an opaque handle to an FM-index of a text of bytes with locate
+ * support. Any byte but 0 may occur in the text. Functions that create an
+ * index return NULL on failure, and functions that return an int return 0 on
+ * success and -1 on failure.
+ *
+ * An index is immutable once built, so a handle may be searched from several
+ * threads at once.
+ */
+#ifndef FM_INDEX_H
+#define FM_INDEX_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct FmIndex FmIndex;
+
+/* Build an index of the len bytes at text, which are copied. The suffix
+ * array is sampled at 1 in every 2^level entries. Returns NULL if the text
+ * is empty or holds a 0 byte other than a final terminator. */
+FmIndex *fm_index_new(const uint8_t *text, size_t len, uint32_t level);
+
+/* Free an index. Passing NULL does nothing. */
+void fm_index_free(FmIndex *index);
+
+/* Save an index to the file at path. */
+int fm_index_save(const FmIndex *index, const char *path);
+
+/* Load an index saved by fm_index_save from the file at path. */
+FmIndex *fm_index_load(const char *path);
+
+/* Save an index to a newly allocated buffer, storing its address in *data
+ * and its length in *len. Free it with fm_index_bytes_free. */
+int fm_index_save_bytes(const FmIndex *index, uint8_t **data, size_t *len);
+
+/* Free a buffer returned by fm_index_save_bytes. */
+void fm_index_bytes_free(uint8_t *data, size_t len);
+
+/* Load an index from the len bytes at data, which are copied. */
+FmIndex *fm_index_load_bytes(const uint8_t *data, size_t len);
+
+/* The number of bytes in the indexed text, including the terminator. */
+uint64_t fm_index_len(const FmIndex *index);
+
+/* The number of occurrences of the len bytes at pattern. */
+uint64_t fm_index_count(const FmIndex *index, const uint8_t *pattern, size_t len);
+
+/* Write the positions of the occurrences of the len bytes at pattern to out,
+ * which has room for capacity positions, in no particular order. Returns
+ * the number of occurrences; if it exceeds capacity, only the first
+ * capacity positions are written. */
+uint64_t fm_index_locate(const FmIndex *index, const uint8_t *pattern, size_t len,
+                         uint64_t *out, size_t capacity);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FM_INDEX_H */
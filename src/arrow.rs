@@ -0,0 +1,181 @@
+//! Building multi-text indexes of Arrow columns and Parquet files.
+use crate::converter::Converter;
+use crate::error::Error;
+use crate::frontend::{MultiTextFMIndex, MultiTextFMIndexWithLocate};
+
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, RecordBatch};
+use arrow_schema::{ArrowError, DataType};
+#[cfg(feature = "parquet")]
+use parquet::{
+    arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ProjectionMask},
+    errors::ParquetError,
+    file::reader::ChunkReader,
+};
+
+/// Builder for a multi-text index of a column of strings or binary values,
+/// with a text for each row.
+///
+/// The rows are numbered in the order they're pushed, across arrays,
+/// record batches and Parquet files, and the text of each row gets its row
+/// number as text id. Null values are indexed as empty texts, so they keep
+/// their row number. Empty rows at the end of the column hold no text and
+/// are left out, so `doc_count` of the index may be less than
+/// [`rows`](Self::rows).
+///
+/// The columns may be of type `Utf8`, `LargeUtf8`, `Utf8View`, `Binary`,
+/// `LargeBinary` or `BinaryView`. Values can't contain \0 bytes, which
+/// separate the texts.
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use arrow_array::{RecordBatch, StringArray};
+/// use fm_index::converter::IdConverter;
+/// use fm_index::ArrowColumnBuilder;
+///
+/// let titles = StringArray::from(vec![Some("The Hobbit"), None, Some("The Silmarillion")]);
+/// let batch = RecordBatch::try_from_iter([("title", Arc::new(titles) as _)]).unwrap();
+///
+/// let mut builder = ArrowColumnBuilder::with_converter(IdConverter::new::<u8>());
+/// builder.push_column(&batch, "title").unwrap();
+/// let index = builder.build_with_locate(2).unwrap();
+///
+/// let rows = index.search("The ").locate_in_docs();
+/// assert_eq!(rows.len(), 2);
+/// assert!(rows.contains(&(0, 0)));
+/// assert!(rows.contains(&(2, 0)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArrowColumnBuilder<C> {
+    converter: C,
+    text: Vec<u8>,
+    rows: u64,
+    // the length of the text up to the last row that isn't empty
+    len: usize,
+}
+
+impl<C: Converter<u8>> ArrowColumnBuilder<C> {
+    /// Create a builder for an index using `converter`.
+    pub fn with_converter(converter: C) -> Self {
+        ArrowColumnBuilder {
+            converter,
+            text: Vec::new(),
+            rows: 0,
+            len: 0,
+        }
+    }
+
+    /// The number of rows pushed so far.
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Add a row for each value of `array`.
+    ///
+    /// Returns an error if `array` isn't of a string or binary type, or
+    /// if a value contains a \0 byte, in which case no row is added.
+    pub fn push_array(&mut self, array: &dyn Array) -> Result<(), ArrowError> {
+        let start = (self.text.len(), self.rows, self.len);
+        let result = match array.data_type() {
+            DataType::Utf8 => self.push_values(array.as_string::<i32>().iter().map(bytes)),
+            DataType::LargeUtf8 => self.push_values(array.as_string::<i64>().iter().map(bytes)),
+            DataType::Utf8View => self.push_values(array.as_string_view().iter().map(bytes)),
+            DataType::Binary => self.push_values(array.as_binary::<i32>().iter()),
+            DataType::LargeBinary => self.push_values(array.as_binary::<i64>().iter()),
+            DataType::BinaryView => self.push_values(array.as_binary_view().iter()),
+            data_type => Err(ArrowError::InvalidArgumentError(format!(
+                "cannot index a column of type {}",
+                data_type
+            ))),
+        };
+        if result.is_err() {
+            (_, self.rows, self.len) = start;
+            self.text.truncate(start.0);
+        }
+        result
+    }
+
+    /// Add a row for each value of the column `name` of `batch`.
+    ///
+    /// See [`push_array`](Self::push_array).
+    pub fn push_column(&mut self, batch: &RecordBatch, name: &str) -> Result<(), ArrowError> {
+        let column = batch
+            .column_by_name(name)
+            .ok_or_else(|| ArrowError::SchemaError(format!("no column named {}", name)))?;
+        self.push_array(column)
+    }
+
+    /// Add a row for each value of the column `name` of the Parquet file
+    /// read from `reader`, such as a [`File`](std::fs::File).
+    ///
+    /// Only that column is read, one record batch at a time. This requires
+    /// the `parquet` feature.
+    #[cfg(feature = "parquet")]
+    pub fn push_parquet<R: ChunkReader + 'static>(
+        &mut self,
+        reader: R,
+        name: &str,
+    ) -> Result<(), ParquetError> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(reader)?;
+        let column = builder.schema().index_of(name)?;
+        let mask = ProjectionMask::roots(builder.parquet_schema(), [column]);
+        for batch in builder.with_projection(mask).build()? {
+            self.push_array(batch?.column(0))?;
+        }
+        Ok(())
+    }
+
+    fn push_values<'a>(
+        &mut self,
+        values: impl Iterator<Item = Option<&'a [u8]>>,
+    ) -> Result<(), ArrowError> {
+        for value in values {
+            let value = value.unwrap_or_default();
+            if value.contains(&0) {
+                return Err(ArrowError::InvalidArgumentError(format!(
+                    "row {} contains a \\0 byte",
+                    self.rows
+                )));
+            }
+            self.text.extend_from_slice(value);
+            self.text.push(0);
+            self.rows += 1;
+            if !value.is_empty() {
+                self.len = self.text.len();
+            }
+        }
+        Ok(())
+    }
+
+    fn into_text(mut self) -> (Vec<u8>, C) {
+        self.text.truncate(self.len);
+        (self.text, self.converter)
+    }
+
+    /// Build an index without locate support.
+    ///
+    /// Returns an error if all rows are empty, or a value has a byte
+    /// outside the converter's alphabet.
+    pub fn build(self) -> Result<MultiTextFMIndex<u8, C>, Error> {
+        let (text, converter) = self.into_text();
+        MultiTextFMIndex::try_new(text, converter)
+    }
+
+    /// Build an index with locate support, sampling the suffix array at
+    /// `level`.
+    ///
+    /// See [`MultiTextFMIndexWithLocate::new`] for the meaning of `level`,
+    /// and [`build`](Self::build) for the errors.
+    pub fn build_with_locate(
+        self,
+        level: usize,
+    ) -> Result<MultiTextFMIndexWithLocate<u8, C>, Error> {
+        let (text, converter) = self.into_text();
+        MultiTextFMIndexWithLocate::try_new(text, converter, level)
+    }
+}
+
+fn bytes(value: Option<&str>) -> Option<&[u8]> {
+    value.map(str::as_bytes)
+}
@@ -0,0 +1,683 @@
+//! Choosing an index implementation based on the text.
+use crate::backend::{HeapSize, SizeBreakdown};
+use crate::character::{check_level, check_text, prepare_text, Character, IntoText};
+use crate::converter::Converter;
+use crate::dyn_search::impl_dyn_search;
+use crate::error::Error;
+use crate::fm_index::bwt_symbols;
+use crate::frontend::{
+    DNAFMIndex, DNAFMIndexSearch, DNAFMIndexSearchWithLocate, DNAFMIndexWithLocate, FMIndex,
+    FMIndexSearch, FMIndexSearchWithLocate, FMIndexWithLocate, RLFMIndex, RLFMIndexSearch,
+    RLFMIndexSearchWithLocate, RLFMIndexWithLocate, Search, SearchIndex, SearchIndexWithLocate,
+    SearchWithLocate,
+};
+use crate::matches::Match;
+use crate::snapshot::{impl_owned_search, SearchSnapshot};
+use crate::stats::IndexStats;
+use crate::suffix_array::sais;
+use alloc::{boxed::Box, vec::Vec};
+
+// At most this many characters of the text are sampled, in windows spread
+// evenly over it.
+const SAMPLE_LEN: usize = 1 << 20;
+const SAMPLE_WINDOWS: usize = 16;
+// The run-length index is chosen if the BWT runs of the sample are at least
+// this long on average.
+const MIN_AVERAGE_RUN: usize = 8;
+
+/// The index implementation chosen for a text by [`AutoFMIndex`] and
+/// [`AutoFMIndexWithLocate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKind {
+    /// The plain FM-Index, [`FMIndex`].
+    FM,
+    /// The run-length FM-Index, [`RLFMIndex`], for repetitive texts.
+    RLFM,
+    /// The FM-Index for texts of at most four characters, [`DNAFMIndex`].
+    DNA,
+}
+
+impl IndexKind {
+    /// Choose an index implementation for `text`.
+    ///
+    /// If the converter's alphabet has at most four characters besides the
+    /// terminator, this is [`IndexKind::DNA`]. Otherwise the BWT of a sample
+    /// of the text is computed: if its runs are long on average, the text is
+    /// repetitive and [`IndexKind::RLFM`] is chosen, else [`IndexKind::FM`].
+    ///
+    /// The sample is the whole text if it has at most 2^20 characters, and
+    /// otherwise consists of windows spread over the text; repetitions
+    /// further apart than the windows are not noticed.
+    pub fn choose<T: Character, C: Converter<T>>(text: &[T], converter: &C) -> Self {
+        if converter.len() <= 5 {
+            return IndexKind::DNA;
+        }
+        let sample = sample_text(text);
+        if sample.is_empty() {
+            return IndexKind::FM;
+        }
+        let sample = prepare_text(sample.into(), converter);
+        let sa = sais::build_suffix_array(&sample, converter);
+        let bwt = bwt_symbols(&sample, &sa, converter);
+        let runs = 1 + bwt
+            .iter()
+            .zip(bwt.iter().skip(1))
+            .filter(|(a, b)| a != b)
+            .count();
+        if bwt.len() >= MIN_AVERAGE_RUN * runs {
+            IndexKind::RLFM
+        } else {
+            IndexKind::FM
+        }
+    }
+}
+
+// The sample of `text`, without the terminators ending it.
+fn sample_text<T: Character>(text: &[T]) -> Vec<T> {
+    let mut sample = if text.len() <= SAMPLE_LEN {
+        text.to_vec()
+    } else {
+        let window = SAMPLE_LEN / SAMPLE_WINDOWS;
+        let stride = text.len() / SAMPLE_WINDOWS;
+        (0..SAMPLE_WINDOWS)
+            .flat_map(|w| &text[w * stride..w * stride + window])
+            .copied()
+            .collect()
+    };
+    // a window can end in terminators too, and only one may end a text
+    while sample.last().is_some_and(|c| c.is_zero()) {
+        sample.pop();
+    }
+    sample
+}
+
+/// FMIndex, count only, using the implementation that suits the text.
+///
+/// See [`IndexKind::choose`] for how the implementation is chosen. Each
+/// variant holds the index it wraps, so it can be matched on to use methods
+/// specific to it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AutoFMIndex<T: Character, C: Converter<T>> {
+    /// A plain FM-Index.
+    FM(FMIndex<T, C>),
+    /// A run-length FM-Index.
+    RLFM(RLFMIndex<T, C>),
+    /// An FM-Index for texts of at most four characters.
+    DNA(DNAFMIndex<T, C>),
+}
+
+/// Search result for AutoFMIndex, count only.
+#[derive(Clone, Debug)]
+pub enum AutoFMIndexSearch<'a, T: Character, C: Converter<T>> {
+    /// A search result of a plain FM-Index.
+    FM(FMIndexSearch<'a, T, C>),
+    /// A search result of a run-length FM-Index.
+    RLFM(RLFMIndexSearch<'a, T, C>),
+    /// A search result of an FM-Index for texts of at most four characters.
+    DNA(DNAFMIndexSearch<'a, T, C>),
+}
+
+/// FMIndex with locate support, using the implementation that suits the
+/// text.
+///
+/// See [`IndexKind::choose`] for how the implementation is chosen.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AutoFMIndexWithLocate<T: Character, C: Converter<T>> {
+    /// A plain FM-Index.
+    FM(FMIndexWithLocate<T, C>),
+    /// A run-length FM-Index.
+    RLFM(RLFMIndexWithLocate<T, C>),
+    /// An FM-Index for texts of at most four characters.
+    DNA(DNAFMIndexWithLocate<T, C>),
+}
+
+/// Search result for AutoFMIndex with locate support.
+#[derive(Clone, Debug)]
+pub enum AutoFMIndexSearchWithLocate<'a, T: Character, C: Converter<T>> {
+    /// A search result of a plain FM-Index.
+    FM(FMIndexSearchWithLocate<'a, T, C>),
+    /// A search result of a run-length FM-Index.
+    RLFM(RLFMIndexSearchWithLocate<'a, T, C>),
+    /// A search result of an FM-Index for texts of at most four characters.
+    DNA(DNAFMIndexSearchWithLocate<'a, T, C>),
+}
+
+// Evaluate `$e` with `$x` bound to the index or search result of whichever
+// variant `$v` is.
+macro_rules! dispatch {
+    ($v:expr, $x:ident => $e:expr) => {
+        match $v {
+            Self::FM($x) => $e,
+            Self::RLFM($x) => $e,
+            Self::DNA($x) => $e,
+        }
+    };
+}
+
+macro_rules! impl_auto_index {
+    ($t:ident, $st:ident, $kind:ident) => {
+        impl_owned_search!($t<T, C>, $st<'_, T, C>);
+        impl_dyn_search!($kind, $t<T, C>, $st<'_, T, C>);
+        impl<T: Character, C: Converter<T>> $t<T, C> {
+            /// The implementation that was chosen.
+            pub fn kind(&self) -> IndexKind {
+                match self {
+                    Self::FM(_) => IndexKind::FM,
+                    Self::RLFM(_) => IndexKind::RLFM,
+                    Self::DNA(_) => IndexKind::DNA,
+                }
+            }
+
+            /// Search for a pattern in the text.
+            pub fn search<K>(&self, pattern: K) -> $st<'_, T, C>
+            where
+                K: AsRef<[T]>,
+            {
+                match self {
+                    Self::FM(index) => $st::FM(index.search(pattern)),
+                    Self::RLFM(index) => $st::RLFM(index.search(pattern)),
+                    Self::DNA(index) => $st::DNA(index.search(pattern)),
+                }
+            }
+
+            /// Search for a pattern in the text like
+            /// [`search`](Self::search), but return an [`Error`] instead of
+            /// panicking if the pattern has a character outside the
+            /// converter's alphabet.
+            pub fn try_search<K>(&self, pattern: K) -> Result<$st<'_, T, C>, Error>
+            where
+                K: AsRef<[T]>,
+            {
+                match self {
+                    Self::FM(index) => index.try_search(pattern).map($st::FM),
+                    Self::RLFM(index) => index.try_search(pattern).map($st::RLFM),
+                    Self::DNA(index) => index.try_search(pattern).map($st::DNA),
+                }
+            }
+
+            /// Resume a search result from a [`SearchSnapshot`] taken on
+            /// this index, without searching again.
+            ///
+            /// # Panics
+            ///
+            /// Panics if the snapshot's interval is out of range for this
+            /// index.
+            pub fn resume(&self, snapshot: &SearchSnapshot<T>) -> $st<'_, T, C> {
+                match self {
+                    Self::FM(index) => $st::FM(index.resume(snapshot)),
+                    Self::RLFM(index) => $st::RLFM(index.resume(snapshot)),
+                    Self::DNA(index) => $st::DNA(index.resume(snapshot)),
+                }
+            }
+
+            /// The size of the text in the index, including the terminator.
+            pub fn len(&self) -> u64 {
+                dispatch!(self, index => index.len())
+            }
+
+            /// The length of the text in the index, without the terminator.
+            ///
+            /// Positions returned by locate queries are less than this.
+            pub fn text_len(&self) -> u64 {
+                dispatch!(self, index => index.text_len())
+            }
+
+            /// The Burrows-Wheeler transform of the text.
+            ///
+            /// This includes the terminator character, so it has the same
+            /// length as [`len`](Self::len).
+            pub fn bwt(&self) -> Vec<T> {
+                dispatch!(self, index => index.bwt())
+            }
+            /// Statistics about the text and its alphabet.
+            pub fn stats(&self) -> IndexStats<T> {
+                dispatch!(self, index => index.stats())
+            }
+
+            /// Count the number of occurrences of each of `patterns`.
+            pub fn count_many<K>(&self, patterns: &[K]) -> Vec<u64>
+            where
+                K: AsRef<[T]>,
+            {
+                dispatch!(self, index => index.count_many(patterns))
+            }
+
+            /// Count the number of occurrences of each of `patterns`,
+            /// advancing the searches for all of them one character at a
+            /// time.
+            ///
+            /// The result is the same as that of
+            /// [`count_many`](Self::count_many). Before each step the
+            /// pending searches are sorted by their position in the BWT, so
+            /// that consecutive lookups hit nearby memory. For batches of
+            /// many short patterns against an index much larger than the
+            /// CPU cache this is faster than searching for the patterns one
+            /// after another, at the cost of memory for the pending
+            /// searches.
+            pub fn count_many_lockstep<K>(&self, patterns: &[K]) -> Vec<u64>
+            where
+                K: AsRef<[T]>,
+            {
+                dispatch!(self, index => index.count_many_lockstep(patterns))
+            }
+
+            /// Whether each of `patterns` occurs at least `n` times.
+            ///
+            /// The search for a pattern stops as soon as fewer than `n` of
+            /// its occurrences are left, skipping its remaining characters.
+            pub fn count_many_at_least<K>(&self, patterns: &[K], n: u64) -> Vec<bool>
+            where
+                K: AsRef<[T]>,
+            {
+                dispatch!(self, index => index.count_many_at_least(patterns, n))
+            }
+        }
+
+        #[cfg(feature = "rayon")]
+        impl<T, C> $t<T, C>
+        where
+            T: Character + Send + Sync,
+            C: Converter<T> + Send + Sync,
+        {
+            /// Count the number of occurrences of each of `patterns`,
+            /// distributing the patterns over the rayon thread pool.
+            ///
+            /// The result is the same as that of
+            /// [`count_many`](Self::count_many).
+            pub fn count_many_parallel<K>(&self, patterns: &[K]) -> Vec<u64>
+            where
+                K: AsRef<[T]> + Sync,
+            {
+                dispatch!(self, index => index.count_many_parallel(patterns))
+            }
+        }
+
+        impl<T: Character, C: Converter<T>> SearchIndex<T> for $t<T, C> {
+            fn search<K>(&self, pattern: K) -> impl Search<'_, T>
+            where
+                K: AsRef<[T]>,
+            {
+                $t::search(self, pattern)
+            }
+
+            fn len(&self) -> u64 {
+                $t::len(self)
+            }
+        }
+
+        impl<T: Character, C: Converter<T>> HeapSize for $t<T, C> {
+            fn heap_size(&self) -> usize {
+                dispatch!(self, index => index.heap_size())
+            }
+
+            fn size_breakdown(&self) -> SizeBreakdown {
+                dispatch!(self, index => index.size_breakdown())
+            }
+        }
+
+        impl<'a, T: Character, C: Converter<T>> Search<'a, T> for $st<'a, T, C> {
+            fn search<K>(&self, pattern: K) -> Self
+            where
+                K: AsRef<[T]>,
+            {
+                match self {
+                    Self::FM(s) => Self::FM(s.search(pattern)),
+                    Self::RLFM(s) => Self::RLFM(s.search(pattern)),
+                    Self::DNA(s) => Self::DNA(s.search(pattern)),
+                }
+            }
+
+            fn pattern(&self) -> &[T] {
+                dispatch!(self, s => s.pattern())
+            }
+
+            fn range(&self) -> (u64, u64) {
+                dispatch!(self, s => s.range())
+            }
+
+            fn index_len(&self) -> u64 {
+                dispatch!(self, s => s.index_len())
+            }
+
+            fn count(&self) -> u64 {
+                dispatch!(self, s => s.count())
+            }
+
+            fn count_at_least(&self, n: u64) -> bool {
+                dispatch!(self, s => s.count_at_least(n))
+            }
+
+            fn extensions(&self) -> Vec<(T, u64)> {
+                dispatch!(self, s => s.extensions())
+            }
+
+            fn left_extensions(&self) -> Vec<(T, u64)> {
+                dispatch!(self, s => s.left_extensions())
+            }
+
+            fn iter_backward(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
+                dispatch!(self, s => Box::new(s.iter_backward(i)) as Box<dyn Iterator<Item = T> + 'a>)
+            }
+
+            fn iter_forward(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
+                dispatch!(self, s => Box::new(s.iter_forward(i)) as Box<dyn Iterator<Item = T> + 'a>)
+            }
+
+            fn iter_backward_in_text(&'a self, i: u64) -> impl Iterator<Item = T> + 'a
+            where
+                T: 'a,
+            {
+                dispatch!(self, s => Box::new(s.iter_backward_in_text(i)) as Box<dyn Iterator<Item = T> + 'a>)
+            }
+
+            fn iter_forward_in_text(&'a self, i: u64) -> impl Iterator<Item = T> + 'a
+            where
+                T: 'a,
+            {
+                dispatch!(self, s => Box::new(s.iter_forward_in_text(i)) as Box<dyn Iterator<Item = T> + 'a>)
+            }
+        }
+        // inherent
+        impl<'a, T: Character, C: Converter<T>> $st<'a, T, C> {
+            /// Search in the current search result, refining it.
+            ///
+            /// This adds a prefix `pattern` to the existing pattern, and
+            /// looks for those expanded patterns in the text.
+            pub fn search<K>(&self, pattern: K) -> Self
+            where
+                K: AsRef<[T]>,
+            {
+                Search::search(self, pattern)
+            }
+
+            /// Search in the current search result like
+            /// [`search`](Self::search), but return an [`Error`] instead of
+            /// panicking if the pattern has a character outside the
+            /// converter's alphabet.
+            pub fn try_search<K>(&self, pattern: K) -> Result<Self, Error>
+            where
+                K: AsRef<[T]>,
+            {
+                match self {
+                    Self::FM(s) => s.try_search(pattern).map(Self::FM),
+                    Self::RLFM(s) => s.try_search(pattern).map(Self::RLFM),
+                    Self::DNA(s) => s.try_search(pattern).map(Self::DNA),
+                }
+            }
+
+            /// Extend the pattern by prepending the character `c`, a single
+            /// step of backward search.
+            pub fn extend_backward(&self, c: T) -> Self {
+                Search::extend_backward(self, c)
+            }
+
+            /// The pattern searched for, including the prefixes added by
+            /// refining searches.
+            pub fn pattern(&self) -> &[T] {
+                Search::pattern(self)
+            }
+
+            /// The range `[s, e)` of the suffix array matched by the pattern.
+            ///
+            /// Its width is the number of occurrences, counting those in
+            /// texts that were deleted from a multi-text index.
+            pub fn range(&self) -> (u64, u64) {
+                Search::range(self)
+            }
+
+            /// The size of the text in the index searched, including the
+            /// terminator.
+            pub fn index_len(&self) -> u64 {
+                Search::index_len(self)
+            }
+
+            /// Count the number of occurrences.
+            pub fn count(&self) -> u64 {
+                Search::count(self)
+            }
+
+            /// Whether the pattern occurs at least `n` times.
+            pub fn count_at_least(&self, n: u64) -> bool {
+                Search::count_at_least(self, n)
+            }
+
+            /// Whether the pattern occurs in the text at all.
+            pub fn is_present(&self) -> bool {
+                Search::is_present(self)
+            }
+
+            /// A snapshot of this search result, which can be serialized and
+            /// turned back into a search result with `resume` on the index.
+            pub fn snapshot(&self) -> SearchSnapshot<T> {
+                dispatch!(self, s => s.snapshot())
+            }
+
+            /// List the characters that can follow the pattern in the text,
+            /// each with the number of occurrences of the pattern extended
+            /// by it.
+            pub fn extensions(&self) -> Vec<(T, u64)> {
+                Search::extensions(self)
+            }
+
+            /// List the characters that can precede the pattern in the text,
+            /// each with the number of occurrences of the pattern extended
+            /// by it.
+            pub fn left_extensions(&self) -> Vec<(T, u64)> {
+                Search::left_extensions(self)
+            }
+
+            /// Get an iterator that goes backwards through the text, producing
+            /// [`Character`].
+            pub fn iter_backward(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
+                Search::iter_backward(self, i)
+            }
+
+            /// Get an iterator that goes forwards through the text, producing
+            /// [`Character`].
+            pub fn iter_forward(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
+                Search::iter_forward(self, i)
+            }
+
+            /// Get an iterator that goes backwards through the text from
+            /// occurrence `i`, ending at the start of the text instead of
+            /// wrapping around.
+            ///
+            /// In a multi-text index it ends at the start of the text
+            /// containing the occurrence.
+            pub fn iter_backward_in_text(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
+                Search::iter_backward_in_text(self, i)
+            }
+
+            /// Get an iterator that goes forwards through the text from
+            /// occurrence `i`, ending at the terminator instead of wrapping
+            /// around.
+            ///
+            /// In a multi-text index it ends at the end of the text
+            /// containing the occurrence.
+            pub fn iter_forward_in_text(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
+                Search::iter_forward_in_text(self, i)
+            }
+
+            /// Get an iterator that goes backwards through the text from
+            /// occurrence `i` like [`iter_backward`](Self::iter_backward),
+            /// but return an [`Error`] if `i` isn't less than the number of
+            /// occurrences.
+            pub fn try_iter_backward(
+                &'a self,
+                i: u64,
+            ) -> Result<impl Iterator<Item = T> + 'a, Error> {
+                self.check_offset(i)?;
+                Ok(Search::iter_backward(self, i))
+            }
+
+            /// Get an iterator that goes forwards through the text from
+            /// occurrence `i` like [`iter_forward`](Self::iter_forward), but
+            /// return an [`Error`] if `i` is out of range.
+            pub fn try_iter_forward(
+                &'a self,
+                i: u64,
+            ) -> Result<impl Iterator<Item = T> + 'a, Error> {
+                self.check_offset(i)?;
+                Ok(Search::iter_forward(self, i))
+            }
+
+            fn check_offset(&self, i: u64) -> Result<(), Error> {
+                let (s, e) = Search::range(self);
+                if i < e - s {
+                    Ok(())
+                } else {
+                    Err(Error::OffsetOutOfRange {
+                        offset: i,
+                        len: e - s,
+                    })
+                }
+            }
+        }
+    };
+}
+
+impl_auto_index!(AutoFMIndex, AutoFMIndexSearch, count);
+impl_auto_index!(AutoFMIndexWithLocate, AutoFMIndexSearchWithLocate, locate);
+
+impl<T: Character, C: Converter<T>> AutoFMIndex<T, C> {
+    /// Create a new index without locate support, choosing the
+    /// implementation with [`IndexKind::choose`].
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C) -> Self {
+        let text = text.into_text();
+        match IndexKind::choose(&text, &converter) {
+            IndexKind::FM => AutoFMIndex::FM(FMIndex::new(text, converter)),
+            IndexKind::RLFM => AutoFMIndex::RLFM(RLFMIndex::new(text, converter)),
+            IndexKind::DNA => AutoFMIndex::DNA(DNAFMIndex::new(text, converter)),
+        }
+    }
+
+    /// Create a new index like [`new`](Self::new), but return an [`Error`]
+    /// instead of panicking if the text can't be indexed.
+    pub fn try_new<'t>(text: impl IntoText<'t, T>, converter: C) -> Result<Self, Error> {
+        let text = text.into_text();
+        check_text(&text, &converter)?;
+        Ok(Self::new(text, converter))
+    }
+}
+
+impl<T: Character, C: Converter<T>> AutoFMIndexWithLocate<T, C> {
+    /// Create a new index with locate support, choosing the implementation
+    /// with [`IndexKind::choose`].
+    ///
+    /// The level argument controls the sampling rate used, as for
+    /// [`FMIndexWithLocate::new`].
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        let text = text.into_text();
+        match IndexKind::choose(&text, &converter) {
+            IndexKind::FM => {
+                AutoFMIndexWithLocate::FM(FMIndexWithLocate::new(text, converter, level))
+            }
+            IndexKind::RLFM => {
+                AutoFMIndexWithLocate::RLFM(RLFMIndexWithLocate::new(text, converter, level))
+            }
+            IndexKind::DNA => {
+                AutoFMIndexWithLocate::DNA(DNAFMIndexWithLocate::new(text, converter, level))
+            }
+        }
+    }
+
+    /// Create a new index like [`new`](Self::new), but return an [`Error`]
+    /// instead of panicking if the text can't be indexed.
+    pub fn try_new<'t>(
+        text: impl IntoText<'t, T>,
+        converter: C,
+        level: usize,
+    ) -> Result<Self, Error> {
+        let text = text.into_text();
+        check_text(&text, &converter)?;
+        check_level(&text, level)?;
+        Ok(Self::new(text, converter, level))
+    }
+
+    /// List the positions of all occurrences of a pattern, or return an
+    /// [`Error`] if the pattern has a character outside the converter's
+    /// alphabet.
+    pub fn try_locate<K>(&self, pattern: K) -> Result<Vec<u64>, Error>
+    where
+        K: AsRef<[T]>,
+    {
+        Ok(self.try_search(pattern)?.locate())
+    }
+}
+
+impl<T: Character, C: Converter<T>> SearchIndexWithLocate<T> for AutoFMIndexWithLocate<T, C> {
+    fn search<K>(&self, pattern: K) -> impl SearchWithLocate<'_, T>
+    where
+        K: AsRef<[T]>,
+    {
+        AutoFMIndexWithLocate::search(self, pattern)
+    }
+}
+
+impl<'a, T: Character, C: Converter<T>> SearchWithLocate<'a, T>
+    for AutoFMIndexSearchWithLocate<'a, T, C>
+{
+    fn locate(&self) -> Vec<u64> {
+        dispatch!(self, s => s.locate())
+    }
+
+    fn fold_positions<A, F>(&self, init: A, f: F) -> A
+    where
+        F: FnMut(A, u64) -> A,
+    {
+        dispatch!(self, s => s.fold_positions(init, f))
+    }
+
+    fn matches(&'a self) -> impl Iterator<Item = Match<'a, T, Self>> + 'a {
+        dispatch!(self, s => Box::new(s.matches().map(move |m| m.with_search(self)))
+            as Box<dyn Iterator<Item = Match<'a, T, Self>> + 'a>)
+    }
+}
+// inherent
+impl<'a, T: Character, C: Converter<T>> AutoFMIndexSearchWithLocate<'a, T, C> {
+    /// List the position of all occurrences.
+    ///
+    /// Positions are offsets in the text, from 0 up to the `text_len` of
+    /// the index.
+    pub fn locate(&self) -> Vec<u64> {
+        SearchWithLocate::locate(self)
+    }
+
+    /// Iterate over all occurrences as [`Match`] objects.
+    pub fn matches(&'a self) -> impl Iterator<Item = Match<'a, T, Self>> + 'a {
+        SearchWithLocate::matches(self)
+    }
+
+    /// Fold `f` over the positions of all occurrences, in no particular
+    /// order, without collecting them in a vector first.
+    pub fn fold_positions<A, F>(&self, init: A, f: F) -> A
+    where
+        F: FnMut(A, u64) -> A,
+    {
+        SearchWithLocate::fold_positions(self, init, f)
+    }
+
+    /// Call `f` with the position of every occurrence, in no particular
+    /// order, without collecting the positions in a vector first.
+    pub fn for_each_position<F>(&self, f: F)
+    where
+        F: FnMut(u64),
+    {
+        SearchWithLocate::for_each_position(self, f)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T, C> AutoFMIndexSearchWithLocate<'_, T, C>
+where
+    T: Character + Send + Sync,
+    C: Converter<T> + Send + Sync,
+{
+    /// List the position of all occurrences, walking the LF-mapping for
+    /// each of them on the rayon thread pool.
+    ///
+    /// The result is the same as that of [`locate`](Self::locate).
+    pub fn locate_parallel(&self) -> Vec<u64> {
+        dispatch!(self, s => s.locate_parallel())
+    }
+}
@@ -1,5 +1,6 @@
 use crate::character::Character;
 use crate::converter::Converter;
+use alloc::vec::Vec;
 
 /// Trait for an FM-Index backend implementation
 pub(crate) trait SearchIndexBackend: Sized {
@@ -15,10 +16,39 @@ pub(crate) trait SearchIndexBackend: Sized {
 
     fn lf_map2(&self, c: Self::T, i: u64) -> u64;
 
+    /// Narrow down the interval `[s, e)` of the suffix array by prepending
+    /// `pattern` to the suffixes in it, character by character.
+    ///
+    /// This stops early once the interval holds fewer than `min` suffixes,
+    /// leaving an interval whose size only bounds the number of
+    /// occurrences from above.
+    /// Pass 1 to stop only when it is empty.
+    ///
+    /// The characters are in the original alphabet. Backends override this
+    /// to hoist work out of the loop.
+    fn search_interval(&self, pattern: &[Self::T], s: u64, e: u64, min: u64) -> (u64, u64) {
+        let mut s = s;
+        let mut e = e;
+        for &c in pattern.iter().rev() {
+            s = self.lf_map2(c, s);
+            e = self.lf_map2(c, e);
+            if e - s < min {
+                break;
+            }
+        }
+        (s, e)
+    }
+
     fn get_f(&self, i: u64) -> Self::T;
 
     fn fl_map(&self, i: u64) -> u64;
 
+    /// List the distinct non-terminator characters in `L[s..e)` together
+    /// with their number of occurrences, ordered by character.
+    ///
+    /// The characters are in the converted alphabet.
+    fn interval_symbols(&self, s: u64, e: u64) -> Vec<(Self::T, u64)>;
+
     /// The size of the text in the index
     ///
     /// Note that this includes an ending \0 (terminator) character
@@ -27,6 +57,23 @@ pub(crate) trait SearchIndexBackend: Sized {
 
     /// Get the converter for this index.
     fn get_converter(&self) -> &Self::C;
+
+    /// The id of the text containing position `pos`, for indexes that
+    /// hold multiple texts.
+    fn doc_id(&self, _pos: u64) -> Option<u64> {
+        None
+    }
+
+    /// Whether position `pos` lies in a text that was marked as deleted.
+    fn is_deleted(&self, _pos: u64) -> bool {
+        false
+    }
+
+    /// The number of suffixes in `[s, e)` of the suffix array that do not
+    /// start in a text marked as deleted.
+    fn live_count(&self, s: u64, e: u64) -> u64 {
+        e - s
+    }
 }
 
 /// Access the heap size of the structure.
@@ -36,9 +83,142 @@ pub(crate) trait SearchIndexBackend: Sized {
 pub trait HeapSize {
     /// The size on the heap of this structure, in bytes.
     fn heap_size(&self) -> usize;
+
+    /// The size on the heap of each component of this structure.
+    ///
+    /// The sizes add up to [`heap_size`](Self::heap_size). Structures that
+    /// aren't made of distinct components report a single `"other"`
+    /// component.
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new().add("other", self.heap_size())
+    }
+}
+
+/// The heap size of an index broken down by component, in bytes.
+///
+/// This shows which part of an index to shrink: the suffix array samples
+/// shrink with a higher sampling level, while the BWT depends on the
+/// backend. The components an index reports depend on its type:
+///
+/// - `"bwt"`: the Burrows-Wheeler transform, or its run heads for the
+///   run-length encoded indexes
+/// - `"runs"`: the run lengths of the run-length encoded indexes
+/// - `"psi"`: the Ψ function of the compressed suffix array
+/// - `"cs"`: the table of the number of smaller characters
+/// - `"suffix_array"`: the suffix array samples
+/// - `"documents"`: the document boundaries and ids of multi-text indexes
+/// - `"metadata"`: the document metadata of multi-text indexes
+/// - `"deleted"`: the set of deleted documents
+/// - `"kmers"`: the k-mer table
+/// - `"occ"`: the occurrence checkpoint table
+/// - `"query_cache"`: the query cache
+/// - `"query_records"`: the queries recorded by an instrumented index
+/// - `"cache"`: the page cache of on-disk indexes
+/// - `"offsets"`: the shard offsets of a sharded index
+///
+/// Components that are absent, such as the suffix array samples of an
+/// index without locate support, aren't listed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeBreakdown {
+    components: Vec<(&'static str, usize)>,
+}
+
+impl SizeBreakdown {
+    pub(crate) fn new() -> Self {
+        SizeBreakdown::default()
+    }
+
+    // Add `size` to component `name`.
+    pub(crate) fn add(mut self, name: &'static str, size: usize) -> Self {
+        match self.components.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, total)) => *total += size,
+            None => self.components.push((name, size)),
+        }
+        self
+    }
+
+    // Add the components of `other` to those of this breakdown.
+    pub(crate) fn merge(self, other: SizeBreakdown) -> Self {
+        other
+            .components
+            .into_iter()
+            .fold(self, |breakdown, (name, size)| breakdown.add(name, size))
+    }
+
+    /// The components with their sizes, in a fixed order per index type.
+    pub fn components(&self) -> &[(&'static str, usize)] {
+        &self.components
+    }
+
+    /// The size of component `name`, if the index has it.
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.components
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|&(_, size)| size)
+    }
+
+    /// The sum of the sizes of all components.
+    pub fn total(&self) -> usize {
+        self.components.iter().map(|&(_, size)| size).sum()
+    }
+}
+
+impl core::fmt::Display for SizeBreakdown {
+    // one line per component with its size and its share of the total
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let total = self.total().max(1) as f64;
+        for &(name, size) in &self.components {
+            writeln!(
+                f,
+                "{:<12} {:>12} bytes {:>5.1}%",
+                name,
+                size,
+                100.0 * size as f64 / total
+            )?;
+        }
+        write!(f, "{:<12} {:>12} bytes", "total", self.total())
+    }
 }
 
 /// A trait for an index that supports locate queries.
-pub(crate) trait HasPosition {
-    fn get_sa(&self, i: u64) -> u64;
+pub(crate) trait HasPosition: SearchIndexBackend {
+    fn get_sa(&self, i: u64) -> u64 {
+        self.get_sa_with_steps(i).0
+    }
+
+    /// Get `SA[i]` together with the number of steps taken to reach a
+    /// sampled position of the suffix array.
+    fn get_sa_with_steps(&self, i: u64) -> (u64, u64);
+
+    /// Get the nearest sampled text position at or after `p`, together
+    /// with its position in the suffix array.
+    fn get_isa_sample(&self, p: u64) -> (u64, u64);
+
+    /// List `SA[s..e)`, where `[s, e)` is the interval of the suffix array
+    /// matching `pattern`.
+    fn get_sa_range(&self, s: u64, e: u64, _pattern: &[Self::T]) -> Vec<u64> {
+        (s..e).map(|i| self.get_sa(i)).collect()
+    }
+
+    /// Fold `f` over `SA[s..e)`, in no particular order, without collecting
+    /// the positions, where `[s, e)` is the interval of the suffix array
+    /// matching `pattern`.
+    fn fold_sa<A, F>(&self, s: u64, e: u64, _pattern: &[Self::T], init: A, mut f: F) -> A
+    where
+        F: FnMut(A, u64) -> A,
+    {
+        (s..e).fold(init, |acc, i| f(acc, self.get_sa(i)))
+    }
+
+    /// Like [`get_sa_range`](Self::get_sa_range), resolving the positions
+    /// on the rayon thread pool.
+    #[cfg(feature = "rayon")]
+    fn get_sa_range_parallel(&self, s: u64, e: u64, _pattern: &[Self::T]) -> Vec<u64>
+    where
+        Self: Sync,
+    {
+        use rayon::prelude::*;
+        (s..e).into_par_iter().map(|i| self.get_sa(i)).collect()
+    }
 }
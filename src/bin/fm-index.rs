@@ -0,0 +1,198 @@
+//! A command line tool to build indexes of files and search them.
+//!
+//! ```text
+//! fm-index build corpus.txt -o corpus.fmi --level 3
+//! fm-index count corpus.fmi dolor amet
+//! fm-index locate corpus.fmi dolor --context 10
+//! fm-index extract corpus.fmi 100 140
+//! fm-index stats corpus.fmi
+//! ```
+//!
+//! Indexes are of the bytes of a file, with locate support, saved in the
+//! format of [`FMIndexWithLocate::save`].
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use fm_index::converter::IdConverter;
+use fm_index::{FMIndexWithLocate, HeapSize, Highlighter, IndexInfo, IndexStats};
+
+type Index = FMIndexWithLocate<u8, IdConverter>;
+
+#[derive(Parser)]
+#[command(
+    name = "fm-index",
+    version,
+    about = "Build FM-indexes of files and search them"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Build an index of the bytes of a file.
+    Build {
+        /// The file to index.
+        text: PathBuf,
+        /// Where to save the index.
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Sample one in every 2^LEVEL suffix array entries. Higher levels
+        /// make the index smaller and locating slower.
+        #[arg(short, long, default_value_t = 2)]
+        level: usize,
+    },
+    /// Count the occurrences of each pattern, one count per line.
+    Count {
+        /// The index file.
+        index: PathBuf,
+        /// The patterns to count.
+        #[arg(required = true)]
+        patterns: Vec<String>,
+    },
+    /// List the positions of the occurrences of a pattern, in order.
+    Locate {
+        /// The index file.
+        index: PathBuf,
+        /// The pattern to locate.
+        pattern: String,
+        /// Also show this many bytes of context around each occurrence.
+        #[arg(short = 'C', long)]
+        context: Option<usize>,
+        /// Highlight the occurrences with colors rather than brackets.
+        #[arg(long, requires = "context")]
+        color: bool,
+    },
+    /// Write the bytes of the text from START up to END.
+    Extract {
+        /// The index file.
+        index: PathBuf,
+        start: u64,
+        end: u64,
+    },
+    /// Show information about an index and statistics about its text.
+    Stats {
+        /// The index file.
+        index: PathBuf,
+    },
+}
+
+fn load(path: &Path) -> Result<Index, Box<dyn Error>> {
+    Index::load_file(path).map_err(|err| format!("{}: {}", path.display(), err).into())
+}
+
+fn run(command: Command) -> Result<(), Box<dyn Error>> {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    match command {
+        Command::Build {
+            text,
+            output,
+            level,
+        } => {
+            let text = fs::read(&text).map_err(|err| format!("{}: {}", text.display(), err))?;
+            let index = Index::try_new(text, IdConverter::new::<u8>(), level)?;
+            let mut writer = BufWriter::new(File::create(&output)?);
+            index.save(&mut writer)?;
+            writer.flush()?;
+        }
+        Command::Count { index, patterns } => {
+            let index = load(&index)?;
+            for pattern in patterns {
+                writeln!(out, "{}", index.search(pattern).count())?;
+            }
+        }
+        Command::Locate {
+            index,
+            pattern,
+            context,
+            color,
+        } => {
+            let index = load(&index)?;
+            let search = index.search(pattern);
+            match context {
+                None => {
+                    let mut positions = search.locate();
+                    positions.sort_unstable();
+                    for position in positions {
+                        writeln!(out, "{}", position)?;
+                    }
+                }
+                Some(context) => {
+                    let highlighter = Highlighter::new(context, context);
+                    let highlighter = if color {
+                        highlighter.ansi()
+                    } else {
+                        highlighter
+                    };
+                    let mut matches = search.matches().collect::<Vec<_>>();
+                    matches.sort_unstable_by_key(|m| m.position());
+                    for m in matches {
+                        let snippet = m.highlight(&highlighter).to_string();
+                        // keep one occurrence per line
+                        let snippet = snippet.replace(['\n', '\r'], " ");
+                        writeln!(out, "{}\t{}", m.position(), snippet)?;
+                    }
+                }
+            }
+        }
+        Command::Extract { index, start, end } => {
+            let index = load(&index)?;
+            let len = index.text_len();
+            if start > end || end > len {
+                return Err(format!(
+                    "{}..{} is out of range for a text of {} bytes",
+                    start, end, len
+                )
+                .into());
+            }
+            out.write_all(&index.extract(start, end))?;
+        }
+        Command::Stats { index: path } => {
+            let info = IndexInfo::peek(BufReader::new(File::open(&path)?))?;
+            let index = load(&path)?;
+            let stats = index.stats();
+            writeln!(out, "kind:            {}", info.kind())?;
+            writeln!(out, "format version:  {}", info.format_version())?;
+            writeln!(out, "crate version:   {}", info.crate_version())?;
+            if let Some(level) = info.sampling_level() {
+                writeln!(out, "sampling level:  {}", level)?;
+            }
+            writeln!(out, "text length:     {}", stats.text_len())?;
+            writeln!(out, "alphabet size:   {}", stats.alphabet_size())?;
+            writeln!(out, "distinct bytes:  {}", stats.frequencies().len())?;
+            writeln!(out, "BWT runs:        {}", stats.runs())?;
+            writeln!(out, "heap size:       {}", index.heap_size())?;
+            for k in 0..=IndexStats::<u8>::MAX_ORDER {
+                if let Some(entropy) = stats.entropy(k) {
+                    writeln!(out, "entropy H_{}:     {:.4}", k, entropy)?;
+                }
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        // a closed pipe, as from `| head`, isn't an error
+        Err(err)
+            if err
+                .downcast_ref::<io::Error>()
+                .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe) =>
+        {
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("fm-index: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
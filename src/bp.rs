@@ -0,0 +1,293 @@
+//! Balanced parentheses sequences, used to represent tree topologies.
+use crate::util;
+
+use alloc::{vec, vec::Vec};
+use vers_vecs::{BitVec, RsVec};
+
+// Number of parentheses covered by a single entry of the block minima table.
+const BLOCK_SIZE: usize = 256;
+
+/// A balanced sequence of parentheses, with 1 for an opening and 0 for a
+/// closing parenthesis.
+///
+/// The excess at position `i` is the number of opening minus the number of
+/// closing parentheses in `[0, i]`. Searches for an excess value scan the
+/// blocks at the ends of the range, and use a sparse table over the block
+/// minima in between.
+pub(crate) struct BalancedParens {
+    bits: RsVec,
+    // sparse[k][b] is the minimum excess within the blocks b..b + 2^k
+    sparse: Vec<Vec<i64>>,
+}
+
+impl BalancedParens {
+    pub(crate) fn new(bits: BitVec) -> Self {
+        let n = bits.len();
+        let mut block_mins = Vec::with_capacity(n.div_ceil(BLOCK_SIZE));
+        let mut e = 0;
+        for i in 0..n {
+            e += if bits.get_unchecked(i) == 1 { 1 } else { -1 };
+            if i % BLOCK_SIZE == 0 {
+                block_mins.push(e);
+            } else if let Some(m) = block_mins.last_mut() {
+                *m = e.min(*m);
+            }
+        }
+        debug_assert_eq!(e, 0, "parentheses must be balanced");
+
+        let blocks = block_mins.len();
+        let mut sparse = vec![block_mins];
+        let mut k = 1;
+        while (1 << k) <= blocks {
+            let prev = &sparse[k - 1];
+            let half = 1 << (k - 1);
+            let level = (0..=(blocks - 2 * half))
+                .map(|b| prev[b].min(prev[b + half]))
+                .collect();
+            sparse.push(level);
+            k += 1;
+        }
+
+        BalancedParens {
+            bits: RsVec::from_bit_vec(bits),
+            sparse,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub(crate) fn is_open(&self, i: usize) -> bool {
+        i < self.len() && self.bits.get_unchecked(i) == 1
+    }
+
+    /// The number of opening parentheses in `[0, i)`.
+    pub(crate) fn rank_open(&self, i: usize) -> usize {
+        self.bits.rank1(i)
+    }
+
+    pub(crate) fn excess(&self, i: usize) -> i64 {
+        2 * self.bits.rank1(i + 1) as i64 - (i + 1) as i64
+    }
+
+    /// The position of the parenthesis closing the one opened at `i`.
+    pub(crate) fn find_close(&self, i: usize) -> usize {
+        debug_assert!(self.is_open(i));
+        self.fwd_search(i, self.excess(i) - 1).unwrap()
+    }
+
+    /// The opening parenthesis of the closest pair enclosing the one opened
+    /// at `i`, if any.
+    pub(crate) fn enclose(&self, i: usize) -> Option<usize> {
+        debug_assert!(self.is_open(i));
+        if i == 0 {
+            return None;
+        }
+        let target = self.excess(i - 1) - 1;
+        match self.bwd_search(i, target) {
+            Some(j) => Some(j + 1),
+            // only the excess before position 0 is left
+            None if target == 0 => Some(0),
+            None => None,
+        }
+    }
+
+    /// The leftmost position of the minimum excess in `[i, j]`.
+    pub(crate) fn rmq(&self, i: usize, j: usize) -> usize {
+        debug_assert!(i <= j && j < self.len());
+        let bs = i.div_ceil(BLOCK_SIZE);
+        let be = (j + 1) / BLOCK_SIZE;
+        if bs >= be {
+            return self.scan_min(i, j + 1).1;
+        }
+
+        let (mut m, mut pos) = self.scan_min(i, bs * BLOCK_SIZE);
+        let k = util::log2((be - bs) as u64) as usize;
+        let middle = self.sparse[k][bs].min(self.sparse[k][be - (1 << k)]);
+        if middle < m {
+            // the first block reaching the minimum; blocks before it are
+            // all larger
+            let b = self.first_block(bs, be, middle + 1);
+            (m, pos) = self.scan_min(b * BLOCK_SIZE, (b + 1) * BLOCK_SIZE);
+        }
+        let (r, rpos) = self.scan_min(be * BLOCK_SIZE, j + 1);
+        if r < m {
+            pos = rpos;
+        }
+        pos
+    }
+
+    // The smallest j > i with excess(j) <= target.
+    fn fwd_search(&self, i: usize, target: i64) -> Option<usize> {
+        let n = self.len();
+        let mut e = self.excess(i);
+        let block_end = ((i / BLOCK_SIZE + 1) * BLOCK_SIZE).min(n);
+        for j in (i + 1)..block_end {
+            e += self.step(j);
+            if e <= target {
+                return Some(j);
+            }
+        }
+
+        let blocks = self.sparse[0].len();
+        let b = self.first_block(i / BLOCK_SIZE + 1, blocks, target + 1);
+        if b == blocks {
+            return None;
+        }
+        let start = b * BLOCK_SIZE;
+        let mut e = self.excess(start - 1);
+        for j in start..((b + 1) * BLOCK_SIZE).min(n) {
+            e += self.step(j);
+            if e <= target {
+                return Some(j);
+            }
+        }
+        unreachable!("block {} must reach excess {}", b, target)
+    }
+
+    // The largest j < i with excess(j) <= target.
+    fn bwd_search(&self, i: usize, target: i64) -> Option<usize> {
+        let block_start = (i - 1) / BLOCK_SIZE * BLOCK_SIZE;
+        let mut e = self.excess(i - 1);
+        for j in (block_start..i).rev() {
+            if e <= target {
+                return Some(j);
+            }
+            e -= self.step(j);
+        }
+
+        // skip the blocks before, from right to left, whose minimum is larger
+        let mut b = block_start / BLOCK_SIZE;
+        for k in (0..self.sparse.len()).rev() {
+            if b >= (1 << k) && self.sparse[k][b - (1 << k)] > target {
+                b -= 1 << k;
+            }
+        }
+        if b == 0 {
+            return None;
+        }
+        let start = (b - 1) * BLOCK_SIZE;
+        let mut e = self.excess(b * BLOCK_SIZE - 1);
+        for j in (start..b * BLOCK_SIZE).rev() {
+            if e <= target {
+                return Some(j);
+            }
+            e -= self.step(j);
+        }
+        unreachable!("block {} must reach excess {}", b - 1, target)
+    }
+
+    // The first block in [b, end) whose minimum is below `bound`, or `end`.
+    fn first_block(&self, mut b: usize, end: usize, bound: i64) -> usize {
+        for k in (0..self.sparse.len()).rev() {
+            if b + (1 << k) <= end && self.sparse[k][b] >= bound {
+                b += 1 << k;
+            }
+        }
+        b
+    }
+
+    // The leftmost minimum excess in [s, e), together with its position.
+    fn scan_min(&self, s: usize, e: usize) -> (i64, usize) {
+        if s >= e {
+            return (i64::MAX, s);
+        }
+        let mut x = self.excess(s);
+        let mut best = (x, s);
+        for j in (s + 1)..e {
+            x += self.step(j);
+            if x < best.0 {
+                best = (x, j);
+            }
+        }
+        best
+    }
+
+    fn step(&self, j: usize) -> i64 {
+        if self.bits.get_unchecked(j) == 1 {
+            1
+        } else {
+            -1
+        }
+    }
+
+    pub(crate) fn heap_size(&self) -> usize {
+        self.bits.heap_size()
+            + self
+                .sparse
+                .iter()
+                .map(|level| level.capacity() * core::mem::size_of::<i64>())
+                .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn random_parens(rng: &mut StdRng, pairs: usize) -> Vec<bool> {
+        let mut parens = vec![];
+        let (mut open, mut close) = (0, 0);
+        while close < pairs {
+            if open < pairs && (open == close || rng.gen::<bool>()) {
+                parens.push(true);
+                open += 1;
+            } else {
+                parens.push(false);
+                close += 1;
+            }
+        }
+        parens
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for pairs in [1, 5, 200, 3000] {
+            // wrap in a root pair, as for a tree
+            let mut parens = vec![true];
+            parens.extend(random_parens(&mut rng, pairs));
+            parens.push(false);
+            let mut bits = BitVec::new();
+            for &p in &parens {
+                bits.append_bit(p as u64);
+            }
+            let bp = BalancedParens::new(bits);
+            let excess = parens
+                .iter()
+                .scan(0, |e, &p| {
+                    *e += if p { 1 } else { -1 };
+                    Some(*e)
+                })
+                .collect::<Vec<i64>>();
+
+            let mut stack = vec![];
+            let mut close = vec![0; parens.len()];
+            let mut parent = vec![None; parens.len()];
+            for (i, &p) in parens.iter().enumerate() {
+                if p {
+                    parent[i] = stack.last().copied();
+                    stack.push(i);
+                } else {
+                    close[stack.pop().unwrap()] = i;
+                }
+            }
+            for (i, &p) in parens.iter().enumerate() {
+                assert_eq!(bp.excess(i), excess[i]);
+                if p {
+                    assert_eq!(bp.find_close(i), close[i], "find_close({})", i);
+                    assert_eq!(bp.enclose(i), parent[i], "enclose({})", i);
+                }
+            }
+            for _ in 0..1000 {
+                let i = rng.gen::<usize>() % parens.len();
+                let j = i + rng.gen::<usize>() % (parens.len() - i);
+                let expected = (i..=j).min_by_key(|&k| (excess[k], k)).unwrap();
+                assert_eq!(bp.rmq(i, j), expected, "rmq({}, {})", i, j);
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Building an index whose implementation is picked at run time.
+use crate::auto::IndexKind;
+use crate::character::{check_text, Character, IntoText};
+use crate::converter::Converter;
+use crate::dyn_search::DynSearchIndex;
+use crate::error::Error;
+use crate::frontend::{
+    DNAFMIndex, DNAFMIndexWithLocate, FMIndex, FMIndexWithLocate, RLFMIndex, RLFMIndexWithLocate,
+};
+
+use alloc::boxed::Box;
+
+/// Builder for an index whose implementation is picked at run time.
+///
+/// The index is returned as a [`DynSearchIndex`] trait object, so indexes
+/// built with different settings have the same type. By default the
+/// implementation is chosen with [`IndexKind::choose`], and the index only
+/// supports counting; setting a sampling level or rate adds locate support.
+///
+/// ```
+/// use fm_index::converter::RangeConverter;
+/// use fm_index::{IndexKind, SearchIndexBuilder};
+///
+/// let index = SearchIndexBuilder::with_converter(RangeConverter::new(b'a', b'z'))
+///     .kind(IndexKind::RLFM)
+///     .sampling_level(2)
+///     .build("mississippi");
+/// assert_eq!(index.count(b"ssi"), 2);
+/// assert!(index.supports_locate());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearchIndexBuilder<C> {
+    converter: C,
+    kind: Option<IndexKind>,
+    rate: Option<u64>,
+}
+
+impl<C> SearchIndexBuilder<C> {
+    /// Create a builder for an index using `converter`.
+    pub fn with_converter(converter: C) -> Self {
+        SearchIndexBuilder {
+            converter,
+            kind: None,
+            rate: None,
+        }
+    }
+
+    /// Use the implementation `kind` instead of choosing one for the text.
+    ///
+    /// [`IndexKind::DNA`] requires a converter with at most four
+    /// characters besides the terminator.
+    pub fn kind(mut self, kind: IndexKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Support locate queries, sampling the suffix array at `level`.
+    ///
+    /// See [`FMIndexWithLocate::new`] for the meaning of `level`.
+    pub fn sampling_level(self, level: usize) -> Self {
+        self.sampling_rate(1 << level)
+    }
+
+    /// Support locate queries, sampling one in every `rate` entries of the
+    /// suffix array.
+    ///
+    /// See [`FMIndexWithLocate::with_sampling_rate`] for the meaning of
+    /// `rate`.
+    pub fn sampling_rate(mut self, rate: u64) -> Self {
+        self.rate = Some(rate);
+        self
+    }
+
+    /// Build the index of `text`.
+    pub fn build<'t, T>(self, text: impl IntoText<'t, T>) -> Box<dyn DynSearchIndex<T>>
+    where
+        T: Character,
+        C: Converter<T> + 'static,
+    {
+        let text = text.into_text();
+        let converter = self.converter;
+        let kind = self
+            .kind
+            .unwrap_or_else(|| IndexKind::choose(&text, &converter));
+        match (kind, self.rate) {
+            (IndexKind::FM, None) => Box::new(FMIndex::new(text, converter)),
+            (IndexKind::FM, Some(rate)) => Box::new(FMIndexWithLocate::<T, C>::with_sampling_rate(
+                text, converter, rate,
+            )),
+            (IndexKind::RLFM, None) => Box::new(RLFMIndex::new(text, converter)),
+            (IndexKind::RLFM, Some(rate)) => Box::new(
+                RLFMIndexWithLocate::<T, C>::with_sampling_rate(text, converter, rate),
+            ),
+            (IndexKind::DNA, None) => Box::new(DNAFMIndex::new(text, converter)),
+            (IndexKind::DNA, Some(rate)) => Box::new(
+                DNAFMIndexWithLocate::<T, C>::with_sampling_rate(text, converter, rate),
+            ),
+        }
+    }
+
+    /// Build the index of `text` like [`build`](Self::build), but return an
+    /// [`Error`] instead of panicking if the text can't be indexed.
+    pub fn try_build<'t, T>(
+        self,
+        text: impl IntoText<'t, T>,
+    ) -> Result<Box<dyn DynSearchIndex<T>>, Error>
+    where
+        T: Character,
+        C: Converter<T> + 'static,
+    {
+        let text = text.into_text();
+        check_text(&text, &self.converter)?;
+        Ok(self.build(text))
+    }
+}
@@ -0,0 +1,238 @@
+//! A C API to build, save, load and search indexes of bytes.
+//!
+//! The functions are declared in `include/fm_index.h`. An index is an
+//! opaque [`FmIndex`] handle, which is a [`FMIndexWithLocate`] of bytes
+//! with an [`IdConverter`], so any byte but 0 may occur in the text.
+//!
+//! Functions that create an index return a null pointer on failure, and
+//! functions that return an `int` return 0 on success and -1 on failure. A
+//! panic never unwinds into the caller; it is reported as a failure.
+use crate::character::{check_level, check_text};
+use crate::converter::IdConverter;
+use crate::frontend::FMIndexWithLocate;
+
+use std::ffi::{c_char, c_int, CStr};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::{ptr, slice};
+
+/// An index of a text of bytes, with locate support.
+pub struct FmIndex(FMIndexWithLocate<u8, IdConverter>);
+
+// Run `f`, turning a panic into `None`.
+fn guard<R>(f: impl FnOnce() -> Option<R>) -> Option<R> {
+    catch_unwind(AssertUnwindSafe(f)).ok().flatten()
+}
+
+// View `len` bytes at `data` as a slice, allowing a null pointer for an
+// empty slice.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(data, len))
+    }
+}
+
+fn into_handle(index: FMIndexWithLocate<u8, IdConverter>) -> *mut FmIndex {
+    Box::into_raw(Box::new(FmIndex(index)))
+}
+
+/// Build an index of the `len` bytes at `text`, sampling the suffix array
+/// at `level` as in [`FMIndexWithLocate::new`].
+///
+/// The text is copied, so the caller keeps ownership of it. Returns null
+/// if the text is empty or holds a 0 byte other than a final terminator, or
+/// if `level` is too large for the text.
+///
+/// # Safety
+///
+/// `text` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fm_index_new(text: *const u8, len: usize, level: u32) -> *mut FmIndex {
+    guard(|| {
+        let text = bytes(text, len)?;
+        let converter = IdConverter::new::<u8>();
+        check_text(text, &converter).ok()?;
+        check_level(text, level as usize).ok()?;
+        Some(into_handle(FMIndexWithLocate::new(
+            text,
+            converter,
+            level as usize,
+        )))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Free an index. Passing null does nothing.
+///
+/// # Safety
+///
+/// `index` must be null or a handle returned by this API that hasn't been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn fm_index_free(index: *mut FmIndex) {
+    if !index.is_null() {
+        drop(Box::from_raw(index));
+    }
+}
+
+/// Save an index to the file at `path`, a NUL-terminated UTF-8 string, in
+/// the format of [`FMIndexWithLocate::save`].
+///
+/// # Safety
+///
+/// `index` must be a live handle and `path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fm_index_save(index: *const FmIndex, path: *const c_char) -> c_int {
+    guard(|| {
+        let index = index.as_ref()?;
+        let path = CStr::from_ptr(path).to_str().ok()?;
+        let mut writer = BufWriter::new(File::create(path).ok()?);
+        index.0.save(&mut writer).ok()?;
+        writer.flush().ok()
+    })
+    .map_or(-1, |()| 0)
+}
+
+/// Load an index saved by [`fm_index_save`] from the file at `path`, a
+/// NUL-terminated UTF-8 string. Returns null if the file can't be read or
+/// doesn't hold an index of bytes.
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fm_index_load(path: *const c_char) -> *mut FmIndex {
+    guard(|| {
+        let path = CStr::from_ptr(path).to_str().ok()?;
+        FMIndexWithLocate::load_file(path).ok().map(into_handle)
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Save an index to a buffer allocated by this API, storing its address
+/// in `*data` and its length in `*len`. Free the buffer with
+/// [`fm_index_bytes_free`].
+///
+/// # Safety
+///
+/// `index` must be a live handle, and `data` and `len` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn fm_index_save_bytes(
+    index: *const FmIndex,
+    data: *mut *mut u8,
+    len: *mut usize,
+) -> c_int {
+    guard(|| {
+        let index = index.as_ref()?;
+        let mut buffer = Vec::new();
+        index.0.save(&mut buffer).ok()?;
+        let buffer = buffer.into_boxed_slice();
+        *len = buffer.len();
+        *data = Box::into_raw(buffer) as *mut u8;
+        Some(())
+    })
+    .map_or(-1, |()| 0)
+}
+
+/// Free a buffer returned by [`fm_index_save_bytes`]. Passing null does
+/// nothing.
+///
+/// # Safety
+///
+/// `data` and `len` must be null or as returned by
+/// [`fm_index_save_bytes`], and the buffer must not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn fm_index_bytes_free(data: *mut u8, len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+    }
+}
+
+/// Load an index from the `len` bytes at `data`, as saved by
+/// [`fm_index_save_bytes`] or [`fm_index_save`]. The bytes are copied into
+/// the index, so the caller keeps ownership of them. Returns null if they
+/// don't hold an index of bytes.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fm_index_load_bytes(data: *const u8, len: usize) -> *mut FmIndex {
+    guard(|| {
+        let data = bytes(data, len)?;
+        FMIndexWithLocate::load(data).ok().map(into_handle)
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// The number of bytes in the indexed text, including the terminator.
+///
+/// # Safety
+///
+/// `index` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn fm_index_len(index: *const FmIndex) -> u64 {
+    guard(|| Some(index.as_ref()?.0.len())).unwrap_or(0)
+}
+
+/// The number of occurrences of the `len` bytes at `pattern` in the text.
+///
+/// # Safety
+///
+/// `index` must be a live handle and `pattern` must point to `len`
+/// readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fm_index_count(
+    index: *const FmIndex,
+    pattern: *const u8,
+    len: usize,
+) -> u64 {
+    guard(|| {
+        let index = index.as_ref()?;
+        Some(index.0.search(bytes(pattern, len)?).count())
+    })
+    .unwrap_or(0)
+}
+
+/// Write the positions of the occurrences of the `len` bytes at `pattern`
+/// to `out`, which has room for `capacity` positions.
+///
+/// Returns the number of occurrences, of which the first `capacity` are
+/// written, in no particular order; call again with a larger buffer if it
+/// exceeds `capacity`. `out` may be null if `capacity` is 0.
+///
+/// # Safety
+///
+/// `index` must be a live handle, `pattern` must point to `len` readable
+/// bytes and `out` to room for `capacity` positions.
+#[no_mangle]
+pub unsafe extern "C" fn fm_index_locate(
+    index: *const FmIndex,
+    pattern: *const u8,
+    len: usize,
+    out: *mut u64,
+    capacity: usize,
+) -> u64 {
+    guard(|| {
+        let index = index.as_ref()?;
+        let search = index.0.search(bytes(pattern, len)?);
+        let count = search.count();
+        if capacity > 0 && !out.is_null() {
+            let out = slice::from_raw_parts_mut(out, capacity);
+            let mut written = 0;
+            search.for_each_position(|position| {
+                if written < capacity {
+                    out[written] = position;
+                    written += 1;
+                }
+            });
+        }
+        Some(count)
+    })
+    .unwrap_or(0)
+}
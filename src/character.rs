@@ -1,3 +1,7 @@
+use crate::converter::Converter;
+use crate::error::Error;
+
+use alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
 use num_traits::{Bounded, Num};
 
 /// A character is a type that can be used to store data and to compose a
@@ -9,18 +13,159 @@ use num_traits::{Bounded, Num};
 ///
 /// These can be converted into u64 using `.into()` and from u64 using
 /// `from_u64`. When converted from u64, they are truncated.
-pub trait Character: Into<u64> + Copy + Clone + Num + Ord + Bounded + std::fmt::Debug {
+pub trait Character:
+    Into<u64> + Copy + Clone + Num + Ord + Bounded + core::fmt::Debug + 'static
+{
     /// Take a u64 and convert it into the given data type.
     ///
     /// Truncates the u64 if it is too large to fit in the type.
     fn from_u64(n: u64) -> Self;
 }
 
-pub(crate) fn prepare_text<T: Character>(mut text: Vec<T>) -> Vec<T> {
-    if !text[text.len() - 1].is_zero() {
-        text.push(T::zero());
+/// A text to build an index of, owned or borrowed.
+///
+/// Index constructors take any `impl IntoText`, such as a `Vec<T>` or a
+/// `&[T]`, and for byte texts a `String` or a `&str`. An owned text is
+/// terminated in place, while a borrowed text is only copied if it doesn't
+/// end with a terminator yet, so a text that is already terminated can be
+/// indexed straight from an arena or a memory map.
+pub trait IntoText<'a, T: Clone> {
+    /// Turn this into the text.
+    fn into_text(self) -> Cow<'a, [T]>;
+}
+
+impl<'a, T: Clone> IntoText<'a, T> for Cow<'a, [T]> {
+    fn into_text(self) -> Cow<'a, [T]> {
+        self
+    }
+}
+
+impl<'a, T: Clone> IntoText<'a, T> for Vec<T> {
+    fn into_text(self) -> Cow<'a, [T]> {
+        Cow::Owned(self)
+    }
+}
+
+impl<'a, T: Clone> IntoText<'a, T> for Box<[T]> {
+    fn into_text(self) -> Cow<'a, [T]> {
+        Cow::Owned(self.into_vec())
+    }
+}
+
+impl<'a, T: Clone> IntoText<'a, T> for &'a [T] {
+    fn into_text(self) -> Cow<'a, [T]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<'a, T: Clone> IntoText<'a, T> for &'a Vec<T> {
+    fn into_text(self) -> Cow<'a, [T]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<'a, T: Clone, const N: usize> IntoText<'a, T> for &'a [T; N] {
+    fn into_text(self) -> Cow<'a, [T]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<'a, T: Clone, const N: usize> IntoText<'a, T> for [T; N] {
+    fn into_text(self) -> Cow<'a, [T]> {
+        Cow::Owned(self.into())
+    }
+}
+
+impl<'a> IntoText<'a, u8> for &'a str {
+    fn into_text(self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+}
+
+impl<'a> IntoText<'a, u8> for &'a String {
+    fn into_text(self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+}
+
+impl<'a> IntoText<'a, u8> for String {
+    fn into_text(self) -> Cow<'a, [u8]> {
+        Cow::Owned(self.into_bytes())
+    }
+}
+
+// Check that a text can be indexed with `converter`. The text may end with
+// a \0 terminator, but mustn't contain one anywhere else.
+pub(crate) fn check_text<T: Character, C: Converter<T>>(
+    text: &[T],
+    converter: &C,
+) -> Result<(), Error> {
+    check_texts(text, converter)?;
+    // a search would run across an inner terminator
+    match text[..text.len() - 1].iter().position(|c| c.is_zero()) {
+        Some(position) => Err(Error::TerminatorInText { position }),
+        None => Ok(()),
+    }
+}
+
+// Check that texts separated by \0, as held by a multi-text index or an
+// index merged from others, can be indexed with `converter`.
+pub(crate) fn check_texts<T: Character, C: Converter<T>>(
+    text: &[T],
+    converter: &C,
+) -> Result<(), Error> {
+    if text.is_empty() || text.len() == 1 && text[0].is_zero() {
+        return Err(Error::EmptyText);
+    }
+    converter.validate(text)?;
+    // suffix array construction needs a single terminator at the end
+    let trailing = text.iter().rev().take_while(|c| c.is_zero()).count();
+    if trailing > 1 {
+        return Err(Error::TerminatorInText {
+            position: text.len() - trailing,
+        });
+    }
+    Ok(())
+}
+
+// Check that the suffix array of the text can be sampled at `level`. The
+// text must already have passed `check_text` or `check_texts`.
+pub(crate) fn check_level<T: Character>(text: &[T], level: usize) -> Result<(), Error> {
+    // the index holds the text with its terminator
+    let len = text.len() as u64 + u64::from(!text.last().is_some_and(|c| c.is_zero()));
+    crate::suffix_array::sample::check_level(len, level)
+}
+
+// The sampling rate for `level`, panicking with the message of the error
+// `try_new` would return if the text can't be indexed, or can't be sampled
+// at that level. Like `new`, this allows \0 inside the text.
+pub(crate) fn level_rate<T: Character, C: Converter<T>>(
+    text: &[T],
+    converter: &C,
+    level: usize,
+) -> u64 {
+    if let Err(err) = check_texts(text, converter).and_then(|()| check_level(text, level)) {
+        panic!("{}", err);
+    }
+    1 << level
+}
+
+// Check that the text can be indexed, and end it with a terminator. A
+// borrowed text is only copied if the terminator has to be added. Like
+// `new`, this allows \0 inside the text.
+pub(crate) fn prepare_text<'a, T: Character, C: Converter<T>>(
+    text: Cow<'a, [T]>,
+    converter: &C,
+) -> Cow<'a, [T]> {
+    if let Err(err) = check_texts(&text, converter) {
+        panic!("{}", err);
+    }
+    if text[text.len() - 1].is_zero() {
+        return text;
     }
-    text
+    let mut text = text.into_owned();
+    text.push(T::zero());
+    Cow::Owned(text)
 }
 
 macro_rules! impl_character {
@@ -3,6 +3,10 @@
 
 use crate::character::Character;
 
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::{format, string::String, vec::Vec};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// If we know a [Character] data type can only consists of particular values,
@@ -21,6 +25,132 @@ where
     fn convert_inv(&self, c: T) -> T;
     /// Get the size of the restricted alphabet.
     fn len(&self) -> u64;
+
+    /// A name identifying the kind of converter, such as `RangeConverter`,
+    /// which is saved in the metadata of an index.
+    ///
+    /// Unlike the type name, this stays the same across compiler and crate
+    /// versions. Converters wrapping others include the names of those,
+    /// such as `CaseFoldConverter<RangeConverter>`. By default it is
+    /// `custom`.
+    fn name(&self) -> String {
+        String::from("custom")
+    }
+
+    /// Whether `c` is in the restricted alphabet.
+    ///
+    /// The terminator \0 always is. By default a character is in the
+    /// alphabet if it converts to a character smaller than
+    /// [`len`](Self::len).
+    fn contains(&self, c: T) -> bool {
+        c.is_zero() || self.convert(c).into() < self.len()
+    }
+
+    /// Check that all characters of `text` are in the restricted alphabet.
+    ///
+    /// Returns an error for the first character that isn't.
+    fn validate(&self, text: &[T]) -> Result<(), InvalidCharacter<T>> {
+        match text.iter().position(|&c| !self.contains(c)) {
+            Some(position) => Err(InvalidCharacter {
+                position,
+                character: text[position],
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Chain this converter with `next`, which converts the characters this
+    /// converter produces.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use fm_index::converter::{CaseFoldConverter, Converter, IdConverter, RangeConverter};
+    ///
+    /// // fold case, then restrict the alphabet to lower case letters
+    /// let converter =
+    ///     CaseFoldConverter::new(IdConverter::new::<u8>()).then(RangeConverter::new(b'a', b'z'));
+    /// assert_eq!(converter.convert(b'B'), 2);
+    /// assert_eq!(converter.convert_inv(2), b'b');
+    /// assert_eq!(converter.len(), 27);
+    /// ```
+    fn then<D>(self, next: D) -> Then<Self, D>
+    where
+        Self: Sized,
+        D: Converter<T>,
+    {
+        Then { first: self, next }
+    }
+}
+
+/// A character of a text that isn't in the alphabet of a converter, found by
+/// [`Converter::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidCharacter<T> {
+    /// The position of the character in the text.
+    pub position: usize,
+    /// The character.
+    pub character: T,
+}
+
+impl<T: Character> core::fmt::Display for InvalidCharacter<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "character {:?} at position {} is not in the converter's alphabet",
+            self.character, self.position
+        )
+    }
+}
+
+impl<T: Character> core::error::Error for InvalidCharacter<T> {}
+
+/// Two converters applied one after the other, created by
+/// [`Converter::then`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Then<A, B> {
+    first: A,
+    next: B,
+}
+
+impl<A, B> Then<A, B> {
+    /// The converter applied first.
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// The converter applied to the characters produced by the first one.
+    pub fn next(&self) -> &B {
+        &self.next
+    }
+}
+
+impl<T, A, B> Converter<T> for Then<A, B>
+where
+    T: Character,
+    A: Converter<T>,
+    B: Converter<T>,
+{
+    fn convert(&self, c: T) -> T {
+        self.next.convert(self.first.convert(c))
+    }
+
+    fn convert_inv(&self, c: T) -> T {
+        self.first.convert_inv(self.next.convert_inv(c))
+    }
+
+    fn contains(&self, c: T) -> bool {
+        self.first.contains(c) && self.next.contains(self.first.convert(c))
+    }
+
+    fn len(&self) -> u64 {
+        self.next.len()
+    }
+
+    fn name(&self) -> String {
+        format!("Then<{}, {}>", self.first.name(), self.next.name())
+    }
 }
 
 /// Restrict characters to a range defining the alphabet.
@@ -30,7 +160,8 @@ where
 /// The range is defined by the minimum and maximum values of the alphabet.
 ///
 /// The null (zero) character is handled separately and is always accepted.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RangeConverter<T>
 where
     T: Character,
@@ -48,6 +179,17 @@ where
         debug_assert!(!T::is_zero(&min), "min should not be zero");
         RangeConverter { min, max }
     }
+
+    /// Create a converter for the range of the characters in `text`.
+    ///
+    /// Terminator characters are ignored. The text must have at least one
+    /// other character.
+    pub fn from_text(text: &[T]) -> Self {
+        let mut chars = text.iter().copied().filter(|c| !c.is_zero());
+        let first = chars.next().expect("text must have a non-zero character");
+        let (min, max) = chars.fold((first, first), |(min, max), c| (min.min(c), max.max(c)));
+        RangeConverter::new(min, max)
+    }
 }
 
 impl<T> Converter<T> for RangeConverter<T>
@@ -70,13 +212,23 @@ where
         }
     }
 
+    fn contains(&self, c: T) -> bool {
+        c.is_zero() || (self.min <= c && c <= self.max)
+    }
+
     fn len(&self) -> u64 {
         // [min, max] + sentinel
         (self.max - self.min).into() + 2
     }
+
+    fn name(&self) -> String {
+        String::from("RangeConverter")
+    }
 }
 
 /// An identity converter that does not restrict the alphabet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IdConverter {
     size: u64,
 }
@@ -119,4 +271,705 @@ where
     fn len(&self) -> u64 {
         self.size
     }
+    fn name(&self) -> String {
+        String::from("IdConverter")
+    }
+}
+
+// The smallest non-zero character not in `chars`, which must be ordered and
+// distinct.
+fn smallest_absent<T: Character>(chars: impl Iterator<Item = T>) -> Option<T> {
+    let mut absent = Some(T::one());
+    for c in chars {
+        match absent {
+            Some(a) if a == c => absent = (c < T::max_value()).then(|| c + T::one()),
+            _ => break,
+        }
+    }
+    absent
+}
+
+/// Map exactly the characters that occur in a text to the codes `1..=k`,
+/// in order.
+///
+/// Unlike [`RangeConverter`], which takes the whole range between the
+/// smallest and the largest character, or [`IdConverter`], which takes every
+/// value of the character type, this leaves no gaps in the alphabet. It is a
+/// good default when the alphabet isn't known in advance.
+///
+/// Characters that don't occur in the text all share one additional code,
+/// so patterns containing them have no occurrences.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DenseConverter<T>
+where
+    T: Character,
+{
+    // the characters of the text, ordered; the code of each is its index
+    // plus one
+    chars: Vec<T>,
+    // a character that doesn't occur in the text, standing in for all of
+    // them; there is none if the text contains every non-zero value
+    absent: Option<T>,
+}
+
+impl<T> DenseConverter<T>
+where
+    T: Character,
+{
+    /// Create a converter for the distinct characters of `text`.
+    ///
+    /// Terminator characters are ignored.
+    pub fn from_text(text: &[T]) -> Self {
+        let mut chars = text
+            .iter()
+            .copied()
+            .filter(|c| !c.is_zero())
+            .collect::<Vec<_>>();
+        chars.sort_unstable();
+        chars.dedup();
+        chars.shrink_to_fit();
+        let absent = smallest_absent(chars.iter().copied());
+        DenseConverter { chars, absent }
+    }
+}
+
+impl<T> Converter<T> for DenseConverter<T>
+where
+    T: Character,
+{
+    fn convert(&self, c: T) -> T {
+        if c.is_zero() {
+            return c;
+        }
+        match self.chars.binary_search(&c) {
+            Ok(i) => T::from_u64(i as u64 + 1),
+            Err(_) => T::from_u64(self.chars.len() as u64 + 1),
+        }
+    }
+
+    fn contains(&self, c: T) -> bool {
+        c.is_zero() || self.chars.binary_search(&c).is_ok()
+    }
+
+    fn convert_inv(&self, c: T) -> T {
+        if c.is_zero() {
+            return c;
+        }
+        match self.chars.get(c.into() as usize - 1) {
+            Some(&c) => c,
+            None => self.absent.unwrap(),
+        }
+    }
+
+    fn len(&self) -> u64 {
+        // the characters of the text, the code for absent characters and
+        // the sentinel
+        self.chars.len() as u64 + self.absent.is_some() as u64 + 1
+    }
+
+    fn name(&self) -> String {
+        String::from("DenseConverter")
+    }
+}
+
+/// Map the characters of a text to codes ordered by how often they occur.
+///
+/// The most frequent character gets code 1, the next one code 2, and so on.
+/// The alphabet is then exactly as large as the number of distinct
+/// characters in the text, however sparsely they are spread over the values
+/// of the character type, such as `u32` token ids of which only a few
+/// thousand occur.
+///
+/// Characters that don't occur in the text all share one additional code,
+/// so patterns containing them have no occurrences.
+///
+/// The index sorts suffixes by code rather than by character, so results
+/// that are ordered by character, such as those of `extensions`, are in
+/// order of frequency instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FrequencyConverter<T>
+where
+    T: Character,
+{
+    // (character, code) pairs, ordered by character
+    codes: Vec<(T, T)>,
+    // the character of each code, starting at code 1
+    chars: Vec<T>,
+    // a character that doesn't occur in the text, standing in for all of
+    // them; there is none if the text contains every non-zero value
+    absent: Option<T>,
+}
+
+impl<T> FrequencyConverter<T>
+where
+    T: Character,
+{
+    /// Create a converter for the characters of `text`.
+    ///
+    /// Terminator characters are ignored. Characters that occur equally
+    /// often are ordered by value.
+    pub fn from_text(text: &[T]) -> Self {
+        let mut counts = BTreeMap::new();
+        for &c in text.iter().filter(|c| !c.is_zero()) {
+            *counts.entry(c).or_insert(0u64) += 1;
+        }
+
+        let absent = smallest_absent(counts.keys().copied());
+
+        let mut by_frequency = counts.into_iter().collect::<Vec<_>>();
+        // stable, so ties stay ordered by character
+        by_frequency.sort_by_key(|&(_, count)| core::cmp::Reverse(count));
+        let chars = by_frequency.into_iter().map(|(c, _)| c).collect::<Vec<_>>();
+        let mut codes = chars
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| (c, T::from_u64(i as u64 + 1)))
+            .collect::<Vec<_>>();
+        codes.sort();
+
+        FrequencyConverter {
+            codes,
+            chars,
+            absent,
+        }
+    }
+}
+
+impl<T> Converter<T> for FrequencyConverter<T>
+where
+    T: Character,
+{
+    fn convert(&self, c: T) -> T {
+        if c.is_zero() {
+            return c;
+        }
+        match self.codes.binary_search_by_key(&c, |&(c, _)| c) {
+            Ok(i) => self.codes[i].1,
+            Err(_) => T::from_u64(self.chars.len() as u64 + 1),
+        }
+    }
+
+    fn contains(&self, c: T) -> bool {
+        c.is_zero() || self.codes.binary_search_by_key(&c, |&(c, _)| c).is_ok()
+    }
+
+    fn convert_inv(&self, c: T) -> T {
+        if c.is_zero() {
+            return c;
+        }
+        match self.chars.get(c.into() as usize - 1) {
+            Some(&c) => c,
+            None => self.absent.unwrap(),
+        }
+    }
+
+    fn len(&self) -> u64 {
+        // the characters of the text, the code for absent characters and
+        // the sentinel
+        self.chars.len() as u64 + self.absent.is_some() as u64 + 1
+    }
+
+    fn name(&self) -> String {
+        String::from("FrequencyConverter")
+    }
+}
+
+/// How [`DNAConverter`] handles `N` and the other IUPAC ambiguity codes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AmbiguityPolicy {
+    /// Reject texts that contain them.
+    Error,
+    /// Replace them all by `N`, which becomes a fifth character.
+    Symbol,
+    /// Split the text at them into separate texts, separated by \0, for use
+    /// with a multi-text index.
+    Split,
+}
+
+/// A character of a text for [`DNAConverter`] that isn't allowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidBase {
+    /// The position of the character in the text.
+    pub position: usize,
+    /// The character.
+    pub base: u8,
+}
+
+impl core::fmt::Display for InvalidBase {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "invalid base {:?} at position {}",
+            self.base as char, self.position
+        )
+    }
+}
+
+impl core::error::Error for InvalidBase {}
+
+/// Convert DNA, in either case, to the codes 1 to 4 for `A`, `C`, `G` and
+/// `T`.
+///
+/// Texts should be passed through [`prepare`](Self::prepare) first, which
+/// normalizes the case and applies the [`AmbiguityPolicy`] to `N` and the
+/// other ambiguity codes. Unless the policy is
+/// [`AmbiguityPolicy::Symbol`], the alphabet has four characters, as
+/// [`DNAFMIndex`](crate::DNAFMIndex) requires.
+///
+/// Searching for a pattern with a character other than these panics.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DNAConverter {
+    policy: AmbiguityPolicy,
+}
+
+impl DNAConverter {
+    /// Create a converter that handles ambiguity codes with `policy`.
+    pub fn new(policy: AmbiguityPolicy) -> Self {
+        DNAConverter { policy }
+    }
+
+    /// The policy for ambiguity codes.
+    pub fn policy(&self) -> AmbiguityPolicy {
+        self.policy
+    }
+
+    /// Prepare `text` for an index with this converter.
+    ///
+    /// Bases are converted to upper case, and ambiguity codes are handled
+    /// according to the policy. With [`AmbiguityPolicy::Split`], a run of
+    /// ambiguity codes becomes a single \0 separator, and the result ends
+    /// with one.
+    ///
+    /// Returns an error for the first character that is neither a base nor
+    /// an ambiguity code, or for the first ambiguity code with
+    /// [`AmbiguityPolicy::Error`].
+    pub fn prepare(&self, text: &[u8]) -> Result<Vec<u8>, InvalidBase> {
+        let mut result = Vec::with_capacity(text.len() + 1);
+        for (position, &c) in text.iter().enumerate() {
+            let invalid = InvalidBase { position, base: c };
+            match c.to_ascii_uppercase() {
+                c @ (b'A' | b'C' | b'G' | b'T') => result.push(c),
+                b'N' | b'R' | b'Y' | b'S' | b'W' | b'K' | b'M' | b'B' | b'D' | b'H' | b'V' => {
+                    match self.policy {
+                        AmbiguityPolicy::Error => return Err(invalid),
+                        AmbiguityPolicy::Symbol => result.push(b'N'),
+                        AmbiguityPolicy::Split => {
+                            if result.last().is_some_and(|&c| c != 0) {
+                                result.push(0);
+                            }
+                        }
+                    }
+                }
+                _ => return Err(invalid),
+            }
+        }
+        if self.policy == AmbiguityPolicy::Split && result.last().is_some_and(|&c| c != 0) {
+            result.push(0);
+        }
+        Ok(result)
+    }
+}
+
+impl Converter<u8> for DNAConverter {
+    fn convert(&self, c: u8) -> u8 {
+        match c {
+            0 => 0,
+            b'A' | b'a' => 1,
+            b'C' | b'c' => 2,
+            b'G' | b'g' => 3,
+            b'T' | b't' => 4,
+            b'N' | b'n' if self.policy == AmbiguityPolicy::Symbol => 5,
+            _ => panic!("{:?} is not a DNA base", c as char),
+        }
+    }
+
+    fn convert_inv(&self, c: u8) -> u8 {
+        b"\0ACGTN"[c as usize]
+    }
+
+    fn contains(&self, c: u8) -> bool {
+        match c {
+            0 | b'A' | b'a' | b'C' | b'c' | b'G' | b'g' | b'T' | b't' => true,
+            b'N' | b'n' => self.policy == AmbiguityPolicy::Symbol,
+            _ => false,
+        }
+    }
+
+    fn len(&self) -> u64 {
+        match self.policy {
+            AmbiguityPolicy::Symbol => 6,
+            _ => 5,
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from("DNAConverter")
+    }
+}
+
+/// Convert protein sequences, in either case, to a 5-bit alphabet.
+///
+/// The alphabet consists of the 20 standard amino acids, the ambiguity
+/// codes `B`, `J`, `X` and `Z`, the rare amino acids `O` and `U`, and `*`
+/// for a stop codon: `*` has code 1 and the letters `A` to `Z` have codes 2
+/// to 27. Lower case letters are converted like upper case ones, and
+/// converted back to upper case.
+///
+/// Converting any other character panics.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProteinConverter;
+
+impl ProteinConverter {
+    /// Create a new protein converter.
+    pub fn new() -> Self {
+        ProteinConverter
+    }
+}
+
+impl Converter<u8> for ProteinConverter {
+    fn convert(&self, c: u8) -> u8 {
+        match c {
+            0 => 0,
+            b'*' => 1,
+            b'A'..=b'Z' => c - b'A' + 2,
+            b'a'..=b'z' => c - b'a' + 2,
+            _ => panic!("{:?} is not an amino acid", c as char),
+        }
+    }
+
+    fn convert_inv(&self, c: u8) -> u8 {
+        match c {
+            0 => 0,
+            1 => b'*',
+            _ => c - 2 + b'A',
+        }
+    }
+
+    fn contains(&self, c: u8) -> bool {
+        c == 0 || c == b'*' || c.is_ascii_alphabetic()
+    }
+
+    fn len(&self) -> u64 {
+        28
+    }
+
+    fn name(&self) -> String {
+        String::from("ProteinConverter")
+    }
+}
+
+/// Fold ASCII letters to lower case before converting them with another
+/// converter.
+///
+/// An index with this converter is case-insensitive: `A` and `a` are the
+/// same character, both in the text and in patterns. The inner converter
+/// only needs to handle lower case letters, so the alphabet is smaller.
+/// Characters are converted back in lower case.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CaseFoldConverter<C> {
+    inner: C,
+}
+
+impl<C> CaseFoldConverter<C> {
+    /// Create a converter that folds case and then converts with `inner`.
+    pub fn new(inner: C) -> Self {
+        CaseFoldConverter { inner }
+    }
+
+    /// The converter applied after folding case.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+}
+
+fn fold_case<T: Character>(c: T) -> T {
+    let value = c.into();
+    if (b'A' as u64..=b'Z' as u64).contains(&value) {
+        T::from_u64(value + (b'a' - b'A') as u64)
+    } else {
+        c
+    }
+}
+
+impl<T, C> Converter<T> for CaseFoldConverter<C>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    fn convert(&self, c: T) -> T {
+        self.inner.convert(fold_case(c))
+    }
+
+    fn convert_inv(&self, c: T) -> T {
+        self.inner.convert_inv(c)
+    }
+
+    fn contains(&self, c: T) -> bool {
+        self.inner.contains(fold_case(c))
+    }
+
+    fn len(&self) -> u64 {
+        self.inner.len()
+    }
+
+    fn name(&self) -> String {
+        format!("CaseFoldConverter<{}>", self.inner.name())
+    }
+}
+
+/// A normalization of strings, such as Unicode NFC.
+///
+/// This is implemented for functions from `&str` to `String`, for a custom
+/// normalization.
+pub trait Normalizer {
+    /// Normalize `s`.
+    fn normalize<'a>(&self, s: &'a str) -> Cow<'a, str>;
+
+    /// A name identifying the normalization, such as `Nfc`, for
+    /// [`Converter::name`]. By default it is `custom`.
+    fn name(&self) -> String {
+        String::from("custom")
+    }
+}
+
+impl<F> Normalizer for F
+where
+    F: Fn(&str) -> String,
+{
+    fn normalize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        Cow::Owned(self(s))
+    }
+}
+
+/// Unicode Normalization Form C, canonical composition.
+///
+/// This requires the `unicode-normalization` feature.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Nfc;
+
+#[cfg(feature = "unicode-normalization")]
+impl Normalizer for Nfc {
+    fn normalize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+        match is_nfc_quick(s.chars()) {
+            IsNormalized::Yes => Cow::Borrowed(s),
+            _ => Cow::Owned(s.nfc().collect()),
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from("Nfc")
+    }
+}
+
+/// Unicode Normalization Form KC, compatibility decomposition followed by
+/// canonical composition.
+///
+/// This requires the `unicode-normalization` feature.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Nfkc;
+
+#[cfg(feature = "unicode-normalization")]
+impl Normalizer for Nfkc {
+    fn normalize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        use unicode_normalization::{is_nfkc_quick, IsNormalized, UnicodeNormalization};
+        match is_nfkc_quick(s.chars()) {
+            IsNormalized::Yes => Cow::Borrowed(s),
+            _ => Cow::Owned(s.nfkc().collect()),
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from("Nfkc")
+    }
+}
+
+/// A converter for the Unicode code points of strings, used by the string
+/// API of the indexes, such as `search_str`.
+pub trait StrConverter: Converter<u32> {
+    /// Normalize a string before its code points are converted.
+    ///
+    /// This is applied both to the text when creating an index and to
+    /// patterns when searching it. By default strings are left as they are.
+    fn normalize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(s)
+    }
+}
+
+impl StrConverter for RangeConverter<u32> {}
+
+/// Normalize strings before converting their code points with another
+/// converter.
+///
+/// Without normalization, strings that are canonically equivalent but
+/// encoded differently, such as `é` as a single code point or as `e`
+/// followed by a combining accent, don't match each other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NormalizingConverter<C, N> {
+    inner: C,
+    normalizer: N,
+}
+
+impl<C, N> NormalizingConverter<C, N> {
+    /// Create a converter that normalizes strings with `normalizer` and
+    /// converts their code points with `inner`.
+    pub fn new(inner: C, normalizer: N) -> Self {
+        NormalizingConverter { inner, normalizer }
+    }
+
+    /// The converter applied to the code points.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<C, N> Converter<u32> for NormalizingConverter<C, N>
+where
+    C: Converter<u32>,
+    N: Normalizer,
+{
+    fn convert(&self, c: u32) -> u32 {
+        self.inner.convert(c)
+    }
+
+    fn convert_inv(&self, c: u32) -> u32 {
+        self.inner.convert_inv(c)
+    }
+
+    fn len(&self) -> u64 {
+        self.inner.len()
+    }
+
+    fn name(&self) -> String {
+        format!(
+            "NormalizingConverter<{}, {}>",
+            self.inner.name(),
+            self.normalizer.name()
+        )
+    }
+
+    fn contains(&self, c: u32) -> bool {
+        self.inner.contains(c)
+    }
+}
+
+impl<C, N> StrConverter for NormalizingConverter<C, N>
+where
+    C: Converter<u32>,
+    N: Normalizer,
+{
+    fn normalize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        self.normalizer.normalize(s)
+    }
+}
+
+/// Treat fixed sequences of bytes, such as the digraphs `ch` and `ll`, as
+/// single characters.
+///
+/// Texts and patterns are encoded into `u32` characters with
+/// [`encode`](Self::encode) before indexing or searching: a byte `b` becomes
+/// the character `b`, and the `i`th sequence the character `256 + i`, so
+/// sequences sort after single bytes. At every position the longest
+/// sequence that matches is taken. A pattern then only matches whole
+/// sequences: with `ch` as a sequence, `c` doesn't match the start of `ch`.
+///
+/// The characters are converted as by a [`DenseConverter`] for the encoded
+/// text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SequenceConverter {
+    sequences: Vec<Vec<u8>>,
+    inner: DenseConverter<u32>,
+}
+
+impl SequenceConverter {
+    /// Create a converter for `text` that treats each of `sequences` as a
+    /// single character.
+    ///
+    /// The sequences must be at least two bytes long and must not contain
+    /// \0 bytes.
+    pub fn from_text(text: &[u8], sequences: &[&[u8]]) -> Self {
+        debug_assert!(
+            sequences.iter().all(|s| s.len() >= 2 && !s.contains(&0)),
+            "sequences must be at least two bytes long and must not contain \\0"
+        );
+        let mut converter = SequenceConverter {
+            sequences: sequences.iter().map(|s| s.to_vec()).collect(),
+            inner: DenseConverter::from_text(&[]),
+        };
+        converter.inner = DenseConverter::from_text(&converter.encode(text));
+        converter
+    }
+
+    /// Encode `text` into characters, replacing the sequences by single
+    /// characters.
+    pub fn encode(&self, text: &[u8]) -> Vec<u32> {
+        let mut result = Vec::with_capacity(text.len());
+        let mut i = 0;
+        while i < text.len() {
+            let longest = self
+                .sequences
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| text[i..].starts_with(s))
+                .max_by_key(|(_, s)| s.len());
+            match longest {
+                Some((k, s)) => {
+                    result.push(256 + k as u32);
+                    i += s.len();
+                }
+                None => {
+                    result.push(text[i] as u32);
+                    i += 1;
+                }
+            }
+        }
+        result
+    }
+
+    /// Decode characters produced by [`encode`](Self::encode) back into
+    /// bytes.
+    pub fn decode(&self, chars: &[u32]) -> Vec<u8> {
+        let mut result = Vec::with_capacity(chars.len());
+        for &c in chars {
+            match c.checked_sub(256) {
+                Some(k) => result.extend_from_slice(&self.sequences[k as usize]),
+                None => result.push(c as u8),
+            }
+        }
+        result
+    }
+}
+
+impl Converter<u32> for SequenceConverter {
+    fn convert(&self, c: u32) -> u32 {
+        self.inner.convert(c)
+    }
+
+    fn convert_inv(&self, c: u32) -> u32 {
+        self.inner.convert_inv(c)
+    }
+
+    fn len(&self) -> u64 {
+        self.inner.len()
+    }
+
+    fn name(&self) -> String {
+        String::from("SequenceConverter")
+    }
+
+    fn contains(&self, c: u32) -> bool {
+        self.inner.contains(c)
+    }
 }
@@ -0,0 +1,285 @@
+//! A compressed suffix array backend based on the Ψ function.
+use crate::backend::{HasPosition, HeapSize, SearchIndexBackend, SizeBreakdown};
+use crate::character::{prepare_text, Character};
+#[cfg(doc)]
+use crate::converter;
+use crate::converter::Converter;
+#[cfg(all(feature = "std", feature = "serde"))]
+use crate::persist::impl_sections;
+use crate::suffix_array::sais;
+use crate::suffix_array::sample::SuffixArraySamples;
+use crate::util;
+
+use alloc::{borrow::Cow, vec, vec::Vec};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use vers_vecs::EliasFanoVec;
+
+/// A compressed suffix array, based on the Ψ function.
+///
+/// `Ψ(i)` is the position in the suffix array of the suffix that follows
+/// `SA[i]` in the text. It is increasing within the rows that start with the
+/// same character, so it is stored as one Elias-Fano sequence per character.
+///
+/// Moving forward through the text and backward search are fast, while
+/// accessing the BWT takes time proportional to the size of the alphabet.
+///
+/// Sadakane, K. (2003). New text indexing functionalities of the compressed
+/// suffix arrays. Journal of Algorithms, 48(2), 294–313.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CSABackend<T, C, S> {
+    // psi[c] lists Ψ over the rows starting with c
+    psi: Vec<EliasFanoVec>,
+    cs: Vec<u64>,
+    converter: C,
+    suffix_array: S,
+    len: u64,
+    _t: core::marker::PhantomData<T>,
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl_sections!(CSABackend<T, C, S> {
+    psi: Vec<EliasFanoVec>,
+    cs: Vec<u64>,
+    converter: C,
+    #[load(Deferrable)]
+    #[part(Samples)]
+    suffix_array: S,
+    len: u64,
+});
+
+impl<T, C: PartialEq, S: PartialEq> PartialEq for CSABackend<T, C, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.psi.len() == other.psi.len()
+            && self
+                .psi
+                .iter()
+                .zip(&other.psi)
+                .all(|(a, b)| util::elias_fano_eq(a, b))
+            && self.cs == other.cs
+            && self.converter == other.converter
+            && self.suffix_array == other.suffix_array
+            && self.len == other.len
+    }
+}
+
+impl<T, C, S> CSABackend<T, C, S>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    pub(crate) fn new(text: Cow<'_, [T]>, converter: C, get_sample: impl Fn(&[u64]) -> S) -> Self {
+        let text = prepare_text(text, &converter);
+        let n = text.len();
+        let cs = sais::get_bucket_start_pos(&sais::count_chars(&text, &converter));
+        let sa = sais::build_suffix_array(&text, &converter);
+
+        // The rows starting with c are the images of the occurrences of c in
+        // the BWT, in order, so Ψ over them lists those occurrences.
+        let mut psi = vec![Vec::new(); converter.len() as usize];
+        for (i, &k) in sa.iter().enumerate() {
+            let k = k as usize;
+            let c = converter.convert(if k > 0 { text[k - 1] } else { text[n - 1] });
+            psi[c.into() as usize].push(i as u64);
+        }
+
+        CSABackend {
+            psi: psi.iter().map(|p| EliasFanoVec::from_slice(p)).collect(),
+            cs,
+            converter,
+            suffix_array: get_sample(&sa),
+            len: n as u64,
+            _t: core::marker::PhantomData::<T>,
+        }
+    }
+
+    // The converted character at L[i], together with its rank.
+    fn l_rank(&self, i: u64) -> (T, u64) {
+        for (c, psi) in self.psi.iter().enumerate() {
+            let rank = psi.rank(i);
+            if rank < psi.len() as u64 && psi.get_unchecked(rank as usize) == i {
+                return (T::from_u64(c as u64), rank);
+            }
+        }
+        unreachable!("{} is out of range", i)
+    }
+}
+
+impl<T, C> HeapSize for CSABackend<T, C, ()>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    fn heap_size(&self) -> usize {
+        self.size_breakdown().total()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new()
+            .add("psi", self.psi.iter().map(|p| p.heap_size()).sum::<usize>())
+            .add("cs", self.cs.capacity() * core::mem::size_of::<u64>())
+    }
+}
+
+impl<T, C, S> HeapSize for CSABackend<T, C, S>
+where
+    T: Character,
+    C: Converter<T>,
+    S: SuffixArraySamples,
+{
+    fn heap_size(&self) -> usize {
+        self.size_breakdown().total()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new()
+            .add("psi", self.psi.iter().map(|p| p.heap_size()).sum::<usize>())
+            .add("cs", self.cs.capacity() * core::mem::size_of::<u64>())
+            .add("suffix_array", self.suffix_array.size())
+    }
+}
+
+impl<T, C, S> SearchIndexBackend for CSABackend<T, C, S>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    type T = T;
+    type C = C;
+
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn get_l(&self, i: u64) -> Self::T {
+        self.l_rank(i).0
+    }
+
+    fn lf_map(&self, i: u64) -> u64 {
+        let (c, rank) = self.l_rank(i);
+        self.cs[c.into() as usize] + rank
+    }
+
+    fn lf_map2(&self, c: T, i: u64) -> u64 {
+        let c = self.converter.convert(c).into() as usize;
+        self.cs[c] + self.psi[c].rank(i)
+    }
+
+    fn search_interval(&self, pattern: &[T], s: u64, e: u64, min: u64) -> (u64, u64) {
+        let mut s = s;
+        let mut e = e;
+        for &c in pattern.iter().rev() {
+            let c = self.converter.convert(c).into() as usize;
+            let psi = &self.psi[c];
+            s = self.cs[c] + psi.rank(s);
+            e = self.cs[c] + psi.rank(e);
+            if e - s < min {
+                break;
+            }
+        }
+        (s, e)
+    }
+
+    fn get_f(&self, i: u64) -> Self::T {
+        // binary search to find c s.t. cs[c] <= i < cs[c+1]
+        let mut s = 0;
+        let mut e = self.cs.len();
+        while e - s > 1 {
+            let m = s + (e - s) / 2;
+            if self.cs[m] <= i {
+                s = m;
+            } else {
+                e = m;
+            }
+        }
+        T::from_u64(s as u64)
+    }
+
+    fn fl_map(&self, i: u64) -> u64 {
+        let c = self.get_f(i).into() as usize;
+        self.psi[c].get_unchecked((i - self.cs[c]) as usize)
+    }
+
+    fn interval_symbols(&self, s: u64, e: u64) -> Vec<(Self::T, u64)> {
+        self.psi
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(c, psi)| (T::from_u64(c as u64), psi.rank(e) - psi.rank(s)))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    fn get_converter(&self) -> &Self::C {
+        &self.converter
+    }
+}
+
+impl<T, C, S> HasPosition for CSABackend<T, C, S>
+where
+    T: Character,
+    C: Converter<T>,
+    S: SuffixArraySamples,
+{
+    fn get_sa_with_steps(&self, mut i: u64) -> (u64, u64) {
+        // Ψ moves forward through the text, so it is cheaper than LF here.
+        let mut steps = 0;
+        loop {
+            match self.suffix_array.get(i) {
+                Some(sa) => {
+                    return ((sa + self.len - steps % self.len) % self.len, steps);
+                }
+                None => {
+                    i = self.fl_map(i);
+                    steps += 1;
+                }
+            }
+        }
+    }
+
+    fn get_isa_sample(&self, p: u64) -> (u64, u64) {
+        self.suffix_array.get_inverse(p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{converter::RangeConverter, suffix_array::sample};
+
+    #[test]
+    fn test_lf_map() {
+        let text = "mississippi".to_string().into_bytes();
+        let ans = vec![1, 6, 7, 2, 8, 10, 3, 9, 11, 4, 5, 0];
+        let csa = CSABackend::new(text.into(), RangeConverter::new(b'a', b'z'), |sa| {
+            sample::sample(sa, 2)
+        });
+        let mut i = 0;
+        for a in ans {
+            i = csa.lf_map(i);
+            assert_eq!(i, a);
+        }
+    }
+
+    #[test]
+    fn test_fl_map() {
+        let text = "mississippi".to_string().into_bytes();
+        let csa = CSABackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_| ());
+        for i in 0..csa.len() {
+            assert_eq!(csa.lf_map(csa.fl_map(i)), i);
+        }
+    }
+
+    #[test]
+    fn test_get_sa() {
+        let text = "mississippi".to_string().into_bytes();
+        let ans = vec![11, 10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2];
+        let csa = CSABackend::new(text.into(), RangeConverter::new(b'a', b'z'), |sa| {
+            sample::sample(sa, 2)
+        });
+        for (i, a) in ans.into_iter().enumerate() {
+            assert_eq!(csa.get_sa(i as u64), a);
+        }
+    }
+}
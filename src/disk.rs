@@ -0,0 +1,573 @@
+//! FM-Indexes stored in a file and read on demand.
+//!
+//! The words of the wavelet matrix and of the suffix array samples stay in
+//! the file and are read a page at a time through a small cache. Only the
+//! rank directories of the wavelet matrix and the character counts are held
+//! in memory, so an index can be queried without loading it.
+//!
+//! The same file can also be queried in place from a buffer holding all of
+//! it, such as a memory map. Then the rank directories are read from the
+//! buffer as well, and nothing is copied.
+use crate::backend::{HasPosition, HeapSize, SearchIndexBackend, SizeBreakdown};
+use crate::character::{prepare_text, Character};
+use crate::converter::Converter;
+use crate::fm_index::bwt_symbols;
+use crate::lru::Lru;
+use crate::rank_wavelet::{
+    rank_directory, rank_directory_len, wavelet_levels, RankWaveletMatrix, Words,
+};
+use crate::suffix_array::sais;
+use crate::suffix_array::sample::{inverse_samples, nearest_inverse};
+use crate::util;
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use vers_vecs::BitVec;
+
+const MAGIC: &[u8; 8] = b"FMIDISK2";
+// The number of header words following the magic.
+const HEADER_WORDS: usize = 8;
+const PAGE_WORDS: usize = 512;
+const PAGE_BYTES: usize = PAGE_WORDS * 8;
+
+struct CacheState {
+    file: File,
+    pages: Lru<u64, Box<[u64]>>,
+}
+
+/// A cache of the pages of a file, read as little-endian 64-bit words.
+///
+/// When the cache is full, the least recently used page is evicted.
+struct PageCache {
+    state: Mutex<CacheState>,
+}
+
+impl PageCache {
+    fn new(file: File, capacity: usize) -> Self {
+        PageCache {
+            state: Mutex::new(CacheState {
+                file,
+                pages: Lru::new(capacity.max(1)),
+            }),
+        }
+    }
+
+    /// The word at index `i` of the file.
+    fn word(&self, i: usize) -> io::Result<u64> {
+        let page = (i / PAGE_WORDS) as u64;
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        if let Some(words) = state.pages.get(&page) {
+            return Ok(words[i % PAGE_WORDS]);
+        }
+        let words = read_page(&mut state.file, page)?;
+        let word = words[i % PAGE_WORDS];
+        state.pages.insert(page, words);
+        Ok(word)
+    }
+
+    fn heap_size(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .pages
+            .heap_size(|_| 0, |words| words.len() * 8)
+    }
+}
+
+// Read a page, padding it with zeros past the end of the file.
+fn read_page(file: &mut File, page: u64) -> io::Result<Box<[u64]>> {
+    let mut bytes = vec![0u8; PAGE_BYTES];
+    file.seek(SeekFrom::Start(page * PAGE_BYTES as u64))?;
+    let mut read = 0;
+    while read < PAGE_BYTES {
+        match file.read(&mut bytes[read..]) {
+            Ok(0) => break,
+            Ok(k) => read += k,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(bytes
+        .chunks_exact(8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .collect())
+}
+
+/// Where the words of an index file are read from.
+enum Source {
+    /// The file, read a page at a time through a cache.
+    Paged(PageCache),
+    /// A buffer holding the whole file.
+    Mapped(Box<dyn AsRef<[u8]> + Send + Sync>),
+}
+
+impl Source {
+    /// The word at index `i` of the file, or 0 past its end.
+    fn try_word(&self, i: usize) -> io::Result<u64> {
+        match self {
+            Source::Paged(cache) => cache.word(i),
+            Source::Mapped(bytes) => Ok((**bytes)
+                .as_ref()
+                .get(i * 8..i * 8 + 8)
+                .map_or(0, |b| u64::from_le_bytes(b.try_into().unwrap()))),
+        }
+    }
+
+    /// The word at index `i` of the file.
+    ///
+    /// Panics if the file can no longer be read.
+    fn word(&self, i: usize) -> u64 {
+        self.try_word(i).expect("failed to read index file")
+    }
+
+    fn heap_size(&self) -> usize {
+        match self {
+            Source::Paged(cache) => cache.heap_size(),
+            Source::Mapped(_) => 0,
+        }
+    }
+}
+
+/// The words of a bit vector stored in a file, starting at word `offset`.
+pub(crate) struct DiskWords {
+    source: Arc<Source>,
+    offset: usize,
+}
+
+impl Words for DiskWords {
+    fn word(&self, i: usize) -> u64 {
+        self.source.word(self.offset + i)
+    }
+
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl DiskWords {
+    // The `width` bits starting at bit `pos`.
+    fn get_bits(&self, pos: usize, width: usize) -> u64 {
+        let shift = pos % 64;
+        let mut bits = self.word(pos / 64) >> shift;
+        if shift + width > 64 {
+            bits |= self.word(pos / 64 + 1) << (64 - shift);
+        }
+        if width < 64 {
+            bits &= (1 << width) - 1;
+        }
+        bits
+    }
+}
+
+/// Suffix array samples stored in a file.
+///
+/// These are sampled like [`SuffixOrderSampledArray`](crate::SuffixOrderSampledArray):
+/// every `2^level`-th row of the suffix array, and the inverse suffix array
+/// at every text position that is a multiple of `2^level`.
+struct DiskSamples {
+    sa: DiskWords,
+    isa: DiskWords,
+    level: usize,
+    word_size: usize,
+    len: usize,
+    hot: Option<HotSamples>,
+}
+
+/// A coarser level of the suffix array samples, copied into memory.
+struct HotSamples {
+    level: usize,
+    sa: BitVec,
+    // the number of LF steps after which the samples on disk are consulted
+    threshold: u64,
+}
+
+impl DiskSamples {
+    /// The suffix array value at row `i`, if it was sampled, when `i` was
+    /// reached after `steps` LF steps.
+    fn get(&self, i: u64, steps: u64) -> Option<u64> {
+        debug_assert!(i < self.len as u64);
+        if let Some(hot) = &self.hot {
+            if i & ((1 << hot.level) - 1) == 0 {
+                let j = (i as usize >> hot.level) * self.word_size;
+                return Some(hot.sa.get_bits_unchecked(j, self.word_size));
+            }
+            if steps < hot.threshold {
+                return None;
+            }
+        }
+        self.get_cold(i)
+    }
+
+    fn get_cold(&self, i: u64) -> Option<u64> {
+        if i & ((1 << self.level) - 1) == 0 {
+            Some(
+                self.sa
+                    .get_bits((i as usize >> self.level) * self.word_size, self.word_size),
+            )
+        } else {
+            None
+        }
+    }
+
+    fn get_inverse(&self, p: u64) -> (u64, u64) {
+        nearest_inverse(1 << self.level, self.len, p, |j| {
+            self.isa.get_bits(j * self.word_size, self.word_size)
+        })
+    }
+
+    fn set_hot(&mut self, level: usize, threshold: u64) {
+        assert!(
+            level >= self.level,
+            "the in-memory sampling level must be at least that of the file (L = {}, file L = {})",
+            level,
+            self.level,
+        );
+        let count = ((self.len - 1) >> level) + 1;
+        let mut sa = BitVec::with_capacity(count * self.word_size);
+        for k in 0..count {
+            sa.append_bits(self.get_cold((k << level) as u64).unwrap(), self.word_size);
+        }
+        self.hot = Some(HotSamples {
+            level,
+            sa,
+            threshold,
+        });
+    }
+
+    fn heap_size(&self) -> usize {
+        self.hot.as_ref().map_or(0, |hot| hot.sa.heap_size())
+    }
+}
+
+// Pack values of `width` bits into words, least significant bit first.
+fn pack(values: &[u64], width: usize) -> Vec<u64> {
+    let mut words = vec![0u64; (values.len() * width).div_ceil(64)];
+    for (i, &v) in values.iter().enumerate() {
+        let pos = i * width;
+        let shift = pos % 64;
+        words[pos / 64] |= v << shift;
+        if shift + width > 64 {
+            words[pos / 64 + 1] |= v >> (64 - shift);
+        }
+    }
+    words
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// An FM-Index whose BWT and suffix array samples are read from a file on
+/// demand.
+///
+/// The file layout is a magic number followed by little-endian 64-bit
+/// words: the header, the character counts, the number of zeros in each
+/// level of the wavelet matrix, the rank directory of each level, the
+/// words of each level, and the packed suffix array and inverse suffix
+/// array samples.
+///
+/// The rank directories are held in `D`: in memory when the file is read
+/// through the page cache, and in place when it is mapped.
+pub struct DiskFMIndexBackend<T, C, D = Vec<u64>> {
+    bw: RankWaveletMatrix<DiskWords, D>,
+    cs: Vec<u64>,
+    converter: C,
+    samples: DiskSamples,
+    source: Arc<Source>,
+    _t: std::marker::PhantomData<T>,
+}
+
+impl<T, C> DiskFMIndexBackend<T, C>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    /// Build the index of `text` and write it to `path`.
+    pub(crate) fn create<P: AsRef<Path>>(
+        path: P,
+        text: Cow<'_, [T]>,
+        converter: &C,
+        level: usize,
+    ) -> io::Result<()> {
+        let text = prepare_text(text, converter);
+        let n = text.len();
+        debug_assert!(
+            n > (1 << level),
+            "sampling level L must satisfy 2^L < text_len (L = {}, text_len = {})",
+            level,
+            n,
+        );
+        let cs = sais::get_bucket_start_pos(&sais::count_chars(&text, converter));
+        let sa = sais::build_suffix_array(&text, converter);
+        let (levels, zeros) = wavelet_levels(&bwt_symbols(&text, &sa, converter), converter.len());
+        drop(text);
+        let directories = levels
+            .iter()
+            .flat_map(|words| {
+                let (super_blocks, blocks) = rank_directory(words, n);
+                super_blocks.into_iter().chain(blocks)
+            })
+            .collect::<Vec<_>>();
+
+        let word_size = (util::log2(n as u64) + 1) as usize;
+        let sa_samples = sa.iter().step_by(1 << level).copied().collect::<Vec<_>>();
+        let sa_words = pack(&sa_samples, word_size);
+        let isa_words = pack(&inverse_samples(&sa, 1 << level), word_size);
+        drop(sa);
+
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(MAGIC)?;
+        let header = [
+            n as u64,
+            converter.len(),
+            levels.len() as u64,
+            level as u64,
+            word_size as u64,
+            sa_words.len() as u64,
+            isa_words.len() as u64,
+            cs.len() as u64,
+        ];
+        let zeros = zeros.iter().map(|&z| z as u64).collect::<Vec<_>>();
+        let body = header
+            .iter()
+            .chain(&cs)
+            .chain(&zeros)
+            .chain(&directories)
+            .chain(levels.iter().flatten())
+            .chain(&sa_words)
+            .chain(&isa_words);
+        for word in body {
+            out.write_all(&word.to_le_bytes())?;
+        }
+        out.flush()
+    }
+
+    /// Open the index stored at `path`, caching up to `cache_pages` pages of
+    /// 4 KiB.
+    pub(crate) fn open<P: AsRef<Path>>(
+        path: P,
+        converter: C,
+        cache_pages: usize,
+    ) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let source = Source::Paged(PageCache::new(file, cache_pages));
+        Self::load(source, file_len, converter, |source, offset, len| {
+            (offset..offset + len).map(|i| source.try_word(i)).collect()
+        })
+    }
+
+    /// Copy every `2^level`-th suffix array sample into memory, consulting
+    /// the samples in the file only after `threshold` LF steps.
+    pub(crate) fn set_hot_samples(&mut self, level: usize, threshold: u64) {
+        self.samples.set_hot(level, threshold);
+    }
+}
+
+impl<T, C> DiskFMIndexBackend<T, C, DiskWords>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    /// Query the index file held in `bytes` in place.
+    pub(crate) fn map<B>(bytes: B, converter: C) -> io::Result<Self>
+    where
+        B: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        let len = bytes.as_ref().len() as u64;
+        let source = Source::Mapped(Box::new(bytes));
+        Self::load(source, len, converter, |source, offset, _| {
+            Ok(DiskWords {
+                source: Arc::clone(source),
+                offset,
+            })
+        })
+    }
+}
+
+impl<T, C, D> DiskFMIndexBackend<T, C, D>
+where
+    T: Character,
+    C: Converter<T>,
+    D: Words,
+{
+    // Read the layout of the file of `file_len` bytes in `source`. The rank
+    // directory of `len` words at word `offset` is obtained by `directory`.
+    fn load(
+        source: Source,
+        file_len: u64,
+        converter: C,
+        directory: impl Fn(&Arc<Source>, usize, usize) -> io::Result<D>,
+    ) -> io::Result<Self> {
+        if file_len < ((HEADER_WORDS + 1) * 8) as u64 {
+            return Err(invalid("not an FM-Index file"));
+        }
+        let source = Arc::new(source);
+        let read_words = |offset: usize, count: usize| -> io::Result<Vec<u64>> {
+            (offset..offset + count)
+                .map(|i| source.try_word(i))
+                .collect()
+        };
+        if read_words(0, 1)?[0] != u64::from_le_bytes(*MAGIC) {
+            return Err(invalid("not an FM-Index file"));
+        }
+        let header = read_words(1, HEADER_WORDS)?;
+        let [n, alphabet_size, level_count, level, word_size, sa_len, isa_len, cs_len] =
+            header[..].try_into().unwrap();
+        if alphabet_size != converter.len() {
+            return Err(invalid("the index was built with a different converter"));
+        }
+        let level_words = n.div_ceil(64);
+        let (super_block_words, block_words) = rank_directory_len(n as usize);
+        let directory_words = (super_block_words + block_words) as u64;
+        let expected = (1 + HEADER_WORDS as u64 + cs_len + level_count)
+            .checked_add(level_count.saturating_mul(directory_words + level_words))
+            .and_then(|w| w.checked_add(sa_len))
+            .and_then(|w| w.checked_add(isa_len))
+            .and_then(|w| w.checked_mul(8));
+        if expected != Some(file_len) {
+            return Err(invalid("the index file is truncated or corrupt"));
+        }
+        let mut offset = 1 + HEADER_WORDS;
+        let cs = read_words(offset, cs_len as usize)?;
+        offset += cs_len as usize;
+        let zeros = read_words(offset, level_count as usize)?
+            .into_iter()
+            .map(|z| z as usize)
+            .collect();
+        offset += level_count as usize;
+
+        let mut directories = Vec::with_capacity(level_count as usize);
+        for _ in 0..level_count {
+            let super_blocks = directory(&source, offset, super_block_words)?;
+            offset += super_block_words;
+            let blocks = directory(&source, offset, block_words)?;
+            offset += block_words;
+            directories.push((super_blocks, blocks));
+        }
+        let words_at = |offset: usize| DiskWords {
+            source: Arc::clone(&source),
+            offset,
+        };
+        let mut levels = Vec::with_capacity(level_count as usize);
+        for (super_blocks, blocks) in directories {
+            levels.push((words_at(offset), super_blocks, blocks));
+            offset += level_words as usize;
+        }
+        let samples = DiskSamples {
+            sa: words_at(offset),
+            isa: words_at(offset + sa_len as usize),
+            level: level as usize,
+            word_size: word_size as usize,
+            len: n as usize,
+            hot: None,
+        };
+
+        Ok(DiskFMIndexBackend {
+            bw: RankWaveletMatrix::from_parts(levels, zeros, n as usize),
+            cs,
+            converter,
+            samples,
+            source,
+            _t: std::marker::PhantomData,
+        })
+    }
+}
+
+impl<T, C, D> HeapSize for DiskFMIndexBackend<T, C, D>
+where
+    T: Character,
+    C: Converter<T>,
+    D: Words,
+{
+    fn heap_size(&self) -> usize {
+        self.size_breakdown().total()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new()
+            .add("bwt", self.bw.heap_size())
+            .add("cs", self.cs.capacity() * std::mem::size_of::<u64>())
+            .add("suffix_array", self.samples.heap_size())
+            .add("cache", self.source.heap_size())
+    }
+}
+
+impl<T, C, D> SearchIndexBackend for DiskFMIndexBackend<T, C, D>
+where
+    T: Character,
+    C: Converter<T>,
+    D: Words,
+{
+    type T = T;
+    type C = C;
+
+    fn len(&self) -> u64 {
+        self.bw.len() as u64
+    }
+
+    fn get_l(&self, i: u64) -> Self::T {
+        Self::T::from_u64(self.bw.get(i as usize))
+    }
+
+    fn lf_map(&self, i: u64) -> u64 {
+        let c = self.bw.get(i as usize);
+        self.cs[c as usize] + self.bw.rank(i as usize, c) as u64
+    }
+
+    fn lf_map2(&self, c: T, i: u64) -> u64 {
+        let c = self.converter.convert(c).into();
+        self.cs[c as usize] + self.bw.rank(i as usize, c) as u64
+    }
+
+    fn get_f(&self, i: u64) -> Self::T {
+        // the greatest c such that cs[c] <= i
+        let c = self.cs.partition_point(|&start| start <= i) - 1;
+        T::from_u64(c as u64)
+    }
+
+    fn fl_map(&self, i: u64) -> u64 {
+        let c = self.get_f(i).into();
+        self.bw.select(i as usize - self.cs[c as usize] as usize, c) as u64
+    }
+
+    fn interval_symbols(&self, s: u64, e: u64) -> Vec<(Self::T, u64)> {
+        self.bw
+            .interval_symbols(s as usize, e as usize)
+            .into_iter()
+            .map(|(c, count)| (T::from_u64(c), count))
+            .collect()
+    }
+
+    fn get_converter(&self) -> &Self::C {
+        &self.converter
+    }
+}
+
+impl<T, C, D> HasPosition for DiskFMIndexBackend<T, C, D>
+where
+    T: Character,
+    C: Converter<T>,
+    D: Words,
+{
+    fn get_sa_with_steps(&self, mut i: u64) -> (u64, u64) {
+        let mut steps = 0;
+        loop {
+            match self.samples.get(i, steps) {
+                Some(sa) => return ((sa + steps) % self.bw.len() as u64, steps),
+                None => {
+                    i = self.lf_map(i);
+                    steps += 1;
+                }
+            }
+        }
+    }
+
+    fn get_isa_sample(&self, p: u64) -> (u64, u64) {
+        self.samples.get_inverse(p)
+    }
+}
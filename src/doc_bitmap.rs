@@ -0,0 +1,112 @@
+//! Bitmaps of texts in a multi-text index.
+use alloc::{vec, vec::Vec};
+use core::ops::{BitAnd, BitOr, Sub};
+
+use crate::backend::HeapSize;
+use crate::frontend::DocId;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A set of texts of a multi-text index, stored as a plain bitmap with one
+/// bit per text.
+///
+/// Bitmaps of the same index can be combined with `&` (intersection), `|`
+/// (union) and `-` (difference), which makes boolean combinations of many
+/// queries cheap.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DocBitmap {
+    words: Vec<u64>,
+    len: u64,
+}
+
+impl DocBitmap {
+    /// Create an empty bitmap for `len` texts.
+    pub fn new(len: u64) -> Self {
+        DocBitmap {
+            words: vec![0; len.div_ceil(64) as usize],
+            len,
+        }
+    }
+
+    /// Add text `doc_id` to the set.
+    pub fn insert(&mut self, doc_id: DocId) {
+        debug_assert!(doc_id < self.len, "{} is out of range", doc_id);
+        self.words[(doc_id / 64) as usize] |= 1 << (doc_id % 64);
+    }
+
+    /// Whether text `doc_id` is in the set.
+    pub fn contains(&self, doc_id: DocId) -> bool {
+        doc_id < self.len && self.words[(doc_id / 64) as usize] & (1 << (doc_id % 64)) != 0
+    }
+
+    /// The number of texts the bitmap covers, i.e. the number of texts in
+    /// the index.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// The number of texts in the set.
+    pub fn count(&self) -> u64 {
+        self.words.iter().map(|w| w.count_ones() as u64).sum()
+    }
+
+    /// Iterate over the ids of the texts in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = DocId> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &w)| {
+            let mut w = w;
+            core::iter::from_fn(move || {
+                if w == 0 {
+                    return None;
+                }
+                let bit = w.trailing_zeros() as u64;
+                w &= w - 1;
+                Some(i as u64 * 64 + bit)
+            })
+        })
+    }
+
+    fn combine(&self, other: &DocBitmap, f: impl Fn(u64, u64) -> u64) -> DocBitmap {
+        debug_assert_eq!(self.len, other.len, "bitmaps cover different texts");
+        DocBitmap {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            len: self.len,
+        }
+    }
+}
+
+impl BitAnd for &DocBitmap {
+    type Output = DocBitmap;
+
+    fn bitand(self, other: &DocBitmap) -> DocBitmap {
+        self.combine(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &DocBitmap {
+    type Output = DocBitmap;
+
+    fn bitor(self, other: &DocBitmap) -> DocBitmap {
+        self.combine(other, |a, b| a | b)
+    }
+}
+
+impl Sub for &DocBitmap {
+    type Output = DocBitmap;
+
+    fn sub(self, other: &DocBitmap) -> DocBitmap {
+        self.combine(other, |a, b| a & !b)
+    }
+}
+
+impl HeapSize for DocBitmap {
+    fn heap_size(&self) -> usize {
+        self.words.capacity() * core::mem::size_of::<u64>()
+    }
+}
@@ -0,0 +1,151 @@
+//! Object safe traits to query indexes of any type through trait objects.
+use crate::snapshot::SearchSnapshot;
+
+use alloc::{boxed::Box, vec::Vec};
+
+/// Trait for searching in an index through a trait object.
+///
+/// [`SearchIndex`](crate::SearchIndex) can't be used as a trait object, as
+/// its methods are generic. This trait is object safe, so indexes of
+/// different types can be stored as `Box<dyn DynSearchIndex<T>>` and
+/// queried alike. Every index implements it, whether it supports locate
+/// queries or not.
+pub trait DynSearchIndex<T> {
+    /// Search for a pattern in the text.
+    fn search(&self, pattern: &[T]) -> Box<dyn DynSearch<T> + '_>;
+
+    /// The size of the text in the index, including the terminator.
+    fn len(&self) -> u64;
+
+    /// The length of the text in the index, without the terminator.
+    fn text_len(&self) -> u64 {
+        self.len() - 1
+    }
+
+    /// Whether [`locate`](DynSearch::locate) is supported by the index.
+    fn supports_locate(&self) -> bool;
+
+    /// Count the number of occurrences of a pattern.
+    fn count(&self, pattern: &[T]) -> u64 {
+        self.search(pattern).count()
+    }
+
+    /// List the positions of all occurrences of a pattern, or `None` if the
+    /// index doesn't support locate queries.
+    fn locate(&self, pattern: &[T]) -> Option<Vec<u64>> {
+        self.search(pattern).locate()
+    }
+}
+
+/// The result of a search through a trait object.
+///
+/// This is the object safe counterpart of [`Search`](crate::Search),
+/// returned by [`DynSearchIndex::search`].
+pub trait DynSearch<T> {
+    /// Search in the current search result, refining it.
+    ///
+    /// This adds a prefix `pattern` to the existing pattern, and looks for
+    /// those expanded patterns in the text.
+    fn search(&self, pattern: &[T]) -> Box<dyn DynSearch<T> + '_>;
+
+    /// Extend the pattern by prepending the character `c`, a single step
+    /// of backward search.
+    fn extend_backward(&self, c: T) -> Box<dyn DynSearch<T> + '_> {
+        self.search(core::slice::from_ref(&c))
+    }
+
+    /// The pattern searched for.
+    fn pattern(&self) -> &[T];
+
+    /// Count the number of occurrences.
+    fn count(&self) -> u64;
+
+    /// List the positions of all occurrences, or `None` if the index
+    /// doesn't support locate queries.
+    ///
+    /// Positions are offsets in the text, less than
+    /// [`DynSearchIndex::text_len`].
+    fn locate(&self) -> Option<Vec<u64>>;
+
+    /// Get an iterator that goes backwards through the text from occurrence
+    /// `i`, ending at the start of the text.
+    ///
+    /// In a multi-text index it ends at the start of the text containing the
+    /// occurrence.
+    fn iter_backward(&self, i: u64) -> Box<dyn Iterator<Item = T> + '_>;
+
+    /// Get an iterator that goes forwards through the text from occurrence
+    /// `i`, ending at the terminator.
+    ///
+    /// In a multi-text index it ends at the end of the text containing the
+    /// occurrence.
+    fn iter_forward(&self, i: u64) -> Box<dyn Iterator<Item = T> + '_>;
+
+    /// A snapshot of this search result, which can be turned back into a
+    /// search result with `resume` on the index.
+    fn snapshot(&self) -> SearchSnapshot<T>;
+}
+
+/// Implement [`DynSearchIndex`] for an index type and [`DynSearch`] for its
+/// search results, with `locate` if the index supports it and `count`
+/// otherwise. `$st` is the type of the search results.
+macro_rules! impl_dyn_search {
+    (@locate count, $search:expr) => {
+        None
+    };
+    (@locate locate, $search:expr) => {
+        Some($crate::SearchWithLocate::locate($search))
+    };
+    (@supports count) => {
+        false
+    };
+    (@supports locate) => {
+        true
+    };
+    ($kind:ident, $t:ty, $st:ty $(, $g:ident $(: $b:path)?)*) => {
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $crate::DynSearchIndex<T> for $t {
+            fn search(&self, pattern: &[T]) -> alloc::boxed::Box<dyn $crate::DynSearch<T> + '_> {
+                alloc::boxed::Box::new(<$t>::search(self, pattern))
+            }
+
+            fn len(&self) -> u64 {
+                <$t>::len(self)
+            }
+
+            fn supports_locate(&self) -> bool {
+                impl_dyn_search!(@supports $kind)
+            }
+        }
+
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $crate::DynSearch<T> for $st {
+            fn search(&self, pattern: &[T]) -> alloc::boxed::Box<dyn $crate::DynSearch<T> + '_> {
+                alloc::boxed::Box::new($crate::Search::search(self, pattern))
+            }
+
+            fn pattern(&self) -> &[T] {
+                $crate::Search::pattern(self)
+            }
+
+            fn count(&self) -> u64 {
+                $crate::Search::count(self)
+            }
+
+            fn locate(&self) -> Option<alloc::vec::Vec<u64>> {
+                impl_dyn_search!(@locate $kind, self)
+            }
+
+            fn iter_backward(&self, i: u64) -> alloc::boxed::Box<dyn Iterator<Item = T> + '_> {
+                alloc::boxed::Box::new($crate::Search::iter_backward_in_text(self, i))
+            }
+
+            fn iter_forward(&self, i: u64) -> alloc::boxed::Box<dyn Iterator<Item = T> + '_> {
+                alloc::boxed::Box::new($crate::Search::iter_forward_in_text(self, i))
+            }
+
+            fn snapshot(&self) -> $crate::SearchSnapshot<T> {
+                <$st>::snapshot(self)
+            }
+        }
+    };
+}
+pub(crate) use impl_dyn_search;
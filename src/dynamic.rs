@@ -0,0 +1,259 @@
+//! An index supporting insertion and deletion of texts.
+use crate::backend::{HeapSize, SizeBreakdown};
+use crate::character::{check_text, Character, IntoText};
+use crate::converter::Converter;
+use crate::error::Error;
+use crate::frontend::{concat_texts, DocId};
+use crate::multi_text::MultiTextFMIndexBackend;
+use crate::suffix_array::sample::{self, SuffixOrderSampledArray};
+use crate::util;
+use crate::wrapper::SearchIndexWrapper;
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
+
+// A segment is an immutable multi-text index whose texts carry their ids.
+type Segment<T, C> =
+    SearchIndexWrapper<MultiTextFMIndexBackend<T, C, SuffixOrderSampledArray, DocId>>;
+
+struct State<T: Character, C: Converter<T>> {
+    segments: Vec<Arc<Segment<T, C>>>,
+    // ids of the deleted texts; ids are never reused
+    deleted: HashSet<DocId>,
+    next_id: DocId,
+}
+
+/// A multi-text index with locate support to which texts can be added and
+/// from which they can be deleted.
+///
+/// Each inserted text is indexed as a segment of its own. Deleting a text
+/// only marks it, and searches skip its occurrences. Compacting the index
+/// rebuilds all segments into one without the deleted texts; this can run
+/// on a background thread, as all methods take `&self` and searches remain
+/// available while the new segment is built.
+///
+/// Texts are identified by the [`DocId`] returned when inserting them,
+/// which stays the same across compactions.
+pub struct DynamicFMIndex<T: Character, C: Converter<T>> {
+    converter: C,
+    level: usize,
+    state: RwLock<State<T, C>>,
+    // held while compacting, so compactions don't overlap
+    compaction: Mutex<()>,
+}
+
+impl<T, C> DynamicFMIndex<T, C>
+where
+    T: Character,
+    C: Converter<T> + Clone,
+{
+    /// Create an empty index.
+    ///
+    /// Segments sample their suffix array at `level`, as for
+    /// [`MultiTextFMIndexWithLocate::new`](crate::MultiTextFMIndexWithLocate::new),
+    /// or at a lower level for segments of short texts.
+    pub fn new(converter: C, level: usize) -> Self {
+        DynamicFMIndex {
+            converter,
+            level,
+            state: RwLock::new(State {
+                segments: Vec::new(),
+                deleted: HashSet::new(),
+                next_id: 0,
+            }),
+            compaction: Mutex::new(()),
+        }
+    }
+
+    fn build_segment(&self, docs: Vec<(Vec<T>, DocId)>) -> Segment<T, C> {
+        let (texts, ids): (Vec<_>, Vec<_>) = docs.into_iter().unzip();
+        let text = concat_texts(texts);
+        // the sampling level must satisfy 2^level < text_len
+        let level = self.level.min(util::log2(text.len() as u64 - 1) as usize);
+        SearchIndexWrapper::new(
+            MultiTextFMIndexBackend::new(text.into(), self.converter.clone(), |sa| {
+                sample::sample(sa, level)
+            })
+            .with_metadata(ids),
+        )
+    }
+
+    /// Add a text to the index, returning its id.
+    ///
+    /// Returns an [`Error`] if the text is empty, contains a \0 other than
+    /// a final terminator or has a character outside the converter's
+    /// alphabet; the text isn't added then.
+    pub fn insert_doc<'t>(&self, text: impl IntoText<'t, T>) -> Result<DocId, Error> {
+        let mut text = text.into_text().into_owned();
+        check_text(&text, &self.converter)?;
+        // the segment adds its own terminator
+        if text[text.len() - 1].is_zero() {
+            text.pop();
+        }
+        let doc_id = {
+            let mut state = self.state.write().unwrap();
+            state.next_id += 1;
+            state.next_id - 1
+        };
+        let segment = Arc::new(self.build_segment(vec![(text, doc_id)]));
+        self.state.write().unwrap().segments.push(segment);
+        Ok(doc_id)
+    }
+
+    /// Mark text `doc_id` as deleted.
+    ///
+    /// Returns `false` if it was already deleted, or if no text has that
+    /// id.
+    pub fn delete_doc(&self, doc_id: DocId) -> bool {
+        let mut state = self.state.write().unwrap();
+        // ids aren't reused, so an id yet to be handed out mustn't be marked
+        doc_id < state.next_id && state.deleted.insert(doc_id)
+    }
+
+    /// Whether text `doc_id` was marked as deleted.
+    pub fn is_doc_deleted(&self, doc_id: DocId) -> bool {
+        self.state.read().unwrap().deleted.contains(&doc_id)
+    }
+
+    /// The number of texts in the index that were not deleted.
+    pub fn doc_count(&self) -> u64 {
+        let state = self.state.read().unwrap();
+        state.next_id - state.deleted.len() as u64
+    }
+
+    /// The number of segments the index consists of.
+    pub fn segment_count(&self) -> usize {
+        self.state.read().unwrap().segments.len()
+    }
+
+    /// Count the number of occurrences of `pattern` in the texts that were
+    /// not deleted.
+    pub fn count<K: AsRef<[T]>>(&self, pattern: K) -> u64 {
+        let pattern = pattern.as_ref();
+        let state = self.state.read().unwrap();
+        state
+            .segments
+            .iter()
+            .map(|segment| {
+                let search = segment.search(pattern);
+                if state.deleted.is_empty() {
+                    return search.count();
+                }
+                let (s, e) = search.get_range();
+                let backend = segment.backend();
+                backend
+                    .docs_with_tf(s, e)
+                    .into_iter()
+                    .filter(|&(doc, _)| !state.deleted.contains(backend.metadata(doc).unwrap()))
+                    .map(|(_, tf)| tf)
+                    .sum::<u64>()
+            })
+            .sum()
+    }
+
+    /// List the occurrences of `pattern` in the texts that were not deleted,
+    /// each as the id of the text and the offset within it.
+    ///
+    /// The occurrences are in no particular order.
+    pub fn locate<K: AsRef<[T]>>(&self, pattern: K) -> Vec<(DocId, u64)> {
+        let pattern = pattern.as_ref();
+        let state = self.state.read().unwrap();
+        let mut result = Vec::new();
+        for segment in &state.segments {
+            let backend = segment.backend();
+            for pos in segment.search(pattern).locate() {
+                let (doc, offset) = backend.doc_position(pos);
+                let doc_id = *backend.metadata(doc).unwrap();
+                if !state.deleted.contains(&doc_id) {
+                    result.push((doc_id, offset));
+                }
+            }
+        }
+        result
+    }
+
+    /// Rebuild the segments into a single one without the deleted texts.
+    ///
+    /// Searches, insertions and deletions can continue while the new
+    /// segment is built; texts inserted in the meantime stay in segments of
+    /// their own.
+    pub fn compact(&self) {
+        let _compaction = self.compaction.lock().unwrap();
+        let (segments, deleted) = {
+            let state = self.state.read().unwrap();
+            (state.segments.clone(), state.deleted.clone())
+        };
+
+        let mut docs = Vec::new();
+        let mut doc_total = 0;
+        for segment in &segments {
+            let backend = segment.backend();
+            doc_total += backend.doc_count();
+            for (doc, text) in backend.texts().into_iter().enumerate() {
+                let doc_id = *backend.metadata(doc as u64).unwrap();
+                if !deleted.contains(&doc_id) {
+                    docs.push((text, doc_id));
+                }
+            }
+        }
+        if segments.len() <= 1 && docs.len() as u64 == doc_total {
+            return;
+        }
+        let merged = if docs.is_empty() {
+            None
+        } else {
+            Some(Arc::new(self.build_segment(docs)))
+        };
+
+        // only compaction removes segments, so the ones compacted are still
+        // at the front
+        let mut state = self.state.write().unwrap();
+        let added = state.segments.split_off(segments.len());
+        state.segments = merged.into_iter().chain(added).collect();
+    }
+}
+
+impl<T, C> DynamicFMIndex<T, C>
+where
+    T: Character + Send + Sync + 'static,
+    C: Converter<T> + Clone + Send + Sync + 'static,
+{
+    /// Compact the index on a new thread.
+    ///
+    /// See [`compact`](Self::compact).
+    pub fn compact_in_background(self: &Arc<Self>) -> JoinHandle<()> {
+        let index = Arc::clone(self);
+        std::thread::spawn(move || index.compact())
+    }
+}
+
+impl<T, C> HeapSize for DynamicFMIndex<T, C>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    fn heap_size(&self) -> usize {
+        let state = self.state.read().unwrap();
+        state
+            .segments
+            .iter()
+            .map(|segment| segment.heap_size())
+            .sum::<usize>()
+            + state.deleted.capacity() * std::mem::size_of::<DocId>()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        let state = self.state.read().unwrap();
+        state
+            .segments
+            .iter()
+            .fold(SizeBreakdown::new(), |breakdown, segment| {
+                breakdown.merge(segment.size_breakdown())
+            })
+            .add(
+                "deleted",
+                state.deleted.capacity() * std::mem::size_of::<DocId>(),
+            )
+    }
+}
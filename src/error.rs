@@ -0,0 +1,87 @@
+//! Errors reported when creating or querying an index.
+use crate::character::Character;
+use crate::converter::InvalidCharacter;
+
+/// A reason a text can't be indexed, returned by the `try_new`
+/// constructors, or a reason a query can't be answered, returned by the
+/// checked queries such as `try_search`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The text is empty, or consists of just a \0 terminator.
+    EmptyText,
+    /// The text contains a \0 other than a final terminator, which a search
+    /// could match across. The texts of a multi-text index are separated by
+    /// \0, so there, as for `new`, it's only an error for the text to end
+    /// with more than one \0.
+    TerminatorInText {
+        /// The position of the first \0 that isn't allowed.
+        position: usize,
+    },
+    /// A character of the text or pattern isn't in the alphabet of the
+    /// converter.
+    CharacterOutOfRange {
+        /// The position of the character in the text or pattern.
+        position: usize,
+        /// The character, as a `u64`.
+        character: u64,
+    },
+    /// An offset into a search result isn't less than the number of
+    /// occurrences in it.
+    OffsetOutOfRange {
+        /// The offset asked for.
+        offset: u64,
+        /// The number of occurrences in the search result.
+        len: u64,
+    },
+    /// The sampling level is too large for the text: the sampling rate
+    /// `2^level` must be less than the length of the text, including its
+    /// terminator.
+    InvalidLevel {
+        /// The sampling level asked for.
+        level: usize,
+        /// The length of the text, including its terminator.
+        len: u64,
+    },
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::EmptyText => write!(f, "the text is empty"),
+            Error::TerminatorInText { position } => write!(
+                f,
+                "the text contains a \\0 other than a final terminator, at position {}",
+                position
+            ),
+            Error::CharacterOutOfRange {
+                position,
+                character,
+            } => write!(
+                f,
+                "character {} at position {} is not in the converter's alphabet",
+                character, position
+            ),
+            Error::OffsetOutOfRange { offset, len } => write!(
+                f,
+                "offset {} is out of range for a search result of {} occurrences",
+                offset, len
+            ),
+            Error::InvalidLevel { level, len } => write!(
+                f,
+                "sampling level {} is too large for a text of length {}; 2^level must be less than the length",
+                level, len
+            ),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl<T: Character> From<InvalidCharacter<T>> for Error {
+    fn from(err: InvalidCharacter<T>) -> Self {
+        Error::CharacterOutOfRange {
+            position: err.position,
+            character: err.character.into(),
+        }
+    }
+}
@@ -1,87 +1,224 @@
-use crate::backend::{HasPosition, HeapSize, SearchIndexBackend};
+use crate::backend::{HasPosition, HeapSize, SearchIndexBackend, SizeBreakdown};
 use crate::character::{prepare_text, Character};
 #[cfg(doc)]
 use crate::converter;
 use crate::converter::Converter;
+#[cfg(all(feature = "std", feature = "serde"))]
+use crate::persist::impl_sections;
+use crate::sequence::{PackedSymbols, Sequence};
 use crate::suffix_array::sais;
-use crate::suffix_array::sample::SuffixOrderSampledArray;
-use crate::util;
+use crate::suffix_array::sample::SuffixArraySamples;
+use crate::verify::{VerifyReport, VerifySamples};
 
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use vers_vecs::WaveletMatrix;
 
 /// An FM-Index, a succinct full-text index.
-#[derive(Serialize, Deserialize)]
-pub struct FMIndexBackend<T, C, S> {
-    bw: WaveletMatrix,
+///
+/// The BWT is stored in a [`Sequence`], by default a wavelet matrix.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FMIndexBackend<T, C, S, W = WaveletMatrix> {
+    bw: W,
     cs: Vec<u64>,
     converter: C,
     suffix_array: S,
-    _t: std::marker::PhantomData<T>,
+    _t: core::marker::PhantomData<T>,
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl_sections!(FMIndexBackend<T, C, S, W> {
+    #[encode(Symbols)]
+    bw: W,
+    cs: Vec<u64>,
+    converter: C,
+    #[load(Deferrable)]
+    #[part(Samples)]
+    suffix_array: S,
+});
+
+// The wavelet matrix doesn't implement `PartialEq`, so the BWTs are
+// compared by their symbols.
+impl<T, C: PartialEq, S: PartialEq, W: Sequence> PartialEq for FMIndexBackend<T, C, S, W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bw.same_symbols(&other.bw)
+            && self.cs == other.cs
+            && self.converter == other.converter
+            && self.suffix_array == other.suffix_array
+    }
 }
 
 // TODO: Refactor types (Converter converts T -> u64)
-impl<T, C, S> FMIndexBackend<T, C, S>
+impl<T, C, S, W> FMIndexBackend<T, C, S, W>
 where
     T: Character,
     C: Converter<T>,
+    W: Sequence,
 {
-    pub(crate) fn new(text: Vec<T>, converter: C, get_sample: impl Fn(&[u64]) -> S) -> Self {
-        let text = prepare_text(text);
+    pub(crate) fn new(text: Cow<'_, [T]>, converter: C, get_sample: impl Fn(&[u64]) -> S) -> Self {
+        let text = prepare_text(text, &converter);
         let cs = sais::get_bucket_start_pos(&sais::count_chars(&text, &converter));
         let sa = sais::build_suffix_array(&text, &converter);
-        let bw = Self::wavelet_matrix(text, &sa, &converter);
+        let bw = Self::wavelet_matrix(&text, &sa, &converter);
 
         FMIndexBackend {
             cs,
             bw,
             converter,
             suffix_array: get_sample(&sa),
-            _t: std::marker::PhantomData::<T>,
+            _t: core::marker::PhantomData::<T>,
         }
     }
 
-    fn wavelet_matrix(text: Vec<T>, sa: &[u64], converter: &C) -> WaveletMatrix {
-        let n = text.len();
-        let mut bw = vec![T::zero(); n];
-        for i in 0..n {
-            let k = sa[i] as usize;
-            if k > 0 {
-                bw[i] = converter.convert(text[k - 1]);
+    fn wavelet_matrix(text: &[T], sa: &[u64], converter: &C) -> W {
+        W::from_symbols(&bwt_symbols(text, sa, converter), converter.len())
+    }
+
+    /// Replace the suffix array samples with those taken by `get_sample`.
+    ///
+    /// The suffix array is recovered by LF-mapping from the row of the
+    /// terminator, which visits the text positions from last to first, so
+    /// neither the text nor the current samples are needed.
+    pub(crate) fn resample<S2>(
+        self,
+        get_sample: impl Fn(&[u64]) -> S2,
+    ) -> FMIndexBackend<T, C, S2, W> {
+        let n = self.len();
+        let mut sa = vec![0; n as usize];
+        let mut i = 0;
+        for p in (0..n).rev() {
+            sa[i as usize] = p;
+            i = self.lf_map(i);
+        }
+        FMIndexBackend {
+            bw: self.bw,
+            cs: self.cs,
+            converter: self.converter,
+            suffix_array: get_sample(&sa),
+            _t: core::marker::PhantomData::<T>,
+        }
+    }
+}
+
+impl<T, C, W> FMIndexBackend<T, C, (), W>
+where
+    T: Character,
+    C: Converter<T>,
+    W: Sequence,
+{
+    /// Merge the indexes of two texts into the index of the first text,
+    /// its terminator and the second text.
+    ///
+    /// Every suffix of the first text is ranked among the suffixes of the
+    /// second by backward search in `b`, after which the two BWTs are
+    /// interleaved. Both indexes must use the same converter; that of `a`
+    /// is kept.
+    pub(crate) fn merge(a: Self, b: Self) -> Self {
+        debug_assert_eq!(a.converter.len(), b.converter.len());
+        let n_a = a.bw.len();
+        let n_b = b.bw.len();
+
+        // ranks[k]: the number of suffixes of b smaller than the suffix of a
+        // at row k. The terminator of a is smaller than every suffix of b.
+        let mut ranks = vec![0; n_a];
+        let (mut row, mut rank) = (0, 0);
+        loop {
+            let c = a.bw.get(row);
+            if c == 0 {
+                break;
             }
+            rank = b.cs[c as usize] as usize + b.bw.rank(rank, c);
+            row = a.cs[c as usize] as usize + a.bw.rank(row, c);
+            ranks[row] = rank;
         }
-        let bw = bw.into_iter().map(|c| c.into()).collect::<Vec<u64>>();
 
-        WaveletMatrix::from_slice(&bw, (util::log2(converter.len() - 1) + 1) as u16)
+        let mut symbols = PackedSymbols::with_capacity(n_a + n_b, a.converter.len());
+        let mut j = 0;
+        for (k, &rank) in ranks.iter().enumerate() {
+            symbols.extend((j..rank).map(|i| b.bw.get(i)));
+            j = j.max(rank);
+            symbols.push(a.bw.get(k));
+        }
+        symbols.extend((j..n_b).map(|i| b.bw.get(i)));
+
+        let cs = a.cs.iter().zip(&b.cs).map(|(x, y)| x + y).collect();
+        FMIndexBackend {
+            bw: W::from_symbols(&symbols, a.converter.len()),
+            cs,
+            converter: a.converter,
+            suffix_array: (),
+            _t: core::marker::PhantomData,
+        }
     }
 }
 
-impl<T, C> HeapSize for FMIndexBackend<T, C, ()>
+/// The converted characters of the BWT of `text`, given its suffix array.
+pub(crate) fn bwt_symbols<T: Character, C: Converter<T>>(
+    text: &[T],
+    sa: &[u64],
+    converter: &C,
+) -> PackedSymbols {
+    PackedSymbols::pack(
+        sa.iter().map(|&k| {
+            if k > 0 {
+                converter.convert(text[k as usize - 1]).into()
+            } else {
+                0
+            }
+        }),
+        converter.len(),
+    )
+}
+
+impl<T, C, W> HeapSize for FMIndexBackend<T, C, (), W>
 where
     T: Character,
     C: Converter<T>,
+    W: Sequence,
 {
     fn heap_size(&self) -> usize {
-        self.bw.heap_size() + self.cs.capacity() * std::mem::size_of::<u64>()
+        self.size_breakdown().total()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new()
+            .add("bwt", self.bw.heap_size())
+            .add("cs", self.cs.capacity() * core::mem::size_of::<u64>())
     }
 }
 
-impl<T, C> HeapSize for FMIndexBackend<T, C, SuffixOrderSampledArray>
+impl<T, C, S, W> HeapSize for FMIndexBackend<T, C, S, W>
 where
     T: Character,
     C: Converter<T>,
+    S: SuffixArraySamples,
+    W: Sequence,
 {
     fn heap_size(&self) -> usize {
-        self.bw.heap_size()
-            + self.cs.capacity() * std::mem::size_of::<u64>()
-            + self.suffix_array.size()
+        self.size_breakdown().total()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new()
+            .add("bwt", self.bw.heap_size())
+            .add("cs", self.cs.capacity() * core::mem::size_of::<u64>())
+            .add("suffix_array", self.suffix_array.size())
     }
 }
 
-impl<T, C, S> SearchIndexBackend for FMIndexBackend<T, C, S>
+impl<T, C, S, W> SearchIndexBackend for FMIndexBackend<T, C, S, W>
 where
     T: Character,
     C: Converter<T>,
+    W: Sequence,
 {
     type T = T;
     type C = C;
@@ -91,19 +228,34 @@ where
     }
 
     fn get_l(&self, i: u64) -> Self::T {
-        Self::T::from_u64(self.bw.get_u64_unchecked(i as usize))
+        Self::T::from_u64(self.bw.get(i as usize))
     }
 
     fn lf_map(&self, i: u64) -> u64 {
         let c = self.get_l(i);
         let c_count = self.cs[c.into() as usize];
-        let rank = self.bw.rank_u64_unchecked(i as usize, c.into()) as u64;
+        let rank = self.bw.rank(i as usize, c.into()) as u64;
         c_count + rank
     }
 
     fn lf_map2(&self, c: T, i: u64) -> u64 {
         let c = self.converter.convert(c);
-        self.cs[c.into() as usize] + self.bw.rank_u64_unchecked(i as usize, c.into()) as u64
+        self.cs[c.into() as usize] + self.bw.rank(i as usize, c.into()) as u64
+    }
+
+    fn search_interval(&self, pattern: &[T], s: u64, e: u64, min: u64) -> (u64, u64) {
+        let mut s = s;
+        let mut e = e;
+        for &c in pattern.iter().rev() {
+            let c = self.converter.convert(c).into();
+            let start = self.cs[c as usize];
+            s = start + self.bw.rank(s as usize, c) as u64;
+            e = start + self.bw.rank(e as usize, c) as u64;
+            if e - s < min {
+                break;
+            }
+        }
+        (s, e)
     }
 
     fn get_f(&self, i: u64) -> Self::T {
@@ -126,8 +278,15 @@ where
     fn fl_map(&self, i: u64) -> u64 {
         let c = self.get_f(i);
         self.bw
-            .select_u64_unchecked(i as usize - self.cs[c.into() as usize] as usize, c.into())
-            as u64
+            .select(i as usize - self.cs[c.into() as usize] as usize, c.into()) as u64
+    }
+
+    fn interval_symbols(&self, s: u64, e: u64) -> Vec<(Self::T, u64)> {
+        self.bw
+            .interval_symbols(s as usize, e as usize)
+            .into_iter()
+            .map(|(c, count)| (T::from_u64(c), count))
+            .collect()
     }
 
     fn get_converter(&self) -> &Self::C {
@@ -135,17 +294,19 @@ where
     }
 }
 
-impl<T, C> HasPosition for FMIndexBackend<T, C, SuffixOrderSampledArray>
+impl<T, C, S, W> HasPosition for FMIndexBackend<T, C, S, W>
 where
     T: Character,
     C: Converter<T>,
+    S: SuffixArraySamples,
+    W: Sequence,
 {
-    fn get_sa(&self, mut i: u64) -> u64 {
+    fn get_sa_with_steps(&self, mut i: u64) -> (u64, u64) {
         let mut steps = 0;
         loop {
             match self.suffix_array.get(i) {
                 Some(sa) => {
-                    return (sa + steps) % self.bw.len() as u64;
+                    return ((sa + steps) % self.bw.len() as u64, steps);
                 }
                 None => {
                     i = self.lf_map(i);
@@ -154,6 +315,265 @@ where
             }
         }
     }
+
+    fn get_isa_sample(&self, p: u64) -> (u64, u64) {
+        self.suffix_array.get_inverse(p)
+    }
+
+    fn get_sa_range(&self, s: u64, e: u64, _pattern: &[T]) -> Vec<u64> {
+        let mut result = vec![0; (e - s) as usize];
+        self.resolve_sa(s, &mut result);
+        result
+    }
+
+    fn fold_sa<A, F>(&self, s: u64, e: u64, _pattern: &[T], init: A, mut f: F) -> A
+    where
+        F: FnMut(A, u64) -> A,
+    {
+        let mut buffer = [0; FOLD_CHUNK];
+        let mut acc = init;
+        for start in (s..e).step_by(FOLD_CHUNK) {
+            let chunk = &mut buffer[..(e - start).min(FOLD_CHUNK as u64) as usize];
+            self.resolve_sa(start, chunk);
+            acc = chunk.iter().fold(acc, |acc, &position| f(acc, position));
+        }
+        acc
+    }
+
+    #[cfg(feature = "rayon")]
+    fn get_sa_range_parallel(&self, s: u64, e: u64, _pattern: &[T]) -> Vec<u64>
+    where
+        Self: Sync,
+    {
+        use rayon::prelude::*;
+        let mut result = vec![0; (e - s) as usize];
+        result
+            .par_chunks_mut(PARALLEL_CHUNK)
+            .enumerate()
+            .for_each(|(k, chunk)| self.resolve_sa(s + (k * PARALLEL_CHUNK) as u64, chunk));
+        result
+    }
+}
+
+// The number of LF-walks `resolve_sa` interleaves.
+const LANES: usize = 16;
+
+// The number of occurrences `fold_sa` resolves at a time, on the stack.
+const FOLD_CHUNK: usize = 256;
+
+// The number of occurrences each task of a parallel locate resolves.
+#[cfg(feature = "rayon")]
+const PARALLEL_CHUNK: usize = 1024;
+
+impl<T, C, S, W> FMIndexBackend<T, C, S, W>
+where
+    T: Character,
+    C: Converter<T>,
+    S: SuffixArraySamples,
+    W: Sequence,
+{
+    // Store `SA[s + j]` in `out[j]`.
+    //
+    // Each LF-walk is a chain of dependent random accesses, which leaves
+    // the CPU waiting on memory. Advancing the walks of several
+    // occurrences in turn lets their accesses overlap, and each step
+    // prefetches what the next step of its walk reads.
+    fn resolve_sa(&self, s: u64, out: &mut [u64]) {
+        let n = self.bw.len() as u64;
+        // (offset in out, suffix array position, steps walked)
+        let mut lanes = [(0, 0, 0); LANES];
+        let mut active = 0;
+        let mut next = 0;
+        loop {
+            while active < LANES && next < out.len() {
+                lanes[active] = (next, s + next as u64, 0);
+                active += 1;
+                next += 1;
+            }
+            if active == 0 {
+                return;
+            }
+            let mut k = 0;
+            while k < active {
+                let (j, i, steps) = lanes[k];
+                match self.suffix_array.get(i) {
+                    Some(sa) => {
+                        out[j] = (sa + steps) % n;
+                        active -= 1;
+                        lanes[k] = lanes[active];
+                    }
+                    None => {
+                        let i = self.lf_map(i);
+                        self.bw.prefetch(i as usize);
+                        lanes[k] = (j, i, steps + 1);
+                        k += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The number of rows whose LF-mapping `verify` checks, and the number
+// whose suffix array value it recovers from the samples.
+const VERIFY_ROWS: u64 = 1024;
+const VERIFY_SAMPLES: u64 = 64;
+
+impl<T, C, S, W> FMIndexBackend<T, C, S, W>
+where
+    T: Character,
+    C: Converter<T>,
+    S: VerifySamples,
+    W: Sequence,
+{
+    /// Check the internal invariants of the index.
+    ///
+    /// The character counts are checked against the BWT in full. The
+    /// LF-mapping is checked to be a permutation on evenly spaced rows, by
+    /// mapping each back with the FL-mapping, and the suffix array samples
+    /// are checked to resolve to valid text positions, which agree with the
+    /// inverse samples.
+    ///
+    /// This finds structures that don't fit together, as after loading an
+    /// index from untrusted data. Structures that are inconsistent in ways
+    /// the checks rely on may still make it panic.
+    pub(crate) fn verify(&self) -> VerifyReport {
+        let mut report = VerifyReport::default();
+        let n = self.bw.len() as u64;
+        let sigma = self.converter.len();
+        if n == 0 {
+            report.problem("the BWT is empty".to_string());
+            return report;
+        }
+        if self.cs.len() as u64 != sigma {
+            report.problem(format!(
+                "there are {} character counts for an alphabet of {} characters",
+                self.cs.len(),
+                sigma
+            ));
+            return report;
+        }
+        if self.cs[0] != 0 {
+            report.problem(format!(
+                "the terminators start at row {} instead of row 0",
+                self.cs[0]
+            ));
+        }
+        for (c, w) in self.cs.windows(2).enumerate() {
+            if w[0] > w[1] {
+                report.problem(format!(
+                    "the rows of character {} start at {}, after those of character {} at {}",
+                    c,
+                    w[0],
+                    c + 1,
+                    w[1]
+                ));
+            }
+        }
+        if self.cs[self.cs.len() - 1] > n {
+            report.problem(format!(
+                "the rows of character {} start at {}, past the end of the BWT at {}",
+                self.cs.len() - 1,
+                self.cs[self.cs.len() - 1],
+                n
+            ));
+        }
+        if !report.is_ok() {
+            return report;
+        }
+        for c in 0..self.cs.len() {
+            let end = self.cs.get(c + 1).copied().unwrap_or(n);
+            let count = self.bw.rank(n as usize, c as u64) as u64;
+            if end - self.cs[c] != count {
+                report.problem(format!(
+                    "character {} has {} rows, but occurs {} times in the BWT",
+                    c,
+                    end - self.cs[c],
+                    count
+                ));
+            }
+        }
+        if self.cs.get(1).copied().unwrap_or(n) == 0 {
+            report.problem("the BWT has no terminator".to_string());
+        }
+        if !report.is_ok() {
+            return report;
+        }
+
+        let step = n.div_ceil(VERIFY_ROWS);
+        for i in (0..n).step_by(step as usize) {
+            report.row_checked();
+            let j = self.lf_map(i);
+            if j >= n {
+                report.problem(format!("row {} maps to row {}, past the end", i, j));
+                continue;
+            }
+            if self.get_f(j) != self.get_l(i) {
+                report.problem(format!(
+                    "row {} maps to row {}, which starts with another character",
+                    i, j
+                ));
+            } else if self.fl_map(j) != i {
+                report.problem(format!(
+                    "row {} maps to row {}, which maps back to row {}",
+                    i,
+                    j,
+                    self.fl_map(j)
+                ));
+            }
+        }
+        if !report.is_ok() || !self.suffix_array.has_samples() {
+            return report;
+        }
+
+        let step = n.div_ceil(VERIFY_SAMPLES);
+        for i in (0..n).step_by(step as usize) {
+            if let Err(problem) = self.verify_sa(i) {
+                report.problem(problem);
+            }
+            report.sample_checked();
+        }
+        for p in (0..n).step_by(step as usize) {
+            let (q, row) = self.suffix_array.inverse_sample(p);
+            if row >= n {
+                report.problem(format!(
+                    "the inverse sample of position {} is row {}, past the end",
+                    q, row
+                ));
+                continue;
+            }
+            match self.verify_sa(row) {
+                Ok(sa) if sa != q % n => report.problem(format!(
+                    "the inverse sample of position {} is row {}, which is at position {}",
+                    q, row, sa
+                )),
+                Ok(_) => {}
+                Err(problem) => report.problem(problem),
+            }
+            report.sample_checked();
+        }
+        report
+    }
+
+    // The suffix array value at row `i`, walking the LF-mapping at most `n`
+    // steps to a sample.
+    fn verify_sa(&self, i: u64) -> Result<u64, String> {
+        let n = self.bw.len() as u64;
+        let mut row = i;
+        for steps in 0..n {
+            if let Some(sa) = self.suffix_array.sample_at(row) {
+                if sa >= n {
+                    return Err(format!(
+                        "the sample at row {} is position {}, past the end",
+                        row, sa
+                    ));
+                }
+                return Ok((sa + steps) % n);
+            }
+            row = self.lf_map(row);
+        }
+        Err(format!("no sample is reached from row {}", i))
+    }
 }
 
 #[cfg(test)]
@@ -165,9 +585,10 @@ mod tests {
     fn test_lf_map() {
         let text = "mississippi".to_string().into_bytes();
         let ans = vec![1, 6, 7, 2, 8, 10, 3, 9, 11, 4, 5, 0];
-        let fm_index = FMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |sa| {
-            sample::sample(sa, 2)
-        });
+        let fm_index =
+            FMIndexBackend::<_, _, _>::new(text.into(), RangeConverter::new(b'a', b'z'), |sa| {
+                sample::sample(sa, 2)
+            });
         let mut i = 0;
         for a in ans {
             i = fm_index.lf_map(i);
@@ -178,13 +599,99 @@ mod tests {
     #[test]
     fn test_fl_map() {
         let text = "mississippi".to_string().into_bytes();
-        let fm_index = FMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |sa| {
-            sample::sample(sa, 2)
-        });
+        let fm_index =
+            FMIndexBackend::<_, _, _>::new(text.into(), RangeConverter::new(b'a', b'z'), |sa| {
+                sample::sample(sa, 2)
+            });
         let cases = vec![5u64, 0, 7, 10, 11, 4, 1, 6, 2, 3, 8, 9];
         for (i, expected) in cases.into_iter().enumerate() {
             let actual = fm_index.fl_map(i as u64);
             assert_eq!(actual, expected);
         }
     }
+
+    #[test]
+    fn test_resample() {
+        let text = "mississippi".to_string().into_bytes();
+        let ans = vec![11, 10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2];
+        let fm_index =
+            FMIndexBackend::<_, _, ()>::new(text.into(), RangeConverter::new(b'a', b'z'), |_| ())
+                .resample(|sa| sample::sample(sa, 0));
+        for (i, a) in ans.into_iter().enumerate() {
+            assert_eq!(fm_index.get_sa(i as u64), a);
+        }
+    }
+
+    #[test]
+    fn test_get_sa_range() {
+        use crate::quad_vector::QuadVector;
+        use crate::suffix_array::sample::TextOrderSampledArray;
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let text = (0..5000)
+            .map(|_| b"acgt"[rng.gen_range(0, 4)])
+            .collect::<Vec<u8>>();
+        let converter = RangeConverter::new(b'a', b't');
+        let fm_index = FMIndexBackend::<_, _, _>::new(text.clone().into(), converter, |sa| {
+            sample::sample(sa, 4)
+        });
+        let n = fm_index.len();
+        let expected = (0..n).map(|i| fm_index.get_sa(i)).collect::<Vec<_>>();
+        assert_eq!(fm_index.get_sa_range(0, n, &[]), expected);
+        assert_eq!(fm_index.get_sa_range(7, 30, &[]), expected[7..30]);
+        assert_eq!(fm_index.get_sa_range(9, 9, &[]), Vec::<u64>::new());
+
+        let converter = RangeConverter::new(b'a', b'd');
+        let text = text.iter().map(|&c| c % 4 + b'a').collect::<Vec<_>>();
+        let fm_index = FMIndexBackend::<_, _, _, QuadVector>::new(text.into(), converter, |sa| {
+            TextOrderSampledArray::sample(sa, 3)
+        });
+        let expected = (0..n).map(|i| fm_index.get_sa(i)).collect::<Vec<_>>();
+        assert_eq!(fm_index.get_sa_range(0, n, &[]), expected);
+    }
+
+    #[test]
+    fn test_verify() {
+        let text = "mississippi".to_string().into_bytes();
+        let converter = RangeConverter::new(b'a', b'z');
+        let fm_index =
+            FMIndexBackend::<_, _, _>::new(text.clone().into(), converter.clone(), |sa| {
+                sample::sample(sa, 1)
+            });
+        let report = fm_index.verify();
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!(report.rows_checked(), 12);
+        assert_eq!(report.samples_checked(), 24);
+
+        let mut broken = fm_index.clone();
+        broken.cs.pop();
+        assert_eq!(broken.verify().problems().len(), 1);
+
+        let mut broken = fm_index.clone();
+        broken.cs.swap(b'i' as usize - 96, b'm' as usize - 96);
+        assert!(!broken.verify().is_ok());
+
+        // the right bucket sizes, in the wrong order
+        let mut broken = fm_index.clone();
+        broken.cs[b'i' as usize - 96 + 1] -= 1;
+        broken.cs[b'i' as usize - 96] -= 1;
+        broken.cs[1] = 0;
+        assert!(!broken.verify().is_ok());
+
+        // samples of another text of the same length
+        let other = FMIndexBackend::<_, _, _>::new(
+            "ssissippimi".to_string().into_bytes().into(),
+            converter.clone(),
+            |sa| sample::sample(sa, 1),
+        );
+        let mut broken = fm_index.clone();
+        broken.suffix_array = other.suffix_array;
+        assert!(!broken.verify().is_ok());
+
+        let fm_index = FMIndexBackend::<_, _, ()>::new(text.into(), converter, |_| ());
+        let report = fm_index.verify();
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!(report.samples_checked(), 0);
+    }
 }
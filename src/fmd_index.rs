@@ -0,0 +1,299 @@
+//! FMD-index: an FM-index of a DNA text together with its reverse
+//! complement.
+//!
+//! Li, H. (2012). Exploring single-sample SNP and INDEL calling with
+//! whole-genome de novo assembly. Bioinformatics, 28(14), 1838–1844.
+use crate::backend::{HasPosition, HeapSize, SearchIndexBackend, SizeBreakdown};
+use crate::character::{check_text, IntoText};
+use crate::converter::Converter;
+use crate::error::Error;
+use crate::multi_text::MultiTextFMIndexBackend;
+use crate::suffix_array::sample::{self, SuffixOrderSampledArray};
+use alloc::{vec, vec::Vec};
+
+/// The strand of a DNA text an occurrence lies on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Strand {
+    /// The text as given.
+    Forward,
+    /// The reverse complement of the text.
+    Reverse,
+}
+
+/// The complement of a nucleotide.
+///
+/// `A`/`T` and `C`/`G` are swapped, preserving case. Any other character,
+/// such as `N`, is its own complement.
+pub(crate) fn complement(c: u8) -> u8 {
+    match c {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' => b'A',
+        b'a' => b't',
+        b'c' => b'g',
+        b'g' => b'c',
+        b't' => b'a',
+        c => c,
+    }
+}
+
+/// The reverse complement of a DNA sequence.
+pub(crate) fn reverse_complement(text: &[u8]) -> Vec<u8> {
+    text.iter().rev().map(|&c| complement(c)).collect()
+}
+
+/// FMD-index, with locate support.
+///
+/// This indexes a DNA text together with its reverse complement, so that a
+/// single search finds the occurrences on both strands. Every search result
+/// keeps track of the interval of the pattern as well as that of its reverse
+/// complement, which allows extending the pattern in both directions.
+///
+/// The converter must map the complement of every character of the text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FMDIndex<C: Converter<u8>> {
+    backend: MultiTextFMIndexBackend<u8, C, SuffixOrderSampledArray>,
+    text_len: u64,
+}
+
+/// Search result for FMDIndex.
+///
+/// This is a bidirectional interval: the suffix array interval of the
+/// pattern and that of its reverse complement, which have the same size.
+pub struct FMDIndexSearch<'a, C: Converter<u8>> {
+    index: &'a FMDIndex<C>,
+    k: u64,
+    l: u64,
+    s: u64,
+    pattern_len: u64,
+}
+
+impl<C: Converter<u8>> FMDIndex<C> {
+    /// Create a new FMDIndex.
+    ///
+    /// The level argument controls the sampling rate used, as in
+    /// [`FMIndexWithLocate::new`](crate::FMIndexWithLocate::new).
+    ///
+    /// Panics if the text is empty, contains a \0 other than a final
+    /// terminator or has a character outside the converter's alphabet, or if
+    /// `level` is too large for the text.
+    pub fn new<'t>(text: impl IntoText<'t, u8>, converter: C, level: usize) -> Self {
+        let text = text.into_text();
+        if let Err(err) = Self::check(&text, &converter, level) {
+            panic!("{}", err);
+        }
+        // each strand gets its own terminator
+        let text = match text.split_last() {
+            Some((&0, rest)) => rest,
+            _ => &text,
+        };
+        let text_len = text.len() as u64;
+        let mut both = Vec::with_capacity(2 * text.len() + 2);
+        both.extend_from_slice(text);
+        both.push(0);
+        both.extend(reverse_complement(text));
+        both.push(0);
+        FMDIndex {
+            backend: MultiTextFMIndexBackend::new(both.into(), converter, |sa| {
+                sample::sample(sa, level)
+            }),
+            text_len,
+        }
+    }
+
+    /// Create a new index like [`new`](Self::new), but return an [`Error`]
+    /// instead of panicking if the text can't be indexed.
+    ///
+    /// A \0 inside the text would separate the strands in the wrong place,
+    /// so it is rejected like in [`FMIndex::try_new`](crate::FMIndex::try_new).
+    pub fn try_new<'t>(
+        text: impl IntoText<'t, u8>,
+        converter: C,
+        level: usize,
+    ) -> Result<Self, Error> {
+        let text = text.into_text();
+        Self::check(&text, &converter, level)?;
+        Ok(Self::new(text, converter, level))
+    }
+
+    // Check that the text can be indexed, and that both strands together
+    // can be sampled at `level`.
+    fn check(text: &[u8], converter: &C, level: usize) -> Result<(), Error> {
+        check_text(text, converter)?;
+        let text_len = text.len() - usize::from(text[text.len() - 1] == 0);
+        sample::check_level(2 * text_len as u64 + 2, level)
+    }
+
+    /// Search for a pattern on both strands of the text.
+    pub fn search<K: AsRef<[u8]>>(&self, pattern: K) -> FMDIndexSearch<'_, C> {
+        FMDIndexSearch {
+            index: self,
+            k: 0,
+            l: 0,
+            s: self.backend.len(),
+            pattern_len: 0,
+        }
+        .search(pattern)
+    }
+
+    /// The size of the index.
+    ///
+    /// This covers both strands and their terminators, so it is
+    /// `2 * text_len() + 2`.
+    pub fn len(&self) -> u64 {
+        self.backend.len()
+    }
+
+    /// The length of the text the index was built from.
+    pub fn text_len(&self) -> u64 {
+        self.text_len
+    }
+}
+
+impl<C: Converter<u8>> HeapSize for FMDIndex<C> {
+    fn heap_size(&self) -> usize {
+        self.backend.heap_size()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        self.backend.size_breakdown()
+    }
+}
+
+impl<'a, C: Converter<u8>> FMDIndexSearch<'a, C> {
+    /// Search in the current search result, refining it.
+    ///
+    /// This adds a prefix `pattern` to the existing pattern.
+    pub fn search<K: AsRef<[u8]>>(&self, pattern: K) -> Self {
+        let mut search = *self;
+        for &c in pattern.as_ref().iter().rev() {
+            if search.s == 0 {
+                break;
+            }
+            search = search.extend_backward(c);
+        }
+        FMDIndexSearch {
+            pattern_len: self.pattern_len + pattern.as_ref().len() as u64,
+            ..search
+        }
+    }
+
+    /// Extend the pattern by prepending the character `c`.
+    ///
+    /// If `c` or its complement is outside the converter's alphabet, the
+    /// pattern can't occur and the result is empty.
+    pub fn extend_backward(&self, c: u8) -> Self {
+        let backend = &self.index.backend;
+        let converter = backend.get_converter();
+        if !converter.contains(c) || !converter.contains(complement(c)) {
+            return FMDIndexSearch {
+                s: 0,
+                pattern_len: self.pattern_len + 1,
+                ..*self
+            };
+        }
+        let sigma = converter.len();
+
+        // sizes of the intervals of all one-character backward extensions
+        let mut sizes = vec![0; sigma as usize];
+        let mut starts = vec![0; sigma as usize];
+        for x in 1..sigma {
+            let b = converter.convert_inv(x as u8);
+            starts[x as usize] = backend.lf_map2(b, self.k);
+            sizes[x as usize] = backend.lf_map2(b, self.k + self.s) - starts[x as usize];
+        }
+        sizes[0] = self.s - sizes.iter().sum::<u64>();
+
+        // The reverse complement of the pattern is extended forward with
+        // the complement of `c`. Its interval comes after those extended
+        // with smaller characters.
+        let a = converter.convert(c) as u64;
+        let ca = converter.convert(complement(c)) as u64;
+        let mut l = self.l + sizes[0];
+        for y in 1..ca {
+            let cy = converter.convert(complement(converter.convert_inv(y as u8)));
+            l += sizes[cy as usize];
+        }
+        FMDIndexSearch {
+            index: self.index,
+            k: starts[a as usize],
+            l,
+            s: sizes[a as usize],
+            pattern_len: self.pattern_len + 1,
+        }
+    }
+
+    /// Extend the pattern by appending the character `c`.
+    pub fn extend_forward(&self, c: u8) -> Self {
+        let swapped = FMDIndexSearch {
+            k: self.l,
+            l: self.k,
+            ..*self
+        }
+        .extend_backward(complement(c));
+        FMDIndexSearch {
+            k: swapped.l,
+            l: swapped.k,
+            ..swapped
+        }
+    }
+
+    /// Count the number of occurrences on both strands.
+    ///
+    /// A pattern that is its own reverse complement is counted once for
+    /// each strand.
+    pub fn count(&self) -> u64 {
+        self.s
+    }
+
+    /// The interval `[s, e)` of the suffix array matching the pattern.
+    pub fn forward_range(&self) -> (u64, u64) {
+        (self.k, self.k + self.s)
+    }
+
+    /// The interval `[s, e)` of the suffix array matching the reverse
+    /// complement of the pattern.
+    pub fn reverse_complement_range(&self) -> (u64, u64) {
+        (self.l, self.l + self.s)
+    }
+
+    /// List the occurrences, as the position in the text together with
+    /// the strand.
+    ///
+    /// The position of an occurrence on the reverse strand is that of its
+    /// reverse complement on the forward strand.
+    pub fn locate(&self) -> Vec<(u64, Strand)> {
+        let n = self.index.text_len;
+        let m = self.pattern_len;
+        (self.k..self.k + self.s)
+            .map(|i| {
+                let p = self.index.backend.get_sa(i);
+                if p < n {
+                    (p, Strand::Forward)
+                } else {
+                    (2 * n + 1 - p - m, Strand::Reverse)
+                }
+            })
+            .collect()
+    }
+}
+
+impl<C: Converter<u8>> Clone for FMDIndexSearch<'_, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Converter<u8>> Copy for FMDIndexSearch<'_, C> {}
+
+impl<C: Converter<u8>> core::fmt::Debug for FMDIndexSearch<'_, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FMDIndexSearch")
+            .field("k", &self.k)
+            .field("l", &self.l)
+            .field("s", &self.s)
+            .field("pattern_len", &self.pattern_len)
+            .finish()
+    }
+}
@@ -9,13 +9,56 @@
 // the behavior. This module only exists so we can avoid exposing implementation
 // traits.
 
-use crate::backend::HeapSize;
+use crate::backend::{HeapSize, SearchIndexBackend, SizeBreakdown};
+use crate::converter::{Converter, Normalizer, NormalizingConverter, RangeConverter, StrConverter};
+use crate::csa::CSABackend;
+#[cfg(feature = "std")]
+use crate::disk::{DiskFMIndexBackend, DiskWords};
+use crate::doc_bitmap::DocBitmap;
+use crate::dyn_search::impl_dyn_search;
+use crate::error::Error;
 use crate::fm_index::FMIndexBackend;
+use crate::fmd_index::{reverse_complement, Strand};
+use crate::huffman_wavelet::HuffmanWaveletTree;
+#[cfg(feature = "std")]
+use crate::instrument::QueryStats;
+use crate::lcp::LcpArray;
+use crate::matches::Match;
 use crate::multi_text::MultiTextFMIndexBackend;
+#[cfg(all(feature = "std", feature = "serde"))]
+use crate::persist::{self, IndexInfo, SplitPaths};
+use crate::quad_vector::QuadVector;
+use crate::rank_wavelet::RankWaveletMatrix;
 use crate::rlfmi::RLFMIndexBackend;
-use crate::suffix_array::sample::{self, SuffixOrderSampledArray};
+use crate::seed::Anchor;
+use crate::snapshot::{impl_owned_search, SearchSnapshot};
+use crate::stats::IndexStats;
+use crate::suffix_array::run_sample::{self, RunSampledArray};
+use crate::suffix_array::sample::{self, SamplingStrategy, SuffixOrderSampledArray};
+use crate::suffix_tree::SuffixTree;
+use crate::symbol_vectors::SymbolVectors;
+use crate::verify::VerifyReport;
 use crate::wrapper::SearchWrapper;
-use crate::{converter::Converter, wrapper::SearchIndexWrapper, Character};
+use crate::{
+    character::{check_level, check_text, check_texts, level_rate, IntoText},
+    wrapper::SearchIndexWrapper,
+    Character,
+};
+
+use alloc::{string::String, vec::Vec};
+#[cfg(all(feature = "std", feature = "serde"))]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+/// Identifier of a text in a multi-text index.
+///
+/// Texts are numbered in order, starting at 0.
+pub type DocId = u64;
 
 /// Trait for searching in an index.
 ///
@@ -25,15 +68,26 @@ pub trait SearchIndex<T> {
     ///
     /// Return a [`Search`] object with information about the search
     /// result.
-    fn search<K>(&self, pattern: K) -> impl Search<T>
+    fn search<K>(&self, pattern: K) -> impl Search<'_, T>
     where
         K: AsRef<[T]>;
 
     /// The size of the text in the index
     ///
     /// Note that this includes an ending \0 (terminator) character
-    /// so will be one more than the length of the text.
+    /// so will be one more than the length of the text. Use
+    /// [`text_len`](Self::text_len) for the length of the text itself.
     fn len(&self) -> u64;
+
+    /// The length of the text in the index, without the terminator.
+    ///
+    /// Positions returned by locate queries are offsets in the text, so
+    /// they are less than this. For a multi-text index this is the length
+    /// of the texts together, including the \0 that ends each of them but
+    /// the last.
+    fn text_len(&self) -> u64 {
+        self.len() - 1
+    }
 }
 
 /// Trait for searching in an index that also supports locate queries.
@@ -44,7 +98,7 @@ pub trait SearchIndexWithLocate<T>: SearchIndex<T> {
     ///
     /// Return a [`SearchWithLocate`] object with information about the search
     /// result, which also supports locate queries.
-    fn search<K>(&self, pattern: K) -> impl SearchWithLocate<T>
+    fn search<K>(&self, pattern: K) -> impl SearchWithLocate<'_, T>
     where
         K: AsRef<[T]>;
 }
@@ -56,28 +110,122 @@ pub trait Search<'a, T> {
     /// This adds a prefix `pattern` to the existing pattern, and
     /// looks for those expanded patterns in the text.
     fn search<K: AsRef<[T]>>(&self, pattern: K) -> Self;
+    /// Extend the pattern by prepending the character `c`.
+    ///
+    /// This is a single step of backward search, so an algorithm can
+    /// decide how to go on after each character. The indexes are
+    /// unidirectional, so the pattern can't be extended at its end; an
+    /// [`FMDIndexSearch`](crate::FMDIndexSearch) can be, with
+    /// `extend_forward`.
+    fn extend_backward(&self, c: T) -> Self
+    where
+        Self: Sized,
+    {
+        self.search([c])
+    }
+    /// The pattern searched for, including the prefixes added by refining
+    /// searches.
+    fn pattern(&self) -> &[T];
+    /// The range `[s, e)` of the suffix array matched by the pattern.
+    ///
+    /// Its width is the number of occurrences, counting those in texts
+    /// that were deleted from a multi-text index.
+    fn range(&self) -> (u64, u64);
+    /// The size of the text in the index searched, like
+    /// [`SearchIndex::len`].
+    fn index_len(&self) -> u64;
     /// Count the number of occurrences.
     fn count(&self) -> u64;
+    /// Whether the pattern occurs at least `n` times.
+    fn count_at_least(&self, n: u64) -> bool {
+        self.count() >= n
+    }
+    /// Whether the pattern occurs in the text at all.
+    fn is_present(&self) -> bool {
+        self.count_at_least(1)
+    }
+    /// List the characters that can follow the pattern in the text, each
+    /// with the number of occurrences of the pattern extended by it.
+    ///
+    /// The result is ordered by character. Terminator characters are not
+    /// included.
+    fn extensions(&self) -> Vec<(T, u64)>;
+    /// List the characters that can precede the pattern in the text, each
+    /// with the number of occurrences of the pattern extended by it.
+    ///
+    /// The result is ordered by character. Terminator characters are not
+    /// included.
+    fn left_extensions(&self) -> Vec<(T, u64)>;
     /// Get an iterator that goes backwards through the text, producing
     /// [`Character`].
     fn iter_backward(&'a self, i: u64) -> impl Iterator<Item = T> + 'a;
     /// Get an iterator that goes forwards through the text, producing
     /// [`Character`].
     fn iter_forward(&'a self, i: u64) -> impl Iterator<Item = T> + 'a;
+    /// Get an iterator that goes backwards through the text from
+    /// occurrence `i` like [`iter_backward`](Self::iter_backward), but ends
+    /// at the start of the text instead of wrapping around.
+    ///
+    /// In a multi-text index it ends at the start of the text containing
+    /// the occurrence.
+    fn iter_backward_in_text(&'a self, i: u64) -> impl Iterator<Item = T> + 'a
+    where
+        T: Character + 'a,
+    {
+        self.iter_backward(i).take_while(|c| !c.is_zero())
+    }
+    /// Get an iterator that goes forwards through the text from occurrence
+    /// `i` like [`iter_forward`](Self::iter_forward), but ends at the
+    /// terminator instead of wrapping around.
+    ///
+    /// In a multi-text index it ends at the end of the text containing the
+    /// occurrence.
+    fn iter_forward_in_text(&'a self, i: u64) -> impl Iterator<Item = T> + 'a
+    where
+        T: Character + 'a,
+    {
+        self.iter_forward(i).take_while(|c| !c.is_zero())
+    }
 }
 
 /// The result of a search that also has locate support.
 pub trait SearchWithLocate<'a, T>: Search<'a, T> {
     /// List the position of all occurrences.
+    ///
+    /// Positions are offsets in the text, from 0 up to
+    /// [`SearchIndex::text_len`].
     fn locate(&self) -> Vec<u64>;
+    /// Fold `f` over the positions of all occurrences, in no particular
+    /// order.
+    ///
+    /// Unlike [`locate`](Self::locate), this doesn't collect the positions
+    /// in a vector first.
+    fn fold_positions<A, F>(&self, init: A, f: F) -> A
+    where
+        F: FnMut(A, u64) -> A;
+    /// Call `f` with the position of every occurrence, in no particular
+    /// order, without collecting the positions in a vector first.
+    fn for_each_position<F>(&self, mut f: F)
+    where
+        F: FnMut(u64),
+    {
+        self.fold_positions((), |(), position| f(position))
+    }
+    /// Iterate over all occurrences as [`Match`] objects.
+    fn matches(&'a self) -> impl Iterator<Item = Match<'a, T, Self>> + 'a
+    where
+        Self: Sized;
 }
 
 /// FMIndex, count only.
 ///
 /// The FM-Index is both a search index as well as compact representation of
 /// the text.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FMIndex<T: Character, C: Converter<T>>(SearchIndexWrapper<FMIndexBackend<T, C, ()>>);
 /// Search result for FMIndex, count only.
+#[derive(Clone, Debug)]
 pub struct FMIndexSearch<'a, T: Character, C: Converter<T>>(
     SearchWrapper<'a, FMIndexBackend<T, C, ()>>,
 );
@@ -85,19 +233,200 @@ pub struct FMIndexSearch<'a, T: Character, C: Converter<T>>(
 /// FMIndex with locate support.
 ///
 /// This is an FM-Index which uses additional storage to support locate queries.
-pub struct FMIndexWithLocate<T: Character, C: Converter<T>>(
-    SearchIndexWrapper<FMIndexBackend<T, C, SuffixOrderSampledArray>>,
-);
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FMIndexWithLocate<
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchIndexWrapper<FMIndexBackend<T, C, S>>);
 /// Search result for FMIndex with locate support.
-pub struct FMIndexSearchWithLocate<'a, T: Character, C: Converter<T>>(
-    SearchWrapper<'a, FMIndexBackend<T, C, SuffixOrderSampledArray>>,
+#[derive(Clone, Debug)]
+pub struct FMIndexSearchWithLocate<
+    'a,
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchWrapper<'a, FMIndexBackend<T, C, S>>);
+
+/// Slim FMIndex, count only.
+///
+/// This stores the BWT in a wavelet matrix without select support, which
+/// makes it smaller than [`FMIndex`] at the same counting speed. Forward
+/// iteration has to fall back to binary search and is much slower, so this
+/// is meant for memory-constrained deployments that only count.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SlimFMIndex<T: Character, C: Converter<T>>(
+    SearchIndexWrapper<FMIndexBackend<T, C, (), RankWaveletMatrix>>,
+);
+/// Search result for SlimFMIndex, count only.
+#[derive(Clone, Debug)]
+pub struct SlimFMIndexSearch<'a, T: Character, C: Converter<T>>(
+    SearchWrapper<'a, FMIndexBackend<T, C, (), RankWaveletMatrix>>,
+);
+
+/// FMIndex stored in a file, with locate support.
+///
+/// The BWT and the suffix array samples stay in the file and are read on
+/// demand through a small page cache, so the index can be much larger than
+/// memory. Create the file with [`DiskFMIndex::create`] and query it with
+/// [`DiskFMIndex::open`].
+///
+/// # Panics
+///
+/// The search API has no way to report I/O errors, so every query that
+/// reads from the file panics if the read fails once the index is open, for
+/// instance because the file was removed from a network share. A
+/// [`MappedFMIndex`] over the file read into memory can't fail this way.
+#[cfg(feature = "std")]
+pub struct DiskFMIndex<T: Character, C: Converter<T>>(SearchIndexWrapper<DiskFMIndexBackend<T, C>>);
+/// Search result for DiskFMIndex.
+#[cfg(feature = "std")]
+pub struct DiskFMIndexSearch<'a, T: Character, C: Converter<T>>(
+    SearchWrapper<'a, DiskFMIndexBackend<T, C>>,
+);
+
+/// FMIndex queried in place from a buffer holding an index file, with
+/// locate support.
+///
+/// The buffer holds a file written by [`DiskFMIndex::create`], typically a
+/// memory map of it. Nothing is copied out of the buffer or computed when
+/// the index is created, so loading takes constant time however large the
+/// index is, and the operating system pages the file in as queries touch
+/// it.
+#[cfg(feature = "std")]
+pub struct MappedFMIndex<T: Character, C: Converter<T>>(
+    SearchIndexWrapper<DiskFMIndexBackend<T, C, DiskWords>>,
+);
+/// Search result for MappedFMIndex.
+#[cfg(feature = "std")]
+pub struct MappedFMIndexSearch<'a, T: Character, C: Converter<T>>(
+    SearchWrapper<'a, DiskFMIndexBackend<T, C, DiskWords>>,
+);
+
+/// FMIndex with a Huffman-shaped wavelet tree, count only.
+///
+/// This stores the BWT in a wavelet tree shaped by the Huffman code of the
+/// characters instead of a wavelet matrix. For skewed character
+/// distributions, such as natural language text, it is smaller and queries
+/// need fewer rank operations on average.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HuffmanFMIndex<T: Character, C: Converter<T>>(
+    SearchIndexWrapper<FMIndexBackend<T, C, (), HuffmanWaveletTree>>,
+);
+/// Search result for HuffmanFMIndex, count only.
+#[derive(Clone, Debug)]
+pub struct HuffmanFMIndexSearch<'a, T: Character, C: Converter<T>>(
+    SearchWrapper<'a, FMIndexBackend<T, C, (), HuffmanWaveletTree>>,
+);
+
+/// FMIndex with a Huffman-shaped wavelet tree, with locate support.
+///
+/// This stores the BWT in a wavelet tree shaped by the Huffman code of the
+/// characters instead of a wavelet matrix. It uses additional storage to
+/// support locate queries.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HuffmanFMIndexWithLocate<
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchIndexWrapper<FMIndexBackend<T, C, S, HuffmanWaveletTree>>);
+/// Search result for HuffmanFMIndex with locate support.
+#[derive(Clone, Debug)]
+pub struct HuffmanFMIndexSearchWithLocate<
+    'a,
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchWrapper<'a, FMIndexBackend<T, C, S, HuffmanWaveletTree>>);
+
+/// FMIndex for texts of at most four characters, such as DNA, count only.
+///
+/// This stores the BWT as packed 2-bit codes with a rank directory for
+/// each code, instead of a wavelet matrix. Counting is faster and the index
+/// is smaller. The converter's alphabet must have at most four characters
+/// besides the terminator.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DNAFMIndex<T: Character, C: Converter<T>>(
+    SearchIndexWrapper<FMIndexBackend<T, C, (), QuadVector>>,
 );
+/// Search result for DNAFMIndex, count only.
+#[derive(Clone, Debug)]
+pub struct DNAFMIndexSearch<'a, T: Character, C: Converter<T>>(
+    SearchWrapper<'a, FMIndexBackend<T, C, (), QuadVector>>,
+);
+
+/// FMIndex for texts of at most four characters, such as DNA, with locate
+/// support.
+///
+/// This stores the BWT like [`DNAFMIndex`], and uses additional storage to
+/// support locate queries.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DNAFMIndexWithLocate<
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchIndexWrapper<FMIndexBackend<T, C, S, QuadVector>>);
+/// Search result for DNAFMIndex with locate support.
+#[derive(Clone, Debug)]
+pub struct DNAFMIndexSearchWithLocate<
+    'a,
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchWrapper<'a, FMIndexBackend<T, C, S, QuadVector>>);
+
+/// FMIndex for texts of at most eight characters, count only.
+///
+/// This stores the BWT as a bitvector for each character instead of a
+/// wavelet matrix, so counting takes a single rank query per character of
+/// the pattern. It suits alphabets such as DNA with ambiguity codes. The
+/// converter's alphabet must have at most eight characters besides the
+/// terminator.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SmallFMIndex<T: Character, C: Converter<T>>(
+    SearchIndexWrapper<FMIndexBackend<T, C, (), SymbolVectors>>,
+);
+/// Search result for SmallFMIndex, count only.
+#[derive(Clone, Debug)]
+pub struct SmallFMIndexSearch<'a, T: Character, C: Converter<T>>(
+    SearchWrapper<'a, FMIndexBackend<T, C, (), SymbolVectors>>,
+);
+
+/// FMIndex for texts of at most eight characters, with locate support.
+///
+/// This stores the BWT like [`SmallFMIndex`], and uses additional storage
+/// to support locate queries.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SmallFMIndexWithLocate<
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchIndexWrapper<FMIndexBackend<T, C, S, SymbolVectors>>);
+/// Search result for SmallFMIndex with locate support.
+#[derive(Clone, Debug)]
+pub struct SmallFMIndexSearchWithLocate<
+    'a,
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchWrapper<'a, FMIndexBackend<T, C, S, SymbolVectors>>);
 
 /// RLFMIndex, count only.
 ///
 /// This is a version of the FM-Index that uses less space, but is also less efficient.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RLFMIndex<T: Character, C: Converter<T>>(SearchIndexWrapper<RLFMIndexBackend<T, C, ()>>);
 /// Search result for RLFMIndex, count only.
+#[derive(Clone, Debug)]
 pub struct RLFMIndexSearch<'a, T: Character, C: Converter<T>>(
     SearchWrapper<'a, RLFMIndexBackend<T, C, ()>>,
 );
@@ -106,46 +435,273 @@ pub struct RLFMIndexSearch<'a, T: Character, C: Converter<T>>(
 ///
 /// This is a version of the FM-Index that uses less space, but is also less efficient.
 /// It uses additional storage to support locate queries.
-pub struct RLFMIndexWithLocate<T: Character, C: Converter<T>>(
-    SearchIndexWrapper<RLFMIndexBackend<T, C, SuffixOrderSampledArray>>,
-);
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RLFMIndexWithLocate<
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchIndexWrapper<RLFMIndexBackend<T, C, S>>);
 /// Search result for RLFMIndex with locate support.
-pub struct RLFMIndexSearchWithLocate<'a, T: Character, C: Converter<T>>(
-    SearchWrapper<'a, RLFMIndexBackend<T, C, SuffixOrderSampledArray>>,
+#[derive(Clone, Debug)]
+pub struct RLFMIndexSearchWithLocate<
+    'a,
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchWrapper<'a, RLFMIndexBackend<T, C, S>>);
+
+/// CSAIndex, count only.
+///
+/// This is a compressed suffix array based on the Ψ function. It offers a
+/// different space/time tradeoff than the FM-Index: backward search and
+/// forward iteration are fast, while backward iteration is slower.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CSAIndex<T: Character, C: Converter<T>>(SearchIndexWrapper<CSABackend<T, C, ()>>);
+/// Search result for CSAIndex, count only.
+#[derive(Clone, Debug)]
+pub struct CSAIndexSearch<'a, T: Character, C: Converter<T>>(
+    SearchWrapper<'a, CSABackend<T, C, ()>>,
+);
+
+/// CSAIndex with locate support.
+///
+/// This is a compressed suffix array based on the Ψ function.
+/// It uses additional storage to support locate queries.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CSAIndexWithLocate<
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchIndexWrapper<CSABackend<T, C, S>>);
+/// Search result for CSAIndex with locate support.
+#[derive(Clone, Debug)]
+pub struct CSAIndexSearchWithLocate<
+    'a,
+    T: Character,
+    C: Converter<T>,
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchWrapper<'a, CSABackend<T, C, S>>);
+
+/// r-index.
+///
+/// This is a version of the Run-Length FM-Index that samples the suffix
+/// array only at the boundaries of the runs of the BWT, so that the whole
+/// index takes space proportional to the number of runs. This makes it
+/// suited for highly repetitive collections, where the sampled suffix array
+/// of [`RLFMIndexWithLocate`] dominates the space. It always supports locate
+/// queries.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RIndex<T: Character, C: Converter<T>>(
+    SearchIndexWrapper<RLFMIndexBackend<T, C, RunSampledArray>>,
+);
+/// Search result for RIndex.
+#[derive(Clone, Debug)]
+pub struct RIndexSearch<'a, T: Character, C: Converter<T>>(
+    SearchWrapper<'a, RLFMIndexBackend<T, C, RunSampledArray>>,
 );
 
 /// MultiText index, count only.
 ///
 /// This is a multi-text version of the FM-Index. It allows \0 separated strings.
-pub struct MultiTextFMIndex<T: Character, C: Converter<T>>(
-    SearchIndexWrapper<MultiTextFMIndexBackend<T, C, ()>>,
+///
+/// Each text can optionally be associated with a metadata value of type `M`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultiTextFMIndex<T: Character, C: Converter<T>, M = ()>(
+    SearchIndexWrapper<MultiTextFMIndexBackend<T, C, (), M>>,
 );
 /// Search result for MultiText index, count only.
-pub struct MultiTextFMIndexSearch<'a, T: Character, C: Converter<T>>(
-    SearchWrapper<'a, MultiTextFMIndexBackend<T, C, ()>>,
+#[derive(Clone, Debug)]
+pub struct MultiTextFMIndexSearch<'a, T: Character, C: Converter<T>, M = ()>(
+    SearchWrapper<'a, MultiTextFMIndexBackend<T, C, (), M>>,
 );
 
 /// MultiText index with locate support.
 ///
 /// This is a multi-text version of the FM-Index. It allows \0 separated strings.
 /// It uses additional storage to support locate queries.
-pub struct MultiTextFMIndexWithLocate<T: Character, C: Converter<T>>(
-    SearchIndexWrapper<MultiTextFMIndexBackend<T, C, SuffixOrderSampledArray>>,
-);
+///
+/// Each text can optionally be associated with a metadata value of type `M`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultiTextFMIndexWithLocate<
+    T: Character,
+    C: Converter<T>,
+    M = (),
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchIndexWrapper<MultiTextFMIndexBackend<T, C, S, M>>);
 /// Search result for MultiText index with locate support.
-pub struct MultiTextFMIndexSearchWithLocate<'a, T: Character, C: Converter<T>>(
-    SearchWrapper<'a, MultiTextFMIndexBackend<T, C, SuffixOrderSampledArray>>,
-);
+#[derive(Clone, Debug)]
+pub struct MultiTextFMIndexSearchWithLocate<
+    'a,
+    T: Character,
+    C: Converter<T>,
+    M = (),
+    S: SamplingStrategy = SuffixOrderSampledArray,
+>(SearchWrapper<'a, MultiTextFMIndexBackend<T, C, S, M>>);
 
 impl<T: Character, C: Converter<T>> FMIndex<T, C> {
     /// Create a new FMIndex without locate support.
-    pub fn new(text: Vec<T>, converter: C) -> Self {
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C) -> Self {
         FMIndex(SearchIndexWrapper::new(FMIndexBackend::new(
-            text,
+            text.into_text(),
             converter,
             |_| (),
         )))
     }
+
+    /// Merge the indexes of two texts without building a suffix array.
+    ///
+    /// The result is the index of the text of `a`, a terminator and the
+    /// text of `b`, so it is the same as that of a [`MultiTextFMIndex`] of
+    /// the two texts: no pattern matches across the boundary between them.
+    /// Both indexes must have been created with the same converter.
+    pub fn merge(a: Self, b: Self) -> Self {
+        FMIndex(SearchIndexWrapper::new(FMIndexBackend::merge(
+            a.0.into_backend(),
+            b.0.into_backend(),
+        )))
+    }
+
+    /// Add locate support to the index, sampling the suffix array at
+    /// `level`.
+    ///
+    /// The suffix array is recovered from the index itself, so the text
+    /// isn't needed, and the index isn't rebuilt. This takes one LF step per
+    /// character of the text, and memory for the full suffix array while
+    /// sampling it. The k-mer table, the occurrence table, the query cache
+    /// settings and the recorded queries are kept.
+    ///
+    /// See [`FMIndexWithLocate::new`] for the meaning of `level`.
+    pub fn add_samples(self, level: usize) -> FMIndexWithLocate<T, C> {
+        FMIndexWithLocate(
+            self.0
+                .rebuild(|backend| backend.resample(|sa| sample::sample(sa, level))),
+        )
+    }
+}
+
+// The code points of `text`, and a converter for their range, or the
+// error `try_new` would return if they can't be indexed.
+fn code_points(text: &str) -> Result<(Vec<u32>, RangeConverter<u32>), Error> {
+    let text = text.chars().map(u32::from).collect::<Vec<_>>();
+    let converter = if text.iter().any(|&c| c != 0) {
+        RangeConverter::from_text(&text)
+    } else {
+        // the text is rejected whatever the range
+        RangeConverter::new(1, 1)
+    };
+    check_text(&text, &converter)?;
+    Ok((text, converter))
+}
+
+// The code points of `text`, and a converter for their range, panicking
+// like `new` if they can't be indexed.
+fn expect_code_points(text: &str) -> (Vec<u32>, RangeConverter<u32>) {
+    code_points(text).unwrap_or_else(|err| panic!("{}", err))
+}
+
+impl core::str::FromStr for FMIndex<u32, RangeConverter<u32>> {
+    type Err = Error;
+
+    /// Create a new FMIndex without locate support over the Unicode scalar
+    /// values of `text`.
+    ///
+    /// Characters are indexed as `u32` code points rather than UTF-8 bytes,
+    /// so searches never match inside a multi-byte character. The alphabet
+    /// is the range of code points in `text`. Returns an [`Error`] if the
+    /// text is empty or contains a \0 other than a final terminator.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (text, converter) = code_points(text)?;
+        Ok(FMIndex::new(text, converter))
+    }
+}
+
+impl<N: Normalizer> FMIndex<u32, NormalizingConverter<RangeConverter<u32>, N>> {
+    /// Create a new FMIndex without locate support over the Unicode scalar
+    /// values of `text`, normalized by `normalizer`.
+    ///
+    /// This is like `FMIndex::from_str`, but [`search_str`](Self::search_str)
+    /// normalizes patterns the same way, so canonically equivalent strings
+    /// match.
+    pub fn from_str_normalized(text: &str, normalizer: N) -> Self {
+        let (text, converter) = expect_code_points(&normalizer.normalize(text));
+        FMIndex::new(text, NormalizingConverter::new(converter, normalizer))
+    }
+}
+
+impl<C: StrConverter> FMIndex<u32, C> {
+    /// Search for a string in an index created with `from_str` or
+    /// [`from_str_normalized`](FMIndex::from_str_normalized).
+    ///
+    /// The pattern is normalized like the text was. Patterns with
+    /// characters that are not in the text have no occurrences.
+    pub fn search_str(&self, pattern: &str) -> FMIndexSearch<'_, u32, C> {
+        FMIndexSearch(search_str(&self.0, pattern))
+    }
+}
+
+impl FMIndexWithLocate<u32, RangeConverter<u32>> {
+    /// Create a new FMIndex with locate support over the Unicode scalar
+    /// values of `text`.
+    ///
+    /// The text is indexed as by `FMIndex::from_str`; see
+    /// [`FMIndexWithLocate::new`] for the meaning of `level`. Positions are
+    /// counted in characters, not bytes.
+    pub fn from_str(text: &str, level: usize) -> Self {
+        let (text, converter) = expect_code_points(text);
+        FMIndexWithLocate::new(text, converter, level)
+    }
+}
+
+impl<N: Normalizer> FMIndexWithLocate<u32, NormalizingConverter<RangeConverter<u32>, N>> {
+    /// Create a new FMIndex with locate support over the Unicode scalar
+    /// values of `text`, normalized by `normalizer`.
+    ///
+    /// See [`FMIndex::from_str_normalized`]. Positions are counted in
+    /// characters of the normalized text.
+    pub fn from_str_normalized(text: &str, level: usize, normalizer: N) -> Self {
+        let (text, converter) = expect_code_points(&normalizer.normalize(text));
+        FMIndexWithLocate::new(
+            text,
+            NormalizingConverter::new(converter, normalizer),
+            level,
+        )
+    }
+}
+
+impl<C: StrConverter> FMIndexWithLocate<u32, C> {
+    /// Search for a string in an index created with
+    /// [`from_str`](FMIndexWithLocate::from_str) or
+    /// [`from_str_normalized`](FMIndexWithLocate::from_str_normalized).
+    ///
+    /// The pattern is normalized like the text was. Patterns with
+    /// characters that are not in the text have no occurrences.
+    pub fn search_str(&self, pattern: &str) -> FMIndexSearchWithLocate<'_, u32, C> {
+        FMIndexSearchWithLocate(search_str(&self.0, pattern))
+    }
+}
+
+fn search_str<'a, B>(index: &'a SearchIndexWrapper<B>, pattern: &str) -> SearchWrapper<'a, B>
+where
+    B: SearchIndexBackend<T = u32> + HeapSize,
+    B::C: StrConverter,
+{
+    let converter = index.backend().get_converter();
+    let pattern = converter
+        .normalize(pattern)
+        .chars()
+        .map(u32::from)
+        .collect::<Vec<_>>();
+    if pattern.iter().all(|&c| converter.contains(c)) {
+        index.search(pattern)
+    } else {
+        index.search_absent(pattern)
+    }
 }
 
 impl<T: Character, C: Converter<T>> FMIndexWithLocate<T, C> {
@@ -155,137 +711,931 @@ impl<T: Character, C: Converter<T>> FMIndexWithLocate<T, C> {
     /// less storage, at the cost of performance of locate queries. A level of
     /// 0 means no sampling, and a level of 1 means half of the suffix array is
     /// sampled, a level of 2 means a quarter of the suffix array is sampled,
-    /// and so on.
-    pub fn new(text: Vec<T>, converter: C, level: usize) -> Self {
+    /// and so on. The rate `2^level` must be less than the length of the
+    /// text, including its terminator.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        Self::with_sampling(text, converter, level)
+    }
+}
+
+impl<T: Character, C: Converter<T>, S: SamplingStrategy> FMIndexWithLocate<T, C, S> {
+    /// Create a new FMIndex with locate support, sampling the suffix array
+    /// with strategy `S`.
+    ///
+    /// See [`FMIndexWithLocate::new`] for the meaning of `level`.
+    pub fn with_sampling<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        let text = text.into_text();
+        let rate = level_rate(&text, &converter, level);
+        Self::with_sampling_rate(text, converter, rate)
+    }
+
+    /// Create a new FMIndex with locate support, sampling one in every `rate`
+    /// entries of the suffix array with strategy `S`.
+    ///
+    /// Unlike the sampling level of [`FMIndexWithLocate::new`], which
+    /// stands for the rate `2^level`, the rate can be any positive number.
+    pub fn with_sampling_rate<'t>(text: impl IntoText<'t, T>, converter: C, rate: u64) -> Self {
         FMIndexWithLocate(SearchIndexWrapper::new(FMIndexBackend::new(
-            text,
+            text.into_text(),
+            converter,
+            |sa| S::sample_rate(sa, rate),
+        )))
+    }
+
+    /// Sample the suffix array again at `level`, which may be coarser or
+    /// finer than the current one.
+    ///
+    /// Like [`FMIndex::add_samples`], this recovers the suffix array from
+    /// the index itself, without the text or the current samples, so it
+    /// takes one LF step per character of the text and memory for the full
+    /// suffix array while sampling it. The k-mer table, the occurrence
+    /// table, the query cache settings and the recorded queries are kept.
+    ///
+    /// See [`FMIndexWithLocate::new`] for the meaning of `level`.
+    pub fn resample(self, level: usize) -> Self {
+        if let Err(err) = sample::check_level(self.len(), level) {
+            panic!("{}", err);
+        }
+        self.resample_rate(1 << level)
+    }
+
+    /// Sample the suffix array again, keeping one in every `rate` entries.
+    ///
+    /// See [`resample`](Self::resample), and
+    /// [`with_sampling_rate`](Self::with_sampling_rate) for the meaning of
+    /// `rate`.
+    pub fn resample_rate(self, rate: u64) -> Self {
+        FMIndexWithLocate(
+            self.0
+                .rebuild(|backend| backend.resample(|sa| S::sample_rate(sa, rate))),
+        )
+    }
+}
+
+impl<T: Character, C: Converter<T>> SlimFMIndex<T, C> {
+    /// Create a new SlimFMIndex.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C) -> Self {
+        SlimFMIndex(SearchIndexWrapper::new(FMIndexBackend::new(
+            text.into_text(),
             converter,
-            |sa| sample::sample(sa, level),
+            |_| (),
         )))
     }
 }
 
-impl<T: Character, C: Converter<T>> RLFMIndex<T, C> {
-    /// Create a new RLFMIndex without locate support.
-    pub fn new(text: Vec<T>, converter: C) -> Self {
-        RLFMIndex(SearchIndexWrapper::new(RLFMIndexBackend::new(
-            text,
+#[cfg(feature = "std")]
+impl<T: Character, C: Converter<T>> DiskFMIndex<T, C> {
+    /// Build the index of `text` and write it to the file at `path`.
+    ///
+    /// Construction happens in memory, like for [`FMIndexWithLocate`], with
+    /// the suffix array sampled at `level` the same way.
+    pub fn create<'t, P: AsRef<Path>>(
+        path: P,
+        text: impl IntoText<'t, T>,
+        converter: &C,
+        level: usize,
+    ) -> io::Result<()> {
+        DiskFMIndexBackend::create(path, text.into_text(), converter, level)
+    }
+
+    /// Open the index in the file at `path`, keeping at most `cache_pages`
+    /// pages of 4 KiB in memory.
+    ///
+    /// `converter` must be the one the index was created with. Opening
+    /// reads the rank directories of the BWT into memory.
+    pub fn open<P: AsRef<Path>>(path: P, converter: C, cache_pages: usize) -> io::Result<Self> {
+        Ok(DiskFMIndex(SearchIndexWrapper::new(
+            DiskFMIndexBackend::open(path, converter, cache_pages)?,
+        )))
+    }
+
+    /// Keep a coarser level of the suffix array samples in memory.
+    ///
+    /// Every `2^level`-th row of the suffix array is copied from the file,
+    /// where `level` must be at least the level the index was created with.
+    /// Locating an occurrence walks the LF-mapping until it reaches a row
+    /// sampled in memory, and only looks up the denser samples in the file
+    /// once it has taken `threshold` steps. A threshold of 0 consults the
+    /// file at every step, like an index without samples in memory.
+    pub fn with_hot_samples(mut self, level: usize, threshold: u64) -> Self {
+        self.0.backend_mut().set_hot_samples(level, threshold);
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Character, C: Converter<T>> MappedFMIndex<T, C> {
+    /// Query the index file held in `bytes` in place.
+    ///
+    /// `bytes` holds the whole of a file written by [`DiskFMIndex::create`],
+    /// for instance as a memory map, and `converter` must be the one the
+    /// index was created with. An error of kind
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData) is returned if
+    /// `bytes` doesn't hold such a file.
+    pub fn new<B>(bytes: B, converter: C) -> io::Result<Self>
+    where
+        B: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        Ok(MappedFMIndex(SearchIndexWrapper::new(
+            DiskFMIndexBackend::map(bytes, converter)?,
+        )))
+    }
+
+    /// Memory map the index file at `path`, written by
+    /// [`DiskFMIndex::create`], and query it in place.
+    ///
+    /// Processes that map the same file share one copy of it in the page
+    /// cache. See [`new`](Self::new) for `converter` and the errors. This
+    /// requires the `mmap` feature.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the index exists,
+    /// by this or any other process. Queries read the file as it is at the
+    /// time, and may crash the process if it has been truncated.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn open_mmap<P: AsRef<Path>>(path: P, converter: C) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Self::new(memmap2::Mmap::map(&file)?, converter)
+    }
+}
+
+impl<T: Character, C: Converter<T>> HuffmanFMIndex<T, C> {
+    /// Create a new HuffmanFMIndex without locate support.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C) -> Self {
+        HuffmanFMIndex(SearchIndexWrapper::new(FMIndexBackend::new(
+            text.into_text(),
             converter,
             |_| (),
         )))
     }
 }
 
-impl<T: Character, C: Converter<T>> RLFMIndexWithLocate<T, C> {
-    /// Create a new RLFMIndex with locate support.
+impl<T: Character, C: Converter<T>> HuffmanFMIndexWithLocate<T, C> {
+    /// Create a new HuffmanFMIndex with locate support.
     ///
     /// The level argument controls the sampling rate used. Higher levels use
     /// less storage, at the cost of performance of locate queries. A level of
     /// 0 means no sampling, and a level of 1 means half of the suffix array is
     /// sampled, a level of 2 means a quarter of the suffix array is sampled,
-    /// and so on.
-    pub fn new(text: Vec<T>, converter: C, level: usize) -> Self {
-        RLFMIndexWithLocate(SearchIndexWrapper::new(RLFMIndexBackend::new(
-            text,
+    /// and so on. The rate `2^level` must be less than the length of the
+    /// text, including its terminator.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        Self::with_sampling(text, converter, level)
+    }
+}
+
+impl<T: Character, C: Converter<T>, S: SamplingStrategy> HuffmanFMIndexWithLocate<T, C, S> {
+    /// Create a new HuffmanFMIndex with locate support, sampling the suffix array
+    /// with strategy `S`.
+    ///
+    /// See [`HuffmanFMIndexWithLocate::new`] for the meaning of `level`.
+    pub fn with_sampling<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        let text = text.into_text();
+        let rate = level_rate(&text, &converter, level);
+        Self::with_sampling_rate(text, converter, rate)
+    }
+
+    /// Create a new HuffmanFMIndex with locate support, sampling one in every `rate`
+    /// entries of the suffix array with strategy `S`.
+    ///
+    /// Unlike the sampling level of [`HuffmanFMIndexWithLocate::new`], which
+    /// stands for the rate `2^level`, the rate can be any positive number.
+    pub fn with_sampling_rate<'t>(text: impl IntoText<'t, T>, converter: C, rate: u64) -> Self {
+        HuffmanFMIndexWithLocate(SearchIndexWrapper::new(FMIndexBackend::new(
+            text.into_text(),
             converter,
-            |sa| sample::sample(sa, level),
+            |sa| S::sample_rate(sa, rate),
         )))
     }
 }
 
-impl<T: Character, C: Converter<T>> MultiTextFMIndex<T, C> {
-    /// Create a new MultiTextFMIndex without locate support.
-    pub fn new(text: Vec<T>, converter: C) -> Self {
-        MultiTextFMIndex(SearchIndexWrapper::new(MultiTextFMIndexBackend::new(
-            text,
+impl<T: Character, C: Converter<T>> DNAFMIndex<T, C> {
+    /// Create a new DNAFMIndex without locate support.
+    ///
+    /// Panics if the converter's alphabet has more than four characters
+    /// besides the terminator.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C) -> Self {
+        DNAFMIndex(SearchIndexWrapper::new(FMIndexBackend::new(
+            text.into_text(),
             converter,
             |_| (),
         )))
     }
 }
 
-impl<T: Character, C: Converter<T>> MultiTextFMIndexWithLocate<T, C> {
-    /// Create a new MultiTextFMIndex with locate support.
+impl<T: Character, C: Converter<T>> DNAFMIndexWithLocate<T, C> {
+    /// Create a new DNAFMIndex with locate support.
     ///
     /// The level argument controls the sampling rate used. Higher levels use
     /// less storage, at the cost of performance of locate queries. A level of
     /// 0 means no sampling, and a level of 1 means half of the suffix array is
     /// sampled, a level of 2 means a quarter of the suffix array is sampled,
-    /// and so on.
-    pub fn new(text: Vec<T>, converter: C, level: usize) -> Self {
-        MultiTextFMIndexWithLocate(SearchIndexWrapper::new(MultiTextFMIndexBackend::new(
-            text,
+    /// and so on. The rate `2^level` must be less than the length of the
+    /// text, including its terminator.
+    ///
+    /// Panics if the converter's alphabet has more than four characters
+    /// besides the terminator.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        Self::with_sampling(text, converter, level)
+    }
+}
+
+impl<T: Character, C: Converter<T>, S: SamplingStrategy> DNAFMIndexWithLocate<T, C, S> {
+    /// Create a new DNAFMIndex with locate support, sampling the suffix array
+    /// with strategy `S`.
+    ///
+    /// See [`DNAFMIndexWithLocate::new`] for the meaning of `level`.
+    pub fn with_sampling<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        let text = text.into_text();
+        let rate = level_rate(&text, &converter, level);
+        Self::with_sampling_rate(text, converter, rate)
+    }
+
+    /// Create a new DNAFMIndex with locate support, sampling one in every `rate`
+    /// entries of the suffix array with strategy `S`.
+    ///
+    /// Unlike the sampling level of [`DNAFMIndexWithLocate::new`], which
+    /// stands for the rate `2^level`, the rate can be any positive number.
+    pub fn with_sampling_rate<'t>(text: impl IntoText<'t, T>, converter: C, rate: u64) -> Self {
+        DNAFMIndexWithLocate(SearchIndexWrapper::new(FMIndexBackend::new(
+            text.into_text(),
             converter,
-            |sa| sample::sample(sa, level),
+            |sa| S::sample_rate(sa, rate),
         )))
     }
 }
 
-macro_rules! impl_search_index {
-    ($t:ty, $s:ident, $st:ty) => {
-        impl<T: Character, C: Converter<T>> SearchIndex<T> for $t {
-            fn search<K>(&self, pattern: K) -> impl Search<T>
-            where
-                K: AsRef<[T]>,
-            {
-                $s(self.0.search(pattern))
-            }
+impl<C: Converter<u8>, S: SamplingStrategy> DNAFMIndexWithLocate<u8, C, S> {
+    /// List the occurrences of a pattern on both strands of the text, as
+    /// the position together with the strand, in order of position.
+    ///
+    /// This searches for the pattern and for its reverse complement. The
+    /// position of an occurrence on the reverse strand is that of its
+    /// reverse complement on the forward strand, as with
+    /// [`FMDIndexSearch::locate`](crate::FMDIndexSearch::locate), so it is
+    /// where the occurrence starts when read along the forward strand. A
+    /// pattern that is its own reverse complement is listed once for each
+    /// strand.
+    ///
+    /// The converter must map the complement of every character of the
+    /// pattern.
+    pub fn search_both_strands<K: AsRef<[u8]>>(&self, pattern: K) -> Vec<(u64, Strand)> {
+        let pattern = pattern.as_ref();
+        let forward = self.search(pattern);
+        let reverse = self.search(reverse_complement(pattern));
+        let mut occurrences = Vec::with_capacity((forward.count() + reverse.count()) as usize);
+        forward.for_each_position(|p| occurrences.push((p, Strand::Forward)));
+        reverse.for_each_position(|p| occurrences.push((p, Strand::Reverse)));
+        occurrences.sort_unstable();
+        occurrences
+    }
+}
 
-            fn len(&self) -> u64 {
-                self.0.len()
-            }
-        }
-        impl<T: Character, C: Converter<T>> HeapSize for $t {
-            fn heap_size(&self) -> usize {
-                self.0.heap_size()
-            }
-        }
-        // inherent
-        impl<T: Character, C: Converter<T>> $t {
-            /// Search for a pattern in the text.
-            pub fn search<K>(&self, pattern: K) -> $st
-            where
-                K: AsRef<[T]>,
-            {
-                $s(self.0.search(pattern))
-            }
-            /// The size of the text in the index
-            pub fn len(&self) -> u64 {
-                SearchIndex::len(self)
-            }
-        }
-    };
+impl<T: Character, C: Converter<T>> SmallFMIndex<T, C> {
+    /// Create a new SmallFMIndex without locate support.
+    ///
+    /// Panics if the converter's alphabet has more than eight characters
+    /// besides the terminator.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C) -> Self {
+        SmallFMIndex(SearchIndexWrapper::new(FMIndexBackend::new(
+            text.into_text(),
+            converter,
+            |_| (),
+        )))
+    }
 }
 
-macro_rules! impl_search_index_with_locate {
-    ($t:ty, $s:ident, $st:ty) => {
-        impl<T: Character, C: Converter<T>> SearchIndex<T> for $t {
-            fn search<K>(&self, pattern: K) -> impl Search<T>
-            where
-                K: AsRef<[T]>,
-            {
-                $s(self.0.search(pattern))
-            }
+impl<T: Character, C: Converter<T>> SmallFMIndexWithLocate<T, C> {
+    /// Create a new SmallFMIndex with locate support.
+    ///
+    /// The level argument controls the sampling rate used. Higher levels use
+    /// less storage, at the cost of performance of locate queries. A level of
+    /// 0 means no sampling, and a level of 1 means half of the suffix array is
+    /// sampled, a level of 2 means a quarter of the suffix array is sampled,
+    /// and so on. The rate `2^level` must be less than the length of the
+    /// text, including its terminator.
+    ///
+    /// Panics if the converter's alphabet has more than eight characters
+    /// besides the terminator.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        Self::with_sampling(text, converter, level)
+    }
+}
 
-            fn len(&self) -> u64 {
-                self.0.len()
-            }
-        }
-        impl<T: Character, C: Converter<T>> SearchIndexWithLocate<T> for $t {
-            fn search<K>(&self, pattern: K) -> impl SearchWithLocate<T>
-            where
-                K: AsRef<[T]>,
-            {
-                $s(self.0.search(pattern))
-            }
-        }
-        impl<T: Character, C: Converter<T>> HeapSize for $t {
-            fn heap_size(&self) -> usize {
-                self.0.heap_size()
-            }
-        }
-        // inherent
+impl<T: Character, C: Converter<T>, S: SamplingStrategy> SmallFMIndexWithLocate<T, C, S> {
+    /// Create a new SmallFMIndex with locate support, sampling the suffix
+    /// array with strategy `S`.
+    ///
+    /// See [`SmallFMIndexWithLocate::new`] for the meaning of `level`.
+    pub fn with_sampling<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        let text = text.into_text();
+        let rate = level_rate(&text, &converter, level);
+        Self::with_sampling_rate(text, converter, rate)
+    }
+
+    /// Create a new SmallFMIndex with locate support, sampling one in every
+    /// `rate` entries of the suffix array with strategy `S`.
+    ///
+    /// Unlike the sampling level of [`SmallFMIndexWithLocate::new`], which
+    /// stands for the rate `2^level`, the rate can be any positive number.
+    pub fn with_sampling_rate<'t>(text: impl IntoText<'t, T>, converter: C, rate: u64) -> Self {
+        SmallFMIndexWithLocate(SearchIndexWrapper::new(FMIndexBackend::new(
+            text.into_text(),
+            converter,
+            |sa| S::sample_rate(sa, rate),
+        )))
+    }
+}
+
+impl<T: Character, C: Converter<T>> RLFMIndex<T, C> {
+    /// Create a new RLFMIndex without locate support.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C) -> Self {
+        RLFMIndex(SearchIndexWrapper::new(RLFMIndexBackend::new(
+            text.into_text(),
+            converter,
+            |_, _| (),
+        )))
+    }
+}
+
+impl<T: Character, C: Converter<T>> RLFMIndexWithLocate<T, C> {
+    /// Create a new RLFMIndex with locate support.
+    ///
+    /// The level argument controls the sampling rate used. Higher levels use
+    /// less storage, at the cost of performance of locate queries. A level of
+    /// 0 means no sampling, and a level of 1 means half of the suffix array is
+    /// sampled, a level of 2 means a quarter of the suffix array is sampled,
+    /// and so on. The rate `2^level` must be less than the length of the
+    /// text, including its terminator.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        Self::with_sampling(text, converter, level)
+    }
+}
+
+impl<T: Character, C: Converter<T>, S: SamplingStrategy> RLFMIndexWithLocate<T, C, S> {
+    /// Create a new RLFMIndex with locate support, sampling the suffix array
+    /// with strategy `S`.
+    ///
+    /// See [`RLFMIndexWithLocate::new`] for the meaning of `level`.
+    pub fn with_sampling<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        let text = text.into_text();
+        let rate = level_rate(&text, &converter, level);
+        Self::with_sampling_rate(text, converter, rate)
+    }
+
+    /// Create a new RLFMIndex with locate support, sampling one in every `rate`
+    /// entries of the suffix array with strategy `S`.
+    ///
+    /// Unlike the sampling level of [`RLFMIndexWithLocate::new`], which
+    /// stands for the rate `2^level`, the rate can be any positive number.
+    pub fn with_sampling_rate<'t>(text: impl IntoText<'t, T>, converter: C, rate: u64) -> Self {
+        RLFMIndexWithLocate(SearchIndexWrapper::new(RLFMIndexBackend::new(
+            text.into_text(),
+            converter,
+            |_, sa| S::sample_rate(sa, rate),
+        )))
+    }
+}
+
+impl<T: Character, C: Converter<T>> CSAIndex<T, C> {
+    /// Create a new CSAIndex without locate support.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C) -> Self {
+        CSAIndex(SearchIndexWrapper::new(CSABackend::new(
+            text.into_text(),
+            converter,
+            |_| (),
+        )))
+    }
+}
+
+impl<T: Character, C: Converter<T>> CSAIndexWithLocate<T, C> {
+    /// Create a new CSAIndex with locate support.
+    ///
+    /// The level argument controls the sampling rate used. Higher levels use
+    /// less storage, at the cost of performance of locate queries. A level of
+    /// 0 means no sampling, and a level of 1 means half of the suffix array is
+    /// sampled, a level of 2 means a quarter of the suffix array is sampled,
+    /// and so on. The rate `2^level` must be less than the length of the
+    /// text, including its terminator.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        Self::with_sampling(text, converter, level)
+    }
+}
+
+impl<T: Character, C: Converter<T>, S: SamplingStrategy> CSAIndexWithLocate<T, C, S> {
+    /// Create a new CSAIndex with locate support, sampling the suffix array
+    /// with strategy `S`.
+    ///
+    /// See [`CSAIndexWithLocate::new`] for the meaning of `level`.
+    pub fn with_sampling<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        let text = text.into_text();
+        let rate = level_rate(&text, &converter, level);
+        Self::with_sampling_rate(text, converter, rate)
+    }
+
+    /// Create a new CSAIndex with locate support, sampling one in every `rate`
+    /// entries of the suffix array with strategy `S`.
+    ///
+    /// Unlike the sampling level of [`CSAIndexWithLocate::new`], which
+    /// stands for the rate `2^level`, the rate can be any positive number.
+    pub fn with_sampling_rate<'t>(text: impl IntoText<'t, T>, converter: C, rate: u64) -> Self {
+        CSAIndexWithLocate(SearchIndexWrapper::new(CSABackend::new(
+            text.into_text(),
+            converter,
+            |sa| S::sample_rate(sa, rate),
+        )))
+    }
+}
+
+impl<T: Character, C: Converter<T>> RIndex<T, C> {
+    /// Create a new RIndex.
+    ///
+    /// Locating the occurrences of a pattern takes time proportional to
+    /// the pattern length plus the number of occurrences. Accessing single
+    /// suffix array entries, as done by [`suffix_array`](Self::suffix_array)
+    /// and [`char_at`](Self::char_at), can be slow, as their distance to
+    /// the nearest sample is not bounded.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C) -> Self {
+        RIndex(SearchIndexWrapper::new(RLFMIndexBackend::new(
+            text.into_text(),
+            converter,
+            run_sample::sample,
+        )))
+    }
+}
+
+impl<T: Character, C: Converter<T>> MultiTextFMIndex<T, C> {
+    /// Create a new MultiTextFMIndex without locate support.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C) -> Self {
+        MultiTextFMIndex(SearchIndexWrapper::new(MultiTextFMIndexBackend::new(
+            text.into_text(),
+            converter,
+            |_| (),
+        )))
+    }
+}
+
+impl<T: Character, C: Converter<T>> MultiTextFMIndexWithLocate<T, C> {
+    /// Create a new MultiTextFMIndex with locate support.
+    ///
+    /// The level argument controls the sampling rate used. Higher levels use
+    /// less storage, at the cost of performance of locate queries. A level of
+    /// 0 means no sampling, and a level of 1 means half of the suffix array is
+    /// sampled, a level of 2 means a quarter of the suffix array is sampled,
+    /// and so on. The rate `2^level` must be less than the length of the
+    /// text, including its terminator.
+    pub fn new<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        Self::with_sampling(text, converter, level)
+    }
+}
+
+impl<T: Character, C: Converter<T>, S: SamplingStrategy> MultiTextFMIndexWithLocate<T, C, (), S> {
+    /// Create a new MultiTextFMIndex with locate support, sampling the suffix array
+    /// with strategy `S`.
+    ///
+    /// See [`MultiTextFMIndexWithLocate::new`] for the meaning of `level`.
+    pub fn with_sampling<'t>(text: impl IntoText<'t, T>, converter: C, level: usize) -> Self {
+        let text = text.into_text();
+        let rate = level_rate(&text, &converter, level);
+        Self::with_sampling_rate(text, converter, rate)
+    }
+
+    /// Create a new MultiTextFMIndex with locate support, sampling one in every `rate`
+    /// entries of the suffix array with strategy `S`.
+    ///
+    /// Unlike the sampling level of [`MultiTextFMIndexWithLocate::new`], which
+    /// stands for the rate `2^level`, the rate can be any positive number.
+    pub fn with_sampling_rate<'t>(text: impl IntoText<'t, T>, converter: C, rate: u64) -> Self {
+        MultiTextFMIndexWithLocate(SearchIndexWrapper::new(MultiTextFMIndexBackend::new(
+            text.into_text(),
+            converter,
+            |sa| S::sample_rate(sa, rate),
+        )))
+    }
+}
+
+/// Concatenate texts into a single text, terminating each with \0, or
+/// return an error for the first \0 inside one of them, as it would split
+/// that text in two. The position is that in the concatenated text.
+pub(crate) fn try_concat_texts<T: Character>(texts: Vec<Vec<T>>) -> Result<Vec<T>, Error> {
+    let mut text = Vec::with_capacity(texts.iter().map(|t| t.len() + 1).sum());
+    for t in texts {
+        if let Some(i) = t.iter().position(|c| c.is_zero()) {
+            return Err(Error::TerminatorInText {
+                position: text.len() + i,
+            });
+        }
+        text.extend(t);
+        text.push(T::zero());
+    }
+    Ok(text)
+}
+
+/// Concatenate texts like `try_concat_texts`, panicking with the message of
+/// the error.
+pub(crate) fn concat_texts<T: Character>(texts: Vec<Vec<T>>) -> Vec<T> {
+    try_concat_texts(texts).unwrap_or_else(|err| panic!("{}", err))
+}
+
+impl<T: Character, C: Converter<T>> MultiTextFMIndex<T, C> {
+    /// Create a new MultiTextFMIndex without locate support from separate
+    /// texts.
+    ///
+    /// The texts are concatenated, each followed by a \0 separator. Text
+    /// ids are assigned in order, starting at 0. The texts must not contain
+    /// \0 characters.
+    pub fn from_texts(texts: Vec<Vec<T>>, converter: C) -> Self {
+        Self::new(concat_texts(texts), converter)
+    }
+
+    /// Create a new index like [`from_texts`](Self::from_texts), but return
+    /// an [`Error`] instead of panicking if a text contains a \0, or if the
+    /// texts can't be indexed as by [`try_new`](Self::try_new).
+    pub fn try_from_texts(texts: Vec<Vec<T>>, converter: C) -> Result<Self, Error> {
+        Self::try_new(try_concat_texts(texts)?, converter)
+    }
+}
+
+impl<T: Character, C: Converter<T>> MultiTextFMIndexWithLocate<T, C> {
+    /// Create a new MultiTextFMIndex with locate support from separate
+    /// texts.
+    ///
+    /// The texts are concatenated, each followed by a \0 separator. Text
+    /// ids are assigned in order, starting at 0. The texts must not contain
+    /// \0 characters. See [`MultiTextFMIndexWithLocate::new`] for the
+    /// meaning of `level`.
+    pub fn from_texts(texts: Vec<Vec<T>>, converter: C, level: usize) -> Self {
+        Self::new(concat_texts(texts), converter, level)
+    }
+
+    /// Create a new index like [`from_texts`](Self::from_texts), but return
+    /// an [`Error`] instead of panicking if a text contains a \0, or if the
+    /// texts can't be indexed as by [`try_new`](Self::try_new).
+    pub fn try_from_texts(texts: Vec<Vec<T>>, converter: C, level: usize) -> Result<Self, Error> {
+        Self::try_new(try_concat_texts(texts)?, converter, level)
+    }
+}
+
+impl<T: Character, C: Converter<T>, M> MultiTextFMIndex<T, C, M> {
+    /// Create a new MultiTextFMIndex without locate support from separate
+    /// texts, each associated with a metadata value.
+    ///
+    /// The metadata can be retrieved by text id using `metadata`.
+    pub fn from_texts_with_metadata(docs: Vec<(Vec<T>, M)>, converter: C) -> Self {
+        let (texts, metadata): (Vec<_>, Vec<_>) = docs.into_iter().unzip();
+        let index = MultiTextFMIndex::from_texts(texts, converter);
+        MultiTextFMIndex(index.0.rebuild(|backend| backend.with_metadata(metadata)))
+    }
+}
+
+impl<T: Character, C: Converter<T>, M> MultiTextFMIndexWithLocate<T, C, M> {
+    /// Create a new MultiTextFMIndex with locate support from separate
+    /// texts, each associated with a metadata value.
+    ///
+    /// The metadata can be retrieved by text id using `metadata`, or
+    /// together with each occurrence using `matches_with_metadata` on a
+    /// search result.
+    pub fn from_texts_with_metadata(docs: Vec<(Vec<T>, M)>, converter: C, level: usize) -> Self {
+        let (texts, metadata): (Vec<_>, Vec<_>) = docs.into_iter().unzip();
+        let index = MultiTextFMIndexWithLocate::from_texts(texts, converter, level);
+        MultiTextFMIndexWithLocate(index.0.rebuild(|backend| backend.with_metadata(metadata)))
+    }
+}
+
+impl<T: Character, C: Converter<T>, M> MultiTextFMIndex<T, C, M> {
+    /// Rebuild the index without the texts marked as deleted.
+    ///
+    /// The remaining texts are renumbered in order, starting at 0. At least
+    /// one text must remain.
+    pub fn purge(self) -> Self {
+        if self.0.backend().deleted_count() == 0 {
+            return self;
+        }
+        MultiTextFMIndex(self.0.rebuild(|backend| {
+            let (texts, metadata, converter) = backend.into_live_texts();
+            MultiTextFMIndexBackend::new(concat_texts(texts).into(), converter, |_| ())
+                .with_metadata(metadata)
+        }))
+    }
+}
+
+impl<T: Character, C: Converter<T>, M, S: SamplingStrategy> MultiTextFMIndexWithLocate<T, C, M, S> {
+    /// Rebuild the index without the texts marked as deleted, keeping the
+    /// sampling strategy, sampling rate, k-mer table, occurrence table,
+    /// query cache settings and recorded queries.
+    ///
+    /// The remaining texts are renumbered in order, starting at 0. At least
+    /// one text must remain.
+    pub fn purge(self) -> Self {
+        if self.0.backend().deleted_count() == 0 {
+            return self;
+        }
+        let rate = self.0.backend().sampling_rate();
+        MultiTextFMIndexWithLocate(self.0.rebuild(|backend| {
+            let (texts, metadata, converter) = backend.into_live_texts();
+            MultiTextFMIndexBackend::new(concat_texts(texts).into(), converter, |sa| {
+                S::sample_rate(sa, rate)
+            })
+            .with_metadata(metadata)
+        }))
+    }
+}
+
+macro_rules! impl_multi_text {
+    ($t:ty $(, $g:ident $(: $b:path)?)*) => {
+        // inherent
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
+            /// The number of texts in the index.
+            pub fn doc_count(&self) -> u64 {
+                self.0.backend().doc_count()
+            }
+
+            /// The metadata value of text `doc_id`.
+            ///
+            /// Returns `None` if the index was built without metadata.
+            pub fn metadata(&self, doc_id: DocId) -> Option<&M> {
+                self.0.backend().metadata(doc_id)
+            }
+
+            /// Mark text `doc_id` as deleted.
+            ///
+            /// Occurrences in deleted texts are skipped by subsequent counts
+            /// and locates. Counting then takes time proportional to the
+            /// number of texts containing the pattern. Extensions and
+            /// iterators still see the deleted texts; use `purge` to remove
+            /// them for good.
+            pub fn delete_doc(&mut self, doc_id: DocId) {
+                self.0.backend_mut().delete_doc(doc_id)
+            }
+
+            /// Whether text `doc_id` was marked as deleted.
+            pub fn is_doc_deleted(&self, doc_id: DocId) -> bool {
+                self.0.backend().is_doc_deleted(doc_id)
+            }
+
+            /// The number of texts marked as deleted.
+            pub fn deleted_count(&self) -> u64 {
+                self.0.backend().deleted_count()
+            }
+
+            /// The id of the text containing `position`.
+            ///
+            /// The \0 separator terminating a text belongs to that text.
+            pub fn doc_id(&self, position: u64) -> DocId {
+                debug_assert!(position < self.len(), "{} is out of range", position);
+                self.0.backend().doc_id(position).unwrap()
+            }
+
+            /// Map a position in the concatenated text to the id of the
+            /// text containing it and the offset within that text.
+            pub fn doc_position(&self, position: u64) -> (DocId, u64) {
+                debug_assert!(position < self.len(), "{} is out of range", position);
+                self.0.backend().doc_position(position)
+            }
+        }
+    };
+}
+
+macro_rules! impl_multi_text_search {
+    ($t:ty $(, $g:ident $(: $b:path)?)*) => {
+        // inherent
+        impl<'a, T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
+            /// List the texts containing the pattern, each with the number of
+            /// occurrences in it, ordered by text id.
+            ///
+            /// This uses the document array of the index, so no occurrences
+            /// need to be located.
+            pub fn docs_with_tf(&self) -> Vec<(DocId, u64)> {
+                let (s, e) = self.0.get_range();
+                self.0.backend().docs_with_tf(s, e)
+            }
+
+            /// List the `k` texts with the most occurrences of the pattern.
+            ///
+            /// The result is ordered by decreasing number of occurrences;
+            /// ties are broken by text id.
+            pub fn top_docs(&self, k: usize) -> Vec<(DocId, u64)> {
+                let (s, e) = self.0.get_range();
+                self.0.backend().top_docs(s, e, k)
+            }
+
+            /// The set of texts containing the pattern, as a [`DocBitmap`].
+            pub fn doc_bitmap(&self) -> DocBitmap {
+                let (s, e) = self.0.get_range();
+                let backend = self.0.backend();
+                let mut bitmap = DocBitmap::new(backend.doc_count());
+                for (doc_id, _) in backend.docs_with_tf(s, e) {
+                    bitmap.insert(doc_id);
+                }
+                bitmap
+            }
+        }
+    };
+}
+
+macro_rules! impl_multi_text_search_locate {
+    ($t:ty $(, $g:ident $(: $b:path)?)*) => {
+        // inherent
+        impl<'a, T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
+            /// Iterate over all occurrences as [`Match`] objects, each with
+            /// the metadata value of the text containing it.
+            ///
+            /// The metadata is `None` if the index was built without
+            /// metadata.
+            pub fn matches_with_metadata(
+                &'a self,
+            ) -> impl Iterator<Item = (Match<'a, T, Self>, Option<&'a M>)> + 'a {
+                let backend = self.0.backend();
+                SearchWithLocate::matches(self).map(move |m| {
+                    let metadata = backend.metadata(m.doc_id().unwrap());
+                    (m, metadata)
+                })
+            }
+
+            /// List the positions of all occurrences as pairs of the id of
+            /// the text containing the occurrence and the offset within that
+            /// text.
+            pub fn locate_in_docs(&self) -> Vec<(DocId, u64)> {
+                let backend = self.0.backend();
+                self.0
+                    .locate()
+                    .into_iter()
+                    .map(|position| backend.doc_position(position))
+                    .collect()
+            }
+        }
+    };
+}
+
+macro_rules! impl_try_new {
+    ($t:ty) => {
+        impl_try_new!($t; check_text);
+    };
+    ($t:ty, level) => {
+        impl_try_new!($t, level; check_text);
+    };
+    ($t:ty, texts) => {
+        impl_try_new!($t; check_texts);
+    };
+    ($t:ty, level, texts) => {
+        impl_try_new!($t, level; check_texts);
+    };
+    ($t:ty; $check:ident) => {
         impl<T: Character, C: Converter<T>> $t {
+            /// Create a new index like [`new`](Self::new), but return an
+            /// [`Error`] instead of panicking if the text is empty, has a
+            /// character outside the converter's alphabet or has a \0 where
+            /// [`Error::TerminatorInText`] doesn't allow one.
+            pub fn try_new<'t>(text: impl IntoText<'t, T>, converter: C) -> Result<Self, Error> {
+                let text = text.into_text();
+                $check(&text, &converter)?;
+                Ok(Self::new(text, converter))
+            }
+        }
+    };
+    ($t:ty, level; $check:ident) => {
+        impl<T: Character, C: Converter<T>> $t {
+            /// Create a new index like [`new`](Self::new), but return an
+            /// [`Error`] instead of panicking if the text is empty, has a
+            /// character outside the converter's alphabet or has a \0 where
+            /// [`Error::TerminatorInText`] doesn't allow one, or if `level`
+            /// is too large for the text.
+            pub fn try_new<'t>(
+                text: impl IntoText<'t, T>,
+                converter: C,
+                level: usize,
+            ) -> Result<Self, Error> {
+                let text = text.into_text();
+                $check(&text, &converter)?;
+                check_level(&text, level)?;
+                Ok(Self::new(text, converter, level))
+            }
+        }
+    };
+}
+
+macro_rules! impl_save_load {
+    ($t:ty, $name:literal $(, $g:ident $(: $b:path)?)*) => {
+        #[cfg(all(feature = "std", feature = "serde"))]
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t
+        where
+            T: Serialize + DeserializeOwned,
+            C: Serialize + DeserializeOwned,
+            $($g: Serialize + DeserializeOwned,)*
+        {
+            /// Save the index to `writer` in a stable, versioned binary
+            /// format.
+            ///
+            /// The components of the index, such as the BWT and the suffix
+            /// array samples, are written one after the other, each prefixed
+            /// with its length. The encoded index is never held in memory as
+            /// a whole, so `writer` can be a pipe or a network stream.
+            ///
+            /// The writer isn't buffered, so wrap it in a
+            /// [`BufWriter`](std::io::BufWriter) when writing to a file.
+            pub fn save<W: io::Write>(&self, writer: W) -> io::Result<()> {
+                persist::save(
+                    &self.0,
+                    &IndexInfo::new(self.0.backend(), $name)?,
+                    writer,
+                    persist::Compression::None,
+                )
+            }
+
+            /// Save the index to `writer` like [`save`](Self::save),
+            /// compressed with zstd at `level`.
+            ///
+            /// Levels range from 1 to 22; 0 selects zstd's default level.
+            /// [`load`](Self::load) detects and decompresses compressed
+            /// indexes. This requires the `compression` feature.
+            #[cfg(feature = "compression")]
+            pub fn save_compressed<W: io::Write>(&self, writer: W, level: i32) -> io::Result<()> {
+                persist::save(
+                    &self.0,
+                    &IndexInfo::new(self.0.backend(), $name)?,
+                    writer,
+                    persist::Compression::Zstd(level),
+                )
+            }
+
+            /// Load an index saved by [`save`](Self::save) from `reader`.
+            ///
+            /// Indexes saved by earlier versions of this crate can be
+            /// loaded. An error of kind
+            /// [`InvalidData`](std::io::ErrorKind::InvalidData) is returned
+            /// if `reader` doesn't hold an index of this kind, or holds one
+            /// in a format version newer than this crate supports.
+            pub fn load<R: io::Read>(reader: R) -> io::Result<Self> {
+                Ok(Self(persist::load(&persist::kind::<T>($name), reader)?))
+            }
+
+            /// Load an index saved by [`save`](Self::save) from the file at
+            /// `path`.
+            ///
+            /// This is like [`load`](Self::load), except that with the
+            /// [`LazySamples`](crate::LazySamples) sampling strategy the
+            /// suffix array samples are left in the file until they're
+            /// first needed.
+            pub fn load_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+                Ok(Self(persist::load_file(
+                    &persist::kind::<T>($name),
+                    path.as_ref(),
+                )?))
+            }
+
+            /// Save the index split into several files, so its parts can
+            /// be placed on different storage.
+            ///
+            /// See [`SplitPaths`] for the parts an index can be split
+            /// into. Load the index with [`load_split`](Self::load_split).
+            pub fn save_split(&self, paths: &SplitPaths) -> io::Result<()> {
+                persist::save_split(&self.0, &IndexInfo::new(self.0.backend(), $name)?, paths)
+            }
+
+            /// Load an index saved by [`save_split`](Self::save_split),
+            /// given the same files.
+            ///
+            /// An error of kind
+            /// [`InvalidData`](std::io::ErrorKind::InvalidData) is returned
+            /// if the files don't hold the parts they were given for, or
+            /// hold parts of different indexes. With the
+            /// [`LazySamples`](crate::LazySamples) sampling strategy the
+            /// samples file is only read when the samples are first needed.
+            pub fn load_split(paths: &SplitPaths) -> io::Result<Self> {
+                Ok(Self(persist::load_split(&persist::kind::<T>($name), paths)?))
+            }
+        }
+    };
+}
+
+macro_rules! impl_search_index {
+    ($t:ty, $s:ident, $st:ty $(, $g:ident $(: $b:path)?)*) => {
+        impl_owned_search!($t, $st $(, $g $(: $b)?)*);
+        impl_dyn_search!(count, $t, $st $(, $g $(: $b)?)*);
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> SearchIndex<T> for $t {
+            fn search<K>(&self, pattern: K) -> impl Search<'_, T>
+            where
+                K: AsRef<[T]>,
+            {
+                $s(self.0.search(pattern))
+            }
+
+            fn len(&self) -> u64 {
+                self.0.len()
+            }
+        }
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> HeapSize for $t {
+            fn heap_size(&self) -> usize {
+                self.0.heap_size()
+            }
+
+            fn size_breakdown(&self) -> SizeBreakdown {
+                self.0.size_breakdown()
+            }
+        }
+        // inherent
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
             /// Search for a pattern in the text.
             pub fn search<K>(&self, pattern: K) -> $st
             where
@@ -293,17 +1643,542 @@ macro_rules! impl_search_index_with_locate {
             {
                 $s(self.0.search(pattern))
             }
-            /// The size of the text in the index
+            /// Search for a pattern in the text like
+            /// [`search`](Self::search), but return an [`Error`] instead of
+            /// panicking if the pattern has a character outside the
+            /// converter's alphabet.
+            pub fn try_search<K>(&self, pattern: K) -> Result<$st, Error>
+            where
+                K: AsRef<[T]>,
+            {
+                self.0.check_pattern(pattern.as_ref())?;
+                Ok(self.search(pattern))
+            }
+            /// Resume a search result from a [`SearchSnapshot`] taken on
+            /// this index, without searching again.
+            ///
+            /// The snapshot must come from a search on this index, or one
+            /// loaded from the same saved index; for any other index the
+            /// result is meaningless.
+            ///
+            /// # Panics
+            ///
+            /// Panics if the snapshot's interval is out of range for this
+            /// index.
+            pub fn resume(&self, snapshot: &SearchSnapshot<T>) -> $st {
+                $s(self.0.resume(snapshot))
+            }
+            /// The size of the text in the index, including the terminator.
             pub fn len(&self) -> u64 {
                 SearchIndex::len(self)
             }
+            /// The length of the text in the index, without the terminator.
+            ///
+            /// Positions returned by locate queries are less than this.
+            pub fn text_len(&self) -> u64 {
+                SearchIndex::text_len(self)
+            }
+            /// The Burrows-Wheeler transform of the text.
+            ///
+            /// This includes the terminator character, so it has the same
+            /// length as [`len`](Self::len).
+            pub fn bwt(&self) -> Vec<T> {
+                self.0.bwt()
+            }
+            /// Statistics about the text and its alphabet.
+            ///
+            /// This takes time linear in the length of the text.
+            pub fn stats(&self) -> IndexStats<T> {
+                self.0.stats()
+            }
+            /// Precompute the suffix array interval of every pattern of
+            /// length `k`, so searches skip the first `k` steps.
+            ///
+            /// The table has `(σ - 1)^k` entries, where `σ` is the size of
+            /// the converter's alphabet, so this is only practical for small
+            /// alphabets and small `k`, such as DNA with `k` up to 12. Only
+            /// searches on the index itself use the table, not refinements
+            /// of a search result.
+            ///
+            /// Panics if the table would have more than 2^24 entries.
+            pub fn with_kmer_table(self, k: usize) -> Self {
+                Self(self.0.with_kmer_table(k))
+            }
+            /// Build a table of the occurrences of each character at every
+            /// `k`-th position of the BWT, so counting a character is a
+            /// lookup plus a scan of at most `k` positions.
+            ///
+            /// `k` is rounded up to a multiple of 16. The table takes
+            /// `8σ/k + 1/2` bytes per character of the text, where `σ` is
+            /// the size of the converter's alphabet, and makes count-heavy
+            /// workloads on small alphabets such as DNA several times
+            /// faster. It isn't saved along with the index.
+            ///
+            /// Panics if the converter's alphabet has more than 15
+            /// characters besides the terminator.
+            pub fn with_occ_table(self, k: usize) -> Self {
+                Self(self.0.with_occ_table(k))
+            }
+            /// Record the time and work of every search and locate on the
+            /// index, to be read back with
+            /// [`query_stats`](Self::query_stats).
+            ///
+            /// Searches also record their number of backward search steps,
+            /// and locates the lengths of their walks to sampled suffix
+            /// array positions. Measuring these repeats some of the work
+            /// after the time is taken, so an instrumented index is slower,
+            /// and keeps a record of every query in memory. Refinements of
+            /// a search result aren't recorded.
+            #[cfg(feature = "std")]
+            pub fn with_instrumentation(self) -> Self {
+                Self(self.0.with_instrumentation())
+            }
+            /// The queries recorded since the index was instrumented or the
+            /// records were last reset, or `None` if it isn't instrumented.
+            #[cfg(feature = "std")]
+            pub fn query_stats(&self) -> Option<QueryStats> {
+                self.0.query_stats()
+            }
+            /// Forget the queries recorded so far.
+            #[cfg(feature = "std")]
+            pub fn reset_query_stats(&self) {
+                self.0.reset_query_stats()
+            }
+            /// Keep the suffix array intervals of the `capacity` patterns
+            /// searched for most recently, so searching for one of them
+            /// again takes a hash lookup instead of a backward search.
+            ///
+            /// The cache is shared by all threads using the index, behind a
+            /// lock. It isn't saved along with the index, and is cleared
+            /// when the index changes. Refinements of a search result don't
+            /// use it.
+            #[cfg(feature = "cache")]
+            pub fn with_query_cache(self, capacity: usize) -> Self {
+                Self(self.0.with_query_cache(capacity, false))
+            }
+        }
+        impl_count_many!($t $(, $g $(: $b)?)*);
+        impl_seeds!($t $(, $g $(: $b)?)*);
+    };
+}
+
+macro_rules! impl_count_many {
+    ($t:ty $(, $g:ident $(: $b:path)?)*) => {
+        // inherent
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
+            /// Count the number of occurrences of each of `patterns`.
+            pub fn count_many<K>(&self, patterns: &[K]) -> Vec<u64>
+            where
+                K: AsRef<[T]>,
+            {
+                self.0.count_many(patterns)
+            }
+
+            /// Count the number of occurrences of each of `patterns`,
+            /// advancing the searches for all of them one character at a
+            /// time.
+            ///
+            /// The result is the same as that of
+            /// [`count_many`](Self::count_many). Before each step the
+            /// pending searches are sorted by their position in the BWT, so
+            /// that consecutive lookups hit nearby memory. For batches of
+            /// many short patterns against an index much larger than the
+            /// CPU cache this is faster than searching for the patterns one
+            /// after another, at the cost of memory for the pending
+            /// searches.
+            pub fn count_many_lockstep<K>(&self, patterns: &[K]) -> Vec<u64>
+            where
+                K: AsRef<[T]>,
+            {
+                self.0.count_many_lockstep(patterns)
+            }
+
+            /// Whether each of `patterns` occurs at least `n` times.
+            ///
+            /// The search for a pattern stops as soon as fewer than `n` of
+            /// its occurrences are left, skipping its remaining characters.
+            pub fn count_many_at_least<K>(&self, patterns: &[K], n: u64) -> Vec<bool>
+            where
+                K: AsRef<[T]>,
+            {
+                self.0.count_many_at_least(patterns, n)
+            }
+        }
+        #[cfg(feature = "rayon")]
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t
+        where
+            T: Send + Sync,
+            C: Send + Sync,
+            $($g: Send + Sync,)*
+        {
+            /// Count the number of occurrences of each of `patterns`,
+            /// distributing the patterns over the rayon thread pool.
+            ///
+            /// The result is the same as that of
+            /// [`count_many`](Self::count_many).
+            pub fn count_many_parallel<K>(&self, patterns: &[K]) -> Vec<u64>
+            where
+                K: AsRef<[T]> + Sync,
+            {
+                self.0.count_many_parallel(patterns)
+            }
+        }
+    };
+}
+
+macro_rules! impl_search_index_with_locate {
+    ($t:ty, $s:ident, $st:ty $(, $g:ident $(: $b:path)?)*) => {
+        impl_owned_search!($t, $st $(, $g $(: $b)?)*);
+        impl_dyn_search!(locate, $t, $st $(, $g $(: $b)?)*);
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> SearchIndex<T> for $t {
+            fn search<K>(&self, pattern: K) -> impl Search<'_, T>
+            where
+                K: AsRef<[T]>,
+            {
+                $s(self.0.search(pattern))
+            }
+
+            fn len(&self) -> u64 {
+                self.0.len()
+            }
+        }
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> SearchIndexWithLocate<T> for $t {
+            fn search<K>(&self, pattern: K) -> impl SearchWithLocate<'_, T>
+            where
+                K: AsRef<[T]>,
+            {
+                $s(self.0.search(pattern))
+            }
+        }
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> HeapSize for $t {
+            fn heap_size(&self) -> usize {
+                self.0.heap_size()
+            }
+
+            fn size_breakdown(&self) -> SizeBreakdown {
+                self.0.size_breakdown()
+            }
+        }
+        // inherent
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
+            /// Search for a pattern in the text.
+            pub fn search<K>(&self, pattern: K) -> $st
+            where
+                K: AsRef<[T]>,
+            {
+                $s(self.0.search(pattern))
+            }
+            /// Search for a pattern in the text like
+            /// [`search`](Self::search), but return an [`Error`] instead of
+            /// panicking if the pattern has a character outside the
+            /// converter's alphabet.
+            pub fn try_search<K>(&self, pattern: K) -> Result<$st, Error>
+            where
+                K: AsRef<[T]>,
+            {
+                self.0.check_pattern(pattern.as_ref())?;
+                Ok(self.search(pattern))
+            }
+            /// List the positions of all occurrences of a pattern, or return
+            /// an [`Error`] if the pattern has a character outside the
+            /// converter's alphabet.
+            pub fn try_locate<K>(&self, pattern: K) -> Result<Vec<u64>, Error>
+            where
+                K: AsRef<[T]>,
+            {
+                Ok(self.try_search(pattern)?.locate())
+            }
+            /// Resume a search result from a [`SearchSnapshot`] taken on
+            /// this index, without searching again.
+            ///
+            /// The snapshot must come from a search on this index, or one
+            /// loaded from the same saved index; for any other index the
+            /// result is meaningless.
+            ///
+            /// # Panics
+            ///
+            /// Panics if the snapshot's interval is out of range for this
+            /// index.
+            pub fn resume(&self, snapshot: &SearchSnapshot<T>) -> $st {
+                $s(self.0.resume(snapshot))
+            }
+            /// The size of the text in the index, including the terminator.
+            pub fn len(&self) -> u64 {
+                SearchIndex::len(self)
+            }
+            /// The length of the text in the index, without the terminator.
+            ///
+            /// Positions returned by locate queries are less than this.
+            pub fn text_len(&self) -> u64 {
+                SearchIndex::text_len(self)
+            }
+            /// The Burrows-Wheeler transform of the text.
+            ///
+            /// This includes the terminator character, so it has the same
+            /// length as [`len`](Self::len).
+            pub fn bwt(&self) -> Vec<T> {
+                self.0.bwt()
+            }
+            /// Statistics about the text and its alphabet.
+            ///
+            /// This takes time linear in the length of the text.
+            pub fn stats(&self) -> IndexStats<T> {
+                self.0.stats()
+            }
+            /// Precompute the suffix array interval of every pattern of
+            /// length `k`, so searches skip the first `k` steps.
+            ///
+            /// The table has `(σ - 1)^k` entries, where `σ` is the size of
+            /// the converter's alphabet, so this is only practical for small
+            /// alphabets and small `k`, such as DNA with `k` up to 12. Only
+            /// searches on the index itself use the table, not refinements
+            /// of a search result.
+            ///
+            /// Panics if the table would have more than 2^24 entries.
+            pub fn with_kmer_table(self, k: usize) -> Self {
+                Self(self.0.with_kmer_table(k))
+            }
+            /// Build a table of the occurrences of each character at every
+            /// `k`-th position of the BWT, so counting a character is a
+            /// lookup plus a scan of at most `k` positions.
+            ///
+            /// `k` is rounded up to a multiple of 16. The table takes
+            /// `8σ/k + 1/2` bytes per character of the text, where `σ` is
+            /// the size of the converter's alphabet, and makes count-heavy
+            /// workloads on small alphabets such as DNA several times
+            /// faster. It isn't saved along with the index.
+            ///
+            /// Panics if the converter's alphabet has more than 15
+            /// characters besides the terminator.
+            pub fn with_occ_table(self, k: usize) -> Self {
+                Self(self.0.with_occ_table(k))
+            }
+            /// Record the time and work of every search and locate on the
+            /// index, to be read back with
+            /// [`query_stats`](Self::query_stats).
+            ///
+            /// Searches also record their number of backward search steps,
+            /// and locates the lengths of their walks to sampled suffix
+            /// array positions. Measuring these repeats some of the work
+            /// after the time is taken, so an instrumented index is slower,
+            /// and keeps a record of every query in memory. Refinements of
+            /// a search result aren't recorded.
+            #[cfg(feature = "std")]
+            pub fn with_instrumentation(self) -> Self {
+                Self(self.0.with_instrumentation())
+            }
+            /// The queries recorded since the index was instrumented or the
+            /// records were last reset, or `None` if it isn't instrumented.
+            #[cfg(feature = "std")]
+            pub fn query_stats(&self) -> Option<QueryStats> {
+                self.0.query_stats()
+            }
+            /// Forget the queries recorded so far.
+            #[cfg(feature = "std")]
+            pub fn reset_query_stats(&self) {
+                self.0.reset_query_stats()
+            }
+            /// Keep the suffix array intervals of the `capacity` patterns
+            /// searched for most recently, so searching for one of them
+            /// again takes a hash lookup instead of a backward search.
+            ///
+            /// The cache is shared by all threads using the index, behind a
+            /// lock. It isn't saved along with the index, and is cleared
+            /// when the index changes. Refinements of a search result don't
+            /// use it.
+            #[cfg(feature = "cache")]
+            pub fn with_query_cache(self, capacity: usize) -> Self {
+                Self(self.0.with_query_cache(capacity, false))
+            }
+            /// Like [`with_query_cache`](Self::with_query_cache), but also
+            /// keep the positions found by the `capacity` most recent
+            /// distinct `locate` calls.
+            ///
+            /// Locate results are keyed by the suffix array interval of the
+            /// search, so they're also shared by refined and resumed
+            /// searches. As a result can hold many positions, the memory of
+            /// the cache is only bounded by the number of occurrences of
+            /// the patterns located.
+            #[cfg(feature = "cache")]
+            pub fn with_locate_cache(self, capacity: usize) -> Self {
+                Self(self.0.with_query_cache(capacity, true))
+            }
+            /// Reconstruct the full suffix array of the text.
+            ///
+            /// Entries missing from the sampled suffix array are recovered
+            /// by walking the LF-mapping, so this takes time proportional to
+            /// the sampling rate. With a sampling level of 0 no walking is
+            /// needed.
+            pub fn suffix_array(&self) -> Vec<u64> {
+                self.0.suffix_array()
+            }
+            /// Get the character at position `pos` of the text.
+            ///
+            /// This takes time proportional to the sampling rate.
+            pub fn char_at(&self, pos: u64) -> T {
+                self.0.char_at(pos)
+            }
+
+            /// Get the characters at positions `start..end` of the text.
+            ///
+            /// This takes time proportional to the sampling rate plus the
+            /// length of the range.
+            pub fn extract(&self, start: u64, end: u64) -> Vec<T> {
+                self.0.extract(start, end)
+            }
+
+            /// Iterate over the `len` characters of the text starting at
+            /// position `pos`.
+            ///
+            /// The iterator knows its length and can be reversed, so it
+            /// works with adapters such as `rev`. The characters are
+            /// extracted up front as with [`extract`](Self::extract), which
+            /// takes time proportional to the sampling rate plus `len`.
+            pub fn iter_range(
+                &self,
+                pos: u64,
+                len: u64,
+            ) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator {
+                self.extract(pos, pos + len).into_iter()
+            }
+
+            /// Get the characters at positions `start..end` of the text,
+            /// rendered as a string by `detokenize`.
+            ///
+            /// This is useful for texts of token ids, where `detokenize`
+            /// is the tokenizer's decoder.
+            pub fn extract_with<F>(&self, start: u64, end: u64, detokenize: F) -> String
+            where
+                F: FnOnce(&[T]) -> String,
+            {
+                detokenize(&self.extract(start, end))
+            }
+
+            /// Build the LCP array of the text.
+            ///
+            /// The returned [`LcpArray`] is independent of the index and can
+            /// be stored alongside it.
+            pub fn lcp_array(&self) -> LcpArray {
+                self.0.lcp_array()
+            }
+        }
+        impl_count_many!($t $(, $g $(: $b)?)*);
+        impl_seeds!($t $(, $g $(: $b)?)*);
+    };
+}
+
+macro_rules! impl_seeds {
+    ($t:ty $(, $g:ident $(: $b:path)?)*) => {
+        // inherent
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
+            /// Find the anchors of `query` of at least `min_len` characters,
+            /// and at least one, for an aligner to extend, in order of
+            /// their offset in the query.
+            ///
+            /// The longest substring ending at the end of the query that
+            /// occurs in the text is found by backward search. The
+            /// character before it is taken to be a mismatch, and the
+            /// search restarts just before it, until the start of the query
+            /// is reached. So the anchors don't overlap, and the search
+            /// takes one step for each character of the query. Characters
+            /// outside the converter's alphabet are mismatches too.
+            pub fn seeds<K>(&self, query: K, min_len: usize) -> Vec<Anchor>
+            where
+                K: AsRef<[T]>,
+            {
+                self.0.seeds(query.as_ref(), min_len)
+            }
+
+            /// Find the anchors of each of `reads` like
+            /// [`seeds`](Self::seeds), leaving out anchors with more than
+            /// `max_hits` occurrences, which are too repetitive to place a
+            /// read.
+            ///
+            /// The searches for all reads are advanced one character at a
+            /// time, sorted by their position in the BWT before each step,
+            /// so that consecutive lookups hit nearby memory. For many short
+            /// reads against an index much larger than the CPU cache this is
+            /// faster than seeding the reads one after another.
+            pub fn seed_reads<K>(
+                &self,
+                reads: &[K],
+                min_len: usize,
+                max_hits: u64,
+            ) -> Vec<Vec<Anchor>>
+            where
+                K: AsRef<[T]>,
+            {
+                self.0.seed_reads(reads, min_len, max_hits)
+            }
+        }
+        #[cfg(feature = "rayon")]
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t
+        where
+            T: Send + Sync,
+            C: Send + Sync,
+            $($g: Send + Sync,)*
+        {
+            /// Find the anchors of each of `reads` like
+            /// [`seed_reads`](Self::seed_reads), distributing chunks of
+            /// reads over the rayon thread pool.
+            pub fn seed_reads_parallel<K>(
+                &self,
+                reads: &[K],
+                min_len: usize,
+                max_hits: u64,
+            ) -> Vec<Vec<Anchor>>
+            where
+                K: AsRef<[T]> + Sync,
+            {
+                self.0.seed_reads_parallel(reads, min_len, max_hits)
+            }
+        }
+    };
+}
+
+macro_rules! impl_suffix_tree {
+    ($t:ty $(, $g:ident $(: $b:path)?)*) => {
+        // inherent
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
+            /// Build a suffix tree of the text on top of the index.
+            ///
+            /// The tree borrows the index, and only adds its topology and
+            /// the string depths of its internal nodes. Building it takes
+            /// the time needed to reconstruct the suffix array.
+            pub fn suffix_tree(&self) -> SuffixTree<'_, T> {
+                self.0.suffix_tree()
+            }
+        }
+    };
+}
+
+macro_rules! impl_verify {
+    ($t:ty $(, $g:ident $(: $b:path)?)*) => {
+        // inherent
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
+            /// Check the internal invariants of the index, such as an
+            /// index loaded from untrusted or possibly corrupted storage.
+            ///
+            /// The character counts are checked against the BWT, the
+            /// LF-mapping is checked to be a permutation on a sample of
+            /// rows, and the suffix array samples, if any, are checked to
+            /// resolve to valid text positions. This takes time
+            /// proportional to the size of the alphabet and the sampling
+            /// rate, not to the length of the text, so damage outside the
+            /// checked rows can go unnoticed. The checksums of the saved
+            /// format catch damage to stored bytes; this catches indexes
+            /// whose parts don't fit together. A badly damaged index may
+            /// still make it panic.
+            pub fn verify(&self) -> VerifyReport {
+                self.0.backend().verify()
+            }
         }
     };
 }
 
 macro_rules! impl_search {
-    ($t:ty) => {
-        impl<'a, T: Character, C: Converter<T>> Search<'a, T> for $t {
+    ($t:ty $(, $g:ident $(: $b:path)?)*) => {
+        impl<'a, T: Character, C: Converter<T> $(, $g $(: $b)?)*> Search<'a, T> for $t {
             fn search<K>(&self, pattern: K) -> Self
             where
                 K: AsRef<[T]>,
@@ -311,10 +2186,34 @@ macro_rules! impl_search {
                 Self(self.0.search(pattern))
             }
 
+            fn pattern(&self) -> &[T] {
+                self.0.pattern()
+            }
+
+            fn range(&self) -> (u64, u64) {
+                self.0.range()
+            }
+
+            fn index_len(&self) -> u64 {
+                self.0.backend().len()
+            }
+
             fn count(&self) -> u64 {
                 self.0.count()
             }
 
+            fn count_at_least(&self, n: u64) -> bool {
+                self.0.count_at_least(n)
+            }
+
+            fn extensions(&self) -> Vec<(T, u64)> {
+                self.0.extensions()
+            }
+
+            fn left_extensions(&self) -> Vec<(T, u64)> {
+                self.0.left_extensions()
+            }
+
             fn iter_backward(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
                 self.0.iter_backward(i)
             }
@@ -322,9 +2221,23 @@ macro_rules! impl_search {
             fn iter_forward(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
                 self.0.iter_forward(i)
             }
+
+            fn iter_backward_in_text(&'a self, i: u64) -> impl Iterator<Item = T> + 'a
+            where
+                T: 'a,
+            {
+                self.0.iter_backward_in_text(i)
+            }
+
+            fn iter_forward_in_text(&'a self, i: u64) -> impl Iterator<Item = T> + 'a
+            where
+                T: 'a,
+            {
+                self.0.iter_forward_in_text(i)
+            }
         }
         // inherent
-        impl<'a, T: Character, C: Converter<T>> $t {
+        impl<'a, T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
             /// Search in the current search result, refining it.
             ///
             /// This adds a prefix `pattern` to the existing pattern, and
@@ -336,11 +2249,85 @@ macro_rules! impl_search {
                 Search::search(self, pattern)
             }
 
+            /// Search in the current search result like
+            /// [`search`](Self::search), but return an [`Error`] instead of
+            /// panicking if the pattern has a character outside the
+            /// converter's alphabet.
+            pub fn try_search<K>(&self, pattern: K) -> Result<Self, Error>
+            where
+                K: AsRef<[T]>,
+            {
+                self.0.check_pattern(pattern.as_ref())?;
+                Ok(Search::search(self, pattern))
+            }
+
+            /// Extend the pattern by prepending the character `c`, a single
+            /// step of backward search.
+            pub fn extend_backward(&self, c: T) -> Self {
+                Search::extend_backward(self, c)
+            }
+
+            /// The pattern searched for, including the prefixes added by
+            /// refining searches.
+            pub fn pattern(&self) -> &[T] {
+                Search::pattern(self)
+            }
+
+            /// The range `[s, e)` of the suffix array matched by the pattern.
+            ///
+            /// Its width is the number of occurrences, counting those in
+            /// texts that were deleted from a multi-text index.
+            pub fn range(&self) -> (u64, u64) {
+                Search::range(self)
+            }
+
+            /// The size of the text in the index searched, including the
+            /// terminator.
+            pub fn index_len(&self) -> u64 {
+                Search::index_len(self)
+            }
+
             /// Count the number of occurrences.
             pub fn count(&self) -> u64 {
                 Search::count(self)
             }
 
+            /// Whether the pattern occurs at least `n` times.
+            pub fn count_at_least(&self, n: u64) -> bool {
+                Search::count_at_least(self, n)
+            }
+
+            /// Whether the pattern occurs in the text at all.
+            pub fn is_present(&self) -> bool {
+                Search::is_present(self)
+            }
+
+            /// A snapshot of this search result, which can be serialized and
+            /// turned back into a search result with `resume` on the index.
+            pub fn snapshot(&self) -> SearchSnapshot<T> {
+                self.0.snapshot()
+            }
+
+            /// List the characters that can follow the pattern in the text,
+            /// each with the number of occurrences of the pattern extended
+            /// by it.
+            ///
+            /// The result is ordered by character. Terminator characters are
+            /// not included.
+            pub fn extensions(&self) -> Vec<(T, u64)> {
+                Search::extensions(self)
+            }
+
+            /// List the characters that can precede the pattern in the text,
+            /// each with the number of occurrences of the pattern extended
+            /// by it.
+            ///
+            /// The result is ordered by character. Terminator characters are
+            /// not included.
+            pub fn left_extensions(&self) -> Vec<(T, u64)> {
+                Search::left_extensions(self)
+            }
+
             /// Get an iterator that goes backwards through the text, producing
             /// [`Character`].
             pub fn iter_backward(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
@@ -352,44 +2339,256 @@ macro_rules! impl_search {
             pub fn iter_forward(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
                 Search::iter_forward(self, i)
             }
+
+            /// Get an iterator that goes backwards through the text from
+            /// occurrence `i`, ending at the start of the text instead of
+            /// wrapping around.
+            ///
+            /// In a multi-text index it ends at the start of the text
+            /// containing the occurrence.
+            pub fn iter_backward_in_text(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
+                Search::iter_backward_in_text(self, i)
+            }
+
+            /// Get an iterator that goes forwards through the text from
+            /// occurrence `i`, ending at the terminator instead of wrapping
+            /// around.
+            ///
+            /// In a multi-text index it ends at the end of the text
+            /// containing the occurrence.
+            pub fn iter_forward_in_text(&'a self, i: u64) -> impl Iterator<Item = T> + 'a {
+                Search::iter_forward_in_text(self, i)
+            }
+
+            /// Get an iterator that goes backwards through the text from
+            /// occurrence `i` like [`iter_backward`](Self::iter_backward),
+            /// but return an [`Error`] if `i` isn't less than the number of
+            /// occurrences, instead of panicking in debug builds and
+            /// producing arbitrary characters otherwise.
+            pub fn try_iter_backward(
+                &'a self,
+                i: u64,
+            ) -> Result<impl Iterator<Item = T> + 'a, Error> {
+                self.0.check_offset(i)?;
+                Ok(Search::iter_backward(self, i))
+            }
+
+            /// Get an iterator that goes forwards through the text from
+            /// occurrence `i` like [`iter_forward`](Self::iter_forward), but
+            /// return an [`Error`] if `i` is out of range.
+            pub fn try_iter_forward(
+                &'a self,
+                i: u64,
+            ) -> Result<impl Iterator<Item = T> + 'a, Error> {
+                self.0.check_offset(i)?;
+                Ok(Search::iter_forward(self, i))
+            }
         }
     };
 }
 
 macro_rules! impl_search_locate {
-    ($t:ty) => {
-        impl<'a, T: Character, C: Converter<T>> SearchWithLocate<'a, T> for $t {
+    ($t:ty $(, $g:ident $(: $b:path)?)*) => {
+        impl<'a, T: Character, C: Converter<T> $(, $g $(: $b)?)*> SearchWithLocate<'a, T> for $t {
             fn locate(&self) -> Vec<u64> {
                 self.0.locate()
             }
+
+            fn fold_positions<A, F>(&self, init: A, f: F) -> A
+            where
+                F: FnMut(A, u64) -> A,
+            {
+                self.0.fold_positions(init, f)
+            }
+
+            fn matches(&'a self) -> impl Iterator<Item = Match<'a, T, Self>> + 'a {
+                let pattern_len = self.0.pattern_len();
+                self.0
+                    .locate_each()
+                    .map(move |(i, position, doc_id)| {
+                        Match::new(self, i, position, pattern_len, doc_id)
+                    })
+            }
         }
         // inherent
-        impl<'a, T: Character, C: Converter<T>> $t {
+        impl<'a, T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
             /// List the position of all occurrences.
+            ///
+            /// Positions are offsets in the text, from 0 up to the
+            /// `text_len` of the index.
             pub fn locate(&self) -> Vec<u64> {
                 SearchWithLocate::locate(self)
             }
+
+            /// Iterate over all occurrences as [`Match`] objects.
+            pub fn matches(&'a self) -> impl Iterator<Item = Match<'a, T, Self>> + 'a {
+                SearchWithLocate::matches(self)
+            }
+
+            /// Fold `f` over the positions of all occurrences, in no
+            /// particular order, without collecting them in a vector first.
+            pub fn fold_positions<A, F>(&self, init: A, f: F) -> A
+            where
+                F: FnMut(A, u64) -> A,
+            {
+                SearchWithLocate::fold_positions(self, init, f)
+            }
+
+            /// Call `f` with the position of every occurrence, in no
+            /// particular order, without collecting the positions in a
+            /// vector first.
+            pub fn for_each_position<F>(&self, f: F)
+            where
+                F: FnMut(u64),
+            {
+                SearchWithLocate::for_each_position(self, f)
+            }
+        }
+        #[cfg(feature = "rayon")]
+        impl<'a, T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t
+        where
+            T: Send + Sync,
+            C: Send + Sync,
+            $($g: Send + Sync,)*
+        {
+            /// List the position of all occurrences, walking the LF-mapping
+            /// for each of them on the rayon thread pool.
+            ///
+            /// The result is the same as that of [`locate`](Self::locate).
+            pub fn locate_parallel(&self) -> Vec<u64> {
+                self.0.locate_parallel()
+            }
         }
     };
 }
 
-impl_search_index!(FMIndex<T, C>, FMIndexSearch, FMIndexSearch<T, C>);
+impl_search_index!(FMIndex<T, C>, FMIndexSearch, FMIndexSearch<'_, T, C>);
 impl_search!(FMIndexSearch<'a, T, C>);
 
-impl_search_index_with_locate!(FMIndexWithLocate<T, C>, FMIndexSearchWithLocate, FMIndexSearchWithLocate<T, C>);
-impl_search!(FMIndexSearchWithLocate<'a, T, C>);
-impl_search_locate!(FMIndexSearchWithLocate<'a, T, C>);
+impl_search_index_with_locate!(FMIndexWithLocate<T, C, S>, FMIndexSearchWithLocate, FMIndexSearchWithLocate<'_, T, C, S>, S: SamplingStrategy);
+impl_search!(FMIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_search_locate!(FMIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_suffix_tree!(FMIndexWithLocate<T, C, S>, S: SamplingStrategy);
+
+impl_search_index!(SlimFMIndex<T, C>, SlimFMIndexSearch, SlimFMIndexSearch<'_, T, C>);
+impl_search!(SlimFMIndexSearch<'a, T, C>);
 
-impl_search_index!(RLFMIndex<T, C>, RLFMIndexSearch, RLFMIndexSearch<T, C>);
+#[cfg(feature = "std")]
+impl_search_index_with_locate!(DiskFMIndex<T, C>, DiskFMIndexSearch, DiskFMIndexSearch<'_, T, C>);
+#[cfg(feature = "std")]
+impl_search!(DiskFMIndexSearch<'a, T, C>);
+#[cfg(feature = "std")]
+impl_search_locate!(DiskFMIndexSearch<'a, T, C>);
+#[cfg(feature = "std")]
+impl_suffix_tree!(DiskFMIndex<T, C>);
+
+#[cfg(feature = "std")]
+impl_search_index_with_locate!(MappedFMIndex<T, C>, MappedFMIndexSearch, MappedFMIndexSearch<'_, T, C>);
+#[cfg(feature = "std")]
+impl_search!(MappedFMIndexSearch<'a, T, C>);
+#[cfg(feature = "std")]
+impl_search_locate!(MappedFMIndexSearch<'a, T, C>);
+#[cfg(feature = "std")]
+impl_suffix_tree!(MappedFMIndex<T, C>);
+
+impl_search_index!(HuffmanFMIndex<T, C>, HuffmanFMIndexSearch, HuffmanFMIndexSearch<'_, T, C>);
+impl_search!(HuffmanFMIndexSearch<'a, T, C>);
+
+impl_search_index_with_locate!(HuffmanFMIndexWithLocate<T, C, S>, HuffmanFMIndexSearchWithLocate, HuffmanFMIndexSearchWithLocate<'_, T, C, S>, S: SamplingStrategy);
+impl_search!(HuffmanFMIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_search_locate!(HuffmanFMIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_suffix_tree!(HuffmanFMIndexWithLocate<T, C, S>, S: SamplingStrategy);
+
+impl_search_index!(DNAFMIndex<T, C>, DNAFMIndexSearch, DNAFMIndexSearch<'_, T, C>);
+impl_search!(DNAFMIndexSearch<'a, T, C>);
+
+impl_search_index_with_locate!(DNAFMIndexWithLocate<T, C, S>, DNAFMIndexSearchWithLocate, DNAFMIndexSearchWithLocate<'_, T, C, S>, S: SamplingStrategy);
+impl_search!(DNAFMIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_search_locate!(DNAFMIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_suffix_tree!(DNAFMIndexWithLocate<T, C, S>, S: SamplingStrategy);
+
+impl_search_index!(SmallFMIndex<T, C>, SmallFMIndexSearch, SmallFMIndexSearch<'_, T, C>);
+impl_search!(SmallFMIndexSearch<'a, T, C>);
+
+impl_search_index_with_locate!(SmallFMIndexWithLocate<T, C, S>, SmallFMIndexSearchWithLocate, SmallFMIndexSearchWithLocate<'_, T, C, S>, S: SamplingStrategy);
+impl_search!(SmallFMIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_search_locate!(SmallFMIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_suffix_tree!(SmallFMIndexWithLocate<T, C, S>, S: SamplingStrategy);
+
+impl_search_index!(RLFMIndex<T, C>, RLFMIndexSearch, RLFMIndexSearch<'_, T, C>);
 impl_search!(RLFMIndexSearch<'a, T, C>);
 
-impl_search_index_with_locate!(RLFMIndexWithLocate<T, C>, RLFMIndexSearchWithLocate, RLFMIndexSearchWithLocate<T, C>);
-impl_search!(RLFMIndexSearchWithLocate<'a, T, C>);
-impl_search_locate!(RLFMIndexSearchWithLocate<'a, T, C>);
+impl_search_index_with_locate!(RLFMIndexWithLocate<T, C, S>, RLFMIndexSearchWithLocate, RLFMIndexSearchWithLocate<'_, T, C, S>, S: SamplingStrategy);
+impl_search!(RLFMIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_search_locate!(RLFMIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_suffix_tree!(RLFMIndexWithLocate<T, C, S>, S: SamplingStrategy);
+
+impl_search_index!(CSAIndex<T, C>, CSAIndexSearch, CSAIndexSearch<'_, T, C>);
+impl_search!(CSAIndexSearch<'a, T, C>);
+
+impl_search_index_with_locate!(CSAIndexWithLocate<T, C, S>, CSAIndexSearchWithLocate, CSAIndexSearchWithLocate<'_, T, C, S>, S: SamplingStrategy);
+impl_search!(CSAIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_search_locate!(CSAIndexSearchWithLocate<'a, T, C, S>, S: SamplingStrategy);
+impl_suffix_tree!(CSAIndexWithLocate<T, C, S>, S: SamplingStrategy);
+
+impl_search_index_with_locate!(RIndex<T, C>, RIndexSearch, RIndexSearch<'_, T, C>);
+impl_search!(RIndexSearch<'a, T, C>);
+impl_search_locate!(RIndexSearch<'a, T, C>);
+impl_suffix_tree!(RIndex<T, C>);
+
+impl_search_index!(MultiTextFMIndex<T, C, M>, MultiTextFMIndexSearch, MultiTextFMIndexSearch<'_, T, C, M>, M);
+impl_search!(MultiTextFMIndexSearch<'a, T, C, M>, M);
+
+impl_search_index_with_locate!(MultiTextFMIndexWithLocate<T, C, M, S>, MultiTextFMIndexSearchWithLocate, MultiTextFMIndexSearchWithLocate<'_, T, C, M, S>, M, S: SamplingStrategy);
+impl_search!(MultiTextFMIndexSearchWithLocate<'a, T, C, M, S>, M, S: SamplingStrategy);
+impl_search_locate!(MultiTextFMIndexSearchWithLocate<'a, T, C, M, S>, M, S: SamplingStrategy);
+
+impl_multi_text!(MultiTextFMIndex<T, C, M>, M);
+impl_multi_text!(MultiTextFMIndexWithLocate<T, C, M, S>, M, S: SamplingStrategy);
+impl_multi_text_search!(MultiTextFMIndexSearch<'a, T, C, M>, M);
+impl_multi_text_search!(MultiTextFMIndexSearchWithLocate<'a, T, C, M, S>, M, S: SamplingStrategy);
+impl_multi_text_search_locate!(MultiTextFMIndexSearchWithLocate<'a, T, C, M, S>, M, S: SamplingStrategy);
+
+impl_verify!(FMIndex<T, C>);
+impl_verify!(FMIndexWithLocate<T, C, S>, S: SamplingStrategy);
+impl_verify!(SlimFMIndex<T, C>);
+impl_verify!(HuffmanFMIndex<T, C>);
+impl_verify!(HuffmanFMIndexWithLocate<T, C, S>, S: SamplingStrategy);
+impl_verify!(DNAFMIndex<T, C>);
+impl_verify!(DNAFMIndexWithLocate<T, C, S>, S: SamplingStrategy);
+impl_verify!(SmallFMIndex<T, C>);
+impl_verify!(SmallFMIndexWithLocate<T, C, S>, S: SamplingStrategy);
 
-impl_search_index!(MultiTextFMIndex<T, C>, MultiTextFMIndexSearch, MultiTextFMIndexSearch<T, C>);
-impl_search!(MultiTextFMIndexSearch<'a, T, C>);
+impl_try_new!(FMIndex<T, C>);
+impl_try_new!(FMIndexWithLocate<T, C>, level);
+impl_try_new!(SlimFMIndex<T, C>);
+impl_try_new!(HuffmanFMIndex<T, C>);
+impl_try_new!(HuffmanFMIndexWithLocate<T, C>, level);
+impl_try_new!(DNAFMIndex<T, C>);
+impl_try_new!(DNAFMIndexWithLocate<T, C>, level);
+impl_try_new!(SmallFMIndex<T, C>);
+impl_try_new!(SmallFMIndexWithLocate<T, C>, level);
+impl_try_new!(RLFMIndex<T, C>);
+impl_try_new!(RLFMIndexWithLocate<T, C>, level);
+impl_try_new!(CSAIndex<T, C>);
+impl_try_new!(CSAIndexWithLocate<T, C>, level);
+impl_try_new!(RIndex<T, C>);
+impl_try_new!(MultiTextFMIndex<T, C>, texts);
+impl_try_new!(MultiTextFMIndexWithLocate<T, C>, level, texts);
 
-impl_search_index_with_locate!(MultiTextFMIndexWithLocate<T, C>, MultiTextFMIndexSearchWithLocate, MultiTextFMIndexSearchWithLocate<T, C>);
-impl_search!(MultiTextFMIndexSearchWithLocate<'a, T, C>);
-impl_search_locate!(MultiTextFMIndexSearchWithLocate<'a, T, C>);
+impl_save_load!(FMIndex<T, C>, "FMIndex");
+impl_save_load!(FMIndexWithLocate<T, C, S>, "FMIndexWithLocate", S: SamplingStrategy);
+impl_save_load!(SlimFMIndex<T, C>, "SlimFMIndex");
+impl_save_load!(HuffmanFMIndex<T, C>, "HuffmanFMIndex");
+impl_save_load!(HuffmanFMIndexWithLocate<T, C, S>, "HuffmanFMIndexWithLocate", S: SamplingStrategy);
+impl_save_load!(DNAFMIndex<T, C>, "DNAFMIndex");
+impl_save_load!(DNAFMIndexWithLocate<T, C, S>, "DNAFMIndexWithLocate", S: SamplingStrategy);
+impl_save_load!(SmallFMIndex<T, C>, "SmallFMIndex");
+impl_save_load!(SmallFMIndexWithLocate<T, C, S>, "SmallFMIndexWithLocate", S: SamplingStrategy);
+impl_save_load!(RLFMIndex<T, C>, "RLFMIndex");
+impl_save_load!(RLFMIndexWithLocate<T, C, S>, "RLFMIndexWithLocate", S: SamplingStrategy);
+impl_save_load!(CSAIndex<T, C>, "CSAIndex");
+impl_save_load!(CSAIndexWithLocate<T, C, S>, "CSAIndexWithLocate", S: SamplingStrategy);
+impl_save_load!(RIndex<T, C>, "RIndex");
+impl_save_load!(MultiTextFMIndex<T, C, M>, "MultiTextFMIndex", M);
+impl_save_load!(MultiTextFMIndexWithLocate<T, C, M, S>, "MultiTextFMIndexWithLocate", M, S: SamplingStrategy);
@@ -0,0 +1,345 @@
+//! Huffman-shaped wavelet trees.
+use crate::sequence::{PackedSymbols, Sequence};
+
+use alloc::collections::BinaryHeap;
+use alloc::{vec, vec::Vec};
+use core::cmp::Reverse;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use vers_vecs::{BitVec, RsVec};
+
+/// A wavelet tree shaped by the Huffman code of its symbols.
+///
+/// Frequent symbols get short codes, so on skewed alphabets such as natural
+/// language text both the average number of rank operations per query and
+/// the total size are smaller than with a balanced wavelet structure.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HuffmanWaveletTree {
+    // the bits of all nodes at the same depth, concatenated
+    levels: Vec<RsVec>,
+    nodes: Vec<Node>,
+    root: Child,
+    // (code, code length) per symbol; code bits are read from the least
+    // significant one. Symbols that do not occur have length u8::MAX.
+    codes: Vec<(u64, u8)>,
+    len: usize,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Node {
+    level: usize,
+    // start of the bits of this node in its level
+    offset: usize,
+    // number of ones in the level before `offset`
+    ones: usize,
+    children: [Child; 2],
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Child {
+    Node(usize),
+    Leaf(u64),
+}
+
+// The code of each symbol, the children of each inner node and the root.
+type Shape = (Vec<(u64, u8)>, Vec<(Child, Child)>, Child);
+
+impl HuffmanWaveletTree {
+    fn huffman_codes(counts: &[u64]) -> Shape {
+        // Merge the two lightest subtrees until one is left. Ties are broken
+        // by id, so the shape is deterministic.
+        let sigma = counts.len();
+        let child = |id: usize| {
+            if id < sigma {
+                Child::Leaf(id as u64)
+            } else {
+                Child::Node(id - sigma)
+            }
+        };
+        let mut heap = counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .map(|(c, &count)| Reverse((count, c)))
+            .collect::<BinaryHeap<_>>();
+        let mut merged = Vec::new();
+        while heap.len() > 1 {
+            let Reverse((w0, id0)) = heap.pop().unwrap();
+            let Reverse((w1, id1)) = heap.pop().unwrap();
+            merged.push((child(id0), child(id1)));
+            heap.push(Reverse((w0 + w1, sigma + merged.len() - 1)));
+        }
+        let root = heap
+            .pop()
+            .map_or(Child::Leaf(0), |Reverse((_, id))| child(id));
+
+        let mut codes = vec![(0, u8::MAX); counts.len()];
+        let mut stack = vec![(root, 0u64, 0u8)];
+        while let Some((child, code, len)) = stack.pop() {
+            match child {
+                Child::Leaf(c) => codes[c as usize] = (code, len),
+                Child::Node(m) => {
+                    let (c0, c1) = merged[m];
+                    stack.push((c0, code, len + 1));
+                    stack.push((c1, code | (1 << len), len + 1));
+                }
+            }
+        }
+        (codes, merged, root)
+    }
+
+    fn child(&self, child: Child) -> Option<&Node> {
+        match child {
+            Child::Node(m) => Some(&self.nodes[m]),
+            Child::Leaf(_) => None,
+        }
+    }
+
+    fn bit(&self, node: &Node, i: usize) -> u64 {
+        self.levels[node.level].get_unchecked(node.offset + i)
+    }
+
+    // The number of `b` bits in the first `i` bits of `node`.
+    fn node_rank(&self, node: &Node, b: u64, i: usize) -> usize {
+        let ones = self.levels[node.level].rank1(node.offset + i) - node.ones;
+        if b == 1 {
+            ones
+        } else {
+            i - ones
+        }
+    }
+
+    // The position in `node` of the `b` bit with rank `k`.
+    fn node_select(&self, node: &Node, b: u64, k: usize) -> usize {
+        let level = &self.levels[node.level];
+        let p = if b == 1 {
+            level.select1(node.ones + k)
+        } else {
+            level.select0(node.offset - node.ones + k)
+        };
+        p - node.offset
+    }
+}
+
+impl Sequence for HuffmanWaveletTree {
+    fn from_symbols(symbols: &PackedSymbols, alphabet_size: u64) -> Self {
+        let mut counts = vec![0; alphabet_size as usize];
+        for c in symbols.iter() {
+            counts[c as usize] += 1;
+        }
+        let (codes, merged, root) = Self::huffman_codes(&counts);
+
+        // Distribute the symbols over the nodes, top-down. The symbols at a
+        // node keep their relative order.
+        let mut bits = vec![BitVec::new(); merged.len()];
+        let mut depth = vec![0u8; merged.len()];
+        if let Child::Node(r) = root {
+            let mut stack = vec![(r, symbols.clone())];
+            while let Some((m, seq)) = stack.pop() {
+                let d = depth[m];
+                let mut parts = [
+                    PackedSymbols::with_capacity(0, alphabet_size),
+                    PackedSymbols::with_capacity(0, alphabet_size),
+                ];
+                for c in seq.iter() {
+                    let b = (codes[c as usize].0 >> d) & 1;
+                    bits[m].append(b == 1);
+                    parts[b as usize].push(c);
+                }
+                let (c0, c1) = merged[m];
+                for (child, part) in [c0, c1].into_iter().zip(parts) {
+                    if let Child::Node(k) = child {
+                        depth[k] = d + 1;
+                        stack.push((k, part));
+                    }
+                }
+            }
+        }
+        let height = depth.iter().map(|&d| d as usize + 1).max().unwrap_or(0);
+        let mut levels = vec![BitVec::new(); height];
+        let mut offsets = Vec::with_capacity(merged.len());
+        for (bits, &d) in bits.iter().zip(&depth) {
+            let level = &mut levels[d as usize];
+            offsets.push(level.len());
+            for i in 0..bits.len() {
+                level.append_bit(bits.get_unchecked(i));
+            }
+        }
+        let levels = levels
+            .into_iter()
+            .map(RsVec::from_bit_vec)
+            .collect::<Vec<_>>();
+        let nodes = merged
+            .into_iter()
+            .zip(depth.iter().zip(offsets))
+            .map(|((c0, c1), (&d, offset))| Node {
+                level: d as usize,
+                offset,
+                ones: levels[d as usize].rank1(offset),
+                children: [c0, c1],
+            })
+            .collect();
+
+        HuffmanWaveletTree {
+            levels,
+            nodes,
+            root,
+            codes,
+            len: symbols.len(),
+        }
+    }
+
+    #[cfg(all(feature = "std", feature = "serde"))]
+    fn alphabet_size(&self) -> u64 {
+        self.codes.len() as u64
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, mut i: usize) -> u64 {
+        let mut child = self.root;
+        while let Some(node) = self.child(child) {
+            let b = self.bit(node, i);
+            i = self.node_rank(node, b, i);
+            child = node.children[b as usize];
+        }
+        match child {
+            Child::Leaf(c) => c,
+            Child::Node(_) => unreachable!(),
+        }
+    }
+
+    fn rank(&self, mut i: usize, c: u64) -> usize {
+        let (code, len) = match self.codes.get(c as usize) {
+            Some(&(_, u8::MAX)) | None => return 0,
+            Some(&code) => code,
+        };
+        let mut child = self.root;
+        for d in 0..len {
+            let node = self.child(child).unwrap();
+            let b = (code >> d) & 1;
+            i = self.node_rank(node, b, i);
+            child = node.children[b as usize];
+        }
+        i
+    }
+
+    fn select(&self, mut k: usize, c: u64) -> usize {
+        let (code, len) = self.codes[c as usize];
+        debug_assert!(len != u8::MAX, "{} does not occur", c);
+        let mut path = Vec::with_capacity(len as usize);
+        let mut child = self.root;
+        for d in 0..len {
+            let Child::Node(m) = child else {
+                unreachable!()
+            };
+            path.push(m);
+            child = self.nodes[m].children[((code >> d) & 1) as usize];
+        }
+        for (d, &m) in path.iter().enumerate().rev() {
+            k = self.node_select(&self.nodes[m], (code >> d) & 1, k);
+        }
+        k
+    }
+
+    fn interval_symbols(&self, s: usize, e: usize) -> Vec<(u64, u64)> {
+        let mut result = Vec::new();
+        let mut stack = vec![(self.root, s, e)];
+        while let Some((child, s, e)) = stack.pop() {
+            if s == e {
+                continue;
+            }
+            match child {
+                Child::Leaf(c) => {
+                    if c != 0 {
+                        result.push((c, (e - s) as u64));
+                    }
+                }
+                Child::Node(m) => {
+                    let node = &self.nodes[m];
+                    let (s1, e1) = (self.node_rank(node, 1, s), self.node_rank(node, 1, e));
+                    stack.push((node.children[0], s - s1, e - e1));
+                    stack.push((node.children[1], s1, e1));
+                }
+            }
+        }
+        result.sort_unstable();
+        result
+    }
+
+    fn heap_size(&self) -> usize {
+        self.levels.iter().map(|l| l.heap_size()).sum::<usize>()
+            + self.nodes.capacity() * core::mem::size_of::<Node>()
+            + self.codes.capacity() * core::mem::size_of::<(u64, u8)>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vers_vecs::WaveletMatrix;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_against_wavelet_matrix() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // a skewed distribution over 10 symbols
+        let symbols = (0..20000)
+            .map(|_| {
+                let x: u64 = rng.gen_range(0, 1024);
+                (64 - x.leading_zeros()) as u64
+            })
+            .collect::<Vec<_>>();
+        let packed = PackedSymbols::pack(symbols.iter().copied(), 12);
+        let hwt = HuffmanWaveletTree::from_symbols(&packed, 12);
+        let wm = WaveletMatrix::from_symbols(&packed, 12);
+        for (i, &c) in symbols.iter().enumerate() {
+            assert_eq!(hwt.get(i), c);
+        }
+        for c in 0..12 {
+            for i in (0..=symbols.len()).step_by(7) {
+                assert_eq!(
+                    hwt.rank(i, c),
+                    Sequence::rank(&wm, i, c),
+                    "rank({}, {})",
+                    i,
+                    c
+                );
+            }
+            let count = Sequence::rank(&wm, symbols.len(), c);
+            for k in 0..count {
+                assert_eq!(
+                    hwt.select(k, c),
+                    Sequence::select(&wm, k, c),
+                    "select({}, {})",
+                    k,
+                    c
+                );
+            }
+        }
+        for _ in 0..100 {
+            let s = rng.gen_range(0, symbols.len());
+            let e = rng.gen_range(s, symbols.len() + 1);
+            assert_eq!(
+                hwt.interval_symbols(s, e),
+                Sequence::interval_symbols(&wm, s, e)
+            );
+        }
+        assert!(hwt.heap_size() < Sequence::heap_size(&wm));
+    }
+
+    #[test]
+    fn test_single_symbol() {
+        let hwt = HuffmanWaveletTree::from_symbols(&PackedSymbols::pack([3, 3, 3], 4), 4);
+        assert_eq!(hwt.get(1), 3);
+        assert_eq!(hwt.rank(2, 3), 2);
+        assert_eq!(hwt.rank(2, 1), 0);
+        assert_eq!(hwt.select(2, 3), 2);
+    }
+}
@@ -0,0 +1,189 @@
+//! Timings and work counts of the queries on an instrumented index.
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+/// A query recorded by an instrumented index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryRecord {
+    /// A search for a pattern in the index.
+    Search {
+        /// The length of the pattern.
+        pattern_len: usize,
+        /// The number of backward search steps, each of which maps both
+        /// ends of the interval with the LF-mapping. Characters looked up
+        /// in a k-mer table don't take a step, and a search stops once no
+        /// occurrences are left. Results taken from a query cache still
+        /// report the steps of the search.
+        steps: u64,
+        /// The number of occurrences, including those in deleted texts.
+        occurrences: u64,
+        /// The time the search took.
+        time: Duration,
+    },
+    /// Listing the positions of the occurrences of a search result.
+    Locate {
+        /// The number of occurrences, including those in deleted texts.
+        occurrences: u64,
+        /// The total number of steps of the walks from the occurrences to
+        /// sampled suffix array positions.
+        walk_steps: u64,
+        /// The number of steps of the longest walk.
+        max_walk: u64,
+        /// The time the locate took, which excludes measuring the walks.
+        time: Duration,
+    },
+}
+
+/// The queries recorded by an instrumented index, in order, with
+/// summaries to compare sampling levels and backends.
+///
+/// Take these with `query_stats` on an index built with
+/// `with_instrumentation`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    records: Vec<QueryRecord>,
+}
+
+impl QueryStats {
+    /// All recorded queries, in the order they finished.
+    pub fn records(&self) -> &[QueryRecord] {
+        &self.records
+    }
+
+    fn searches(&self) -> impl Iterator<Item = (u64, Duration)> + '_ {
+        self.records.iter().filter_map(|record| match record {
+            QueryRecord::Search { steps, time, .. } => Some((*steps, *time)),
+            QueryRecord::Locate { .. } => None,
+        })
+    }
+
+    fn locates(&self) -> impl Iterator<Item = (u64, u64, u64, Duration)> + '_ {
+        self.records.iter().filter_map(|record| match record {
+            QueryRecord::Locate {
+                occurrences,
+                walk_steps,
+                max_walk,
+                time,
+            } => Some((*occurrences, *walk_steps, *max_walk, *time)),
+            QueryRecord::Search { .. } => None,
+        })
+    }
+
+    /// The number of searches.
+    pub fn search_count(&self) -> usize {
+        self.searches().count()
+    }
+
+    /// The total time of all searches.
+    pub fn search_time(&self) -> Duration {
+        self.searches().map(|(_, time)| time).sum()
+    }
+
+    /// The total number of backward search steps of all searches.
+    pub fn search_steps(&self) -> u64 {
+        self.searches().map(|(steps, _)| steps).sum()
+    }
+
+    /// The number of locates.
+    pub fn locate_count(&self) -> usize {
+        self.locates().count()
+    }
+
+    /// The total time of all locates.
+    pub fn locate_time(&self) -> Duration {
+        self.locates().map(|(_, _, _, time)| time).sum()
+    }
+
+    /// The total number of occurrences located.
+    pub fn located(&self) -> u64 {
+        self.locates().map(|(occurrences, ..)| occurrences).sum()
+    }
+
+    /// The total number of steps walked to locate all occurrences.
+    pub fn walk_steps(&self) -> u64 {
+        self.locates().map(|(_, walk_steps, ..)| walk_steps).sum()
+    }
+
+    /// The mean number of steps walked to locate an occurrence, or 0 if
+    /// nothing was located.
+    pub fn mean_walk(&self) -> f64 {
+        match self.located() {
+            0 => 0.0,
+            located => self.walk_steps() as f64 / located as f64,
+        }
+    }
+
+    /// The number of steps of the longest walk to locate an occurrence.
+    pub fn max_walk(&self) -> u64 {
+        self.locates()
+            .map(|(_, _, max_walk, _)| max_walk)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl std::fmt::Display for QueryStats {
+    // a line for searches and one for locates, with totals and means
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let searches = self.search_count().max(1) as u32;
+        writeln!(
+            f,
+            "{:<8} {:>10} queries {:>12?} total {:>12?} mean {:>8.1} steps",
+            "search",
+            self.search_count(),
+            self.search_time(),
+            self.search_time() / searches,
+            self.search_steps() as f64 / searches as f64
+        )?;
+        let locates = self.locate_count().max(1) as u32;
+        write!(
+            f,
+            "{:<8} {:>10} queries {:>12?} total {:>12?} mean {:>8.1} steps per occurrence, {} at most",
+            "locate",
+            self.locate_count(),
+            self.locate_time(),
+            self.locate_time() / locates,
+            self.mean_walk(),
+            self.max_walk()
+        )
+    }
+}
+
+/// Collects the queries of an instrumented index, from any thread.
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    records: Mutex<Vec<QueryRecord>>,
+}
+
+impl Clone for Recorder {
+    fn clone(&self) -> Self {
+        Recorder {
+            records: Mutex::new(self.records().clone()),
+        }
+    }
+}
+
+impl Recorder {
+    fn records(&self) -> MutexGuard<'_, Vec<QueryRecord>> {
+        // a push can't leave the records in an invalid state
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn record(&self, record: QueryRecord) {
+        self.records().push(record);
+    }
+
+    pub(crate) fn stats(&self) -> QueryStats {
+        QueryStats {
+            records: self.records().clone(),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.records().clear();
+    }
+
+    pub(crate) fn heap_size(&self) -> usize {
+        self.records().capacity() * std::mem::size_of::<QueryRecord>()
+    }
+}
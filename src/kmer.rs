@@ -0,0 +1,129 @@
+//! Lookup tables from short patterns to suffix array intervals.
+use crate::backend::{HeapSize, SearchIndexBackend};
+use crate::character::Character;
+use crate::converter::Converter;
+use crate::util;
+
+use alloc::vec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use vers_vecs::BitVec;
+
+/// The suffix array interval of every pattern of length `k`.
+///
+/// Patterns are numbered by reading their converted characters as digits,
+/// so the table has `(σ - 1)^k` entries, where `σ` is the size of the
+/// converted alphabet including the terminator.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct KmerTable {
+    k: usize,
+    base: u64,
+    word_size: usize,
+    // the interval [s, e) of each pattern, as consecutive words
+    intervals: BitVec,
+}
+
+impl KmerTable {
+    /// The largest number of patterns a table holds, enough for DNA with
+    /// `k` up to 12 or bytes with `k` up to 3.
+    pub(crate) const MAX_ENTRIES: u64 = 1 << 24;
+
+    /// Build the table of all patterns of length `k` for `backend`.
+    ///
+    /// Panics if there are more than [`MAX_ENTRIES`](Self::MAX_ENTRIES)
+    /// such patterns.
+    pub(crate) fn new<B: SearchIndexBackend>(backend: &B, k: usize) -> Self {
+        debug_assert!(k > 0, "k must be positive");
+        let converter = backend.get_converter();
+        let base = converter.len() - 1;
+        let n = backend.len();
+        let word_size = (util::log2(n) + 1) as usize;
+        let entries = u32::try_from(k)
+            .ok()
+            .and_then(|k| base.checked_pow(k))
+            .filter(|&entries| entries <= Self::MAX_ENTRIES);
+        let Some(entries) = entries else {
+            panic!(
+                "a k-mer table holds at most {} patterns, not {}^{}",
+                Self::MAX_ENTRIES,
+                base,
+                k
+            );
+        };
+        let entries = entries as usize;
+        let mut table = vec![(0, 0); entries];
+
+        // Prepend characters depth-first, so every interval is derived from
+        // that of its suffix with a single LF step.
+        let mut stack = vec![(0u64, 0usize, 0u64, n)];
+        while let Some((code, depth, s, e)) = stack.pop() {
+            if depth == k {
+                table[code as usize] = (s, e);
+                continue;
+            }
+            for c in 1..=base {
+                let (s, e) = if s < e {
+                    let c = converter.convert_inv(B::T::from_u64(c));
+                    (backend.lf_map2(c, s), backend.lf_map2(c, e))
+                } else {
+                    (s, e)
+                };
+                // the first character of the pattern is the most significant
+                // digit, and it is prepended last
+                let code = code + (c - 1) * base.pow(depth as u32);
+                stack.push((code, depth + 1, s, e));
+            }
+        }
+
+        let mut intervals = BitVec::with_capacity(entries * 2 * word_size);
+        for (s, e) in table {
+            intervals.append_bits(s, word_size);
+            intervals.append_bits(e, word_size);
+        }
+
+        KmerTable {
+            k,
+            base,
+            word_size,
+            intervals,
+        }
+    }
+
+    /// The length of the patterns in the table.
+    pub(crate) fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Look up the interval of `pattern`, which must have length `k`.
+    ///
+    /// Returns `None` if the pattern contains characters that convert to
+    /// the terminator.
+    pub(crate) fn get<T: Character, C: Converter<T>>(
+        &self,
+        pattern: &[T],
+        converter: &C,
+    ) -> Option<(u64, u64)> {
+        debug_assert_eq!(pattern.len(), self.k);
+        let mut code = 0;
+        for &c in pattern {
+            let c = converter.convert(c).into();
+            if c == 0 {
+                return None;
+            }
+            code = code * self.base + c - 1;
+        }
+        let i = code as usize * 2 * self.word_size;
+        Some((
+            self.intervals.get_bits_unchecked(i, self.word_size),
+            self.intervals
+                .get_bits_unchecked(i + self.word_size, self.word_size),
+        ))
+    }
+}
+
+impl HeapSize for KmerTable {
+    fn heap_size(&self) -> usize {
+        self.intervals.heap_size()
+    }
+}
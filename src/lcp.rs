@@ -0,0 +1,199 @@
+//! Longest common prefix (LCP) arrays.
+use crate::backend::HeapSize;
+use crate::character::Character;
+use crate::util;
+
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use vers_vecs::BitVec;
+
+// Number of LCP values covered by a single entry of the block minima table.
+const BLOCK_SIZE: usize = 64;
+
+/// The LCP array of a text, with range-minimum support.
+///
+/// `lcp(i)` is the length of the longest common prefix of the suffixes at
+/// positions `i - 1` and `i` of the suffix array. Terminator (`\0`)
+/// characters never match each other, so a common prefix never extends
+/// across the end of a text.
+///
+/// Range-minimum queries are answered with a sparse table over block minima,
+/// which keeps the additional space sublinear.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LcpArray {
+    word_size: usize,
+    lcp: BitVec,
+    len: usize,
+    // sparse[k][b] is the minimum of the blocks b..b + 2^k
+    sparse: Vec<Vec<u64>>,
+}
+
+impl LcpArray {
+    /// Build the LCP array using the Φ algorithm.
+    ///
+    /// Kärkkäinen, J., Manzini, G., & Puglisi, S. J. (2009). Permuted longest
+    /// common prefix array. In Lecture Notes in Computer Science (Vol. 5577).
+    pub(crate) fn new<T: Character>(text: &[T], sa: &[u64]) -> Self {
+        let n = sa.len();
+        debug_assert_eq!(text.len(), n);
+
+        // phi[sa[i]] = sa[i - 1]; the first suffix has no predecessor.
+        let mut phi = vec![u64::MAX; n];
+        for i in 1..n {
+            phi[sa[i] as usize] = sa[i - 1];
+        }
+
+        // plcp[p] is the LCP value of the suffix starting at text position p.
+        let mut plcp = vec![0u64; n];
+        let mut l = 0;
+        for (i, &j) in phi.iter().enumerate() {
+            if j == u64::MAX {
+                l = 0;
+                continue;
+            }
+            let j = j as usize;
+            while i + l < n && j + l < n && !text[i + l].is_zero() && text[i + l] == text[j + l] {
+                l += 1;
+            }
+            plcp[i] = l as u64;
+            l = l.saturating_sub(1);
+        }
+
+        let max = plcp.iter().copied().max().unwrap_or(0);
+        let word_size = (util::log2(max + 1) + 1) as usize;
+        let mut lcp = BitVec::with_capacity(n * word_size);
+        let mut block_mins = Vec::with_capacity(n.div_ceil(BLOCK_SIZE));
+        for (i, &p) in sa.iter().enumerate() {
+            let v = if i == 0 { 0 } else { plcp[p as usize] };
+            lcp.append_bits(v, word_size);
+            if i % BLOCK_SIZE == 0 {
+                block_mins.push(v);
+            } else if let Some(m) = block_mins.last_mut() {
+                *m = (*m).min(v);
+            }
+        }
+
+        let blocks = block_mins.len();
+        let mut sparse = vec![block_mins];
+        let mut k = 1;
+        while (1 << k) <= blocks {
+            let prev = &sparse[k - 1];
+            let half = 1 << (k - 1);
+            let level = (0..=(blocks - 2 * half))
+                .map(|b| prev[b].min(prev[b + half]))
+                .collect();
+            sparse.push(level);
+            k += 1;
+        }
+
+        LcpArray {
+            word_size,
+            lcp,
+            len: n,
+            sparse,
+        }
+    }
+
+    /// The number of entries, equal to the length of the suffix array.
+    pub fn len(&self) -> u64 {
+        self.len as u64
+    }
+
+    /// The length of the longest common prefix of the suffixes at positions
+    /// `i - 1` and `i` of the suffix array.
+    ///
+    /// `lcp(0)` is always 0.
+    pub fn lcp(&self, i: u64) -> u64 {
+        debug_assert!(i < self.len as u64, "{} is out of range", i);
+        self.lcp
+            .get_bits_unchecked(i as usize * self.word_size, self.word_size)
+    }
+
+    /// The minimum LCP value in the range `[s, e)`.
+    ///
+    /// For `s < e`, `range_min(s + 1, e)` is the length of the longest
+    /// common prefix of the suffixes at positions `s` and `e - 1` of the
+    /// suffix array.
+    pub fn range_min(&self, s: u64, e: u64) -> u64 {
+        debug_assert!(s < e, "empty range [{}, {})", s, e);
+        debug_assert!(e <= self.len as u64, "{} is out of range", e);
+        let (s, e) = (s as usize, e as usize);
+        let bs = s.div_ceil(BLOCK_SIZE);
+        let be = e / BLOCK_SIZE;
+        if bs >= be {
+            return self.scan_min(s, e);
+        }
+
+        let mut m = self.scan_min(s, bs * BLOCK_SIZE);
+        let k = util::log2((be - bs) as u64) as usize;
+        m = m.min(self.sparse[k][bs]);
+        m = m.min(self.sparse[k][be - (1 << k)]);
+        m.min(self.scan_min(be * BLOCK_SIZE, e))
+    }
+
+    fn scan_min(&self, s: usize, e: usize) -> u64 {
+        (s..e).map(|i| self.lcp(i as u64)).min().unwrap_or(u64::MAX)
+    }
+}
+
+impl HeapSize for LcpArray {
+    fn heap_size(&self) -> usize {
+        self.lcp.heap_size()
+            + self
+                .sparse
+                .iter()
+                .map(|level| level.capacity() * core::mem::size_of::<u64>())
+                .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::RangeConverter;
+    use crate::suffix_array::sais;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn naive_lcp(text: &[u8], a: usize, b: usize) -> u64 {
+        text[a..]
+            .iter()
+            .zip(&text[b..])
+            .take_while(|(x, y)| **x != 0 && x == y)
+            .count() as u64
+    }
+
+    #[test]
+    fn test_lcp() {
+        let text = "mississippi\0".to_string().into_bytes();
+        let sa = sais::build_suffix_array(&text, &RangeConverter::new(b'a', b'z'));
+        let lcp = LcpArray::new(&text, &sa);
+        let ans = vec![0, 0, 1, 1, 4, 0, 0, 1, 0, 2, 1, 3];
+        for (i, a) in ans.into_iter().enumerate() {
+            assert_eq!(lcp.lcp(i as u64), a, "lcp[{}]", i);
+        }
+    }
+
+    #[test]
+    fn test_range_min() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut text = (0..1000)
+            .map(|_| rng.gen::<u8>() % 3 + 1)
+            .collect::<Vec<_>>();
+        text.push(0);
+        let sa = sais::build_suffix_array(&text, &RangeConverter::new(1, 3));
+        let lcp = LcpArray::new(&text, &sa);
+        for _ in 0..1000 {
+            let s = rng.gen::<usize>() % (sa.len() - 1);
+            let e = s + 1 + rng.gen::<usize>() % (sa.len() - s - 1);
+            assert_eq!(
+                lcp.range_min(s as u64 + 1, e as u64 + 1),
+                naive_lcp(&text, sa[s] as usize, sa[e] as usize),
+                "s = {}, e = {}",
+                s,
+                e
+            );
+        }
+    }
+}
@@ -8,9 +8,10 @@
 //! - `extract`: Given an integer, gets the character of the text at that
 //!   position.
 //!
-//! `fm-index` crate does not support the third query (extracting a character
-//! from arbitrary position). Instead, it provides backward/forward iterators
-//! that return the text characters starting from a search result.
+//! Indexes with locate support can extract a character from an arbitrary
+//! position using `char_at`. Additionally, all indexes provide
+//! backward/forward iterators that return the text characters starting from
+//! a search result.
 //!
 //! # Usage
 //!
@@ -21,18 +22,87 @@
 //! fm-index = "0.2.0"
 //! ```
 //!
+//! The `std` and `serde` features are enabled by default. [`DiskFMIndex`],
+//! [`DynamicFMIndex`], instrumentation and the parallel queries of
+//! [`ShardedIndex`] need `std`, as do the `cache`, `compression` and
+//! `rayon` features. Without it the indexes can still be built, searched
+//! and located in. This doesn't make the crate usable on targets without
+//! `std`: the `vers-vecs` crate the indexes are built on depends on `std`.
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize` for the
+//! indexes. Saving and loading, and [`LazySamples`], are built on serde and
+//! need both `std` and `serde`; `std` alone doesn't depend on serde. The
+//! `compression`, `capi`, `wasm`, `python` and `cli` features enable
+//! `serde`, as they save and load indexes.
+//!
+//! The `unicode-normalization` feature adds the Unicode normalizations NFC
+//! and NFKC for indexes of strings.
+//!
+//! The `cache` feature adds `with_query_cache`, which keeps the results of
+//! the most recent searches, and `with_locate_cache`, which also keeps
+//! those of the most recent `locate` calls.
+//!
+//! The `compression` feature adds `save_compressed`, which saves an index
+//! compressed with zstd.
+//!
+//! The `rayon` feature adds `count_many_parallel`, `locate_parallel` and
+//! `seed_reads_parallel`, which spread the work of a query over the rayon
+//! thread pool.
+//!
+//! The `capi` feature adds a C API to build, save, load and search indexes
+//! of bytes, declared in `include/fm_index.h`; see the `capi` module. The
+//! crate is also built as a shared library for it.
+//!
+//! The `wasm` feature adds JavaScript bindings with `wasm-bindgen`, to build,
+//! load and search indexes of bytes in the browser; see the `wasm`
+//! module. The crate builds for `wasm32-unknown-unknown` with the `std`,
+//! `serde`, `cache` and `unicode-normalization` features. The target has no
+//! threads, clock or files, so the parallel queries of [`ShardedIndex`],
+//! instrumentation and background compaction of a [`DynamicFMIndex`] panic
+//! there, and indexes have to be loaded from bytes rather than a path. The
+//! `compression` and `rayon` features aren't supported on it.
+//!
+//! The `python` feature adds Python bindings with `pyo3`, building the
+//! crate as the `fm_index` extension module; see the `python` module.
+//!
+//! The `cli` feature builds the `fm-index` command line tool, which builds
+//! indexes of files and counts, locates and extracts in them:
+//! `cargo install fm-index --features cli`.
+//!
+//! The `arrow` feature adds `ArrowColumnBuilder`, which builds a
+//! multi-text index of a column of Arrow strings or binary values, with a
+//! text for each row. The `parquet` feature lets it read the column from a
+//! Parquet file.
+//!
+//! The `testing` feature adds `ReferenceIndex`, a naive index that answers
+//! queries by scanning the text, with methods to check the results of an
+//! index against it.
+//!
+//! # Threads
+//!
+//! Indexes are immutable once built, and are `Send` and `Sync` whenever their
+//! character and converter types are, which holds for all converters in this
+//! crate. An index can be shared between threads by reference, for instance
+//! with [`std::thread::scope`], or by wrapping it in an
+//! [`Arc`](std::sync::Arc), and searched from all of them at once. Search
+//! results are `Send` and `Sync` under the same conditions, so they can be
+//! held across an `.await` in an async server. The query cache and the
+//! query records of an instrumented index are shared by all threads,
+//! behind a lock.
+//!
 //! # Example
 //! ```
 //! use fm_index::FMIndexWithLocate;
 //! use fm_index::converter::RangeConverter;
 //!
-//! // Prepare a text string to search for patterns.
+//! // Prepare a text string to search for patterns. Indexes of bytes take
+//! // the text and the patterns as `&str` as well as byte vectors.
 //! let text = concat!(
 //!     "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua.",
 //!     "Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat.",
 //!     "Duis aute irure dolor in reprehenderit in voluptate velit esse cillum dolore eu fugiat nulla pariatur.",
 //!     "Excepteur sint occaecat cupidatat non proident, sunt in culpa qui officia deserunt mollit anim id est laborum.",
-//! ).as_bytes().to_vec();
+//! );
 //!
 //! // Converter converts each character into packed representation.
 //! // `' '` ~ `'~'` represents a range of ASCII printable characters.
@@ -93,6 +163,36 @@
 //!   number of characters smaller than a given character
 //! - a (sampled) suffix array
 //!
+//! The BWT can alternatively be stored in a Huffman-shaped wavelet tree
+//! (`HuffmanFMIndex`), which is smaller and faster for skewed character
+//! distributions such as natural language text.
+//!
+//! For count-only use, `SlimFMIndex` stores the BWT in a wavelet matrix
+//! without select support, which is smaller at the cost of slow forward
+//! iteration.
+//!
+//! For texts of at most four characters, such as DNA, `DNAFMIndex` stores
+//! the BWT as packed 2-bit codes, which is smaller and counts faster.
+//! `converter::DNAConverter` prepares DNA for it, handling `N` and the other
+//! ambiguity codes according to a policy.
+//! `DNAFMIndexWithLocate::search_both_strands` also finds the occurrences
+//! of the reverse complement of a pattern.
+//! For texts of at most eight characters, `SmallFMIndex` stores the BWT as
+//! a bitvector for each character, so counting takes a single rank query
+//! per character of the pattern.
+//!
+//! [`FMIndex::verify`] and its counterparts on these variants check that
+//! the parts of an index fit together, for instance after loading it from
+//! untrusted storage, and return a [`VerifyReport`].
+//!
+//! `DiskFMIndex` keeps the BWT and the suffix array samples in a file and
+//! reads them on demand through a small page cache, for indexes larger than
+//! memory.
+//! `MappedFMIndex` queries the same file in place from a buffer holding
+//! all of it, such as a memory map, so loading it takes constant time.
+//! With the `mmap` feature, `MappedFMIndex::open_mmap` maps the file
+//! itself, so processes querying the same index share one copy of it.
+//!
 //! ## Run-Length FM-Index
 //!
 //! This index is more compact than FM-Index if there are a lot of repeated
@@ -111,6 +211,106 @@
 //! - an array of size _O(σ)_ (_σ_: number of characters) which stores the
 //!   number of characters smaller than a given character in run heads
 //!
+//! ## Compressed Suffix Array
+//!
+//! An alternative to the FM-Index based on the Ψ function [^7], which maps
+//! each suffix to the one following it in the text. Ψ is stored as one
+//! Elias-Fano sequence per character. Backward search and forward iteration
+//! are fast, while accessing the BWT takes time proportional to the size of
+//! the alphabet.
+//!
+//! ## FMD-Index
+//!
+//! An FM-Index of a DNA text together with its reverse complement [^6].
+//! Search results track the interval of the pattern and that of its reverse
+//! complement, so occurrences on both strands are found at once and patterns
+//! can be extended in both directions.
+//!
+//! ## r-index
+//!
+//! A Run-Length FM-Index whose suffix array is sampled only at the
+//! boundaries of BWT runs [^5], so that locate queries are supported in
+//! space proportional to the number of runs. Suited for highly repetitive
+//! collections.
+//!
+//! ## Compressed suffix tree
+//!
+//! The single-text indexes with locate support can emulate a suffix tree
+//! with `suffix_tree` [^8]. Only the tree topology, as balanced parentheses,
+//! and the string depths of the internal nodes are stored; everything else
+//! is derived from the index.
+//!
+//! ## Sharded index
+//!
+//! `ShardedIndex` searches several independently built indexes as if their
+//! texts were concatenated, so a corpus too large for a single build can be
+//! indexed in partitions. The shards can be searched in parallel.
+//!
+//! ## Dynamic index
+//!
+//! `DynamicFMIndex` supports inserting and deleting texts. Every inserted
+//! text is indexed as a segment of its own, deleted texts are only marked,
+//! and compaction, which can run in the background, rebuilds the segments
+//! into one.
+//!
+//! ## Automatic selection
+//!
+//! `AutoFMIndex` and `AutoFMIndexWithLocate` choose between the FM-Index,
+//! the run-length FM-Index and `DNAFMIndex` based on the converter's
+//! alphabet and the average length of the BWT runs of a sample of the text.
+//!
+//! ## Suffix array sampling
+//!
+//! The indexes with locate support take the sampling strategy of the suffix
+//! array as a type parameter. `SuffixOrderSampledArray`, the default,
+//! samples every `2^level`-th row of the suffix array. `TextOrderSampledArray`
+//! samples the rows whose text position is a multiple of `2^level`, which
+//! bounds the number of LF steps needed to locate an occurrence. Use
+//! `with_sampling` to choose it:
+//!
+//! ```
+//! use fm_index::{FMIndexWithLocate, TextOrderSampledArray};
+//! use fm_index::converter::RangeConverter;
+//!
+//! let text = "mississippi".as_bytes().to_vec();
+//! let index = FMIndexWithLocate::<_, _, TextOrderSampledArray>::with_sampling(
+//!     text,
+//!     RangeConverter::new(b'a', b'z'),
+//!     2,
+//! );
+//! let mut positions = index.search("ss").locate();
+//! positions.sort();
+//! assert_eq!(positions, vec![2, 5]);
+//! ```
+//!
+//! # Saving and loading
+//!
+//! The in-memory indexes have `save` and `load` methods, which write and
+//! read a versioned binary format that later versions of this crate can
+//! still load:
+//!
+//! ```
+//! # #[cfg(all(feature = "std", feature = "serde"))] {
+//! use fm_index::FMIndex;
+//! use fm_index::converter::RangeConverter;
+//!
+//! let text = "mississippi".as_bytes().to_vec();
+//! let index = FMIndex::new(text, RangeConverter::new(b'a', b'z'));
+//! let mut bytes = Vec::new();
+//! index.save(&mut bytes).unwrap();
+//!
+//! let index = FMIndex::<u8, RangeConverter<u8>>::load(&bytes[..]).unwrap();
+//! assert_eq!(index.search("ss").count(), 2);
+//! # }
+//! ```
+//!
+//! An index with locate support loaded with `load_file` and the
+//! [`LazySamples`] sampling strategy reads its suffix array samples from the
+//! file only when a query first needs them.
+//!
+//! [`IndexInfo::peek`] reads the kind of a saved index and the parameters it
+//! was built with, such as its sampling level, without loading it.
+//!
 //! # Reference
 //!
 //! [^1]: Ferragina, P., & Manzini, G. (2000). Opportunistic data structures
@@ -129,26 +329,126 @@
 //!     Calderón-Benavides L., González-Caro C., Chávez E., Ziviani N. (eds)
 //!     String Processing and Information Retrieval. SPIRE 2012.
 //!     <https://doi.org/10.1007/978-3-642-34109-0_18>
+//!
+//! [^5]: Gagie, T., Navarro, G., & Prezza, N. (2020). Fully functional
+//!     suffix trees and optimal text searching in BWT-runs bounded space.
+//!     Journal of the ACM, 67(1). <https://doi.org/10.1145/3375890>
+//!
+//! [^6]: Li, H. (2012). Exploring single-sample SNP and INDEL calling with
+//!     whole-genome de novo assembly. Bioinformatics, 28(14), 1838–1844.
+//!     <https://doi.org/10.1093/bioinformatics/bts280>
+//!
+//! [^7]: Sadakane, K. (2003). New text indexing functionalities of the
+//!     compressed suffix arrays. Journal of Algorithms, 48(2), 294–313.
+//!     <https://doi.org/10.1016/S0196-6774(03)00087-7>
+//!
+//! [^8]: Sadakane, K. (2007). Compressed suffix trees with full
+//!     functionality. Theory of Computing Systems, 41(4), 589–607.
+//!     <https://doi.org/10.1007/s00224-006-1198-x>
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(clippy::len_without_is_empty)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
 pub mod converter;
 
+#[cfg(feature = "arrow")]
+mod arrow;
+mod auto;
 mod backend;
+mod bp;
+mod builder;
+#[cfg(feature = "capi")]
+pub mod capi;
 mod character;
+mod csa;
+#[cfg(feature = "std")]
+mod disk;
+mod doc_bitmap;
+mod dyn_search;
+#[cfg(feature = "std")]
+mod dynamic;
+mod error;
 mod fm_index;
+mod fmd_index;
 mod frontend;
+mod huffman_wavelet;
+#[cfg(feature = "std")]
+mod instrument;
+mod kmer;
+mod lcp;
+#[cfg(feature = "std")]
+mod lru;
+mod matches;
 mod multi_text;
+mod occ;
+#[cfg(all(feature = "std", feature = "serde"))]
+mod persist;
+#[cfg(feature = "python")]
+pub mod python;
+mod quad_vector;
+#[cfg(feature = "cache")]
+mod query_cache;
+mod rank_wavelet;
+#[cfg(feature = "testing")]
+mod reference;
 mod rlfmi;
+mod seed;
+mod sequence;
+mod sharded;
+mod snapshot;
+mod stats;
 mod suffix_array;
+mod suffix_tree;
+mod symbol_vectors;
 mod util;
+mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 mod wrapper;
 
-pub use backend::HeapSize;
-pub use character::Character;
+#[cfg(feature = "arrow")]
+pub use arrow::ArrowColumnBuilder;
+pub use auto::{
+    AutoFMIndex, AutoFMIndexSearch, AutoFMIndexSearchWithLocate, AutoFMIndexWithLocate, IndexKind,
+};
+pub use backend::{HeapSize, SizeBreakdown};
+pub use builder::SearchIndexBuilder;
+pub use character::{Character, IntoText};
+pub use doc_bitmap::DocBitmap;
+pub use dyn_search::{DynSearch, DynSearchIndex};
+#[cfg(feature = "std")]
+pub use dynamic::DynamicFMIndex;
+pub use error::Error;
+pub use fmd_index::{FMDIndex, FMDIndexSearch, Strand};
 pub use frontend::{
-    FMIndex, FMIndexSearch, FMIndexSearchWithLocate, FMIndexWithLocate, MultiTextFMIndex,
-    MultiTextFMIndexSearch, MultiTextFMIndexSearchWithLocate, MultiTextFMIndexWithLocate,
-    RLFMIndex, RLFMIndexSearch, RLFMIndexSearchWithLocate, RLFMIndexWithLocate, Search,
-    SearchIndex, SearchIndexWithLocate, SearchWithLocate,
+    CSAIndex, CSAIndexSearch, CSAIndexSearchWithLocate, CSAIndexWithLocate, DNAFMIndex,
+    DNAFMIndexSearch, DNAFMIndexSearchWithLocate, DNAFMIndexWithLocate, DocId, FMIndex,
+    FMIndexSearch, FMIndexSearchWithLocate, FMIndexWithLocate, HuffmanFMIndex,
+    HuffmanFMIndexSearch, HuffmanFMIndexSearchWithLocate, HuffmanFMIndexWithLocate,
+    MultiTextFMIndex, MultiTextFMIndexSearch, MultiTextFMIndexSearchWithLocate,
+    MultiTextFMIndexWithLocate, RIndex, RIndexSearch, RLFMIndex, RLFMIndexSearch,
+    RLFMIndexSearchWithLocate, RLFMIndexWithLocate, Search, SearchIndex, SearchIndexWithLocate,
+    SearchWithLocate, SlimFMIndex, SlimFMIndexSearch, SmallFMIndex, SmallFMIndexSearch,
+    SmallFMIndexSearchWithLocate, SmallFMIndexWithLocate,
 };
+#[cfg(feature = "std")]
+pub use frontend::{DiskFMIndex, DiskFMIndexSearch, MappedFMIndex, MappedFMIndexSearch};
+#[cfg(feature = "std")]
+pub use instrument::{QueryRecord, QueryStats};
+pub use lcp::LcpArray;
+pub use matches::{Highlighted, Highlighter, Match};
+#[cfg(all(feature = "std", feature = "serde"))]
+pub use persist::{ChecksumMismatch, IndexInfo, SplitPaths};
+#[cfg(feature = "testing")]
+pub use reference::{Mismatch, ReferenceIndex};
+pub use seed::Anchor;
+pub use sharded::ShardedIndex;
+pub use snapshot::{OwnedSearch, SearchSnapshot};
+pub use stats::IndexStats;
+#[cfg(all(feature = "std", feature = "serde"))]
+pub use suffix_array::sample::LazySamples;
+pub use suffix_array::sample::{SamplingStrategy, SuffixOrderSampledArray, TextOrderSampledArray};
+pub use suffix_tree::{Node, SuffixTree};
+pub use verify::VerifyReport;
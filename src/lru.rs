@@ -0,0 +1,150 @@
+//! A map that evicts its least recently used entry when full.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// The index of no entry, ending the list of entries.
+const NIL: usize = usize::MAX;
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    prev: usize,
+    next: usize,
+}
+
+// A map holding at most `capacity` entries, evicting the least recently
+// used entry to make room for a new one.
+pub(crate) struct Lru<K, V> {
+    capacity: usize,
+    slots: HashMap<K, usize>,
+    // a list from the most to the least recently used entry
+    entries: Vec<Entry<K, V>>,
+    head: usize,
+    tail: usize,
+}
+
+impl<K: Hash + Eq + Clone, V> Lru<K, V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Lru {
+            capacity,
+            slots: HashMap::new(),
+            entries: Vec::new(),
+            head: NIL,
+            tail: NIL,
+        }
+    }
+
+    pub(crate) fn get(&mut self, key: &K) -> Option<&V> {
+        let i = *self.slots.get(key)?;
+        self.unlink(i);
+        self.push_front(i);
+        Some(&self.entries[i].value)
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some(&i) = self.slots.get(&key) {
+            self.entries[i].value = value;
+            self.unlink(i);
+            self.push_front(i);
+            return;
+        }
+        let i = if self.entries.len() < self.capacity {
+            self.entries.push(Entry {
+                key: key.clone(),
+                value,
+                prev: NIL,
+                next: NIL,
+            });
+            self.entries.len() - 1
+        } else {
+            let i = self.tail;
+            self.unlink(i);
+            let entry = &mut self.entries[i];
+            self.slots.remove(&entry.key);
+            entry.key = key.clone();
+            entry.value = value;
+            i
+        };
+        self.slots.insert(key, i);
+        self.push_front(i);
+    }
+
+    #[cfg(any(test, feature = "cache"))]
+    pub(crate) fn clear(&mut self) {
+        self.slots.clear();
+        self.entries.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    fn unlink(&mut self, i: usize) {
+        let (prev, next) = (self.entries[i].prev, self.entries[i].next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.entries[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.entries[next].prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, i: usize) {
+        self.entries[i].prev = NIL;
+        self.entries[i].next = self.head;
+        match self.head {
+            NIL => self.tail = i,
+            head => self.entries[head].prev = i,
+        }
+        self.head = i;
+    }
+
+    // The heap size, given that of the data keys and values point to.
+    pub(crate) fn heap_size(
+        &self,
+        key_size: impl Fn(&K) -> usize,
+        value_size: impl Fn(&V) -> usize,
+    ) -> usize {
+        self.entries.capacity() * std::mem::size_of::<Entry<K, V>>()
+            + self.slots.capacity() * std::mem::size_of::<(K, usize)>()
+            + self
+                .entries
+                .iter()
+                // every key is stored twice, in its entry and in `slots`
+                .map(|entry| 2 * key_size(&entry.key) + value_size(&entry.value))
+                .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru() {
+        let mut lru = Lru::new(2);
+        lru.insert("a", 1);
+        lru.insert("b", 2);
+        assert_eq!(lru.get(&"a"), Some(&1));
+        // "b" is now the least recently used
+        lru.insert("c", 3);
+        assert_eq!(lru.get(&"b"), None);
+        assert_eq!(lru.get(&"a"), Some(&1));
+        assert_eq!(lru.get(&"c"), Some(&3));
+        lru.insert("a", 4);
+        lru.insert("d", 5);
+        assert_eq!(lru.get(&"c"), None);
+        assert_eq!(lru.get(&"a"), Some(&4));
+        lru.clear();
+        assert_eq!(lru.get(&"a"), None);
+        lru.insert("e", 6);
+        assert_eq!(lru.get(&"e"), Some(&6));
+
+        let mut empty = Lru::new(0);
+        empty.insert("a", 1);
+        assert_eq!(empty.get(&"a"), None);
+    }
+}
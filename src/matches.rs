@@ -0,0 +1,275 @@
+//! Rich match objects for search results with locate support.
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+use core::marker::PhantomData;
+
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::character::Character;
+use crate::frontend::{DocId, Search};
+
+/// A single occurrence of a pattern in the text.
+///
+/// This is produced by `matches` on a search result with locate support.
+/// Besides the position of the occurrence it gives access to the text
+/// surrounding it; the context is only computed when it is iterated over.
+///
+/// A match serializes as a struct with its `position`, `pattern_len` and
+/// `doc_id`. It borrows the index, so it can't be deserialized; deserialize
+/// into a struct with those fields instead.
+pub struct Match<'a, T, S> {
+    search: &'a S,
+    i: u64,
+    position: u64,
+    pattern_len: u64,
+    doc_id: Option<DocId>,
+    _t: PhantomData<T>,
+}
+
+impl<'a, T, S> Match<'a, T, S>
+where
+    S: Search<'a, T>,
+{
+    pub(crate) fn new(
+        search: &'a S,
+        i: u64,
+        position: u64,
+        pattern_len: u64,
+        doc_id: Option<DocId>,
+    ) -> Self {
+        Match {
+            search,
+            i,
+            position,
+            pattern_len,
+            doc_id,
+            _t: PhantomData,
+        }
+    }
+
+    // The same occurrence, iterating the text through `search` instead, which
+    // must be a search result for the same pattern in the same index.
+    pub(crate) fn with_search<S2>(self, search: &'a S2) -> Match<'a, T, S2> {
+        Match {
+            search,
+            i: self.i,
+            position: self.position,
+            pattern_len: self.pattern_len,
+            doc_id: self.doc_id,
+            _t: PhantomData,
+        }
+    }
+
+    /// The position of the occurrence in the text.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The length of the pattern that was searched for.
+    pub fn pattern_len(&self) -> u64 {
+        self.pattern_len
+    }
+
+    /// The id of the text containing the occurrence.
+    ///
+    /// This is only available for multi-text indexes, where texts are
+    /// numbered in order starting at 0. For other indexes this is `None`.
+    pub fn doc_id(&self) -> Option<DocId> {
+        self.doc_id
+    }
+
+    /// Get an iterator that goes backwards through the text from the
+    /// character just before the occurrence.
+    pub fn iter_backward(&self) -> impl Iterator<Item = T> + 'a {
+        self.search.iter_backward(self.i)
+    }
+
+    /// Get an iterator that goes forwards through the text from the start of
+    /// the occurrence.
+    pub fn iter_forward(&self) -> impl Iterator<Item = T> + 'a {
+        self.search.iter_forward(self.i)
+    }
+}
+
+impl<'a, T, S> Match<'a, T, S>
+where
+    T: Character + 'a,
+    S: Search<'a, T>,
+{
+    /// The occurrence with up to `before` characters of the text before it
+    /// and up to `after` characters after it.
+    ///
+    /// The context doesn't extend beyond a terminator, so it stays within
+    /// the text.
+    pub fn context(&self, before: usize, after: usize) -> Vec<T> {
+        let mut context = self
+            .search
+            .iter_backward_in_text(self.i)
+            .take(before)
+            .collect::<Vec<_>>();
+        context.reverse();
+        context.extend(
+            self.search
+                .iter_forward_in_text(self.i)
+                .take(self.pattern_len as usize + after),
+        );
+        context
+    }
+
+    /// The [`context`](Self::context) of the occurrence, rendered as a
+    /// string by `detokenize`.
+    ///
+    /// This is useful for texts of token ids, where `detokenize` is the
+    /// tokenizer's decoder.
+    pub fn snippet<F>(&self, before: usize, after: usize, detokenize: F) -> String
+    where
+        F: FnOnce(&[T]) -> String,
+    {
+        detokenize(&self.context(before, after))
+    }
+
+    /// The occurrence with its context, like [`context`](Self::context),
+    /// split so that `highlighter` can set the occurrence off.
+    pub fn highlight<'h>(&self, highlighter: &'h Highlighter) -> Highlighted<'h, T> {
+        let mut before = self
+            .search
+            .iter_backward_in_text(self.i)
+            .take(highlighter.before)
+            .collect::<Vec<_>>();
+        before.reverse();
+        let mut matched = self
+            .search
+            .iter_forward_in_text(self.i)
+            .take(self.pattern_len as usize + highlighter.after)
+            .collect::<Vec<_>>();
+        let after = matched.split_off(matched.len().min(self.pattern_len as usize));
+        Highlighted {
+            before,
+            matched,
+            after,
+            highlighter,
+        }
+    }
+}
+
+/// Settings to render occurrences with their context, with the occurrence
+/// set off by markers.
+///
+/// Pass it to [`Match::highlight`]. By default the occurrence is put in
+/// square brackets; use [`markers`](Self::markers) for other markers, or
+/// [`ansi`](Self::ansi) to color it on a terminal.
+///
+/// ```
+/// use fm_index::converter::RangeConverter;
+/// use fm_index::{FMIndexWithLocate, Highlighter};
+///
+/// let index = FMIndexWithLocate::new("mississippi", RangeConverter::new(b'a', b'z'), 2);
+/// let search = index.search("ssi");
+/// let highlighter = Highlighter::new(2, 1);
+/// let mut hits = search
+///     .matches()
+///     .map(|m| m.highlight(&highlighter).to_string())
+///     .collect::<Vec<_>>();
+/// hits.sort();
+/// assert_eq!(hits, vec!["mi[ssi]s", "si[ssi]p"]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Highlighter {
+    before: usize,
+    after: usize,
+    start: String,
+    end: String,
+}
+
+impl Highlighter {
+    /// Render up to `before` characters of context before each occurrence,
+    /// and up to `after` characters after it.
+    pub fn new(before: usize, after: usize) -> Self {
+        Highlighter {
+            before,
+            after,
+            start: "[".to_owned(),
+            end: "]".to_owned(),
+        }
+    }
+
+    /// Put `start` before the occurrence and `end` after it.
+    pub fn markers(mut self, start: &str, end: &str) -> Self {
+        self.start = start.to_owned();
+        self.end = end.to_owned();
+        self
+    }
+
+    /// Render the occurrence in bold red with ANSI escape codes.
+    pub fn ansi(self) -> Self {
+        self.markers("\x1b[1;31m", "\x1b[0m")
+    }
+}
+
+/// An occurrence with its context, to be rendered with the markers of a
+/// [`Highlighter`].
+///
+/// This is produced by [`Match::highlight`]. For a text of bytes it
+/// implements [`Display`](fmt::Display), decoding the bytes as UTF-8 and
+/// replacing invalid sequences; for other texts use
+/// [`render`](Self::render).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Highlighted<'h, T> {
+    before: Vec<T>,
+    matched: Vec<T>,
+    after: Vec<T>,
+    highlighter: &'h Highlighter,
+}
+
+impl<T> Highlighted<'_, T> {
+    /// The context before the occurrence.
+    pub fn before(&self) -> &[T] {
+        &self.before
+    }
+
+    /// The occurrence itself.
+    pub fn matched(&self) -> &[T] {
+        &self.matched
+    }
+
+    /// The context after the occurrence.
+    pub fn after(&self) -> &[T] {
+        &self.after
+    }
+
+    /// Render the context and the occurrence, each turned into a string by
+    /// `detokenize`, with the occurrence between the markers.
+    pub fn render<F>(&self, mut detokenize: F) -> String
+    where
+        F: FnMut(&[T]) -> String,
+    {
+        let mut rendered = detokenize(&self.before);
+        rendered.push_str(&self.highlighter.start);
+        rendered.push_str(&detokenize(&self.matched));
+        rendered.push_str(&self.highlighter.end);
+        rendered.push_str(&detokenize(&self.after));
+        rendered
+    }
+}
+
+impl fmt::Display for Highlighted<'_, u8> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(|text| String::from_utf8_lossy(text).to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, S> Serialize for Match<'_, T, S> {
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        let mut state = serializer.serialize_struct("Match", 3)?;
+        state.serialize_field("position", &self.position)?;
+        state.serialize_field("pattern_len", &self.pattern_len)?;
+        state.serialize_field("doc_id", &self.doc_id)?;
+        state.end()
+    }
+}
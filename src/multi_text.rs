@@ -1,27 +1,85 @@
-use std::ops::Sub;
+use alloc::{borrow::Cow, vec, vec::Vec};
+use core::ops::Sub;
 
-use crate::backend::{HasPosition, SearchIndexBackend};
+use crate::backend::{HasPosition, SearchIndexBackend, SizeBreakdown};
 use crate::character::{prepare_text, Character};
 #[cfg(doc)]
 use crate::converter;
 use crate::converter::Converter;
+use crate::fm_index::bwt_symbols;
+#[cfg(all(feature = "std", feature = "serde"))]
+use crate::persist::impl_sections;
+use crate::sequence::{PackedSymbols, Sequence};
 use crate::suffix_array::sais;
-use crate::suffix_array::sample::SuffixOrderSampledArray;
-use crate::util;
+use crate::suffix_array::sample::SuffixArraySamples;
 use crate::HeapSize;
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use vers_vecs::{BitVec, RsVec, WaveletMatrix};
 
 // An FM-Index supporting multiple \0 separated texts
-#[derive(Serialize, Deserialize)]
-pub struct MultiTextFMIndexBackend<T, C, S> {
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultiTextFMIndexBackend<T, C, S, M = ()> {
     bw: WaveletMatrix,
     cs: Vec<u64>,
     converter: C,
     suffix_array: S,
     doc: Vec<usize>,
-    _t: std::marker::PhantomData<T>,
+    // the inverse of `doc`: the rank in the BWT of the \0 preceding each
+    // terminator row
+    doc_rank: Vec<usize>,
+    // end marker positions in text order
+    end_markers: RsVec,
+    // the id of the text containing each suffix, in suffix array order
+    doc_array: WaveletMatrix,
+    metadata: Vec<M>,
+    deleted: BitVec,
+    deleted_count: u64,
+    _t: core::marker::PhantomData<T>,
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl_sections!(MultiTextFMIndexBackend<T, C, S, M> {
+    #[encode(Symbols)]
+    bw: WaveletMatrix,
+    cs: Vec<u64>,
+    converter: C,
+    #[load(Deferrable)]
+    #[part(Samples)]
+    suffix_array: S,
+    #[part(Documents)]
+    doc: Vec<usize>,
+    #[part(Documents)]
+    doc_rank: Vec<usize>,
+    #[part(Documents)]
+    end_markers: RsVec,
+    #[encode(Symbols)]
+    #[part(Documents)]
+    doc_array: WaveletMatrix,
+    #[part(Documents)]
+    metadata: Vec<M>,
+    deleted: BitVec,
+    deleted_count: u64,
+});
+
+impl<T, C: PartialEq, S: PartialEq, M: PartialEq> PartialEq
+    for MultiTextFMIndexBackend<T, C, S, M>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.bw.same_symbols(&other.bw)
+            && self.cs == other.cs
+            && self.converter == other.converter
+            && self.suffix_array == other.suffix_array
+            && self.doc == other.doc
+            && self.doc_rank == other.doc_rank
+            && self.end_markers == other.end_markers
+            && self.doc_array.same_symbols(&other.doc_array)
+            && self.metadata == other.metadata
+            && self.deleted == other.deleted
+            && self.deleted_count == other.deleted_count
+    }
 }
 
 // TODO: Refactor types (Converter converts T -> u64)
@@ -30,12 +88,17 @@ where
     T: Character,
     C: Converter<T>,
 {
-    pub(crate) fn new(text: Vec<T>, converter: C, get_sample: impl Fn(&[u64]) -> S) -> Self {
-        let text = prepare_text(text);
+    /// Build the index, without metadata; see `with_metadata`.
+    pub(crate) fn new(text: Cow<'_, [T]>, converter: C, get_sample: impl Fn(&[u64]) -> S) -> Self {
+        let text = prepare_text(text, &converter);
         let cs = sais::get_bucket_start_pos(&sais::count_chars(&text, &converter));
         let sa = Self::suffix_array(&text, &converter);
         let bw = Self::wavelet_matrix(&text, &sa, &converter);
-        let doc = Self::doc(&text, &bw, &sa);
+        let end_markers = Self::end_markers(&text);
+        let doc = Self::doc(&end_markers, &bw, &sa);
+        let doc_rank = Self::doc_rank(&doc);
+        let doc_array = Self::doc_array(&end_markers, &sa);
+        let deleted = BitVec::from_zeros(end_markers.rank1(text.len()));
 
         MultiTextFMIndexBackend {
             cs,
@@ -43,10 +106,166 @@ where
             converter,
             suffix_array: get_sample(&sa),
             doc,
-            _t: std::marker::PhantomData::<T>,
+            doc_rank,
+            end_markers,
+            doc_array,
+            metadata: Vec::new(),
+            deleted,
+            deleted_count: 0,
+            _t: core::marker::PhantomData::<T>,
+        }
+    }
+}
+
+impl<T, C, S, M> MultiTextFMIndexBackend<T, C, S, M>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    /// Attach a metadata value to each text, replacing any attached
+    /// before.
+    ///
+    /// An empty `metadata` means the texts have no metadata.
+    pub(crate) fn with_metadata<N>(self, metadata: Vec<N>) -> MultiTextFMIndexBackend<T, C, S, N> {
+        debug_assert!(metadata.is_empty() || metadata.len() as u64 == self.doc_count());
+        MultiTextFMIndexBackend {
+            bw: self.bw,
+            cs: self.cs,
+            converter: self.converter,
+            suffix_array: self.suffix_array,
+            doc: self.doc,
+            doc_rank: self.doc_rank,
+            end_markers: self.end_markers,
+            doc_array: self.doc_array,
+            metadata,
+            deleted: self.deleted,
+            deleted_count: self.deleted_count,
+            _t: core::marker::PhantomData,
         }
     }
 
+    /// The metadata value of text `doc_id`, if metadata was attached.
+    pub(crate) fn metadata(&self, doc_id: u64) -> Option<&M> {
+        self.metadata.get(doc_id as usize)
+    }
+
+    /// The number of texts in the index.
+    pub(crate) fn doc_count(&self) -> u64 {
+        self.end_markers.rank1(self.end_markers.len()) as u64
+    }
+
+    /// The position in the whole text at which text `doc_id` starts.
+    pub(crate) fn doc_start(&self, doc_id: u64) -> u64 {
+        debug_assert!(doc_id < self.doc_count(), "{} is out of range", doc_id);
+        if doc_id == 0 {
+            0
+        } else {
+            self.end_markers.select1(doc_id as usize - 1) as u64 + 1
+        }
+    }
+
+    /// Map a position in the whole text to the id of the text containing it
+    /// and the offset within that text.
+    pub(crate) fn doc_position(&self, pos: u64) -> (u64, u64) {
+        let doc_id = self.end_markers.rank1(pos as usize) as u64;
+        (doc_id, pos - self.doc_start(doc_id))
+    }
+
+    /// Mark text `doc_id` as deleted.
+    pub(crate) fn delete_doc(&mut self, doc_id: u64) {
+        debug_assert!(doc_id < self.doc_count(), "{} is out of range", doc_id);
+        if !self.is_doc_deleted(doc_id) {
+            self.deleted.set(doc_id as usize, 1).unwrap();
+            self.deleted_count += 1;
+        }
+    }
+
+    /// Whether text `doc_id` was marked as deleted.
+    pub(crate) fn is_doc_deleted(&self, doc_id: u64) -> bool {
+        self.deleted.is_bit_set_unchecked(doc_id as usize)
+    }
+
+    /// The number of texts marked as deleted.
+    pub(crate) fn deleted_count(&self) -> u64 {
+        self.deleted_count
+    }
+
+    /// List the texts containing the suffixes in `[s, e)` of the suffix
+    /// array, each with the number of those suffixes, ordered by text id.
+    ///
+    /// Texts marked as deleted are skipped.
+    pub(crate) fn docs_with_tf(&self, s: u64, e: u64) -> Vec<(u64, u64)> {
+        let range = s as usize..e as usize;
+        let mut result = Vec::new();
+        let doc_count = self.doc_count();
+        let mut d = 0;
+        // successor_u64 does not handle values beyond the bit width of the
+        // array, so stop at the last text explicitly.
+        while d < doc_count {
+            let Some(doc_id) = self.doc_array.successor_u64(range.clone(), d) else {
+                break;
+            };
+            if !self.is_doc_deleted(doc_id) {
+                let tf =
+                    self.doc_array
+                        .rank_range_u64_unchecked(range.clone(), doc_id) as u64;
+                result.push((doc_id, tf));
+            }
+            d = doc_id + 1;
+        }
+        result
+    }
+
+    /// List the `k` texts containing the most suffixes in `[s, e)` of the
+    /// suffix array, ordered by decreasing number of suffixes and then by
+    /// text id.
+    pub(crate) fn top_docs(&self, s: u64, e: u64, k: usize) -> Vec<(u64, u64)> {
+        let mut docs = self.docs_with_tf(s, e);
+        let order = |a: &(u64, u64), b: &(u64, u64)| b.1.cmp(&a.1).then(a.0.cmp(&b.0));
+        if k < docs.len() {
+            docs.select_nth_unstable_by(k, order);
+            docs.truncate(k);
+        }
+        docs.sort_unstable_by(order);
+        docs
+    }
+
+    /// Recover all texts, in the original alphabet, including deleted ones.
+    pub(crate) fn texts(&self) -> Vec<Vec<T>> {
+        let n = self.len();
+        let mut text = Vec::with_capacity(n as usize);
+        // the last end marker is at the last position of the text
+        let mut i = self.doc_count() - 1;
+        for _ in 1..n {
+            text.push(self.converter.convert_inv(self.get_l(i)));
+            i = self.lf_map(i);
+        }
+        text.reverse();
+        text.split(|c| c.is_zero()).map(|t| t.to_vec()).collect()
+    }
+
+    /// Recover the texts that were not deleted, in the original alphabet,
+    /// together with their metadata values.
+    pub(crate) fn into_live_texts(self) -> (Vec<Vec<T>>, Vec<M>, C) {
+        let texts = self
+            .texts()
+            .into_iter()
+            .enumerate()
+            .filter(|(doc_id, _)| !self.is_doc_deleted(*doc_id as u64))
+            .map(|(_, t)| t)
+            .collect::<Vec<_>>();
+        let has_metadata = !self.metadata.is_empty();
+        let metadata = self
+            .metadata
+            .into_iter()
+            .enumerate()
+            .filter(|(doc_id, _)| !self.deleted.is_bit_set_unchecked(*doc_id))
+            .map(|(_, m)| m)
+            .collect::<Vec<_>>();
+        debug_assert!(!has_metadata || metadata.len() == texts.len());
+        (texts, metadata, self.converter)
+    }
+
     /**
      * Compute the suffix array of the given text.
      * This algorithm is aware of the order of end markers (zeros).
@@ -80,19 +299,19 @@ where
         sa
     }
 
-    fn doc(text: &[T], bw: &WaveletMatrix, sa: &[u64]) -> Vec<usize> {
+    fn end_markers(text: &[T]) -> RsVec {
         let mut end_marker_bits = BitVec::from_zeros(text.len());
-        let mut end_marker_count = 0;
         for (i, c) in text.iter().enumerate() {
             if c.is_zero() {
                 end_marker_bits.set(i, 1).unwrap();
-                end_marker_count += 1;
             }
         }
-        let end_marker_flags = RsVec::from_bit_vec(end_marker_bits);
+        RsVec::from_bit_vec(end_marker_bits)
+    }
 
+    fn doc(end_marker_flags: &RsVec, bw: &WaveletMatrix, sa: &[u64]) -> Vec<usize> {
         let mut end_marker_rank_l = 0;
-        let mut doc = vec![0; end_marker_count];
+        let mut doc = vec![0; end_marker_flags.rank1(sa.len())];
         while let Some(p) = bw.select_u64(end_marker_rank_l, 0) {
             let end_marker_idx = modular_sub(sa[p] as usize, 1, sa.len());
             let text_id = end_marker_flags.rank1(end_marker_idx);
@@ -104,45 +323,84 @@ where
         doc
     }
 
-    fn wavelet_matrix(text: &[T], sa: &[u64], converter: &C) -> WaveletMatrix {
-        let n = text.len();
-        let mut bw = vec![T::zero(); n];
-        for i in 0..n {
-            let k = sa[i] as usize;
-            if k > 0 {
-                bw[i] = converter.convert(text[k - 1]);
-            }
+    fn doc_rank(doc: &[usize]) -> Vec<usize> {
+        let mut doc_rank = vec![0; doc.len()];
+        for (rank, &row) in doc.iter().enumerate() {
+            doc_rank[row] = rank;
         }
-        let bw = bw.into_iter().map(|c| c.into()).collect::<Vec<u64>>();
+        doc_rank
+    }
 
-        WaveletMatrix::from_slice(&bw, (util::log2(converter.len() - 1) + 1) as u16)
+    fn doc_array(end_marker_flags: &RsVec, sa: &[u64]) -> WaveletMatrix {
+        let doc_count = end_marker_flags.rank1(sa.len()) as u64;
+        let doc_array = PackedSymbols::pack(
+            sa.iter()
+                .map(|&p| end_marker_flags.rank1(p as usize) as u64),
+            doc_count.max(2),
+        );
+        WaveletMatrix::from_symbols(&doc_array, doc_count.max(2))
+    }
+
+    fn wavelet_matrix(text: &[T], sa: &[u64], converter: &C) -> WaveletMatrix {
+        WaveletMatrix::from_symbols(&bwt_symbols(text, sa, converter), converter.len())
     }
 }
 
-impl<T, C> HeapSize for MultiTextFMIndexBackend<T, C, ()>
+impl<T, C, M> HeapSize for MultiTextFMIndexBackend<T, C, (), M>
 where
     T: Character,
     C: Converter<T>,
 {
     fn heap_size(&self) -> usize {
-        self.bw.heap_size() + self.cs.capacity() * std::mem::size_of::<u64>()
+        self.size_breakdown().total()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new()
+            .add("bwt", self.bw.heap_size())
+            .add("cs", self.cs.capacity() * core::mem::size_of::<u64>())
+            .add(
+                "documents",
+                self.end_markers.heap_size() + self.doc_array.heap_size(),
+            )
+            .add(
+                "metadata",
+                self.metadata.capacity() * core::mem::size_of::<M>(),
+            )
+            .add("deleted", self.deleted.heap_size())
     }
 }
 
-impl<T, C> HeapSize for MultiTextFMIndexBackend<T, C, SuffixOrderSampledArray>
+impl<T, C, S, M> HeapSize for MultiTextFMIndexBackend<T, C, S, M>
 where
     T: Character,
     C: Converter<T>,
+    S: SuffixArraySamples,
 {
     fn heap_size(&self) -> usize {
-        self.bw.heap_size()
-            + self.cs.capacity() * std::mem::size_of::<u64>()
-            + self.suffix_array.size()
-            + self.doc.capacity() * std::mem::size_of::<usize>()
+        self.size_breakdown().total()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new()
+            .add("bwt", self.bw.heap_size())
+            .add("cs", self.cs.capacity() * core::mem::size_of::<u64>())
+            .add("suffix_array", self.suffix_array.size())
+            .add(
+                "documents",
+                (self.doc.capacity() + self.doc_rank.capacity()) * core::mem::size_of::<usize>()
+                    + self.end_markers.heap_size()
+                    + self.doc_array.heap_size(),
+            )
+            .add(
+                "metadata",
+                self.metadata.capacity() * core::mem::size_of::<M>(),
+            )
+            .add("deleted", self.deleted.heap_size())
     }
 }
 
-impl<T, C, S> SearchIndexBackend for MultiTextFMIndexBackend<T, C, S>
+impl<T, C, S, M> SearchIndexBackend for MultiTextFMIndexBackend<T, C, S, M>
 where
     T: Character,
     C: Converter<T>,
@@ -199,26 +457,73 @@ where
         T::from_u64(s as u64)
     }
 
-    fn fl_map(&self, _i: u64) -> u64 {
-        todo!("implement inverse LF-mapping");
+    fn fl_map(&self, i: u64) -> u64 {
+        let c = self.get_f(i);
+        let rank = if c.is_zero() {
+            // the terminator rows are permuted by `doc` in `lf_map`
+            self.doc_rank[i as usize]
+        } else {
+            (i - self.cs[c.into() as usize]) as usize
+        };
+        self.bw.select_u64_unchecked(rank, c.into()) as u64
+    }
+
+    fn interval_symbols(&self, s: u64, e: u64) -> Vec<(Self::T, u64)> {
+        let range = s as usize..e as usize;
+        let mut result = Vec::new();
+        let mut c = 1;
+        while let Some(d) = self.bw.successor_u64(range.clone(), c) {
+            let count = self.bw.rank_range_u64_unchecked(range.clone(), d) as u64;
+            result.push((T::from_u64(d), count));
+            c = d + 1;
+        }
+        result
     }
 
     fn get_converter(&self) -> &Self::C {
         &self.converter
     }
+
+    fn doc_id(&self, pos: u64) -> Option<u64> {
+        Some(self.end_markers.rank1(pos as usize) as u64)
+    }
+
+    fn is_deleted(&self, pos: u64) -> bool {
+        self.deleted_count > 0 && self.is_doc_deleted(self.end_markers.rank1(pos as usize) as u64)
+    }
+
+    fn live_count(&self, s: u64, e: u64) -> u64 {
+        if self.deleted_count == 0 {
+            e - s
+        } else {
+            self.docs_with_tf(s, e).iter().map(|(_, tf)| tf).sum()
+        }
+    }
 }
 
-impl<T, C> HasPosition for MultiTextFMIndexBackend<T, C, SuffixOrderSampledArray>
+impl<T, C, S, M> MultiTextFMIndexBackend<T, C, S, M>
 where
     T: Character,
     C: Converter<T>,
+    S: SuffixArraySamples,
 {
-    fn get_sa(&self, mut i: u64) -> u64 {
+    pub(crate) fn sampling_rate(&self) -> u64 {
+        self.suffix_array.rate()
+    }
+}
+
+impl<T, C, S, M> HasPosition for MultiTextFMIndexBackend<T, C, S, M>
+where
+    T: Character,
+    C: Converter<T>,
+    S: SuffixArraySamples,
+{
+    fn get_sa_with_steps(&self, mut i: u64) -> (u64, u64) {
         let mut steps = 0;
         loop {
             match self.suffix_array.get(i) {
                 Some(sa) => {
-                    return (sa + steps) % self.bw.len() as u64;
+                    return ((sa + steps) % self.bw.len() as u64, steps);
                 }
                 None => {
                     i = self.lf_map(i);
@@ -227,6 +532,10 @@ where
             }
         }
     }
+
+    fn get_isa_sample(&self, p: u64) -> (u64, u64) {
+        self.suffix_array.get_inverse(p)
+    }
 }
 
 fn modular_sub<T: Sub<Output = T> + Ord + num_traits::Zero>(a: T, b: T, m: T) -> T {
@@ -255,7 +564,9 @@ mod tests {
         let converter = IdConverter::new::<u8>();
         let suffix_array = MultiTextFMIndexBackend::<_, _, ()>::suffix_array(&text, &converter);
         let inv_suffix_array = inv_suffix_array(&suffix_array);
-        let fm_index = MultiTextFMIndexBackend::new(text, converter, |sa| sample::sample(sa, 0));
+        let fm_index = MultiTextFMIndexBackend::<_, _, _>::new(text.into(), converter, |sa| {
+            sample::sample(sa, 0)
+        });
 
         let mut lf_map_expected = vec![0; text_size];
         let mut lf_map_actual = vec![0; text_size];
@@ -268,6 +579,16 @@ mod tests {
         assert_eq!(lf_map_expected, lf_map_actual);
     }
 
+    #[test]
+    fn test_fl_map() {
+        let text = generate_text_random(4096, 8);
+        let fm_index =
+            MultiTextFMIndexBackend::<_, _, ()>::new(text.into(), IdConverter::new::<u8>(), |_| ());
+        for i in 0..fm_index.len() {
+            assert_eq!(fm_index.lf_map(fm_index.fl_map(i)), i);
+        }
+    }
+
     fn generate_text_random(text_size: usize, alphabet_size: u8) -> Vec<u8> {
         let mut rng = StdRng::seed_from_u64(0);
 
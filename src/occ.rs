@@ -0,0 +1,170 @@
+//! Occurrence checkpoint tables for counting over small alphabets.
+use crate::backend::{HeapSize, SearchIndexBackend};
+use crate::character::Character;
+use crate::converter::Converter;
+use alloc::{vec, vec::Vec};
+
+// Each character of the BWT is stored in a 4-bit field.
+const FIELD_BITS: usize = 4;
+const FIELDS_PER_WORD: usize = 64 / FIELD_BITS;
+const LOW_BITS: u64 = 0x1111_1111_1111_1111;
+
+/// The number of occurrences of each character before every `k`-th
+/// position of the BWT, together with a copy of the BWT in 4-bit fields.
+///
+/// Rank of a character is a lookup of the checkpoint before the position
+/// plus a scan of the few words after it, where each word is scanned with a
+/// single popcount. Unlike rank in a wavelet matrix, this doesn't descend a
+/// level per bit of the alphabet. This is the layout BWA uses for DNA.
+#[derive(Clone, Debug)]
+pub(crate) struct OccTable {
+    k: usize,
+    alphabet_size: usize,
+    // the number of characters smaller than each character
+    starts: Vec<u64>,
+    // the occurrences of each character before position j * k, at
+    // j * alphabet_size
+    checkpoints: Vec<u64>,
+    words: Vec<u64>,
+}
+
+// The number of fields equal to `c` among the first `n` fields of `word`.
+fn count_field(word: u64, c: u64, n: usize) -> u64 {
+    let x = word ^ (c * LOW_BITS);
+    // the low bit of a field is set if any bit of the field is
+    let x = x | (x >> 1);
+    let x = x | (x >> 2);
+    let mut matches = !x & LOW_BITS;
+    if n < FIELDS_PER_WORD {
+        matches &= (1 << (FIELD_BITS * n)) - 1;
+    }
+    matches.count_ones() as u64
+}
+
+impl OccTable {
+    /// The largest alphabet, including the terminator, a table supports.
+    pub(crate) const MAX_ALPHABET_SIZE: u64 = 1 << FIELD_BITS;
+
+    /// Build the table for the BWT of `backend`, with a checkpoint every
+    /// `k` positions, rounded up to a multiple of 16.
+    ///
+    /// Panics if the converter's alphabet is larger than
+    /// [`MAX_ALPHABET_SIZE`](Self::MAX_ALPHABET_SIZE).
+    pub(crate) fn new<B: SearchIndexBackend>(backend: &B, k: usize) -> Self {
+        let alphabet_size = backend.get_converter().len();
+        assert!(
+            alphabet_size <= Self::MAX_ALPHABET_SIZE,
+            "an occurrence table holds at most {} characters besides the terminator, not {}",
+            Self::MAX_ALPHABET_SIZE - 1,
+            alphabet_size - 1
+        );
+        let alphabet_size = alphabet_size as usize;
+        let k = k.max(1).next_multiple_of(FIELDS_PER_WORD);
+        let n = backend.len() as usize;
+
+        let mut words = vec![0u64; n.div_ceil(FIELDS_PER_WORD)];
+        // include the checkpoint at `n`, so rank(n) needs no special case
+        let mut checkpoints = Vec::with_capacity((n / k + 1) * alphabet_size);
+        let mut counts = vec![0u64; alphabet_size];
+        for i in 0..n {
+            if i.is_multiple_of(k) {
+                checkpoints.extend_from_slice(&counts);
+            }
+            let c = backend.get_l(i as u64).into();
+            words[i / FIELDS_PER_WORD] |= c << (FIELD_BITS * (i % FIELDS_PER_WORD));
+            counts[c as usize] += 1;
+        }
+        if n.is_multiple_of(k) {
+            checkpoints.extend_from_slice(&counts);
+        }
+
+        let starts = counts
+            .iter()
+            .scan(0, |start, &count| {
+                let s = *start;
+                *start += count;
+                Some(s)
+            })
+            .collect();
+
+        OccTable {
+            k,
+            alphabet_size,
+            starts,
+            checkpoints,
+            words,
+        }
+    }
+
+    /// The checkpoint interval.
+    pub(crate) fn k(&self) -> usize {
+        self.k
+    }
+
+    // The number of occurrences of converted character `c` in [0, i).
+    fn rank(&self, i: usize, c: u64) -> u64 {
+        let block = i / self.k;
+        let mut rank = self.checkpoints[block * self.alphabet_size + c as usize];
+        for w in block * self.k / FIELDS_PER_WORD..i / FIELDS_PER_WORD {
+            rank += count_field(self.words[w], c, FIELDS_PER_WORD);
+        }
+        if !i.is_multiple_of(FIELDS_PER_WORD) {
+            rank += count_field(self.words[i / FIELDS_PER_WORD], c, i % FIELDS_PER_WORD);
+        }
+        // the fields past the end of the BWT hold zeros, so they are never
+        // counted for a character; the terminator is never searched for
+        rank
+    }
+
+    /// Narrow down the interval `[s, e)` like
+    /// [`SearchIndexBackend::search_interval`], using the table.
+    pub(crate) fn search_interval<T, C>(
+        &self,
+        converter: &C,
+        pattern: &[T],
+        s: u64,
+        e: u64,
+        min: u64,
+    ) -> (u64, u64)
+    where
+        T: Character,
+        C: Converter<T>,
+    {
+        let mut s = s;
+        let mut e = e;
+        for &c in pattern.iter().rev() {
+            let c = converter.convert(c).into();
+            let start = self.starts[c as usize];
+            s = start + self.rank(s as usize, c);
+            e = start + self.rank(e as usize, c);
+            if e - s < min {
+                break;
+            }
+        }
+        (s, e)
+    }
+}
+
+impl HeapSize for OccTable {
+    fn heap_size(&self) -> usize {
+        (self.starts.capacity() + self.checkpoints.capacity() + self.words.capacity())
+            * core::mem::size_of::<u64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_field() {
+        // fields from least significant: 3, 1, 3, 0, ...
+        let word = 0x0313;
+        assert_eq!(count_field(word, 3, 16), 2);
+        assert_eq!(count_field(word, 3, 1), 1);
+        assert_eq!(count_field(word, 1, 16), 1);
+        assert_eq!(count_field(word, 0, 16), 13);
+        assert_eq!(count_field(word, 0, 3), 0);
+        assert_eq!(count_field(u64::MAX, 15, 16), 16);
+    }
+}
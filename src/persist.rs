@@ -0,0 +1,992 @@
+//! Saving indexes to a stable, versioned binary format.
+//!
+//! A saved index starts with a header:
+//!
+//! - the magic bytes `FMINDEX\0`,
+//! - the format version, a little-endian `u32`,
+//! - the kind of index, such as `FMIndex<u8>`, as a length byte followed by
+//!   that many bytes of UTF-8,
+//! - a byte of flags; bit 0 is set if the components are compressed with
+//!   zstd, bit 1 if the file holds one part of a split index,
+//! - for a part of a split index, a byte naming the part, a byte with a bit
+//!   set for each part the index was split into, and a little-endian `u64`
+//!   identifying the index, which is the same in all its parts,
+//! - the metadata described by [`IndexInfo`], encoded like a component but
+//!   never compressed, so it can be read without loading the index.
+//!
+//! The components of the index follow in sequence, such as the BWT, the
+//! character counts, the converter and the suffix array samples, and last
+//! the k-mer table if there is one. Each is prefixed with its length in
+//! bytes as a little-endian `u64`, encoded with bincode 1 in its default
+//! configuration (little-endian, with fixed size integers and lengths as
+//! `u64`), and followed by the XXH3 64-bit hash of its encoding as a
+//! little-endian `u64`. The components are written and read one at a time,
+//! so the index is never held in memory in its encoded form.
+//!
+//! Sequences marked in [`impl_sections!`], such as the BWT, are saved as
+//! their symbols rather than in the layout of their rank and select
+//! support: the size of the alphabet and the number of symbols as `u64`,
+//! and the symbols packed into the fewest bits that hold the alphabet,
+//! as a sequence of `u64` words. Their rank and select support is rebuilt
+//! when they are loaded. Other components, such as the bit vectors of the
+//! run-length FM-index and the suffix array samples, are in the serde
+//! encoding of the `vers-vecs` types they hold, which is why this crate
+//! depends on one exact version of `vers-vecs`.
+//!
+//! The encoding doesn't depend on the platform: integers are always
+//! little-endian, and `usize` values are encoded as `u64`. Saving an index
+//! gives the same bytes on every target, so an index saved on an x86_64
+//! server loads on an aarch64 or big-endian device. A 32-bit target can
+//! load any index whose sizes fit in its `usize`, and reports an error
+//! otherwise.
+//!
+//! A split index is saved as several files, each with the header and the
+//! components of one [`Part`]. The components of a part that wasn't split
+//! off are in the core part.
+//!
+//! Components marked as deferrable in [`impl_sections!`], such as the
+//! suffix array samples, may be left in the file when loading from a path,
+//! to be read on first use.
+//!
+//! The format version is increased whenever the encoding of an index
+//! changes. Loading reads all versions up to the current one, and rejects
+//! later versions with an error rather than misreading them.
+use crate::backend::SearchIndexBackend;
+use crate::converter::Converter;
+use crate::sequence::{packed_words, PackedSymbols, Sequence};
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use xxhash_rust::xxh3::Xxh3;
+
+const MAGIC: &[u8; 8] = b"FMINDEX\0";
+
+/// The version of the format written by `save`.
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+// Set in the flags if the components are compressed with zstd.
+const COMPRESSED: u8 = 1;
+// Set in the flags if the file holds one part of an index that was split
+// into several files.
+const SPLIT: u8 = 2;
+
+/// The part of a split index a component is stored in.
+///
+/// This is not exported; it's public only because [`Deferrable`] uses it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    /// The data needed to count, such as the BWT, and everything not in
+    /// another part.
+    Core = 0,
+    /// The suffix array samples.
+    Samples = 1,
+    /// The mapping from text positions to documents.
+    Documents = 2,
+}
+
+impl Part {
+    fn from_u8(part: u8) -> Option<Self> {
+        match part {
+            0 => Some(Part::Core),
+            1 => Some(Part::Samples),
+            2 => Some(Part::Documents),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Part::Core => "core",
+            Part::Samples => "samples",
+            Part::Documents => "documents",
+        }
+    }
+}
+
+/// The files an index is split into by `save_split`.
+///
+/// The core part holds what is needed to count and is always written. The
+/// suffix array samples and, for multi-text indexes, the mapping from text
+/// positions to documents, can each be split off into a file of their own,
+/// so they can be placed on different storage. Parts without a file of
+/// their own are kept in the core file.
+///
+/// Load the index with `load_split`, given the same files. With the
+/// [`LazySamples`](crate::LazySamples) sampling strategy the samples file
+/// isn't read until the first query that needs it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitPaths {
+    core: PathBuf,
+    samples: Option<PathBuf>,
+    documents: Option<PathBuf>,
+}
+
+impl SplitPaths {
+    /// Keep the whole index in the file at `core`, until other parts are
+    /// split off.
+    pub fn new<P: Into<PathBuf>>(core: P) -> Self {
+        SplitPaths {
+            core: core.into(),
+            samples: None,
+            documents: None,
+        }
+    }
+
+    /// Store the suffix array samples in the file at `path`.
+    pub fn samples<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.samples = Some(path.into());
+        self
+    }
+
+    /// Store the mapping from text positions to documents in the file at
+    /// `path`. Only multi-text indexes have this part.
+    pub fn documents<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.documents = Some(path.into());
+        self
+    }
+
+    // The parts with a file of their own, the core part first.
+    fn files(&self) -> Vec<(Part, &Path)> {
+        let mut files = vec![(Part::Core, self.core.as_path())];
+        files.extend(self.samples.as_deref().map(|path| (Part::Samples, path)));
+        files.extend(
+            self.documents
+                .as_deref()
+                .map(|path| (Part::Documents, path)),
+        );
+        files
+    }
+}
+
+/// How the components of a saved index are compressed.
+pub(crate) enum Compression {
+    None,
+    /// zstd at the given level.
+    #[cfg(feature = "compression")]
+    Zstd(i32),
+}
+
+/// A structure that is saved as a sequence of components.
+pub(crate) trait Sections: Sized {
+    fn save_sections<W: Write>(&self, out: &mut SectionWriter<W>) -> io::Result<()>;
+
+    fn load_sections<R: Read>(input: &mut SectionReader<R>) -> io::Result<Self>;
+
+    /// The level the suffix array was sampled at, if it was sampled at
+    /// regular intervals.
+    fn sampling_level(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// A component whose loading can be deferred until it's first used.
+///
+/// This is not exported; it's public only because [`SuffixArraySamples`]
+/// requires it.
+///
+/// [`SuffixArraySamples`]: crate::suffix_array::sample::SuffixArraySamples
+pub trait Deferrable: Sized {
+    /// Load the component from `part`, or arrange to load it later.
+    fn load_deferrable<R: Read>(input: &mut SectionReader<R>, part: Part) -> io::Result<Self>
+    where
+        Self: DeserializeOwned,
+    {
+        input.read_part(part)
+    }
+
+    /// The level the component sampled the suffix array at, if any.
+    fn sampling_level(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl Deferrable for () {}
+
+/// A sequence, such as the BWT, saved as its symbols rather than in the
+/// layout of its rank and select support, which is rebuilt on load.
+///
+/// This is not exported; it's public only because [`Sections`]
+/// implementations require it.
+pub trait Symbols: Sized {
+    /// Write the symbols to `part`.
+    fn save_encoded<W: Write>(&self, out: &mut SectionWriter<W>, part: Part) -> io::Result<()>;
+
+    /// Read the symbols from `part` and build the sequence.
+    fn load_encoded<R: Read>(input: &mut SectionReader<R>, part: Part) -> io::Result<Self>;
+}
+
+impl<S: Sequence> Symbols for S {
+    fn save_encoded<W: Write>(&self, out: &mut SectionWriter<W>, part: Part) -> io::Result<()> {
+        out.write_part(part, &SymbolsRef(self))
+    }
+
+    fn load_encoded<R: Read>(input: &mut SectionReader<R>, part: Part) -> io::Result<Self> {
+        let saved: SavedSymbols = input.read_part(part)?;
+        let len = usize::try_from(saved.len)
+            .map_err(|_| invalid("the index is too large for this platform".to_string()))?;
+        let symbols = PackedSymbols::from_words(saved.words, len, saved.alphabet_size)
+            .ok_or_else(|| invalid("corrupt index: invalid symbols".to_string()))?;
+        Ok(S::from_symbols(&symbols, saved.alphabet_size))
+    }
+}
+
+// The symbols of a sequence as saved: the size of the alphabet, the number
+// of symbols, and the symbols packed into words by `packed_words`.
+#[derive(Deserialize)]
+struct SavedSymbols {
+    alphabet_size: u64,
+    len: u64,
+    words: Vec<u64>,
+}
+
+// Serializes a sequence like `SavedSymbols`, packing the words as they are
+// written.
+struct SymbolsRef<'a, S>(&'a S);
+
+impl<S: Sequence> Serialize for SymbolsRef<'_, S> {
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        struct Words<'a, S>(&'a S);
+
+        impl<S: Sequence> Serialize for Words<'_, S> {
+            fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+                serializer.collect_seq(packed_words(self.0))
+            }
+        }
+
+        let mut saved = serializer.serialize_struct("SavedSymbols", 3)?;
+        saved.serialize_field("alphabet_size", &self.0.alphabet_size())?;
+        saved.serialize_field("len", &(self.0.len() as u64))?;
+        saved.serialize_field("words", &Words(self.0))?;
+        saved.end()
+    }
+}
+
+/// Implement [`Sections`] for a backend, with a section for each of the
+/// listed fields. The backend's `_t` field is a `PhantomData`.
+///
+/// A field marked with `#[load(Deferrable)]` is loaded through
+/// [`Deferrable`], which may leave it in the file. A field marked with
+/// `#[encode(Symbols)]` is saved and loaded through [`Symbols`]. A field marked with
+/// `#[part(P)]` is stored in [`Part::P`] of a split index; the others are
+/// in the core part.
+macro_rules! impl_sections {
+    (@part) => {
+        $crate::persist::Part::Core
+    };
+    (@part $part:ident) => {
+        $crate::persist::Part::$part
+    };
+    (@save $out:ident, $part:expr, $value:expr) => {
+        $out.write_part($part, $value)?
+    };
+    (@save $out:ident, $part:expr, $value:expr, $enc:ident) => {
+        $crate::persist::$enc::save_encoded($value, $out, $part)?
+    };
+    (@load $input:ident, $part:expr, $ft:ty;;) => {
+        $input.read_part($part)?
+    };
+    (@load $input:ident, $part:expr, $ft:ty; $via:ident;) => {
+        <$ft as $crate::persist::$via>::load_deferrable($input, $part)?
+    };
+    (@load $input:ident, $part:expr, $ft:ty;; $enc:ident) => {
+        <$ft as $crate::persist::$enc>::load_encoded($input, $part)?
+    };
+    ($t:ident<$($g:ident),*> {
+        $(
+            $(#[load($via:ident)])? $(#[encode($enc:ident)])? $(#[part($part:ident)])?
+            $f:ident: $ft:ty
+        ),* $(,)?
+    }) => {
+        impl<$($g),*> $crate::persist::Sections for $t<$($g),*>
+        where
+            $($ft: serde::Serialize + serde::de::DeserializeOwned,)*
+            $($($ft: $crate::persist::$via,)?)*
+            $($($ft: $crate::persist::$enc,)?)*
+        {
+            fn save_sections<Wr: std::io::Write>(
+                &self,
+                out: &mut $crate::persist::SectionWriter<Wr>,
+            ) -> std::io::Result<()> {
+                $(impl_sections!(
+                    @save out, impl_sections!(@part $($part)?), &self.$f $(, $enc)?
+                );)*
+                Ok(())
+            }
+
+            fn load_sections<Rd: std::io::Read>(
+                input: &mut $crate::persist::SectionReader<Rd>,
+            ) -> std::io::Result<Self> {
+                Ok($t {
+                    $($f: impl_sections!(
+                        @load input, impl_sections!(@part $($part)?), $ft; $($via)?; $($enc)?
+                    ),)*
+                    _t: std::marker::PhantomData,
+                })
+            }
+
+            fn sampling_level(&self) -> Option<usize> {
+                None$($(.or($crate::persist::$via::sampling_level(&self.$f)))?)*
+            }
+        }
+    };
+}
+pub(crate) use impl_sections;
+
+/// The error for a saved index whose contents don't match their checksum.
+///
+/// Loading returns this inside an [`io::Error`] of kind
+/// [`InvalidData`](io::ErrorKind::InvalidData); get it with
+/// `err.get_ref().and_then(|e| e.downcast_ref::<ChecksumMismatch>())`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    /// The position of the corrupt component in the saved index, starting
+    /// at 0.
+    pub component: usize,
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "corrupt index: component {} doesn't match its checksum",
+            self.component
+        )
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+/// Information about a saved index, read from its header.
+///
+/// [`IndexInfo::peek`] reads this without loading the index, so tools can
+/// inspect index files cheaply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexInfo {
+    format_version: u32,
+    kind: String,
+    compressed: bool,
+    metadata: Metadata,
+}
+
+// The part of the information that is stored after the header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Metadata {
+    character: String,
+    converter: String,
+    converter_config: Vec<u8>,
+    sampling_level: Option<usize>,
+    text_len: u64,
+    alphabet_size: u64,
+    crate_version: String,
+}
+
+impl IndexInfo {
+    pub(crate) fn new<B>(backend: &B, name: &str) -> io::Result<Self>
+    where
+        B: SearchIndexBackend + Sections,
+        B::C: Serialize,
+    {
+        let converter = backend.get_converter();
+        let text_len = backend
+            .interval_symbols(0, backend.len())
+            .iter()
+            .map(|&(_, count)| count)
+            .sum();
+        Ok(IndexInfo {
+            format_version: FORMAT_VERSION,
+            kind: kind::<B::T>(name),
+            compressed: false,
+            metadata: Metadata {
+                character: format!("u{}", std::mem::size_of::<B::T>() * 8),
+                converter: converter.name(),
+                converter_config: bincode::serialize(converter)
+                    .map_err(|err| from_bincode(*err))?,
+                sampling_level: backend.sampling_level(),
+                text_len,
+                alphabet_size: converter.len(),
+                crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+        })
+    }
+
+    /// Read the information from the start of a saved index, without
+    /// reading the rest of it.
+    ///
+    /// An error of kind [`InvalidData`](io::ErrorKind::InvalidData) is
+    /// returned if `reader` doesn't hold a saved index.
+    pub fn peek<R: Read>(mut reader: R) -> io::Result<Self> {
+        let header = read_header(&mut reader)?;
+        Ok(IndexInfo {
+            format_version: header.version,
+            kind: header.kind,
+            compressed: header.flags & COMPRESSED != 0,
+            metadata: read_metadata(reader)?,
+        })
+    }
+
+    /// The version of the format the index was saved in.
+    pub fn format_version(&self) -> u32 {
+        self.format_version
+    }
+
+    /// The kind of index, such as `FMIndexWithLocate<u8>`.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Whether the components of the index are compressed.
+    pub fn compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// The character type of the index, such as `u8`.
+    pub fn character(&self) -> &str {
+        &self.metadata.character
+    }
+
+    /// The name of the converter, as given by [`Converter::name`], such as
+    /// `RangeConverter` or `CaseFoldConverter<RangeConverter>`.
+    pub fn converter(&self) -> &str {
+        &self.metadata.converter
+    }
+
+    /// Decode the configuration of the converter, which must be of type
+    /// `C`, such as the range of a
+    /// [`RangeConverter`](crate::converter::RangeConverter).
+    pub fn converter_config<C: DeserializeOwned>(&self) -> io::Result<C> {
+        bincode::deserialize(&self.metadata.converter_config).map_err(|err| from_bincode(*err))
+    }
+
+    /// The level the suffix array was sampled at, or `None` if the index
+    /// has no locate support or samples the suffix array at the runs of
+    /// the BWT.
+    pub fn sampling_level(&self) -> Option<usize> {
+        self.metadata.sampling_level
+    }
+
+    /// The number of characters in the text, not counting terminators.
+    pub fn text_len(&self) -> u64 {
+        self.metadata.text_len
+    }
+
+    /// The size of the converter's alphabet, including the terminator.
+    pub fn alphabet_size(&self) -> u64 {
+        self.metadata.alphabet_size
+    }
+
+    /// The version of this crate that saved the index.
+    pub fn crate_version(&self) -> &str {
+        &self.metadata.crate_version
+    }
+}
+
+// Hashes the bytes written through it.
+struct HashingWriter<'a, W> {
+    writer: &'a mut W,
+    hasher: Xxh3,
+}
+
+impl<W: Write> Write for HashingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+// Hashes the bytes read through it.
+struct HashingReader<R> {
+    reader: R,
+    hasher: Xxh3,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn from_bincode(err: bincode::ErrorKind) -> io::Error {
+    match err {
+        bincode::ErrorKind::Io(err) => err,
+        err => invalid(format!("corrupt index: {}", err)),
+    }
+}
+
+/// Writes components, each prefixed with its length.
+pub(crate) struct SectionWriter<W> {
+    // the writer of each part with a file of its own, the core part first
+    writers: Vec<(Part, W)>,
+    // hashes the checksums of all components, to identify the index
+    id: Xxh3,
+}
+
+impl<W: Write> SectionWriter<W> {
+    fn new(writer: W) -> Self {
+        Self::split(vec![(Part::Core, writer)])
+    }
+
+    fn split(writers: Vec<(Part, W)>) -> Self {
+        SectionWriter {
+            writers,
+            id: Xxh3::new(),
+        }
+    }
+
+    /// Write a component to the core part.
+    pub(crate) fn write<S: Serialize>(&mut self, value: &S) -> io::Result<()> {
+        self.write_part(Part::Core, value)
+    }
+
+    /// Write a component to `part`, which is the core part unless it has a
+    /// file of its own.
+    pub(crate) fn write_part<S: Serialize>(&mut self, part: Part, value: &S) -> io::Result<()> {
+        let len = bincode::serialized_size(value).map_err(|err| from_bincode(*err))?;
+        let i = self
+            .writers
+            .iter()
+            .position(|&(p, _)| p == part)
+            .unwrap_or(0);
+        let writer = &mut self.writers[i].1;
+        writer.write_all(&len.to_le_bytes())?;
+        let mut hashing = HashingWriter {
+            writer: &mut *writer,
+            hasher: Xxh3::new(),
+        };
+        bincode::serialize_into(&mut hashing, value).map_err(|err| from_bincode(*err))?;
+        let checksum = hashing.hasher.digest().to_le_bytes();
+        writer.write_all(&checksum)?;
+        self.id.update(&checksum);
+        Ok(())
+    }
+
+    fn into_writers(self) -> Vec<(Part, W)> {
+        self.writers
+    }
+
+    fn into_writer(self) -> W {
+        self.writers.into_iter().next().unwrap().1
+    }
+}
+
+/// Reads components written by a [`SectionWriter`].
+///
+/// This is not exported; it's public only because [`Deferrable`] uses it.
+pub struct SectionReader<R> {
+    // the reader of each part with a file of its own, the core part first
+    inputs: Vec<Input<R>>,
+    // the number of components read so far
+    component: usize,
+    // set if the inputs are files that deferred components can be read
+    // from later
+    seek: Option<fn(&mut R, SeekFrom) -> io::Result<u64>>,
+}
+
+struct Input<R> {
+    part: Part,
+    reader: R,
+    path: Option<PathBuf>,
+}
+
+/// The location of a component left in a file, to be loaded later.
+#[derive(Clone, Debug)]
+pub(crate) struct DeferredSection {
+    path: PathBuf,
+    // the offset of the component's length prefix
+    offset: u64,
+    component: usize,
+}
+
+impl DeferredSection {
+    /// Read and decode the component, verifying its checksum.
+    pub(crate) fn load<D: DeserializeOwned>(&self) -> io::Result<D> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(self.offset))?;
+        let mut input = SectionReader::new(reader);
+        input.component = self.component;
+        input.read()
+    }
+}
+
+impl<R: Read> SectionReader<R> {
+    fn new(reader: R) -> Self {
+        SectionReader {
+            inputs: vec![Input {
+                part: Part::Core,
+                reader,
+                path: None,
+            }],
+            component: 0,
+            seek: None,
+        }
+    }
+
+    // The input `part` is read from.
+    fn input(&mut self, part: Part) -> &mut Input<R> {
+        let i = self.inputs.iter().position(|input| input.part == part);
+        &mut self.inputs[i.unwrap_or(0)]
+    }
+
+    /// Skip over the next component of `part`, returning where to find it
+    /// later.
+    ///
+    /// Returns `None`, without reading anything, if the reader can't be
+    /// reopened, such as when it isn't a file or is compressed.
+    pub(crate) fn defer(&mut self, part: Part) -> io::Result<Option<DeferredSection>> {
+        let Some(seek) = self.seek else {
+            return Ok(None);
+        };
+        let input = self.input(part);
+        let Some(path) = input.path.clone() else {
+            return Ok(None);
+        };
+        let offset = seek(&mut input.reader, SeekFrom::Current(0))?;
+        let mut len = [0u8; 8];
+        input.reader.read_exact(&mut len)?;
+        // skip the component and its checksum
+        let skip = i64::try_from(u64::from_le_bytes(len))
+            .ok()
+            .and_then(|len| len.checked_add(8))
+            .ok_or_else(|| invalid("corrupt index: a component is too long".to_string()))?;
+        seek(&mut input.reader, SeekFrom::Current(skip))?;
+        let component = self.component;
+        self.component += 1;
+        Ok(Some(DeferredSection {
+            path,
+            offset,
+            component,
+        }))
+    }
+
+    /// Read a component from the core part.
+    pub(crate) fn read<D: DeserializeOwned>(&mut self) -> io::Result<D> {
+        self.read_part(Part::Core)
+    }
+
+    /// Read a component from `part`, which is the core part unless it has
+    /// a file of its own.
+    pub(crate) fn read_part<D: DeserializeOwned>(&mut self, part: Part) -> io::Result<D> {
+        let component = self.component;
+        self.component += 1;
+        let reader = &mut self.input(part).reader;
+        let mut len = [0u8; 8];
+        reader.read_exact(&mut len)?;
+        let mut section = HashingReader {
+            reader: (&mut *reader).take(u64::from_le_bytes(len)),
+            hasher: Xxh3::new(),
+        };
+        let value = bincode::deserialize_from(&mut section).map_err(|err| from_bincode(*err));
+        // Check the checksum even if decoding failed, so corruption is
+        // reported as such.
+        if value.is_err() && io::copy(&mut section, &mut io::sink()).is_err() {
+            return value;
+        }
+        let unread = section.reader.limit();
+        let digest = section.hasher.digest();
+        let mut checksum = [0u8; 8];
+        reader.read_exact(&mut checksum)?;
+        if unread != 0 || u64::from_le_bytes(checksum) != digest {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                ChecksumMismatch { component },
+            ));
+        }
+        value
+    }
+}
+
+// The kind of an index, with the size of its characters.
+pub(crate) fn kind<T>(name: &str) -> String {
+    format!("{}<u{}>", name, std::mem::size_of::<T>() * 8)
+}
+
+// The part of a split index a file holds.
+#[derive(Clone, Copy)]
+struct Split {
+    part: Part,
+    // a bit for each part with a file of its own
+    parts: u8,
+    // identifies the index, so the parts of different indexes can't be
+    // mixed up
+    id: u64,
+}
+
+// Write the header and the metadata.
+fn write_header<W: Write>(
+    mut writer: W,
+    info: &IndexInfo,
+    flags: u8,
+    split: Option<Split>,
+) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&[info.kind.len() as u8])?;
+    writer.write_all(info.kind.as_bytes())?;
+    writer.write_all(&[flags])?;
+    if let Some(split) = split {
+        writer.write_all(&[split.part as u8, split.parts])?;
+        writer.write_all(&split.id.to_le_bytes())?;
+    }
+    SectionWriter::new(writer).write(&info.metadata)
+}
+
+pub(crate) fn save<I: Sections, W: Write>(
+    index: &I,
+    info: &IndexInfo,
+    mut writer: W,
+    compression: Compression,
+) -> io::Result<()> {
+    let flags = match compression {
+        Compression::None => 0,
+        #[cfg(feature = "compression")]
+        Compression::Zstd(_) => COMPRESSED,
+    };
+    write_header(&mut writer, info, flags, None)?;
+    match compression {
+        Compression::None => {
+            let mut out = SectionWriter::new(writer);
+            index.save_sections(&mut out)?;
+            out.into_writer().flush()
+        }
+        #[cfg(feature = "compression")]
+        Compression::Zstd(level) => {
+            let mut out = SectionWriter::new(zstd::Encoder::new(writer, level)?);
+            index.save_sections(&mut out)?;
+            out.into_writer().finish()?.flush()
+        }
+    }
+}
+
+/// Save an index split into the files given by `paths`.
+pub(crate) fn save_split<I: Sections>(
+    index: &I,
+    info: &IndexInfo,
+    paths: &SplitPaths,
+) -> io::Result<()> {
+    // The id is a hash of the checksums of all components, which takes a
+    // pass over the index before writing it.
+    let mut out = SectionWriter::new(io::sink());
+    index.save_sections(&mut out)?;
+    let id = out.id.digest();
+
+    let files = paths.files();
+    let parts = files
+        .iter()
+        .fold(0, |parts, &(part, _)| parts | 1 << part as u8);
+    let mut writers = Vec::with_capacity(files.len());
+    for (part, path) in files {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let split = Split { part, parts, id };
+        write_header(&mut writer, info, SPLIT, Some(split))?;
+        writers.push((part, writer));
+    }
+    let mut out = SectionWriter::split(writers);
+    index.save_sections(&mut out)?;
+    for (_, mut writer) in out.into_writers() {
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+pub(crate) fn load<I, R>(kind: &str, reader: R) -> io::Result<I>
+where
+    I: Sections,
+    R: Read,
+{
+    load_from(kind, reader, None)
+}
+
+fn seek_file(reader: &mut BufReader<File>, pos: SeekFrom) -> io::Result<u64> {
+    reader.seek(pos)
+}
+
+/// Load an index from the file at `path`, leaving deferrable components in
+/// the file if it isn't compressed.
+pub(crate) fn load_file<I>(kind: &str, path: &Path) -> io::Result<I>
+where
+    I: Sections,
+{
+    let reader = BufReader::new(File::open(path)?);
+    load_from(kind, reader, Some((path.to_path_buf(), seek_file)))
+}
+
+/// Load an index split into the files given by `paths`, leaving deferrable
+/// components in their files.
+pub(crate) fn load_split<I>(kind: &str, paths: &SplitPaths) -> io::Result<I>
+where
+    I: Sections,
+{
+    let files = paths.files();
+    let parts = files
+        .iter()
+        .fold(0, |parts, &(part, _)| parts | 1 << part as u8);
+    let mut inputs = Vec::with_capacity(files.len());
+    let mut id = None;
+    for (part, path) in files {
+        let mut reader = BufReader::new(File::open(path)?);
+        let header = read_header(&mut reader)?;
+        check_kind(&header, kind)?;
+        let Some(split) = header.split else {
+            return Err(invalid(format!(
+                "{} isn't part of a split index",
+                path.display()
+            )));
+        };
+        if split.part != part {
+            return Err(invalid(format!(
+                "{} holds the {} part, expected the {} part",
+                path.display(),
+                split.part.name(),
+                part.name()
+            )));
+        }
+        if split.parts != parts {
+            return Err(invalid(format!(
+                "the index was split into the parts {}, but files for the parts {} were given",
+                part_names(split.parts),
+                part_names(parts)
+            )));
+        }
+        if id.is_some_and(|id| id != split.id) {
+            return Err(invalid("the parts belong to different indexes".to_string()));
+        }
+        id = Some(split.id);
+        read_metadata(&mut reader)?;
+        inputs.push(Input {
+            part,
+            reader,
+            path: Some(path.to_path_buf()),
+        });
+    }
+    let mut input = SectionReader {
+        inputs,
+        component: 0,
+        seek: Some(seek_file),
+    };
+    I::load_sections(&mut input)
+}
+
+// The names of the parts whose bits are set in `parts`.
+fn part_names(parts: u8) -> String {
+    (0..8)
+        .filter(|&bit| parts & 1 << bit != 0)
+        .map(|bit| Part::from_u8(bit).map_or("unknown", Part::name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+struct Header {
+    version: u32,
+    kind: String,
+    flags: u8,
+    split: Option<Split>,
+}
+
+// Read the header, up to the metadata.
+fn read_header<R: Read>(mut reader: R) -> io::Result<Header> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a saved FM-Index".to_string()));
+    }
+    let mut version = [0u8; 4];
+    reader.read_exact(&mut version)?;
+    let version = u32::from_le_bytes(version);
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(invalid(format!(
+            "unsupported format version {}, expected at most {}",
+            version, FORMAT_VERSION
+        )));
+    }
+    let mut len = [0u8; 1];
+    reader.read_exact(&mut len)?;
+    let mut kind = vec![0u8; len[0] as usize];
+    reader.read_exact(&mut kind)?;
+    let kind = String::from_utf8_lossy(&kind).into_owned();
+    let mut flags = [0u8; 1];
+    reader.read_exact(&mut flags)?;
+    let flags = flags[0];
+    let mut split = None;
+    if flags & SPLIT != 0 {
+        let mut part = [0u8; 2];
+        reader.read_exact(&mut part)?;
+        let mut id = [0u8; 8];
+        reader.read_exact(&mut id)?;
+        split = Some(Split {
+            part: Part::from_u8(part[0])
+                .ok_or_else(|| invalid(format!("unknown part {}", part[0])))?,
+            parts: part[1],
+            id: u64::from_le_bytes(id),
+        });
+    }
+    Ok(Header {
+        version,
+        kind,
+        flags: flags & !SPLIT,
+        split,
+    })
+}
+
+fn check_kind(header: &Header, kind: &str) -> io::Result<()> {
+    if header.kind != kind {
+        return Err(invalid(format!(
+            "expected a saved {}, found {}",
+            kind, header.kind
+        )));
+    }
+    Ok(())
+}
+
+fn read_metadata<R: Read>(reader: R) -> io::Result<Metadata> {
+    SectionReader::new(reader).read()
+}
+
+type SeekableFile<R> = (PathBuf, fn(&mut R, SeekFrom) -> io::Result<u64>);
+
+fn load_from<I, R>(kind: &str, mut reader: R, file: Option<SeekableFile<R>>) -> io::Result<I>
+where
+    I: Sections,
+    R: Read,
+{
+    let header = read_header(&mut reader)?;
+    check_kind(&header, kind)?;
+    if header.split.is_some() {
+        return Err(invalid(
+            "the index is split into several files, which load_split loads".to_string(),
+        ));
+    }
+    read_metadata(&mut reader)?;
+    match header.flags {
+        0 => {
+            let mut input = SectionReader::new(reader);
+            if let Some((path, seek)) = file {
+                input.inputs[0].path = Some(path);
+                input.seek = Some(seek);
+            }
+            I::load_sections(&mut input)
+        }
+        #[cfg(feature = "compression")]
+        COMPRESSED => I::load_sections(&mut SectionReader::new(zstd::Decoder::new(reader)?)),
+        #[cfg(not(feature = "compression"))]
+        COMPRESSED => Err(invalid(
+            "the index is compressed, which requires the compression feature".to_string(),
+        )),
+        flags => Err(invalid(format!("unsupported flags {:#x}", flags))),
+    }
+}
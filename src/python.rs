@@ -0,0 +1,154 @@
+//! Python bindings, built with `pyo3`.
+//!
+//! This is the `fm_index` Python extension module, which exports a single
+//! class, `FMIndex`, an index of a text of bytes with locate support. Build
+//! it with [maturin](https://www.maturin.rs), enabling pyo3's
+//! `extension-module` feature:
+//!
+//! ```text
+//! maturin build --release --features python,pyo3/extension-module
+//! ```
+//!
+//! Texts and patterns may be any object supporting the buffer protocol with
+//! a contiguous buffer of bytes, such as `bytes`, `bytearray`, `memoryview`
+//! or a numpy array of `uint8`. They are read in place, without converting
+//! them to `bytes` first.
+//!
+//! ```python
+//! from fm_index import FMIndex
+//!
+//! index = FMIndex(b"mississippi", level=2)
+//! assert index.count(b"ssi") == 2
+//! assert sorted(index.locate(b"ssi")) == [2, 5]
+//! assert index.extract(2, 6) == b"ssis"
+//! assert FMIndex.from_bytes(index.to_bytes()).count(b"p") == 2
+//! ```
+//!
+//! Indexes can be pickled, so they can be passed to other processes, for
+//! instance with `multiprocessing`.
+use crate::character::{check_level, check_text};
+use crate::converter::IdConverter;
+use crate::frontend::FMIndexWithLocate;
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::{PyBufferError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+/// An index of a text of bytes with locate support, exported to Python as
+/// `FMIndex`.
+#[pyclass(name = "FMIndex", module = "fm_index", frozen)]
+pub struct PyFMIndex(FMIndexWithLocate<u8, IdConverter>);
+
+// View a contiguous buffer of bytes as a slice, without copying it.
+//
+// The bytes of a mutable buffer may only be read while the GIL is held and
+// no Python code runs, so they can't change meanwhile.
+fn as_bytes<'a>(py: Python<'a>, buffer: &'a PyBuffer<u8>) -> PyResult<&'a [u8]> {
+    let cells = buffer
+        .as_slice(py)
+        .ok_or_else(|| PyBufferError::new_err("the buffer is not contiguous"))?;
+    // SAFETY: `ReadOnlyCell<u8>` has the layout of `u8`.
+    Ok(unsafe { std::slice::from_raw_parts(cells.as_ptr() as *const u8, cells.len()) })
+}
+
+#[pymethods]
+impl PyFMIndex {
+    /// Build an index of `text`, sampling the suffix array at `level` as in
+    /// [`FMIndexWithLocate::new`].
+    ///
+    /// The GIL is released while the index is built. A mutable buffer, such
+    /// as a `bytearray`, is copied first so it can't change meanwhile.
+    #[new]
+    #[pyo3(signature = (text, level = 2))]
+    fn new(py: Python<'_>, text: &Bound<'_, PyAny>, level: usize) -> PyResult<Self> {
+        let converter = IdConverter::new::<u8>();
+        let buffer = PyBuffer::<u8>::get(text)?;
+        let bytes = as_bytes(py, &buffer)?;
+        let text = if buffer.readonly() {
+            Cow::Borrowed(bytes)
+        } else {
+            Cow::Owned(bytes.to_vec())
+        };
+        check_text(&text, &converter)
+            .and_then(|()| check_level(&text, level))
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+        let index = py.detach(|| FMIndexWithLocate::new(text, converter, level));
+        Ok(PyFMIndex(index))
+    }
+
+    /// The length of the indexed text, without the terminator.
+    fn __len__(&self) -> usize {
+        self.0.text_len() as usize
+    }
+
+    /// The number of occurrences of `pattern`.
+    fn count(&self, pattern: &Bound<'_, PyAny>) -> PyResult<u64> {
+        let buffer = PyBuffer::get(pattern)?;
+        Ok(self.0.search(as_bytes(pattern.py(), &buffer)?).count())
+    }
+
+    /// The positions of all occurrences of `pattern`, in no particular
+    /// order.
+    fn locate(&self, pattern: &Bound<'_, PyAny>) -> PyResult<Vec<u64>> {
+        let buffer = PyBuffer::get(pattern)?;
+        Ok(self.0.search(as_bytes(pattern.py(), &buffer)?).locate())
+    }
+
+    /// The bytes of the text from `start` up to `end`, both clamped to the
+    /// length of the text like the bounds of a slice.
+    fn extract<'py>(&self, py: Python<'py>, start: u64, end: u64) -> Bound<'py, PyBytes> {
+        let end = end.min(self.0.text_len());
+        let start = start.min(end);
+        PyBytes::new(py, &self.0.extract(start, end))
+    }
+
+    /// Save the index as `bytes`, in the format of
+    /// [`FMIndexWithLocate::save`].
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let mut bytes = Vec::new();
+        self.0.save(&mut bytes)?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    /// Load an index saved by `to_bytes`, or by [`FMIndexWithLocate::save`]
+    /// for an index of bytes with an [`IdConverter`].
+    #[staticmethod]
+    fn from_bytes(data: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let buffer = PyBuffer::get(data)?;
+        let index = FMIndexWithLocate::load(as_bytes(data.py(), &buffer)?)?;
+        Ok(PyFMIndex(index))
+    }
+
+    /// Save the index to the file at `path`.
+    fn save(&self, path: std::path::PathBuf) -> PyResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.0.save(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Load an index saved by `save` from the file at `path`.
+    #[staticmethod]
+    fn load(path: std::path::PathBuf) -> PyResult<Self> {
+        Ok(PyFMIndex(FMIndexWithLocate::load_file(path)?))
+    }
+
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+        py: Python<'py>,
+    ) -> PyResult<(Bound<'py, PyAny>, (Bound<'py, PyBytes>,))> {
+        let from_bytes = slf.get_type().getattr("from_bytes")?;
+        Ok((from_bytes, (slf.get().to_bytes(py)?,)))
+    }
+}
+
+/// The `fm_index` Python module.
+#[pymodule]
+pub fn fm_index(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyFMIndex>()
+}
@@ -0,0 +1,230 @@
+//! Packed sequences over alphabets of up to four characters.
+use crate::sequence::{PackedSymbols, Sequence};
+use crate::util;
+
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// Symbols covered by an entry of the block and superblock rank tables. Block
+// counts are relative to their superblock, so they fit in 16 bits.
+const BLOCK_SIZE: usize = 256;
+const SUPER_BLOCK_SIZE: usize = 1 << 16;
+const SYMBOLS_PER_WORD: usize = 32;
+const LOW_BITS: u64 = 0x5555_5555_5555_5555;
+
+/// A sequence of 2-bit codes with rank support for each code, for the BWT
+/// of texts over an alphabet of at most four characters, such as DNA.
+///
+/// Character `c > 0` is stored as code `c - 1`. The terminator shares its
+/// code with character 1, and its few positions are kept separately.
+/// Counting the occurrences of a code in a word takes a single popcount, so
+/// rank needs no levels to descend, unlike a wavelet matrix.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuadVector {
+    words: Vec<u64>,
+    // the positions of the terminators, in order
+    zeros: Vec<usize>,
+    // the occurrences of each code before each superblock
+    super_blocks: Vec<[u64; 4]>,
+    // the occurrences of each code before each block, since the start of its
+    // superblock
+    blocks: Vec<[u16; 4]>,
+    alphabet_size: u64,
+    len: usize,
+}
+
+// The number of occurrences of `code` in the first `n` symbols of `word`.
+fn count_code(word: u64, code: u64, n: usize) -> usize {
+    let x = word ^ (code * LOW_BITS);
+    let mut matches = !(x | (x >> 1)) & LOW_BITS;
+    if n < SYMBOLS_PER_WORD {
+        matches &= (1 << (2 * n)) - 1;
+    }
+    matches.count_ones() as usize
+}
+
+impl QuadVector {
+    fn code(&self, i: usize) -> u64 {
+        (self.words[i / SYMBOLS_PER_WORD] >> (2 * (i % SYMBOLS_PER_WORD))) & 3
+    }
+
+    // The number of occurrences of `code` in [0, i).
+    fn rank_code(&self, i: usize, code: u64) -> usize {
+        let block = i / BLOCK_SIZE;
+        let mut rank = self.super_blocks[i / SUPER_BLOCK_SIZE][code as usize] as usize
+            + self.blocks[block][code as usize] as usize;
+        for w in block * BLOCK_SIZE / SYMBOLS_PER_WORD..i / SYMBOLS_PER_WORD {
+            rank += count_code(self.words[w], code, SYMBOLS_PER_WORD);
+        }
+        if !i.is_multiple_of(SYMBOLS_PER_WORD) {
+            rank += count_code(self.words[i / SYMBOLS_PER_WORD], code, i % SYMBOLS_PER_WORD);
+        }
+        rank
+    }
+
+    // The number of terminators in [0, i).
+    fn rank_zero(&self, i: usize) -> usize {
+        self.zeros.partition_point(|&p| p < i)
+    }
+}
+
+impl Sequence for QuadVector {
+    fn from_symbols(symbols: &PackedSymbols, alphabet_size: u64) -> Self {
+        assert!(
+            alphabet_size <= 5,
+            "a quad vector holds at most 4 characters besides the terminator, not {}",
+            alphabet_size - 1
+        );
+        let len = symbols.len();
+        let mut words = vec![0u64; len.div_ceil(SYMBOLS_PER_WORD)];
+        let mut zeros = Vec::new();
+        for (i, c) in symbols.iter().enumerate() {
+            if c == 0 {
+                zeros.push(i);
+            }
+            let code = c.saturating_sub(1);
+            words[i / SYMBOLS_PER_WORD] |= code << (2 * (i % SYMBOLS_PER_WORD));
+        }
+
+        // include the block starting at `len`, so rank(len) needs no special
+        // case
+        let mut super_blocks = Vec::with_capacity(len / SUPER_BLOCK_SIZE + 1);
+        let mut blocks = Vec::with_capacity(len / BLOCK_SIZE + 1);
+        let mut counts = [0u64; 4];
+        let mut since_super_block = [0u32; 4];
+        for b in 0..=len / BLOCK_SIZE {
+            if (b * BLOCK_SIZE).is_multiple_of(SUPER_BLOCK_SIZE) {
+                super_blocks.push(counts);
+                since_super_block = [0; 4];
+            }
+            blocks.push(since_super_block.map(|count| count as u16));
+            for i in (b * BLOCK_SIZE).min(len)..((b + 1) * BLOCK_SIZE).min(len) {
+                let code = symbols.get(i).saturating_sub(1) as usize;
+                counts[code] += 1;
+                since_super_block[code] += 1;
+            }
+        }
+
+        QuadVector {
+            words,
+            zeros,
+            super_blocks,
+            blocks,
+            alphabet_size,
+            len,
+        }
+    }
+
+    #[cfg(all(feature = "std", feature = "serde"))]
+    fn alphabet_size(&self) -> u64 {
+        self.alphabet_size
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, i: usize) -> u64 {
+        let code = self.code(i);
+        if code == 0 && self.zeros.binary_search(&i).is_ok() {
+            0
+        } else {
+            code + 1
+        }
+    }
+
+    fn rank(&self, i: usize, c: u64) -> usize {
+        match c {
+            0 => self.rank_zero(i),
+            1 => self.rank_code(i, 0) - self.rank_zero(i),
+            c if c < self.alphabet_size => self.rank_code(i, c - 1),
+            _ => 0,
+        }
+    }
+
+    fn select(&self, k: usize, c: u64) -> usize {
+        if c == 0 {
+            return self.zeros[k];
+        }
+        // the first position where the rank of c exceeds k
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.rank(mid + 1, c) > k {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        lo
+    }
+
+    fn prefetch(&self, i: usize) {
+        util::prefetch(&self.words[i / SYMBOLS_PER_WORD]);
+        util::prefetch(&self.blocks[i / BLOCK_SIZE]);
+    }
+
+    fn interval_symbols(&self, s: usize, e: usize) -> Vec<(u64, u64)> {
+        (1..self.alphabet_size)
+            .map(|c| (c, (self.rank(e, c) - self.rank(s, c)) as u64))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    fn heap_size(&self) -> usize {
+        self.words.capacity() * core::mem::size_of::<u64>()
+            + self.zeros.capacity() * core::mem::size_of::<usize>()
+            + self.super_blocks.capacity() * core::mem::size_of::<[u64; 4]>()
+            + self.blocks.capacity() * core::mem::size_of::<[u16; 4]>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vers_vecs::WaveletMatrix;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_against_wavelet_matrix() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut symbols = (0..70000)
+            .map(|_| rng.gen_range(1, 5))
+            .collect::<Vec<u64>>();
+        for i in [0, 12345, 65536, 69999] {
+            symbols[i] = 0;
+        }
+        let packed = PackedSymbols::pack(symbols.iter().copied(), 5);
+        let qv = QuadVector::from_symbols(&packed, 5);
+        let wm = WaveletMatrix::from_symbols(&packed, 5);
+        for (i, &c) in symbols.iter().enumerate().step_by(3) {
+            assert_eq!(qv.get(i), c);
+        }
+        for c in 0..6 {
+            for i in (0..=symbols.len()).step_by(97) {
+                assert_eq!(
+                    qv.rank(i, c),
+                    Sequence::rank(&wm, i, c),
+                    "rank({}, {})",
+                    i,
+                    c
+                );
+            }
+        }
+        for c in 0..5 {
+            let count = Sequence::rank(&wm, symbols.len(), c);
+            for k in (0..count).step_by(101) {
+                assert_eq!(qv.select(k, c), Sequence::select(&wm, k, c));
+            }
+        }
+        for _ in 0..100 {
+            let s = rng.gen_range(0, symbols.len());
+            let e = rng.gen_range(s, symbols.len() + 1);
+            assert_eq!(qv.interval_symbols(s, e), wm.interval_symbols(s, e));
+        }
+        assert!(qv.heap_size() < Sequence::heap_size(&wm));
+    }
+}
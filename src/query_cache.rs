@@ -0,0 +1,102 @@
+//! Caches of search results for workloads that repeat the same queries.
+use crate::character::Character;
+use crate::lru::Lru;
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // no code that can panic runs while a cache is locked, and a cache
+    // left in any state still holds valid results
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// A suffix array interval `[s, e)`.
+type Interval = (u64, u64);
+
+/// The suffix array intervals of the patterns searched for most recently,
+/// and optionally the positions of the occurrences in the intervals
+/// located most recently.
+///
+/// Patterns are keyed by their characters, and locate results by their
+/// interval, so refined and resumed searches share results with plain
+/// searches.
+pub(crate) struct QueryCache {
+    capacity: usize,
+    intervals: Mutex<Lru<Vec<u64>, Interval>>,
+    locations: Option<Mutex<Lru<Interval, Vec<u64>>>>,
+}
+
+// The cached results are left out, as they can be recomputed.
+impl core::fmt::Debug for QueryCache {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("QueryCache")
+            .field("capacity", &self.capacity)
+            .field("locate", &self.locations.is_some())
+            .finish()
+    }
+}
+
+impl QueryCache {
+    /// A cache of at most `capacity` intervals, and as many locate results
+    /// if `locate` is set.
+    pub(crate) fn new(capacity: usize, locate: bool) -> Self {
+        QueryCache {
+            capacity,
+            intervals: Mutex::new(Lru::new(capacity)),
+            locations: locate.then(|| Mutex::new(Lru::new(capacity))),
+        }
+    }
+
+    /// The capacity and whether locate results are cached, to build an
+    /// empty cache like this one.
+    pub(crate) fn config(&self) -> (usize, bool) {
+        (self.capacity, self.locations.is_some())
+    }
+
+    /// The interval of `pattern`, computed by `search` unless it's cached.
+    pub(crate) fn interval<T: Character>(
+        &self,
+        pattern: &[T],
+        search: impl FnOnce() -> (u64, u64),
+    ) -> (u64, u64) {
+        let key = pattern.iter().map(|&c| c.into()).collect::<Vec<u64>>();
+        if let Some(&interval) = lock(&self.intervals).get(&key) {
+            return interval;
+        }
+        // search without holding the lock, so other threads can use the
+        // cache in the meantime
+        let interval = search();
+        lock(&self.intervals).insert(key, interval);
+        interval
+    }
+
+    /// The positions of the occurrences in `[s, e)`, computed by `locate`
+    /// unless they're cached or locate results aren't cached at all.
+    pub(crate) fn locate(&self, s: u64, e: u64, locate: impl FnOnce() -> Vec<u64>) -> Vec<u64> {
+        let Some(locations) = &self.locations else {
+            return locate();
+        };
+        if let Some(positions) = lock(locations).get(&(s, e)) {
+            return positions.clone();
+        }
+        let positions = locate();
+        lock(locations).insert((s, e), positions.clone());
+        positions
+    }
+
+    /// Forget all results, for when the index changes.
+    pub(crate) fn clear(&self) {
+        lock(&self.intervals).clear();
+        if let Some(locations) = &self.locations {
+            lock(locations).clear();
+        }
+    }
+
+    pub(crate) fn heap_size(&self) -> usize {
+        let u64_size = std::mem::size_of::<u64>();
+        lock(&self.intervals).heap_size(|key| key.capacity() * u64_size, |_| 0)
+            + self.locations.as_ref().map_or(0, |locations| {
+                lock(locations).heap_size(|_| 0, |positions| positions.capacity() * u64_size)
+            })
+    }
+}
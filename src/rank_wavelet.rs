@@ -0,0 +1,367 @@
+//! Wavelet matrices without select support.
+use crate::sequence::{PackedSymbols, Sequence};
+use crate::util;
+
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// Bits covered by an entry of the block and superblock rank tables. Block
+// ranks are relative to their superblock, so they fit in 16 bits.
+const BLOCK_SIZE: usize = 512;
+const SUPER_BLOCK_SIZE: usize = 1 << 16;
+
+/// Storage of the 64-bit words of a bit vector.
+pub(crate) trait Words {
+    fn word(&self, i: usize) -> u64;
+
+    /// The heap size of the words that are held in memory.
+    fn heap_size(&self) -> usize;
+}
+
+impl Words for Vec<u64> {
+    fn word(&self, i: usize) -> u64 {
+        self[i]
+    }
+
+    fn heap_size(&self) -> usize {
+        self.capacity() * core::mem::size_of::<u64>()
+    }
+}
+
+/// A bit vector supporting rank only.
+///
+/// The rank directory is kept in words as well, so it can be stored
+/// elsewhere than in memory like the bits themselves.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct RankVec<W, D = Vec<u64>> {
+    words: W,
+    len: usize,
+    // ones before each superblock
+    super_blocks: D,
+    // ones before each block, since the start of its superblock, packed as
+    // four 16-bit entries per word
+    blocks: D,
+}
+
+/// Build the rank directory of the `len` bits in `words`.
+///
+/// Returns the words of the superblock and of the block ranks.
+pub(crate) fn rank_directory(words: &impl Words, len: usize) -> (Vec<u64>, Vec<u64>) {
+    // include the block starting at `len`, so rank1(len) needs no special
+    // case
+    let block_count = len / BLOCK_SIZE + 1;
+    let mut super_blocks = Vec::with_capacity(len / SUPER_BLOCK_SIZE + 1);
+    let mut blocks = vec![0u64; block_count.div_ceil(4)];
+    let mut ones = 0;
+    let mut since_super_block = 0;
+    let words_per_block = BLOCK_SIZE / 64;
+    let word_count = len.div_ceil(64);
+    for b in 0..block_count {
+        if (b * BLOCK_SIZE).is_multiple_of(SUPER_BLOCK_SIZE) {
+            super_blocks.push(ones);
+            since_super_block = 0;
+        }
+        blocks[b / 4] |= since_super_block << (16 * (b % 4));
+        let start = (b * words_per_block).min(word_count);
+        let end = ((b + 1) * words_per_block).min(word_count);
+        let count = (start..end)
+            .map(|i| words.word(i).count_ones() as u64)
+            .sum::<u64>();
+        ones += count;
+        since_super_block += count;
+    }
+    (super_blocks, blocks)
+}
+
+/// The number of words of the rank directory of `len` bits, for the
+/// superblocks and the blocks.
+#[cfg(feature = "std")]
+pub(crate) fn rank_directory_len(len: usize) -> (usize, usize) {
+    (
+        len / SUPER_BLOCK_SIZE + 1,
+        (len / BLOCK_SIZE + 1).div_ceil(4),
+    )
+}
+
+impl<W: Words> RankVec<W> {
+    fn new(words: W, len: usize) -> Self {
+        let (super_blocks, blocks) = rank_directory(&words, len);
+        RankVec {
+            words,
+            len,
+            super_blocks,
+            blocks,
+        }
+    }
+}
+
+impl<W: Words, D: Words> RankVec<W, D> {
+    fn get(&self, i: usize) -> u64 {
+        (self.words.word(i / 64) >> (i % 64)) & 1
+    }
+
+    /// The number of ones in `[0, i)`.
+    fn rank1(&self, i: usize) -> usize {
+        debug_assert!(i <= self.len);
+        let block = i / BLOCK_SIZE;
+        let mut rank = self.super_blocks.word(i / SUPER_BLOCK_SIZE)
+            + ((self.blocks.word(block / 4) >> (16 * (block % 4))) & 0xffff);
+        for w in block * BLOCK_SIZE / 64..i / 64 {
+            rank += self.words.word(w).count_ones() as u64;
+        }
+        if !i.is_multiple_of(64) {
+            rank += (self.words.word(i / 64) & ((1 << (i % 64)) - 1)).count_ones() as u64;
+        }
+        rank as usize
+    }
+
+    fn heap_size(&self) -> usize {
+        self.words.heap_size() + self.super_blocks.heap_size() + self.blocks.heap_size()
+    }
+}
+
+/// A wavelet matrix that supports rank but not select.
+///
+/// Leaving out the select structures makes it smaller than the wavelet
+/// matrix used by default. Select is answered by binary search over rank
+/// instead, so the FL-mapping, and with it forward iteration, is slow.
+///
+/// The words of the levels can be kept elsewhere than in memory; only the
+/// rank directories always are.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RankWaveletMatrix<W = Vec<u64>, D = Vec<u64>> {
+    levels: Vec<RankVec<W, D>>,
+    // the number of zeros in each level
+    zeros: Vec<usize>,
+    len: usize,
+}
+
+impl<W: Words> RankWaveletMatrix<W> {
+    /// Assemble a wavelet matrix from the words of its levels, from the
+    /// most significant bit down, of `len` bits each.
+    pub(crate) fn from_levels(levels: Vec<W>, zeros: Vec<usize>, len: usize) -> Self {
+        RankWaveletMatrix {
+            levels: levels
+                .into_iter()
+                .map(|words| RankVec::new(words, len))
+                .collect(),
+            zeros,
+            len,
+        }
+    }
+}
+
+impl<W: Words, D: Words> RankWaveletMatrix<W, D> {
+    /// Assemble a wavelet matrix from the words of its levels together with
+    /// their rank directories, as built by [`rank_directory`].
+    #[cfg(feature = "std")]
+    pub(crate) fn from_parts(levels: Vec<(W, D, D)>, zeros: Vec<usize>, len: usize) -> Self {
+        RankWaveletMatrix {
+            levels: levels
+                .into_iter()
+                .map(|(words, super_blocks, blocks)| RankVec {
+                    words,
+                    len,
+                    super_blocks,
+                    blocks,
+                })
+                .collect(),
+            zeros,
+            len,
+        }
+    }
+
+    fn next(&self, level: usize, i: usize, bit: u64) -> usize {
+        let ones = self.levels[level].rank1(i);
+        if bit == 0 {
+            i - ones
+        } else {
+            self.zeros[level] + ones
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn get(&self, mut i: usize) -> u64 {
+        let mut c = 0;
+        for l in 0..self.levels.len() {
+            let bit = self.levels[l].get(i);
+            c = (c << 1) | bit;
+            i = self.next(l, i, bit);
+        }
+        c
+    }
+
+    /// The number of occurrences of `c` in `[0, i)`.
+    pub(crate) fn rank(&self, i: usize, c: u64) -> usize {
+        let bits = self.levels.len();
+        if bits < 64 && c >> bits != 0 {
+            return 0;
+        }
+        let (mut s, mut e) = (0, i);
+        for l in 0..bits {
+            let bit = (c >> (bits - 1 - l)) & 1;
+            s = self.next(l, s, bit);
+            e = self.next(l, e, bit);
+        }
+        e - s
+    }
+
+    /// The position of the occurrence of `c` with rank `k`.
+    pub(crate) fn select(&self, k: usize, c: u64) -> usize {
+        // the first position where the rank of c exceeds k
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.rank(mid + 1, c) > k {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        lo
+    }
+
+    /// List the distinct symbols in `[s, e)` other than 0 together with
+    /// their number of occurrences, ordered by symbol.
+    pub(crate) fn interval_symbols(&self, s: usize, e: usize) -> Vec<(u64, u64)> {
+        let bits = self.levels.len();
+        let mut result = Vec::new();
+        let mut stack = vec![(0, 0u64, s, e)];
+        while let Some((l, c, s, e)) = stack.pop() {
+            if s == e {
+                continue;
+            }
+            if l == bits {
+                if c != 0 {
+                    result.push((c, (e - s) as u64));
+                }
+                continue;
+            }
+            stack.push((l + 1, c << 1 | 1, self.next(l, s, 1), self.next(l, e, 1)));
+            stack.push((l + 1, c << 1, self.next(l, s, 0), self.next(l, e, 0)));
+        }
+        result
+    }
+
+    pub(crate) fn heap_size(&self) -> usize {
+        self.levels.iter().map(|l| l.heap_size()).sum::<usize>()
+            + self.zeros.capacity() * core::mem::size_of::<usize>()
+    }
+}
+
+/// Split symbols into the levels of a wavelet matrix.
+///
+/// Returns the words of each level, from the most significant bit down,
+/// together with the number of zeros in each level.
+pub(crate) fn wavelet_levels(
+    symbols: &PackedSymbols,
+    alphabet_size: u64,
+) -> (Vec<Vec<u64>>, Vec<usize>) {
+    let bits = (util::log2(alphabet_size - 1) + 1) as usize;
+    let len = symbols.len();
+    let mut levels = Vec::with_capacity(bits);
+    let mut zeros = Vec::with_capacity(bits);
+    let mut next = None;
+    for l in 0..bits {
+        let current: &PackedSymbols = next.as_ref().unwrap_or(symbols);
+        let shift = bits - 1 - l;
+        let mut words = vec![0u64; len.div_ceil(64)];
+        for (i, c) in current.iter().enumerate() {
+            words[i / 64] |= ((c >> shift) & 1) << (i % 64);
+        }
+        let mut zero = PackedSymbols::with_capacity(len, alphabet_size);
+        zero.extend(current.iter().filter(|&c| (c >> shift) & 1 == 0));
+        zeros.push(zero.len());
+        zero.extend(current.iter().filter(|&c| (c >> shift) & 1 == 1));
+        next = Some(zero);
+        levels.push(words);
+    }
+    (levels, zeros)
+}
+
+impl Sequence for RankWaveletMatrix {
+    fn from_symbols(symbols: &PackedSymbols, alphabet_size: u64) -> Self {
+        let (levels, zeros) = wavelet_levels(symbols, alphabet_size);
+        RankWaveletMatrix::from_levels(levels, zeros, symbols.len())
+    }
+
+    #[cfg(all(feature = "std", feature = "serde"))]
+    fn alphabet_size(&self) -> u64 {
+        1 << self.levels.len()
+    }
+
+    fn len(&self) -> usize {
+        RankWaveletMatrix::len(self)
+    }
+
+    fn get(&self, i: usize) -> u64 {
+        RankWaveletMatrix::get(self, i)
+    }
+
+    fn rank(&self, i: usize, c: u64) -> usize {
+        RankWaveletMatrix::rank(self, i, c)
+    }
+
+    fn select(&self, k: usize, c: u64) -> usize {
+        RankWaveletMatrix::select(self, k, c)
+    }
+
+    fn interval_symbols(&self, s: usize, e: usize) -> Vec<(u64, u64)> {
+        RankWaveletMatrix::interval_symbols(self, s, e)
+    }
+
+    fn heap_size(&self) -> usize {
+        RankWaveletMatrix::heap_size(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vers_vecs::WaveletMatrix;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_against_wavelet_matrix() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let symbols = (0..70000)
+            .map(|_| rng.gen_range(0, 11))
+            .collect::<Vec<u64>>();
+        let packed = PackedSymbols::pack(symbols.iter().copied(), 11);
+        let rwm: RankWaveletMatrix = Sequence::from_symbols(&packed, 11);
+        let wm = WaveletMatrix::from_symbols(&packed, 11);
+        for (i, &c) in symbols.iter().enumerate().step_by(3) {
+            assert_eq!(rwm.get(i), c);
+        }
+        for c in 0..12 {
+            for i in (0..=symbols.len()).step_by(997) {
+                assert_eq!(
+                    rwm.rank(i, c),
+                    Sequence::rank(&wm, i, c),
+                    "rank({}, {})",
+                    i,
+                    c
+                );
+            }
+        }
+        for c in 0..11 {
+            let count = Sequence::rank(&wm, symbols.len(), c);
+            for k in (0..count).step_by(101) {
+                assert_eq!(rwm.select(k, c), Sequence::select(&wm, k, c));
+            }
+        }
+        for _ in 0..100 {
+            let s = rng.gen_range(0, symbols.len());
+            let e = rng.gen_range(s, symbols.len() + 1);
+            assert_eq!(rwm.interval_symbols(s, e), wm.interval_symbols(s, e));
+        }
+        assert!(rwm.heap_size() < Sequence::heap_size(&wm));
+    }
+}
@@ -0,0 +1,251 @@
+//! A naive index to check the results of the real ones against.
+use crate::character::{Character, IntoText};
+use crate::frontend::{Search, SearchIndex, SearchIndexWithLocate, SearchWithLocate};
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A naive index, which answers queries by scanning the text.
+///
+/// It has the count, locate and extract queries of the indexes of this
+/// crate, with the same results, and is meant to test them or code built on
+/// them. Queries take time proportional to the length of the text times the
+/// length of the pattern, so use it with small texts. This requires the
+/// `testing` feature.
+///
+/// Like an FM-Index, it treats the text followed by its \0 terminator as
+/// cyclic, so a pattern containing \0 can match across the end of the text.
+/// This only holds for single texts; the texts of a multi-text index are
+/// ordered differently.
+///
+/// The `check_` methods compare the results of an index with those of the
+/// reference, returning a [`Mismatch`] if they differ.
+///
+/// ```
+/// use fm_index::converter::RangeConverter;
+/// use fm_index::{FMIndexWithLocate, ReferenceIndex};
+///
+/// let text = "mississippi".to_string().into_bytes();
+/// let reference = ReferenceIndex::new(text.clone());
+/// let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+///
+/// assert_eq!(reference.locate("ssi"), vec![2, 5]);
+/// for pattern in ["ssi", "p", "ippi", "x"] {
+///     reference.check_locate(&index, pattern).unwrap();
+/// }
+/// reference.check_extract(1, 7, |start, end| index.extract(start, end)).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReferenceIndex<T> {
+    // the text followed by the terminator
+    text: Vec<T>,
+}
+
+impl<T: Character> ReferenceIndex<T> {
+    /// Create a reference index of `text`.
+    ///
+    /// A \0 terminator is added unless the text already ends with one, as
+    /// for the real indexes.
+    pub fn new<'t>(text: impl IntoText<'t, T>) -> Self {
+        let mut text = text.into_text().into_owned();
+        if !text.last().is_some_and(|c| c.is_zero()) {
+            text.push(T::zero());
+        }
+        ReferenceIndex { text }
+    }
+
+    /// The size of the text, including the terminator.
+    pub fn len(&self) -> u64 {
+        self.text.len() as u64
+    }
+
+    /// The length of the text, without the terminator.
+    pub fn text_len(&self) -> u64 {
+        self.len() - 1
+    }
+
+    fn occurs_at(&self, pattern: &[T], p: usize) -> bool {
+        let n = self.text.len();
+        pattern
+            .iter()
+            .enumerate()
+            .all(|(k, &c)| self.text[(p + k) % n] == c)
+    }
+
+    /// The number of occurrences of `pattern`.
+    pub fn count<K: AsRef<[T]>>(&self, pattern: K) -> u64 {
+        self.locate(pattern).len() as u64
+    }
+
+    /// The positions of all occurrences of `pattern`, in increasing order.
+    pub fn locate<K: AsRef<[T]>>(&self, pattern: K) -> Vec<u64> {
+        let pattern = pattern.as_ref();
+        (0..self.text.len())
+            .filter(|&p| self.occurs_at(pattern, p))
+            .map(|p| p as u64)
+            .collect()
+    }
+
+    /// Get the characters at positions `start..end` of the text.
+    pub fn extract(&self, start: u64, end: u64) -> Vec<T> {
+        self.text[start as usize..end as usize].to_vec()
+    }
+
+    fn check_len(&self, len: u64) -> Result<(), Mismatch<T>> {
+        if len != self.len() {
+            return Err(Mismatch::Len {
+                expected: self.len(),
+                actual: len,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that `index` has the same length as the reference, and counts
+    /// as many occurrences of `pattern`.
+    pub fn check_count<I, K>(&self, index: &I, pattern: K) -> Result<(), Mismatch<T>>
+    where
+        I: SearchIndex<T>,
+        K: AsRef<[T]>,
+    {
+        self.check_len(index.len())?;
+        let pattern = pattern.as_ref();
+        let expected = self.count(pattern);
+        let actual = SearchIndex::search(index, pattern).count();
+        if actual != expected {
+            return Err(Mismatch::Count {
+                pattern: pattern.to_vec(),
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that `index` has the same length as the reference, and finds
+    /// the occurrences of `pattern` at the same positions, in any order.
+    pub fn check_locate<I, K>(&self, index: &I, pattern: K) -> Result<(), Mismatch<T>>
+    where
+        I: SearchIndexWithLocate<T>,
+        K: AsRef<[T]>,
+    {
+        self.check_len(index.len())?;
+        let pattern = pattern.as_ref();
+        let expected = self.locate(pattern);
+        let mut actual = SearchIndexWithLocate::search(index, pattern).locate();
+        actual.sort_unstable();
+        if actual != expected {
+            return Err(Mismatch::Locate {
+                pattern: pattern.to_vec(),
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that `extract` returns the characters at positions
+    /// `start..end` of the text.
+    ///
+    /// The indexes have no trait for extracting text, so pass a closure
+    /// calling `extract` on the index, or whatever is to be checked.
+    pub fn check_extract<F>(&self, start: u64, end: u64, extract: F) -> Result<(), Mismatch<T>>
+    where
+        F: FnOnce(u64, u64) -> Vec<T>,
+    {
+        let expected = self.extract(start, end);
+        let actual = extract(start, end);
+        if actual != expected {
+            return Err(Mismatch::Extract {
+                start,
+                end,
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A difference between the results of an index and a [`ReferenceIndex`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch<T> {
+    /// The index has a text of another length.
+    Len {
+        /// The length of the reference, including the terminator.
+        expected: u64,
+        /// The length of the index, including the terminator.
+        actual: u64,
+    },
+    /// The index counts another number of occurrences of a pattern.
+    Count {
+        /// The pattern.
+        pattern: Vec<T>,
+        /// The count of the reference.
+        expected: u64,
+        /// The count of the index.
+        actual: u64,
+    },
+    /// The index finds the occurrences of a pattern at other positions.
+    Locate {
+        /// The pattern.
+        pattern: Vec<T>,
+        /// The positions found by the reference, in increasing order.
+        expected: Vec<u64>,
+        /// The positions found by the index, in increasing order.
+        actual: Vec<u64>,
+    },
+    /// Other characters are extracted from a range of the text.
+    Extract {
+        /// The start of the range.
+        start: u64,
+        /// The end of the range.
+        end: u64,
+        /// The characters of the reference.
+        expected: Vec<T>,
+        /// The characters extracted.
+        actual: Vec<T>,
+    },
+}
+
+impl<T: Character> fmt::Display for Mismatch<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Len { expected, actual } => write!(
+                f,
+                "the index has length {}, but the reference has length {}",
+                actual, expected
+            ),
+            Mismatch::Count {
+                pattern,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "the index counts {} occurrences of {:?}, but the reference counts {}",
+                actual, pattern, expected
+            ),
+            Mismatch::Locate {
+                pattern,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "the index locates {:?} at {:?}, but the reference locates it at {:?}",
+                pattern, actual, expected
+            ),
+            Mismatch::Extract {
+                start,
+                end,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{:?} was extracted from {}..{}, but the reference has {:?}",
+                actual, start, end, expected
+            ),
+        }
+    }
+}
+
+impl<T: Character> core::error::Error for Mismatch<T> {}
@@ -1,19 +1,29 @@
-use crate::backend::{HasPosition, HeapSize, SearchIndexBackend};
+use crate::backend::{HasPosition, HeapSize, SearchIndexBackend, SizeBreakdown};
 use crate::character::{prepare_text, Character};
 #[cfg(doc)]
 use crate::converter;
 use crate::converter::Converter;
+#[cfg(all(feature = "std", feature = "serde"))]
+use crate::persist::impl_sections;
+use crate::sequence::{PackedSymbols, Sequence};
+use crate::suffix_array::run_sample::RunSampledArray;
 use crate::suffix_array::sais;
-use crate::suffix_array::sample::SuffixOrderSampledArray;
-use crate::util;
+use crate::suffix_array::sample::SuffixArraySamples;
 
+use alloc::{borrow::Cow, vec, vec::Vec};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use vers_vecs::{BitVec, RsVec, WaveletMatrix};
 
 /// A Run-Length FM-index.
 ///
-/// This can be more space-efficient than the FM-index, but is slower.
-#[derive(Serialize, Deserialize)]
+/// The BWT is stored as runs: the run heads are kept in a wavelet matrix
+/// and the run lengths in two bit vectors, one in BWT order and one with the
+/// runs sorted by character. Space is proportional to the number of runs
+/// rather than the text length, so this can be much more space-efficient
+/// than the FM-index on repetitive texts, but is slower.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RLFMIndexBackend<T, C, S> {
     converter: C,
     suffix_array: S,
@@ -22,7 +32,33 @@ pub struct RLFMIndexBackend<T, C, S> {
     bp: RsVec,
     cs: Vec<u64>,
     len: u64,
-    _t: std::marker::PhantomData<T>,
+    _t: core::marker::PhantomData<T>,
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl_sections!(RLFMIndexBackend<T, C, S> {
+    converter: C,
+    #[load(Deferrable)]
+    #[part(Samples)]
+    suffix_array: S,
+    #[encode(Symbols)]
+    s: WaveletMatrix,
+    b: RsVec,
+    bp: RsVec,
+    cs: Vec<u64>,
+    len: u64,
+});
+
+impl<T, C: PartialEq, S: PartialEq> PartialEq for RLFMIndexBackend<T, C, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.converter == other.converter
+            && self.suffix_array == other.suffix_array
+            && self.s.same_symbols(&other.s)
+            && self.b == other.b
+            && self.bp == other.bp
+            && self.cs == other.cs
+            && self.len == other.len
+    }
 }
 
 impl<T, C, S> RLFMIndexBackend<T, C, S>
@@ -30,8 +66,12 @@ where
     T: Character,
     C: Converter<T>,
 {
-    pub(crate) fn new(text: Vec<T>, converter: C, get_sample: impl Fn(&[u64]) -> S) -> Self {
-        let text = prepare_text(text);
+    pub(crate) fn new(
+        text: Cow<'_, [T]>,
+        converter: C,
+        get_sample: impl Fn(&[T], &[u64]) -> S,
+    ) -> Self {
+        let text = prepare_text(text, &converter);
 
         let n = text.len();
         let m = converter.len();
@@ -39,7 +79,7 @@ where
 
         let mut c0 = T::zero();
         // sequence of run heads
-        let mut s = Vec::new();
+        let mut s = PackedSymbols::with_capacity(0, m);
         // sequence of run lengths
         // run length `l` is encoded as 10^{l-1}
         let mut b = BitVec::new();
@@ -50,7 +90,7 @@ where
             // We do not allow consecutive occurrences of zeroes,
             // so text[sa[0] - 1] = text[n - 2] is not zero.
             if c0 != c {
-                s.push(c);
+                s.push(c.into());
                 b.append(true);
                 runs_by_char[c.into() as usize].push(1);
             } else {
@@ -62,8 +102,7 @@ where
             }
             c0 = c;
         }
-        let s: Vec<u64> = s.into_iter().map(|c| c.into()).collect();
-        let s = WaveletMatrix::from_slice(&s, (util::log2(m - 1) + 1) as u16);
+        let s = WaveletMatrix::from_symbols(&s, m);
         let mut bp = BitVec::new();
         let mut cs = vec![0u64; m as usize];
         let mut c = 0;
@@ -82,13 +121,36 @@ where
         let bp = RsVec::from_bit_vec(bp);
         RLFMIndexBackend {
             converter,
-            suffix_array: get_sample(&sa),
+            suffix_array: get_sample(&text, &sa),
             s,
             b,
             bp,
             cs,
             len: n as u64,
-            _t: std::marker::PhantomData::<T>,
+            _t: core::marker::PhantomData::<T>,
+        }
+    }
+}
+
+impl<T, C, S> RLFMIndexBackend<T, C, S>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    // The number of occurrences of the converted character `c` in `L[..i)`.
+    fn rank(&self, c: T, i: u64) -> u64 {
+        let start = self.bp.select1(self.cs[c.into() as usize] as usize) as u64;
+        self.lf_map_converted(c, i) - start
+    }
+
+    fn lf_map_converted(&self, c: T, i: u64) -> u64 {
+        let j = self.b.rank1(i as usize);
+        let nr = self.s.rank_u64_unchecked(j, c.into());
+        if self.get_l(i) != c {
+            self.bp.select1(self.cs[c.into() as usize] as usize + nr) as u64
+        } else {
+            self.bp.select1(self.cs[c.into() as usize] as usize + nr) as u64 + i
+                - self.b.select1(j) as u64
         }
     }
 }
@@ -99,24 +161,51 @@ where
     C: Converter<T>,
 {
     fn heap_size(&self) -> usize {
-        self.s.heap_size()
-            + self.b.heap_size()
-            + self.bp.heap_size()
-            + self.cs.capacity() * std::mem::size_of::<u64>()
+        self.size_breakdown().total()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new()
+            .add("bwt", self.s.heap_size())
+            .add("runs", self.b.heap_size() + self.bp.heap_size())
+            .add("cs", self.cs.capacity() * core::mem::size_of::<u64>())
     }
 }
 
-impl<T, C> HeapSize for RLFMIndexBackend<T, C, SuffixOrderSampledArray>
+impl<T, C, S> HeapSize for RLFMIndexBackend<T, C, S>
 where
     T: Character,
     C: Converter<T>,
+    S: SuffixArraySamples,
 {
     fn heap_size(&self) -> usize {
-        self.s.heap_size()
-            + self.b.heap_size()
-            + self.bp.heap_size()
-            + self.cs.capacity() * std::mem::size_of::<u64>()
-            + self.suffix_array.size()
+        self.size_breakdown().total()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new()
+            .add("bwt", self.s.heap_size())
+            .add("runs", self.b.heap_size() + self.bp.heap_size())
+            .add("cs", self.cs.capacity() * core::mem::size_of::<u64>())
+            .add("suffix_array", self.suffix_array.size())
+    }
+}
+
+impl<T, C> HeapSize for RLFMIndexBackend<T, C, RunSampledArray>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    fn heap_size(&self) -> usize {
+        self.size_breakdown().total()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new()
+            .add("bwt", self.s.heap_size())
+            .add("runs", self.b.heap_size() + self.bp.heap_size())
+            .add("cs", self.cs.capacity() * core::mem::size_of::<u64>())
+            .add("suffix_array", self.suffix_array.size())
     }
 }
 
@@ -146,15 +235,7 @@ where
     }
 
     fn lf_map2(&self, c: T, i: u64) -> u64 {
-        let c = self.converter.convert(c);
-        let j = self.b.rank1(i as usize);
-        let nr = self.s.rank_u64_unchecked(j, c.into());
-        if self.get_l(i) != c {
-            self.bp.select1(self.cs[c.into() as usize] as usize + nr) as u64
-        } else {
-            self.bp.select1(self.cs[c.into() as usize] as usize + nr) as u64 + i
-                - self.b.select1(j) as u64
-        }
+        self.lf_map_converted(self.converter.convert(c), i)
     }
 
     fn get_f(&self, i: u64) -> Self::T {
@@ -183,22 +264,37 @@ where
         n + i - p
     }
 
+    fn interval_symbols(&self, s: u64, e: u64) -> Vec<(Self::T, u64)> {
+        // the run heads covering L[s..e)
+        let range = self.b.rank1(s as usize + 1) - 1..self.b.rank1(e as usize);
+        let mut result = Vec::new();
+        let mut c = 1;
+        while let Some(d) = self.s.successor_u64(range.clone(), c) {
+            let d = T::from_u64(d);
+            let count = self.rank(d, e) - self.rank(d, s);
+            result.push((d, count));
+            c = d.into() + 1;
+        }
+        result
+    }
+
     fn get_converter(&self) -> &Self::C {
         &self.converter
     }
 }
 
-impl<T, C> HasPosition for RLFMIndexBackend<T, C, SuffixOrderSampledArray>
+impl<T, C, S> HasPosition for RLFMIndexBackend<T, C, S>
 where
     T: Character,
     C: Converter<T>,
+    S: SuffixArraySamples,
 {
-    fn get_sa(&self, mut i: u64) -> u64 {
+    fn get_sa_with_steps(&self, mut i: u64) -> (u64, u64) {
         let mut steps = 0;
         loop {
             match self.suffix_array.get(i) {
                 Some(sa) => {
-                    return (sa + steps) % self.len();
+                    return ((sa + steps) % self.len(), steps);
                 }
                 None => {
                     i = self.lf_map(i);
@@ -207,6 +303,92 @@ where
             }
         }
     }
+
+    fn get_isa_sample(&self, p: u64) -> (u64, u64) {
+        self.suffix_array.get_inverse(p)
+    }
+}
+
+impl<T, C> HasPosition for RLFMIndexBackend<T, C, RunSampledArray>
+where
+    T: Character,
+    C: Converter<T>,
+{
+    fn get_sa_with_steps(&self, mut i: u64) -> (u64, u64) {
+        // walk the LF-mapping until the end of a run, where SA is sampled
+        let mut steps = 0;
+        loop {
+            let k = self.b.rank1(i as usize + 1) - 1;
+            if i + 1 == self.len || self.b.get_unchecked(i as usize + 1) == 1 {
+                return ((self.suffix_array.end(k) + steps) % self.len, steps);
+            }
+            i = self.lf_map(i);
+            steps += 1;
+        }
+    }
+
+    fn get_isa_sample(&self, p: u64) -> (u64, u64) {
+        self.suffix_array.get_inverse(p)
+    }
+
+    fn get_sa_range(&self, s: u64, e: u64, pattern: &[T]) -> Vec<u64> {
+        let mut result = vec![0; (e - s) as usize];
+        let mut r = result.len();
+        self.fold_sa(s, e, pattern, (), |(), p| {
+            r -= 1;
+            result[r] = p;
+        });
+        result
+    }
+
+    fn fold_sa<A, F>(&self, s: u64, e: u64, pattern: &[T], init: A, mut f: F) -> A
+    where
+        F: FnMut(A, u64) -> A,
+    {
+        if s == e {
+            return init;
+        }
+        let n = self.len;
+        // Repeat the backward search, keeping track of the last suffix array
+        // value of the interval (the "toehold").
+        let mut ps = 0;
+        let mut pe = n;
+        let mut last = self.suffix_array.end(self.b.rank1(n as usize) - 1);
+        for &c in pattern.iter().rev() {
+            let c = self.converter.convert(c);
+            if self.get_l(pe - 1) != c {
+                // the last occurrence of c in the interval ends a run
+                let j = self.b.rank1(pe as usize);
+                let nr = self.s.rank_u64_unchecked(j, c.into());
+                let k = self.s.select_u64_unchecked(nr - 1, c.into());
+                last = self.suffix_array.end(k);
+            }
+            last = (last + n - 1) % n;
+            ps = self.lf_map_converted(c, ps);
+            pe = self.lf_map_converted(c, pe);
+        }
+        debug_assert_eq!((ps, pe), (s, e));
+
+        // Φ walks up from the toehold to the start of the interval.
+        let mut acc = init;
+        let mut p = last;
+        for _ in s..e {
+            acc = f(acc, p);
+            if p + 1 < n {
+                p = self.suffix_array.phi(p);
+            }
+        }
+        acc
+    }
+
+    // Φ walks the interval in order, so there is nothing to parallelize.
+    #[cfg(feature = "rayon")]
+    fn get_sa_range_parallel(&self, s: u64, e: u64, pattern: &[T]) -> Vec<u64>
+    where
+        Self: Sync,
+    {
+        self.get_sa_range(s, e, pattern)
+    }
 }
 
 #[cfg(test)]
@@ -217,7 +399,7 @@ mod tests {
     #[test]
     fn test_s() {
         let text = "mississippi".to_string().into_bytes();
-        let rlfmi = RLFMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |_| ());
+        let rlfmi = RLFMIndexBackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_, _| ());
         let ans = "ipsm\0pisi".to_string().into_bytes();
         for (i, a) in ans.into_iter().enumerate() {
             let l: u8 = rlfmi.s.get_u64_unchecked(i) as u8;
@@ -228,7 +410,7 @@ mod tests {
     #[test]
     fn test_b() {
         let text = "mississippi".to_string().into_bytes();
-        let rlfmi = RLFMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |_| ());
+        let rlfmi = RLFMIndexBackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_, _| ());
         let n = rlfmi.len();
         let ans = vec![1, 1, 1, 0, 1, 1, 1, 1, 1, 0, 1, 0];
         // l:      ipssm$pissii
@@ -251,7 +433,7 @@ mod tests {
     #[test]
     fn test_bp() {
         let text = "mississippi".to_string().into_bytes();
-        let rlfmi = RLFMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |_| ());
+        let rlfmi = RLFMIndexBackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_, _| ());
         let n = rlfmi.len();
         let ans = vec![1, 1, 1, 1, 0, 1, 1, 1, 1, 0, 1, 0];
         assert_eq!(n as usize, rlfmi.bp.len());
@@ -268,7 +450,7 @@ mod tests {
     #[test]
     fn test_cs() {
         let text = "mississippi".to_string().into_bytes();
-        let rlfmi = RLFMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |_| ());
+        let rlfmi = RLFMIndexBackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_, _| ());
         let ans = vec![(b'\0', 0), (b'i', 1), (b'm', 4), (b'p', 5), (b's', 7)];
         for (c, a) in ans {
             let c = rlfmi.converter.convert(c) as usize;
@@ -279,7 +461,7 @@ mod tests {
     #[test]
     fn test_get_l() {
         let text = "mississippi".to_string().into_bytes();
-        let rlfmi = RLFMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |_| ());
+        let rlfmi = RLFMIndexBackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_, _| ());
         let ans = "ipssm\0pissii".to_string().into_bytes();
 
         for (i, a) in ans.into_iter().enumerate() {
@@ -292,7 +474,7 @@ mod tests {
     fn test_lf_map() {
         let text = "mississippi".to_string().into_bytes();
         let ans = vec![1, 6, 7, 2, 8, 10, 3, 9, 11, 4, 5, 0];
-        let rlfmi = RLFMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |_| ());
+        let rlfmi = RLFMIndexBackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_, _| ());
 
         let mut i = 0;
         for a in ans {
@@ -312,7 +494,7 @@ mod tests {
             (b'p', (6, 8)),
             (b's', (8, 12)),
         ];
-        let rlfmi = RLFMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |_| ());
+        let rlfmi = RLFMIndexBackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_, _| ());
         let n = rlfmi.len();
 
         for (c, r) in ans {
@@ -338,7 +520,7 @@ mod tests {
             ("si", (8, 10)),
             ("ssi", (10, 12)),
         ];
-        let rlfmi = RLFMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |_| ());
+        let rlfmi = RLFMIndexBackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_, _| ());
 
         let wrapper = SearchIndexWrapper::new(rlfmi);
 
@@ -353,7 +535,7 @@ mod tests {
         let mut ans = text.clone();
         ans.push(0);
         ans.sort();
-        let rlfmi = RLFMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |_| ());
+        let rlfmi = RLFMIndexBackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_, _| ());
 
         for (i, a) in ans.into_iter().enumerate() {
             let f = rlfmi.get_f(i as u64);
@@ -364,7 +546,7 @@ mod tests {
     #[test]
     fn test_fl_map() {
         let text = "mississippi".to_string().into_bytes();
-        let rlfmi = RLFMIndexBackend::new(text, RangeConverter::new(b'a', b'z'), |_| ());
+        let rlfmi = RLFMIndexBackend::new(text.into(), RangeConverter::new(b'a', b'z'), |_, _| ());
         let cases = vec![5u64, 0, 7, 10, 11, 4, 1, 6, 2, 3, 8, 9];
         for (i, expected) in cases.into_iter().enumerate() {
             let actual = rlfmi.fl_map(i as u64);
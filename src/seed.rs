@@ -0,0 +1,61 @@
+//! Seeds of a query sequence, for aligners to extend.
+use crate::snapshot::SearchSnapshot;
+
+use core::ops::Range;
+
+/// A substring of a query that occurs in the text, found by `seeds`.
+///
+/// This is where an aligner starts extending an alignment of the query:
+/// the substring at [`query_range`](Self::query_range) of the query matches
+/// the text at each position in the interval [`range`](Self::range) of the
+/// suffix array. To list those positions, turn the anchor into a search
+/// result with [`snapshot`](Self::snapshot) and `resume` on the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Anchor {
+    query_offset: usize,
+    query_len: usize,
+    range: (u64, u64),
+}
+
+impl Anchor {
+    pub(crate) fn new(query_offset: usize, query_len: usize, range: (u64, u64)) -> Self {
+        Anchor {
+            query_offset,
+            query_len,
+            range,
+        }
+    }
+
+    /// The offset in the query at which the anchor starts.
+    pub fn query_offset(&self) -> usize {
+        self.query_offset
+    }
+
+    /// The number of characters of the query the anchor covers.
+    pub fn query_len(&self) -> usize {
+        self.query_len
+    }
+
+    /// The part of the query the anchor covers.
+    pub fn query_range(&self) -> Range<usize> {
+        self.query_offset..self.query_offset + self.query_len
+    }
+
+    /// The interval `[s, e)` of the suffix array matching the anchor.
+    pub fn range(&self) -> (u64, u64) {
+        self.range
+    }
+
+    /// The number of occurrences of the anchor in the text.
+    pub fn count(&self) -> u64 {
+        self.range.1 - self.range.0
+    }
+
+    /// A snapshot of the search result for the anchor, which `resume` on
+    /// the index turns into a search result, without searching again.
+    ///
+    /// `query` must be the query the anchor was found in.
+    pub fn snapshot<T: Clone>(&self, query: &[T]) -> SearchSnapshot<T> {
+        SearchSnapshot::new(query[self.query_range()].to_vec(), self.range)
+    }
+}
@@ -0,0 +1,239 @@
+use crate::util;
+
+use alloc::vec::Vec;
+use vers_vecs::{BitVec, WaveletMatrix};
+
+/// Symbols packed into the fewest bits that hold the alphabet, from which a
+/// [`Sequence`] is built.
+///
+/// Collecting the symbols of a BWT in a `Vec<u64>` would take eight times
+/// the memory of a text of bytes during construction; packed, the symbols of
+/// a DNA text take three bits each.
+#[derive(Clone)]
+pub(crate) struct PackedSymbols {
+    bits: BitVec,
+    width: usize,
+    len: usize,
+}
+
+// The number of bits each symbol of the alphabet `0..alphabet_size` is
+// packed into.
+fn symbol_width(alphabet_size: u64) -> usize {
+    (util::log2(alphabet_size.max(2) - 1) + 1) as usize
+}
+
+impl PackedSymbols {
+    /// An empty sequence over the alphabet `0..alphabet_size`, with room for
+    /// `capacity` symbols.
+    pub(crate) fn with_capacity(capacity: usize, alphabet_size: u64) -> Self {
+        let width = symbol_width(alphabet_size);
+        PackedSymbols {
+            bits: BitVec::with_capacity(capacity * width),
+            width,
+            len: 0,
+        }
+    }
+
+    /// Pack `symbols`, which are in the alphabet `0..alphabet_size`.
+    pub(crate) fn pack(symbols: impl IntoIterator<Item = u64>, alphabet_size: u64) -> Self {
+        let symbols = symbols.into_iter();
+        let mut packed = Self::with_capacity(symbols.size_hint().0, alphabet_size);
+        packed.extend(symbols);
+        packed
+    }
+
+    pub(crate) fn push(&mut self, c: u64) {
+        debug_assert!(c >> self.width == 0, "{} doesn't fit in the alphabet", c);
+        self.bits.append_bits(c, self.width);
+        self.len += 1;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn get(&self, i: usize) -> u64 {
+        self.bits.get_bits_unchecked(i * self.width, self.width)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    /// The `len` symbols over the alphabet `0..alphabet_size` packed into
+    /// `words` by [`packed_words`].
+    ///
+    /// Returns `None` if there are too few or too many words, or a symbol
+    /// isn't in the alphabet.
+    #[cfg(all(feature = "std", feature = "serde"))]
+    pub(crate) fn from_words(words: Vec<u64>, len: usize, alphabet_size: u64) -> Option<Self> {
+        let width = symbol_width(alphabet_size);
+        let used = len.checked_mul(width)?;
+        if words.len() != used.div_ceil(64) {
+            return None;
+        }
+        let mut bits = BitVec::from_vec(words);
+        bits.drop_last(bits.len() - used);
+        let symbols = PackedSymbols { bits, width, len };
+        if symbols.iter().all(|c| c < alphabet_size) {
+            Some(symbols)
+        } else {
+            None
+        }
+    }
+}
+
+/// The symbols of `sequence`, packed as in [`PackedSymbols`] into words of
+/// 64 bits, the first symbol in the least significant bits of the first
+/// word.
+#[cfg(all(feature = "std", feature = "serde"))]
+pub(crate) fn packed_words<S: Sequence>(sequence: &S) -> impl ExactSizeIterator<Item = u64> + '_ {
+    let width = symbol_width(sequence.alphabet_size());
+    let used = sequence.len() * width;
+    (0..used.div_ceil(64)).map(move |w| {
+        let start = w * 64;
+        let end = used.min(start + 64);
+        let mut word = 0;
+        let mut i = start / width;
+        while i * width < end {
+            let c = sequence.get(i);
+            // the symbol may start in the previous word or end in the next
+            if i * width < start {
+                word |= c >> (start - i * width);
+            } else {
+                word |= c << (i * width - start);
+            }
+            i += 1;
+        }
+        word
+    })
+}
+
+impl Extend<u64> for PackedSymbols {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, symbols: I) {
+        for c in symbols {
+            self.push(c);
+        }
+    }
+}
+
+/// A sequence of symbols supporting rank and select, used to store the BWT.
+pub(crate) trait Sequence: Sized {
+    /// Build the sequence over the alphabet `0..alphabet_size`, from symbols
+    /// packed for that alphabet.
+    fn from_symbols(symbols: &PackedSymbols, alphabet_size: u64) -> Self;
+
+    /// The size of an alphabet that `from_symbols` builds this sequence
+    /// for, such as the one it was built with.
+    #[cfg(all(feature = "std", feature = "serde"))]
+    fn alphabet_size(&self) -> u64;
+
+    fn len(&self) -> usize;
+
+    fn get(&self, i: usize) -> u64;
+
+    /// The number of occurrences of `c` in `[0, i)`.
+    fn rank(&self, i: usize, c: u64) -> usize;
+
+    /// The position of the occurrence of `c` with rank `k`.
+    fn select(&self, k: usize, c: u64) -> usize;
+
+    /// Hint that `get` and `rank` at position `i` are needed soon, so the
+    /// memory they read can be fetched in the meantime.
+    fn prefetch(&self, _i: usize) {}
+
+    /// List the distinct symbols in `[s, e)` other than 0 together with
+    /// their number of occurrences, ordered by symbol.
+    fn interval_symbols(&self, s: usize, e: usize) -> Vec<(u64, u64)>;
+
+    fn heap_size(&self) -> usize;
+
+    /// Whether `other` holds the same symbols, for comparing indexes.
+    fn same_symbols(&self, other: &Self) -> bool {
+        self.len() == other.len() && (0..self.len()).all(|i| self.get(i) == other.get(i))
+    }
+}
+
+impl Sequence for WaveletMatrix {
+    fn from_symbols(symbols: &PackedSymbols, _alphabet_size: u64) -> Self {
+        // the symbols are packed in as many bits as the levels of the matrix
+        WaveletMatrix::from_bit_vec(&symbols.bits, symbols.width as u16)
+    }
+
+    #[cfg(all(feature = "std", feature = "serde"))]
+    fn alphabet_size(&self) -> u64 {
+        1 << self.bits_per_element()
+    }
+
+    fn len(&self) -> usize {
+        WaveletMatrix::len(self)
+    }
+
+    fn get(&self, i: usize) -> u64 {
+        self.get_u64_unchecked(i)
+    }
+
+    fn rank(&self, i: usize, c: u64) -> usize {
+        self.rank_u64_unchecked(i, c)
+    }
+
+    fn select(&self, k: usize, c: u64) -> usize {
+        self.select_u64_unchecked(k, c)
+    }
+
+    fn interval_symbols(&self, s: usize, e: usize) -> Vec<(u64, u64)> {
+        let range = s..e;
+        let mut result = Vec::new();
+        let mut c = 1;
+        while let Some(d) = self.successor_u64(range.clone(), c) {
+            let count = self.rank_range_u64_unchecked(range.clone(), d) as u64;
+            result.push((d, count));
+            c = d + 1;
+        }
+        result
+    }
+
+    fn heap_size(&self) -> usize {
+        WaveletMatrix::heap_size(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_symbols() {
+        let symbols = (0..1000).map(|i| (i * 7) % 5).collect::<Vec<u64>>();
+        let packed = PackedSymbols::pack(symbols.iter().copied(), 5);
+        assert_eq!(packed.len(), symbols.len());
+        assert_eq!(packed.iter().collect::<Vec<_>>(), symbols);
+        assert_eq!(packed.bits.len(), 3 * symbols.len());
+
+        let wm = WaveletMatrix::from_symbols(&packed, 5);
+        for (i, &c) in symbols.iter().enumerate() {
+            assert_eq!(Sequence::get(&wm, i), c);
+        }
+    }
+
+    #[cfg(all(feature = "std", feature = "serde"))]
+    #[test]
+    fn test_packed_words() {
+        // widths that do and don't divide 64
+        for alphabet_size in [2, 5, 17, 300] {
+            let symbols = (0..1000)
+                .map(|i| (i * 7) % alphabet_size)
+                .collect::<Vec<u64>>();
+            let packed = PackedSymbols::pack(symbols.iter().copied(), alphabet_size);
+            let wm = WaveletMatrix::from_symbols(&packed, alphabet_size);
+            let words = packed_words(&wm).collect::<Vec<_>>();
+            assert_eq!(words, packed.bits.iter_limbs().collect::<Vec<_>>());
+
+            let unpacked = PackedSymbols::from_words(words.clone(), 1000, alphabet_size).unwrap();
+            assert_eq!(unpacked.iter().collect::<Vec<_>>(), symbols);
+            assert!(PackedSymbols::from_words(words, 1064, alphabet_size).is_none());
+        }
+        // a symbol outside the alphabet
+        assert!(PackedSymbols::from_words(vec![0b111], 2, 5).is_none());
+    }
+}
@@ -0,0 +1,174 @@
+//! Searching several indexes as one.
+use crate::backend::{HeapSize, SizeBreakdown};
+use crate::frontend::{Search, SearchIndex, SearchIndexWithLocate, SearchWithLocate};
+use alloc::vec::Vec;
+
+/// An index made of several independently built indexes, the shards.
+///
+/// The texts of the shards are treated as if they were concatenated in
+/// order, each followed by its terminator, so a position in shard `s` is
+/// offset by the sum of the lengths of the shards before it. This allows a
+/// corpus to be indexed in partitions that are each small enough to build.
+///
+/// Searches run over every shard. The `_parallel` variants of the queries
+/// search the shards on separate threads.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShardedIndex<T, I> {
+    shards: Vec<I>,
+    // the position at which each shard starts
+    offsets: Vec<u64>,
+    _t: core::marker::PhantomData<T>,
+}
+
+impl<T, I> ShardedIndex<T, I>
+where
+    I: SearchIndex<T>,
+{
+    /// Combine `shards`, in order, into a single index.
+    pub fn new(shards: Vec<I>) -> Self {
+        let offsets = shards
+            .iter()
+            .scan(0, |offset, shard| {
+                let start = *offset;
+                *offset += shard.len();
+                Some(start)
+            })
+            .collect();
+        ShardedIndex {
+            shards,
+            offsets,
+            _t: core::marker::PhantomData,
+        }
+    }
+
+    /// The shards, in order.
+    pub fn shards(&self) -> &[I] {
+        &self.shards
+    }
+
+    /// The position at which shard `s` starts.
+    pub fn shard_offset(&self, s: usize) -> u64 {
+        self.offsets[s]
+    }
+
+    /// Split a position into its shard and the position within that shard.
+    pub fn shard_position(&self, pos: u64) -> (usize, u64) {
+        debug_assert!(pos < self.len(), "{} is out of range", pos);
+        let s = self.offsets.partition_point(|&offset| offset <= pos) - 1;
+        (s, pos - self.offsets[s])
+    }
+
+    /// The total size of the texts in the shards, including the terminator
+    /// of each.
+    pub fn len(&self) -> u64 {
+        self.shards.iter().map(|shard| shard.len()).sum()
+    }
+
+    /// Count the number of occurrences of `pattern` in all shards.
+    pub fn count<K: AsRef<[T]>>(&self, pattern: K) -> u64 {
+        let pattern = pattern.as_ref();
+        self.shards
+            .iter()
+            .map(|shard| SearchIndex::search(shard, pattern).count())
+            .sum()
+    }
+}
+
+impl<T, I> ShardedIndex<T, I>
+where
+    I: SearchIndexWithLocate<T>,
+{
+    /// List the positions of all occurrences of `pattern`.
+    ///
+    /// Positions are offset by the start of their shard, and ordered by
+    /// shard.
+    pub fn locate<K: AsRef<[T]>>(&self, pattern: K) -> Vec<u64> {
+        let pattern = pattern.as_ref();
+        self.shards
+            .iter()
+            .zip(&self.offsets)
+            .flat_map(|(shard, &offset)| {
+                let positions = SearchIndexWithLocate::search(shard, pattern).locate();
+                positions.into_iter().map(move |pos| offset + pos)
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, I> ShardedIndex<T, I>
+where
+    T: Sync,
+    I: SearchIndex<T> + Sync,
+{
+    /// Count the number of occurrences of `pattern`, searching each shard on
+    /// its own thread.
+    pub fn count_parallel<K: AsRef<[T]>>(&self, pattern: K) -> u64 {
+        let pattern = pattern.as_ref();
+        std::thread::scope(|scope| {
+            let handles = self
+                .shards
+                .iter()
+                .map(|shard| scope.spawn(move || SearchIndex::search(shard, pattern).count()))
+                .collect::<Vec<_>>();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, I> ShardedIndex<T, I>
+where
+    T: Sync,
+    I: SearchIndexWithLocate<T> + Sync,
+{
+    /// List the positions of all occurrences of `pattern`, searching each
+    /// shard on its own thread.
+    ///
+    /// The result is the same as that of [`locate`](Self::locate).
+    pub fn locate_parallel<K: AsRef<[T]>>(&self, pattern: K) -> Vec<u64> {
+        let pattern = pattern.as_ref();
+        std::thread::scope(|scope| {
+            let handles = self
+                .shards
+                .iter()
+                .zip(&self.offsets)
+                .map(|(shard, &offset)| {
+                    scope.spawn(move || {
+                        let positions = SearchIndexWithLocate::search(shard, pattern).locate();
+                        positions
+                            .into_iter()
+                            .map(|pos| offset + pos)
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap())
+                .collect()
+        })
+    }
+}
+
+impl<T, I: HeapSize> HeapSize for ShardedIndex<T, I> {
+    fn heap_size(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.heap_size())
+            .sum::<usize>()
+            + self.offsets.capacity() * core::mem::size_of::<u64>()
+    }
+
+    fn size_breakdown(&self) -> SizeBreakdown {
+        self.shards
+            .iter()
+            .fold(SizeBreakdown::new(), |breakdown, shard| {
+                breakdown.merge(shard.size_breakdown())
+            })
+            .add(
+                "offsets",
+                self.offsets.capacity() * core::mem::size_of::<u64>(),
+            )
+    }
+}
@@ -0,0 +1,151 @@
+//! Snapshots of search results that can be stored and shipped.
+use alloc::{sync::Arc, vec::Vec};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The state of a search result, detached from the index.
+///
+/// A snapshot holds the pattern and the interval of the suffix array
+/// matching it, so it can be serialized, cached or sent to another process
+/// and turned back into a search result with `resume` on the same index,
+/// without searching again. Take one with `snapshot` on a search result.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SearchSnapshot<T> {
+    pattern: Vec<T>,
+    range: (u64, u64),
+}
+
+impl<T> SearchSnapshot<T> {
+    pub(crate) fn new(pattern: Vec<T>, range: (u64, u64)) -> Self {
+        SearchSnapshot { pattern, range }
+    }
+
+    /// The pattern that was searched for.
+    pub fn pattern(&self) -> &[T] {
+        &self.pattern
+    }
+
+    /// The interval `[s, e)` of the suffix array matching the pattern.
+    pub fn range(&self) -> (u64, u64) {
+        self.range
+    }
+}
+
+/// A search result that owns a reference to its index.
+///
+/// Search results borrow the index they come from, so they can't outlive
+/// the scope it's borrowed in. An owned search holds the index in an
+/// [`Arc`] together with a [`SearchSnapshot`] of the result instead, so it
+/// can be returned from functions, moved into other threads or tasks and
+/// kept around as long as needed. Create one with `search_owned` on an
+/// index in an `Arc`, and query it through the search result returned by
+/// `get`.
+pub struct OwnedSearch<I, T> {
+    index: Arc<I>,
+    snapshot: SearchSnapshot<T>,
+}
+
+impl<I, T> OwnedSearch<I, T> {
+    pub(crate) fn new(index: Arc<I>, snapshot: SearchSnapshot<T>) -> Self {
+        OwnedSearch { index, snapshot }
+    }
+
+    /// The index searched.
+    pub fn index(&self) -> &Arc<I> {
+        &self.index
+    }
+
+    /// The snapshot of the search result.
+    pub fn snapshot(&self) -> &SearchSnapshot<T> {
+        &self.snapshot
+    }
+
+    /// The pattern that was searched for.
+    pub fn pattern(&self) -> &[T] {
+        self.snapshot.pattern()
+    }
+
+    /// Detach the search result from the index, which can be bound to it
+    /// again with `resume` or `resume_owned`.
+    pub fn into_snapshot(self) -> SearchSnapshot<T> {
+        self.snapshot
+    }
+}
+
+impl<I, T: Clone> Clone for OwnedSearch<I, T> {
+    fn clone(&self) -> Self {
+        OwnedSearch {
+            index: Arc::clone(&self.index),
+            snapshot: self.snapshot.clone(),
+        }
+    }
+}
+
+impl<I, T: core::fmt::Debug> core::fmt::Debug for OwnedSearch<I, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OwnedSearch")
+            .field("snapshot", &self.snapshot)
+            .finish()
+    }
+}
+
+/// Implement `search_owned` and `resume_owned` for an index type, and `get`
+/// and `search` for its owned search results. `$st` is the type of the
+/// search results borrowing the index.
+macro_rules! impl_owned_search {
+    ($t:ty, $st:ty $(, $g:ident $(: $b:path)?)*) => {
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $t {
+            /// Search for a pattern in the text, returning a search result
+            /// that holds on to the index instead of borrowing it.
+            pub fn search_owned<K>(
+                self: &alloc::sync::Arc<Self>,
+                pattern: K,
+            ) -> $crate::snapshot::OwnedSearch<Self, T>
+            where
+                K: AsRef<[T]>,
+            {
+                let snapshot = self.search(pattern).snapshot();
+                $crate::snapshot::OwnedSearch::new(alloc::sync::Arc::clone(self), snapshot)
+            }
+
+            /// Resume a search result from a [`SearchSnapshot`] taken on
+            /// this index like `resume`, holding on to the index instead of
+            /// borrowing it.
+            ///
+            /// # Panics
+            ///
+            /// Panics if the snapshot's interval is out of range for this
+            /// index.
+            pub fn resume_owned(
+                self: &alloc::sync::Arc<Self>,
+                snapshot: SearchSnapshot<T>,
+            ) -> $crate::snapshot::OwnedSearch<Self, T> {
+                // check that the snapshot fits this index
+                self.resume(&snapshot);
+                $crate::snapshot::OwnedSearch::new(alloc::sync::Arc::clone(self), snapshot)
+            }
+        }
+
+        impl<T: Character, C: Converter<T> $(, $g $(: $b)?)*> $crate::snapshot::OwnedSearch<$t, T> {
+            /// The search result, borrowing the index from this owned
+            /// search.
+            pub fn get(&self) -> $st {
+                self.index().resume(self.snapshot())
+            }
+
+            /// Search in the current search result, refining it.
+            ///
+            /// This adds a prefix `pattern` to the existing pattern, and
+            /// looks for those expanded patterns in the text.
+            pub fn search<K>(&self, pattern: K) -> Self
+            where
+                K: AsRef<[T]>,
+            {
+                let snapshot = self.get().search(pattern).snapshot();
+                $crate::snapshot::OwnedSearch::new(alloc::sync::Arc::clone(self.index()), snapshot)
+            }
+        }
+    };
+}
+pub(crate) use impl_owned_search;
@@ -0,0 +1,130 @@
+//! Statistics about the text in an index.
+use crate::backend::SearchIndexBackend;
+use crate::character::Character;
+use crate::converter::Converter;
+use alloc::{vec, vec::Vec};
+
+/// Statistics about the text in an index and its alphabet.
+///
+/// These help to choose a converter, a sampling level and a backend for a
+/// text: a small alphabet suits the DNA index, and a BWT with few runs
+/// compared to its length suits the run-length encoded index. The order-k
+/// entropy of the text is a lower bound on the bits per character any
+/// compressor that looks at `k` characters of context can achieve.
+///
+/// Terminator characters are not counted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexStats<T> {
+    text_len: u64,
+    alphabet_size: u64,
+    frequencies: Vec<(T, u64)>,
+    runs: u64,
+    entropies: Vec<f64>,
+}
+
+impl<T: Character> IndexStats<T> {
+    /// The highest order for which the entropy is computed.
+    pub const MAX_ORDER: usize = 4;
+
+    pub(crate) fn new<B>(backend: &B) -> Self
+    where
+        B: SearchIndexBackend<T = T>,
+    {
+        let converter = backend.get_converter();
+        let n = backend.len();
+
+        let frequencies = backend
+            .interval_symbols(0, n)
+            .into_iter()
+            .map(|(c, count)| (converter.convert_inv(c), count))
+            .collect::<Vec<_>>();
+        let text_len = frequencies.iter().map(|&(_, count)| count).sum::<u64>();
+
+        let mut runs = 0;
+        let mut previous = None;
+        for i in 0..n {
+            let c = backend.get_l(i);
+            if !c.is_zero() && previous != Some(c) {
+                runs += 1;
+            }
+            previous = Some(c);
+        }
+
+        // The suffix array interval of each context of k characters holds
+        // the characters preceding that context in the BWT. The order-k
+        // entropy is the order-0 entropy of those characters, weighted by
+        // their number and summed over all contexts.
+        let mut entropies = Vec::with_capacity(Self::MAX_ORDER + 1);
+        let mut intervals = vec![(0, n)];
+        for k in 0..=Self::MAX_ORDER {
+            let mut bits = 0.0;
+            let mut next = Vec::new();
+            for &(s, e) in &intervals {
+                let symbols = backend.interval_symbols(s, e);
+                bits += weighted_entropy(&symbols);
+                if k < Self::MAX_ORDER {
+                    for (c, _) in symbols {
+                        let c = converter.convert_inv(c);
+                        next.push((backend.lf_map2(c, s), backend.lf_map2(c, e)));
+                    }
+                }
+            }
+            entropies.push(if text_len == 0 {
+                0.0
+            } else {
+                bits / text_len as f64
+            });
+            intervals = next;
+        }
+
+        IndexStats {
+            text_len,
+            alphabet_size: converter.len(),
+            frequencies,
+            runs,
+            entropies,
+        }
+    }
+
+    /// The number of characters in the text, not counting terminators.
+    pub fn text_len(&self) -> u64 {
+        self.text_len
+    }
+
+    /// The size of the converter's alphabet, including the terminator.
+    pub fn alphabet_size(&self) -> u64 {
+        self.alphabet_size
+    }
+
+    /// The distinct characters that occur in the text, with their number of
+    /// occurrences, ordered by their code in the converter.
+    pub fn frequencies(&self) -> &[(T, u64)] {
+        &self.frequencies
+    }
+
+    /// The number of runs of equal characters in the Burrows-Wheeler
+    /// transform.
+    pub fn runs(&self) -> u64 {
+        self.runs
+    }
+
+    /// The empirical order-`k` entropy of the text, in bits per character.
+    ///
+    /// Returns `None` if `k` is larger than [`MAX_ORDER`](Self::MAX_ORDER).
+    /// Contexts that run into the end of the text are not counted.
+    pub fn entropy(&self, k: usize) -> Option<f64> {
+        self.entropies.get(k).copied()
+    }
+}
+
+// The number of characters times their order-0 entropy.
+fn weighted_entropy<T>(symbols: &[(T, u64)]) -> f64 {
+    let total = symbols.iter().map(|&(_, count)| count).sum::<u64>() as f64;
+    symbols
+        .iter()
+        .map(|&(_, count)| {
+            let count = count as f64;
+            count * (total / count).log2()
+        })
+        .sum()
+}
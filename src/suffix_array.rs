@@ -2,5 +2,6 @@
 //!
 //! Can also be used in sampled fashion to perform locate queries.
 
+pub mod run_sample;
 pub mod sais;
 pub mod sample;
@@ -0,0 +1,181 @@
+//! Suffix array samples at BWT run boundaries, as used by the r-index.
+//!
+//! Gagie, T., Navarro, G., & Prezza, N. (2020). Fully functional suffix trees
+//! and optimal text searching in BWT-runs bounded space. Journal of the ACM,
+//! 67(1).
+use crate::character::Character;
+use crate::suffix_array::sample::Deferrable;
+use crate::util;
+
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use vers_vecs::{BitVec, EliasFanoVec};
+
+/// Suffix array samples taken at the boundaries of the runs of the BWT.
+///
+/// The space used is proportional to the number of runs `r` rather than the
+/// length of the text. The suffix array value at the end of every run is
+/// stored, which is enough to keep track of one suffix array value while
+/// searching. The remaining values of a search result are recovered with the
+/// function `Φ(SA[i]) = SA[i - 1]`, which is stored at the text positions
+/// of the run starts.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RunSampledArray {
+    word_size: usize,
+    // SA value at the end of each run, indexed by run
+    ends: BitVec,
+    // text positions of the run starts, together with position 0
+    starts: EliasFanoVec,
+    // Φ at each entry of `starts`
+    phi: BitVec,
+    // ISA at each entry of `starts`
+    isa: BitVec,
+    len: usize,
+}
+
+impl PartialEq for RunSampledArray {
+    fn eq(&self, other: &Self) -> bool {
+        self.word_size == other.word_size
+            && self.ends == other.ends
+            && util::elias_fano_eq(&self.starts, &other.starts)
+            && self.phi == other.phi
+            && self.isa == other.isa
+            && self.len == other.len
+    }
+}
+
+impl Deferrable for RunSampledArray {}
+
+impl RunSampledArray {
+    /// The suffix array value at the end of run `k`.
+    pub(crate) fn end(&self, k: usize) -> u64 {
+        self.ends
+            .get_bits_unchecked(k * self.word_size, self.word_size)
+    }
+
+    /// `Φ(p) = SA[ISA[p] - 1]`, for any text position but the terminator.
+    pub(crate) fn phi(&self, p: u64) -> u64 {
+        debug_assert!(p + 1 < self.len as u64, "{} is out of range", p);
+        let j = self.starts.rank(p + 1) as usize - 1;
+        let q = self.starts.get_unchecked(j);
+        self.phi
+            .get_bits_unchecked(j * self.word_size, self.word_size)
+            + (p - q)
+    }
+
+    /// Find the nearest inverse suffix array sample at or after text
+    /// position `p`, where `p <= len`.
+    ///
+    /// Returns the sampled position `q` together with `ISA[q]`. Position
+    /// `len` stands for position 0, as the text is treated as cyclic.
+    pub(crate) fn get_inverse(&self, p: u64) -> (u64, u64) {
+        debug_assert!(p <= self.len as u64);
+        if self.starts.is_empty() {
+            // the text only consists of the terminator
+            return (self.len as u64, 0);
+        }
+        let j = self.starts.rank(p) as usize;
+        if j < self.starts.len() {
+            (self.starts.get_unchecked(j), self.get_isa(j))
+        } else {
+            // position 0 is always sampled
+            (self.len as u64, self.get_isa(0))
+        }
+    }
+
+    fn get_isa(&self, j: usize) -> u64 {
+        self.isa
+            .get_bits_unchecked(j * self.word_size, self.word_size)
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        self.ends.heap_size()
+            + self.starts.heap_size()
+            + self.phi.heap_size()
+            + self.isa.heap_size()
+    }
+}
+
+pub(crate) fn sample<T: Character>(text: &[T], sa: &[u64]) -> RunSampledArray {
+    let n = sa.len();
+    debug_assert_eq!(text.len(), n);
+    let word_size = (util::log2(n as u64) + 1) as usize;
+    let l = |i: usize| {
+        let k = sa[i] as usize;
+        if k > 0 {
+            text[k - 1]
+        } else {
+            text[n - 1]
+        }
+    };
+
+    let mut ends = BitVec::new();
+    // (text position, Φ, ISA) at each run start but the first
+    let mut starts = Vec::new();
+    let mut has_zero = false;
+    for i in 0..n {
+        if i + 1 == n || l(i) != l(i + 1) {
+            ends.append_bits(sa[i], word_size);
+        }
+        if i > 0 && l(i - 1) != l(i) {
+            starts.push((sa[i], sa[i - 1], i as u64));
+            has_zero |= sa[i] == 0;
+        }
+    }
+    // Φ can only be derived from a sample at or before the position asked
+    // for, so make sure position 0 is always sampled.
+    if !has_zero && n > 1 {
+        let i = sa.iter().position(|&p| p == 0).unwrap();
+        starts.push((0, sa[i - 1], i as u64));
+    }
+    starts.sort_unstable();
+
+    let mut phi = BitVec::with_capacity(starts.len() * word_size);
+    let mut isa = BitVec::with_capacity(starts.len() * word_size);
+    for &(_, f, i) in &starts {
+        phi.append_bits(f, word_size);
+        isa.append_bits(i, word_size);
+    }
+    let starts = starts.into_iter().map(|(p, _, _)| p).collect::<Vec<_>>();
+
+    RunSampledArray {
+        word_size,
+        ends,
+        starts: EliasFanoVec::from_slice(&starts),
+        phi,
+        isa,
+        len: n,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::RangeConverter;
+    use crate::suffix_array::sais;
+
+    #[test]
+    fn test_phi() {
+        let text = "mississippi\0".to_string().into_bytes();
+        let sa = sais::build_suffix_array(&text, &RangeConverter::new(b'a', b'z'));
+        let rsa = sample(&text, &sa);
+        for i in 1..sa.len() {
+            assert_eq!(rsa.phi(sa[i]), sa[i - 1], "phi({})", sa[i]);
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        let text = "mississippi\0".to_string().into_bytes();
+        let sa = sais::build_suffix_array(&text, &RangeConverter::new(b'a', b'z'));
+        let isa = vec![5, 4, 11, 9, 3, 10, 8, 2, 7, 6, 1, 0];
+        let rsa = sample(&text, &sa);
+        for p in 0..=sa.len() as u64 {
+            let (q, i) = rsa.get_inverse(p);
+            assert!(p <= q);
+            assert_eq!(i, isa[q as usize % sa.len()], "p {}", p);
+        }
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
     converter::{Converter, IdConverter},
     Character,
 };
+use alloc::{vec, vec::Vec};
 
 pub fn count_chars<T, C, K>(text: K, converter: &C) -> Vec<u64>
 where
@@ -44,10 +45,13 @@ pub fn get_bucket_end_pos(occs: &[u64]) -> Vec<u64> {
     buckets
 }
 
-fn get_types<T, K>(text: K) -> (BitVec, Vec<usize>)
+// The characters are compared after conversion, so the suffix array is
+// sorted in the converter's order, which may differ from that of `T`.
+fn get_types<T, C, K>(text: K, converter: &C) -> (BitVec, Vec<usize>)
 where
-    T: Copy + Clone + Ord,
+    T: Character,
     K: AsRef<[T]>,
+    C: Converter<T>,
 {
     let text = text.as_ref();
     let n = text.len();
@@ -61,13 +65,15 @@ where
 
     let mut lms = vec![n - 1];
     let mut prev_is_s_type = false;
+    let mut next = converter.convert(text[n - 1]);
     for i in (0..(n - 1)).rev() {
+        let c = converter.convert(text[i]);
         // text[i] is S-type if either holds:
         //     - text[i] <  text[i + 1]
         //     - text[i] == text[i + 1] and text[i + 1] is S-type.
         // Otherwise, text[i] is L-type.
         // Notably, text[i] is S-type if text[i] is zero in a multi-text.
-        let is_s_type = text[i] < text[i + 1] || (text[i] == text[i + 1] && prev_is_s_type);
+        let is_s_type = c < next || (c == next && prev_is_s_type);
         if is_s_type {
             types.set(i, 1).unwrap();
         } else if prev_is_s_type {
@@ -75,6 +81,7 @@ where
             lms.push(i + 1);
         }
         prev_is_s_type = is_s_type;
+        next = c;
     }
     (types, lms)
 }
@@ -161,7 +168,7 @@ where
     let text = text.as_ref();
 
     let n = text.len();
-    let (types, lms) = get_types(text);
+    let (types, lms) = get_types(text, converter);
     let lms_len = lms.len();
     let occs = count_chars(text, converter);
 
@@ -218,14 +225,17 @@ where
                 let q = sa_lms[i] as usize;
                 let mut d = 1;
                 // Zeros must be considered different characters.
+                // Characters are compared after conversion, as converters
+                // may map different characters to the same one.
+                let char_at = |i: usize| converter.convert(text[i]);
                 let mut same = text[p].into() != 0
                     && text[p].into() != 0
-                    && text[p] == text[q]
+                    && char_at(p) == char_at(q)
                     && types.is_bit_set(p) == types.is_bit_set(q);
                 while same {
                     if text[p + d].into() == 0
                         || text[q + d].into() == 0
-                        || text[p + d] != text[q + d]
+                        || char_at(p + d) != char_at(q + d)
                         || types.is_bit_set(p + d) != types.is_bit_set(q + d)
                     {
                         same = false;
@@ -295,7 +305,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::converter::RangeConverter;
+    use crate::converter::{CaseFoldConverter, RangeConverter};
     use num_traits::Zero;
     use rand::rngs::StdRng;
     use rand::{Rng, SeedableRng};
@@ -316,7 +326,7 @@ mod tests {
         let n = text.len();
         let types_expected = "LLSSLLSSLLSSLLLLS";
         let lms_expected = marks_to_lms("  *   *   *     *");
-        let (types, lms) = get_types(text);
+        let (types, lms) = get_types(text, &IdConverter::new::<u8>());
         let types_actual = (0..n)
             .map(|i| {
                 if types.is_bit_set(i).unwrap() {
@@ -337,7 +347,7 @@ mod tests {
         let n = text.len();
         let types_expected = "LSSLS".to_string();
         let lms_expected = marks_to_lms(" *  *");
-        let (types, lms) = get_types(text);
+        let (types, lms) = get_types(text, &IdConverter::new::<u8>());
         let types_actual = (0..n)
             .map(|i| {
                 if types.is_bit_set(i).unwrap() {
@@ -398,7 +408,7 @@ mod tests {
     #[should_panic]
     fn test_sais_too_many_trailing_zero() {
         let text = "toomanyzeros\0\0".to_string().into_bytes();
-        let converter = IdConverter::with_size(std::mem::size_of::<u8>() as u64);
+        let converter = IdConverter::with_size(core::mem::size_of::<u8>() as u64);
         build_suffix_array(&text, &converter);
     }
 
@@ -459,7 +469,7 @@ mod tests {
         let ans = build_expected_suffix_array(text);
 
         assert_eq!(sa.len(), ans.len());
-        for (i, (actual, expected)) in sa.into_iter().zip(ans.into_iter()).enumerate() {
+        for (i, (actual, expected)) in sa.into_iter().zip(ans).enumerate() {
             assert_eq!(
                 actual, expected,
                 "wrong at {}-th pos: expected {}, but actual {}",
@@ -478,7 +488,7 @@ mod tests {
         let sa = build_suffix_array(&text, &converter);
         let ans = build_expected_suffix_array(&text);
         assert_eq!(sa.len(), ans.len());
-        for (i, (actual, expected)) in sa.into_iter().zip(ans.into_iter()).enumerate() {
+        for (i, (actual, expected)) in sa.into_iter().zip(ans).enumerate() {
             assert_eq!(
                 actual, expected,
                 "wrong at {}-th pos: expected {}, but actual {}",
@@ -487,6 +497,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sais_rand_case_fold() {
+        let len = 10000;
+        let mut rng: StdRng = SeedableRng::from_seed([0; 32]);
+        let text = build_text(
+            || {
+                let c = rng.gen::<u8>() % 5 + b'a';
+                if rng.gen_bool(0.5) {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            },
+            len,
+        );
+
+        // upper and lower case letters are the same character
+        let converter = CaseFoldConverter::new(RangeConverter::new(b'a', b'e'));
+        let sa = build_suffix_array(&text, &converter);
+        let ans = build_expected_suffix_array(text.to_ascii_lowercase());
+        assert_eq!(sa, ans);
+    }
+
     #[test]
     fn test_sais_rand_binary() {
         let len = 10000;
@@ -498,7 +531,7 @@ mod tests {
         let sa = build_suffix_array(&text, &converter);
         let ans = build_expected_suffix_array(&text);
         assert_eq!(sa.len(), ans.len());
-        for (i, (actual, expected)) in sa.into_iter().zip(ans.into_iter()).enumerate() {
+        for (i, (actual, expected)) in sa.into_iter().zip(ans).enumerate() {
             assert_eq!(
                 actual, expected,
                 "wrong at {}-th pos: expected {}, but actual {}",
@@ -518,7 +551,7 @@ mod tests {
         let ans = build_expected_suffix_array(&text);
         assert_eq!(sa, ans);
         assert_eq!(sa.len(), ans.len());
-        for (i, (actual, expected)) in sa.into_iter().zip(ans.into_iter()).enumerate() {
+        for (i, (actual, expected)) in sa.into_iter().zip(ans).enumerate() {
             assert_eq!(
                 actual, expected,
                 "wrong at {}-th pos: expected {}, but actual {}",
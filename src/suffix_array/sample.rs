@@ -1,36 +1,378 @@
 //! Sampled suffix arrays to perform locate queries.
+use crate::error::Error;
+#[cfg(all(feature = "std", feature = "serde"))]
+pub(crate) use crate::persist::Deferrable;
+#[cfg(all(feature = "std", feature = "serde"))]
+use crate::persist::{DeferredSection, Part, SectionReader};
 use crate::util;
-use std::fmt;
+use alloc::{vec, vec::Vec};
+use core::fmt;
+#[cfg(all(feature = "std", feature = "serde"))]
+use std::io::{self, Read};
+#[cfg(all(feature = "std", feature = "serde"))]
+use std::sync::OnceLock;
 
+#[cfg(all(feature = "std", feature = "serde"))]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use vers_vecs::BitVec;
+#[cfg(all(feature = "std", feature = "serde"))]
+use serde::{Deserializer, Serializer};
+use vers_vecs::{BitVec, RsVec};
 
-/// A sampled suffix array, stored within the index.
-#[derive(Serialize, Deserialize)]
+/// Access to the samples of a sampled suffix array.
+///
+/// This is not exported, which keeps [`SamplingStrategy`] sealed.
+pub trait SuffixArraySamples: Deferrable {
+    /// Sample the suffix array `sa` at level `level`, which is the sampling
+    /// rate `2^level`.
+    fn sample(sa: &[u64], level: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self::sample_rate(sa, 1 << level)
+    }
+
+    /// Sample the suffix array `sa`, keeping one in every `rate` entries.
+    fn sample_rate(sa: &[u64], rate: u64) -> Self
+    where
+        Self: Sized;
+
+    /// The suffix array value at row `i`, if it was sampled.
+    fn get(&self, i: u64) -> Option<u64>;
+
+    /// Find the nearest inverse suffix array sample at or after text
+    /// position `p`, where `p <= len`.
+    ///
+    /// Returns the sampled position `q` together with `ISA[q]`. Position
+    /// `len` stands for position 0, as the text is treated as cyclic.
+    fn get_inverse(&self, p: u64) -> (u64, u64);
+
+    /// The sampling rate this array was built with.
+    fn rate(&self) -> u64;
+
+    /// The sampling level this array was built with, which is the base 2
+    /// logarithm of the rate, rounded down if the rate is not a power of two.
+    fn level(&self) -> usize {
+        util::log2(self.rate()) as usize
+    }
+
+    /// The heap size of the samples.
+    fn size(&self) -> usize;
+}
+
+/// A component whose loading can be deferred until it's first used.
+///
+/// Without `std`, indexes aren't loaded from files, so this only reports
+/// the sampling level.
+#[cfg(not(all(feature = "std", feature = "serde")))]
+pub trait Deferrable {
+    /// The level the component sampled the suffix array at, if any.
+    fn sampling_level(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// A strategy for sampling the suffix array to support locate queries.
+///
+/// The indexes with locate support take the strategy as a type parameter,
+/// which defaults to [`SuffixOrderSampledArray`]. This trait is sealed.
+pub trait SamplingStrategy: SuffixArraySamples {}
+
+/// A suffix array sampled at regular rows.
+///
+/// Every `k`-th entry of the suffix array is stored, where `k` is the
+/// sampling rate, which takes no extra space to find, but the number of LF
+/// steps needed to reach a sample is not bounded.
+///
+/// Alongside the suffix array samples, the inverse suffix array is sampled
+/// at every text position that is a multiple of the sampling rate.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SuffixOrderSampledArray {
-    level: usize,
+    rate: u64,
+    word_size: usize,
+    sa: BitVec,
+    isa: BitVec,
+    len: usize,
+}
+
+impl SuffixArraySamples for SuffixOrderSampledArray {
+    fn sample_rate(sa: &[u64], rate: u64) -> Self {
+        sample_rate(sa, rate)
+    }
+
+    fn get(&self, i: u64) -> Option<u64> {
+        debug_assert!(i < self.len as u64);
+        if i.is_multiple_of(self.rate) {
+            Some(
+                self.sa
+                    .get_bits_unchecked((i / self.rate) as usize * self.word_size, self.word_size),
+            )
+        } else {
+            None
+        }
+    }
+
+    fn get_inverse(&self, p: u64) -> (u64, u64) {
+        get_inverse(&self.isa, self.rate, self.word_size, self.len, p)
+    }
+
+    fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    fn size(&self) -> usize {
+        self.sa.heap_size() + self.isa.heap_size()
+    }
+}
+
+impl SamplingStrategy for SuffixOrderSampledArray {}
+
+impl Deferrable for SuffixOrderSampledArray {
+    fn sampling_level(&self) -> Option<usize> {
+        Some(self.level())
+    }
+}
+
+/// A suffix array sampled at regular text positions.
+///
+/// The entries of the suffix array that are a multiple of the sampling rate
+/// `k` are stored, together with a bit vector marking the rows they are at.
+/// This takes a little more space than [`SuffixOrderSampledArray`], but a
+/// sample is always reached within `k` LF steps, which bounds the time to
+/// locate a single occurrence.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextOrderSampledArray {
+    rate: u64,
     word_size: usize,
+    // the rows whose suffix array value is sampled
+    marked: RsVec,
+    // SA[i] / rate at each marked row
     sa: BitVec,
+    isa: BitVec,
     len: usize,
 }
 
-impl SuffixOrderSampledArray {
-    pub(crate) fn get(&self, i: u64) -> Option<u64> {
+impl SuffixArraySamples for TextOrderSampledArray {
+    fn sample_rate(sa: &[u64], rate: u64) -> Self {
+        let n = sa.len();
+        let word_size = (util::log2(n as u64) + 1) as usize;
+        debug_assert!(n > 0);
+        debug_assert!(rate > 0, "the sampling rate must be positive");
+        let mut marked = BitVec::with_capacity(n);
+        let mut sa_samples = BitVec::with_capacity((n - 1) / rate as usize + 1);
+        for &p in sa {
+            let is_sampled = p.is_multiple_of(rate);
+            marked.append_bit(is_sampled as u64);
+            if is_sampled {
+                sa_samples.append_bits(p / rate, word_size);
+            }
+        }
+        TextOrderSampledArray {
+            rate,
+            word_size,
+            marked: RsVec::from_bit_vec(marked),
+            sa: sa_samples,
+            isa: sample_inverse(sa, rate, word_size),
+            len: n,
+        }
+    }
+
+    fn get(&self, i: u64) -> Option<u64> {
         debug_assert!(i < self.len as u64);
-        if i & ((1 << self.level) - 1) == 0 {
+        if self.marked.get_unchecked(i as usize) == 1 {
+            let j = self.marked.rank1(i as usize);
             Some(
-                self.sa.get_bits_unchecked(
-                    (i as usize >> self.level) * self.word_size,
-                    self.word_size,
-                ),
+                self.sa
+                    .get_bits_unchecked(j * self.word_size, self.word_size)
+                    * self.rate,
             )
         } else {
             None
         }
     }
 
-    pub(crate) fn size(&self) -> usize {
-        self.sa.heap_size()
+    fn get_inverse(&self, p: u64) -> (u64, u64) {
+        get_inverse(&self.isa, self.rate, self.word_size, self.len, p)
+    }
+
+    fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    fn size(&self) -> usize {
+        self.marked.heap_size() + self.sa.heap_size() + self.isa.heap_size()
+    }
+}
+
+impl SamplingStrategy for TextOrderSampledArray {}
+
+impl Deferrable for TextOrderSampledArray {
+    fn sampling_level(&self) -> Option<usize> {
+        Some(self.level())
+    }
+}
+
+/// Suffix array samples that are loaded when first used.
+///
+/// An index with this sampling strategy that is loaded from a file with
+/// `load_file` leaves its suffix array samples in the file until the first
+/// query that needs them, such as `locate`. Counting works right away, so
+/// an index that is mostly used to count loads faster and takes less
+/// memory. The samples are read with the strategy `S`, which must be the
+/// one the index was saved with; it defaults to
+/// [`SuffixOrderSampledArray`].
+///
+/// Indexes loaded from a reader, or saved compressed, load their samples
+/// right away. Building or saving an index also loads them.
+///
+/// # Panics
+///
+/// The query that loads the samples panics if they can't be read, such as
+/// when the file was removed or changed after the index was loaded.
+#[cfg(all(feature = "std", feature = "serde"))]
+#[derive(Clone, Debug)]
+pub struct LazySamples<S = SuffixOrderSampledArray> {
+    samples: OnceLock<S>,
+    // where to load the samples from, if they aren't loaded yet
+    deferred: Option<DeferredSection>,
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl<S: DeserializeOwned> LazySamples<S> {
+    fn samples(&self) -> &S {
+        self.samples.get_or_init(|| {
+            let deferred = self
+                .deferred
+                .as_ref()
+                .expect("the samples are either loaded or deferred");
+            deferred
+                .load()
+                .unwrap_or_else(|err| panic!("failed to load the suffix array samples: {}", err))
+        })
+    }
+}
+
+// Deferred samples are loaded to be compared.
+#[cfg(all(feature = "std", feature = "serde"))]
+impl<S: PartialEq + DeserializeOwned> PartialEq for LazySamples<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.samples() == other.samples()
+    }
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl<S> From<S> for LazySamples<S> {
+    fn from(samples: S) -> Self {
+        LazySamples {
+            samples: OnceLock::from(samples),
+            deferred: None,
+        }
+    }
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl<S: SuffixArraySamples + DeserializeOwned> SuffixArraySamples for LazySamples<S> {
+    fn sample_rate(sa: &[u64], rate: u64) -> Self {
+        S::sample_rate(sa, rate).into()
+    }
+
+    fn get(&self, i: u64) -> Option<u64> {
+        self.samples().get(i)
+    }
+
+    fn get_inverse(&self, p: u64) -> (u64, u64) {
+        self.samples().get_inverse(p)
+    }
+
+    fn rate(&self) -> u64 {
+        self.samples().rate()
+    }
+
+    fn size(&self) -> usize {
+        // samples left in the file take no memory
+        self.samples.get().map_or(0, S::size)
+    }
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl<S: SamplingStrategy + DeserializeOwned> SamplingStrategy for LazySamples<S> {}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl<S: SuffixArraySamples + DeserializeOwned> Deferrable for LazySamples<S> {
+    fn load_deferrable<R: Read>(input: &mut SectionReader<R>, part: Part) -> io::Result<Self> {
+        match input.defer(part)? {
+            Some(deferred) => Ok(LazySamples {
+                samples: OnceLock::new(),
+                deferred: Some(deferred),
+            }),
+            None => input.read_part(part),
+        }
+    }
+
+    fn sampling_level(&self) -> Option<usize> {
+        Some(self.level())
+    }
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl<S: Serialize + DeserializeOwned> Serialize for LazySamples<S> {
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        self.samples().serialize(serializer)
+    }
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl<'de, S: Deserialize<'de>> Deserialize<'de> for LazySamples<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        S::deserialize(deserializer).map(LazySamples::from)
+    }
+}
+
+// The inverse suffix array at every text position that is a multiple of
+// `rate`.
+pub(crate) fn inverse_samples(sa: &[u64], rate: u64) -> Vec<u64> {
+    let mut isa = vec![0; (sa.len() - 1) / rate as usize + 1];
+    for (i, &p) in sa.iter().enumerate() {
+        if p.is_multiple_of(rate) {
+            isa[(p / rate) as usize] = i as u64;
+        }
+    }
+    isa
+}
+
+fn sample_inverse(sa: &[u64], rate: u64, word_size: usize) -> BitVec {
+    let isa = inverse_samples(sa, rate);
+    let mut isa_samples = BitVec::with_capacity(isa.len() * word_size);
+    for i in isa {
+        isa_samples.append_bits(i, word_size);
+    }
+    isa_samples
+}
+
+fn get_inverse(isa: &BitVec, rate: u64, word_size: usize, len: usize, p: u64) -> (u64, u64) {
+    nearest_inverse(rate, len, p, |j| {
+        isa.get_bits_unchecked(j * word_size, word_size)
+    })
+}
+
+// The nearest inverse suffix array sample at or after `p`, given access to
+// the samples by their index.
+pub(crate) fn nearest_inverse(
+    rate: u64,
+    len: usize,
+    p: u64,
+    get_isa: impl Fn(usize) -> u64,
+) -> (u64, u64) {
+    debug_assert!(p <= len as u64);
+    let j = p.div_ceil(rate);
+    let q = j * rate;
+    if q >= len as u64 {
+        (len as u64, get_isa(0))
+    } else {
+        (q, get_isa(j as usize))
     }
 }
 
@@ -46,26 +388,36 @@ impl fmt::Debug for SuffixOrderSampledArray {
     }
 }
 
+// Check that a suffix array of `len` entries can be sampled at `level`,
+// which needs `2^level < len`.
+pub(crate) fn check_level(len: u64, level: usize) -> Result<(), Error> {
+    match u32::try_from(level).ok().and_then(|l| 1u64.checked_shl(l)) {
+        Some(rate) if rate < len => Ok(()),
+        _ => Err(Error::InvalidLevel { level, len }),
+    }
+}
+
 pub(crate) fn sample(sa: &[u64], level: usize) -> SuffixOrderSampledArray {
+    if let Err(err) = check_level(sa.len() as u64, level) {
+        panic!("{}", err);
+    }
+    sample_rate(sa, 1 << level)
+}
+
+pub(crate) fn sample_rate(sa: &[u64], rate: u64) -> SuffixOrderSampledArray {
     let n = sa.len();
     let word_size = (util::log2(n as u64) + 1) as usize;
     debug_assert!(n > 0);
-    debug_assert!(
-        n > (1 << level),
-        "sampling level L must satisfy 2^L < text_len (L = {}, text_len = {})",
-        level,
-        n,
-    );
-    let sa_samples_len = ((n - 1) >> level) + 1;
-    let mut sa_samples = BitVec::with_capacity(sa_samples_len);
-    // fid::BitArray::with_word_size(word_size, sa_samples_len);
-    for i in 0..sa_samples_len {
-        sa_samples.append_bits(sa[i << level], word_size);
+    debug_assert!(rate > 0, "the sampling rate must be positive");
+    let mut sa_samples = BitVec::with_capacity((n - 1) / rate as usize + 1);
+    for &p in sa.iter().step_by(rate as usize) {
+        sa_samples.append_bits(p, word_size);
     }
     SuffixOrderSampledArray {
-        level,
+        rate,
         word_size,
         sa: sa_samples,
+        isa: sample_inverse(sa, rate, word_size),
         len: sa.len(),
     }
 }
@@ -99,4 +451,56 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_rate() {
+        let sa = vec![11, 10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2];
+        let isa = vec![5, 4, 11, 9, 3, 10, 8, 2, 7, 6, 1, 0];
+        for rate in [1, 3, 5, 11] {
+            let ssa = sample_rate(&sa, rate);
+            let tsa = TextOrderSampledArray::sample_rate(&sa, rate);
+            assert_eq!(ssa.rate(), rate);
+            for (i, &p) in sa.iter().enumerate() {
+                let expected = (i as u64).is_multiple_of(rate).then_some(p);
+                assert_eq!(ssa.get(i as u64), expected, "rate {}, ssa[{}]", rate, i);
+                let expected = p.is_multiple_of(rate).then_some(p);
+                assert_eq!(tsa.get(i as u64), expected, "rate {}, tsa[{}]", rate, i);
+            }
+            for p in 0..=sa.len() as u64 {
+                let (q, i) = ssa.get_inverse(p);
+                assert!(p <= q && q - p < rate);
+                assert_eq!(i, isa[q as usize % sa.len()], "rate {}, p {}", rate, p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        let sa = vec![11, 10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2];
+        let isa = vec![5, 4, 11, 9, 3, 10, 8, 2, 7, 6, 1, 0];
+        for level in 0..3 {
+            let ssa = sample(&sa, level);
+            for p in 0..=sa.len() as u64 {
+                let (q, i) = ssa.get_inverse(p);
+                assert!(p <= q && q - p <= 1 << level);
+                assert_eq!(i, isa[q as usize % sa.len()], "level {}, p {}", level, p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_text_order() {
+        let sa = vec![11, 10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2];
+        for level in 0..3 {
+            let tsa = TextOrderSampledArray::sample(&sa, level);
+            for (i, &p) in sa.iter().enumerate() {
+                let v = tsa.get(i as u64);
+                if p & ((1 << level) - 1) == 0 {
+                    assert_eq!(v, Some(p), "level {}, tsa[{}]", level, i);
+                } else {
+                    assert_eq!(v, None, "level {}, tsa[{}]", level, i);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,243 @@
+//! Compressed suffix trees, emulated on top of an index with locate support.
+//!
+//! Sadakane, K. (2007). Compressed suffix trees with full functionality.
+//! Theory of Computing Systems, 41(4), 589–607.
+use crate::backend::HeapSize;
+use crate::bp::BalancedParens;
+use crate::lcp::LcpArray;
+use crate::util;
+
+use alloc::{vec, vec::Vec};
+use vers_vecs::{BitVec, RsVec};
+
+/// Access to the index a suffix tree is built on.
+pub(crate) trait TreeSource<T> {
+    fn len(&self) -> u64;
+    fn get_sa(&self, i: u64) -> u64;
+    /// The position in the suffix array of the suffix following `SA[i]`.
+    fn psi(&self, i: u64) -> u64;
+    fn char_at(&self, pos: u64) -> T;
+}
+
+/// A node of a [`SuffixTree`].
+///
+/// Nodes are only meaningful for the tree they were obtained from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Node(usize);
+
+/// A suffix tree of the text of an index.
+///
+/// The tree topology is stored as a sequence of balanced parentheses,
+/// together with the string depth of every internal node. The leaves are
+/// the suffixes of the text, in suffix array order, and everything else is
+/// derived from the index, which this borrows.
+///
+/// The path label of a node is the string spelled out from the root to the
+/// node. Its length is the string depth of the node. The terminator ends
+/// the path label of every leaf.
+pub struct SuffixTree<'a, T> {
+    source: &'a dyn TreeSource<T>,
+    bp: BalancedParens,
+    // marks the opening parentheses of the leaves
+    leaves: RsVec,
+    word_size: usize,
+    // the string depths of the internal nodes, in preorder
+    depths: BitVec,
+}
+
+enum Child {
+    Leaf,
+    Internal(usize),
+}
+
+impl<'a, T: PartialEq> SuffixTree<'a, T> {
+    pub(crate) fn new(source: &'a dyn TreeSource<T>, lcp: &LcpArray) -> Self {
+        let n = lcp.len() as usize;
+
+        // Build the tree of LCP intervals: every internal node is a maximal
+        // interval of the suffix array whose suffixes share a prefix of the
+        // node's string depth.
+        let mut nodes: Vec<(u64, Vec<Child>)> = vec![(0, vec![])];
+        let mut stack = vec![0];
+        for i in 0..n {
+            let l = if i + 1 < n { lcp.lcp(i as u64 + 1) } else { 0 };
+            let top = *stack.last().unwrap();
+            if l > nodes[top].0 {
+                nodes.push((l, vec![Child::Leaf]));
+                stack.push(nodes.len() - 1);
+                continue;
+            }
+            nodes[top].1.push(Child::Leaf);
+            while l < nodes[*stack.last().unwrap()].0 {
+                let v = stack.pop().unwrap();
+                let top = *stack.last().unwrap();
+                if l <= nodes[top].0 {
+                    nodes[top].1.push(Child::Internal(v));
+                } else {
+                    nodes.push((l, vec![Child::Internal(v)]));
+                    stack.push(nodes.len() - 1);
+                }
+            }
+        }
+
+        // Write the parentheses in preorder.
+        let max_depth = nodes.iter().map(|&(d, _)| d).max().unwrap_or(0);
+        let word_size = (util::log2(max_depth + 1) + 1) as usize;
+        let mut bits = BitVec::with_capacity(2 * (n + nodes.len()));
+        let mut leaves = BitVec::with_capacity(2 * (n + nodes.len()));
+        let mut depths = BitVec::with_capacity(nodes.len() * word_size);
+        bits.append_bit(1);
+        leaves.append_bit(0);
+        depths.append_bits(nodes[0].0, word_size);
+        let mut stack = vec![(0, 0)];
+        while let Some(&(v, k)) = stack.last() {
+            match nodes[v].1.get(k) {
+                Some(child) => {
+                    stack.last_mut().unwrap().1 += 1;
+                    match *child {
+                        Child::Leaf => {
+                            bits.append_bits(0b01, 2);
+                            leaves.append_bits(0b01, 2);
+                        }
+                        Child::Internal(u) => {
+                            bits.append_bit(1);
+                            leaves.append_bit(0);
+                            depths.append_bits(nodes[u].0, word_size);
+                            stack.push((u, 0));
+                        }
+                    }
+                }
+                None => {
+                    bits.append_bit(0);
+                    leaves.append_bit(0);
+                    stack.pop();
+                }
+            }
+        }
+
+        SuffixTree {
+            source,
+            bp: BalancedParens::new(bits),
+            leaves: RsVec::from_bit_vec(leaves),
+            word_size,
+            depths,
+        }
+    }
+
+    /// The root of the tree.
+    pub fn root(&self) -> Node {
+        Node(0)
+    }
+
+    /// The number of nodes in the tree, including the leaves.
+    pub fn node_count(&self) -> u64 {
+        self.bp.len() as u64 / 2
+    }
+
+    /// Whether `v` is a leaf.
+    pub fn is_leaf(&self, v: Node) -> bool {
+        !self.bp.is_open(v.0 + 1)
+    }
+
+    /// The leaf of the suffix at position `i` of the suffix array.
+    pub fn leaf(&self, i: u64) -> Node {
+        debug_assert!(i < self.source.len(), "{} is out of range", i);
+        Node(self.leaves.select1(i as usize))
+    }
+
+    /// The interval `[s, e)` of the suffix array covered by the leaves
+    /// below `v`.
+    pub fn range(&self, v: Node) -> (u64, u64) {
+        let s = self.leaves.rank1(v.0);
+        let e = if self.is_leaf(v) {
+            s + 1
+        } else {
+            self.leaves.rank1(self.bp.find_close(v.0))
+        };
+        (s as u64, e as u64)
+    }
+
+    /// The number of leaves below `v`, which is the number of occurrences
+    /// of its path label.
+    pub fn count(&self, v: Node) -> u64 {
+        let (s, e) = self.range(v);
+        e - s
+    }
+
+    /// The string depth of `v`, the length of its path label.
+    pub fn depth(&self, v: Node) -> u64 {
+        if self.is_leaf(v) {
+            let (s, _) = self.range(v);
+            self.source.len() - self.source.get_sa(s)
+        } else {
+            let i = self.bp.rank_open(v.0) - self.leaves.rank1(v.0);
+            self.depths
+                .get_bits_unchecked(i * self.word_size, self.word_size)
+        }
+    }
+
+    /// The parent of `v`, or `None` for the root.
+    pub fn parent(&self, v: Node) -> Option<Node> {
+        self.bp.enclose(v.0).map(Node)
+    }
+
+    /// Iterate over the children of `v`, ordered by the first character of
+    /// the edge leading to them.
+    pub fn children(&self, v: Node) -> impl Iterator<Item = Node> + '_ {
+        let first = Some(v.0 + 1).filter(|&i| self.bp.is_open(i));
+        core::iter::successors(first, move |&i| {
+            Some(self.bp.find_close(i) + 1).filter(|&j| self.bp.is_open(j))
+        })
+        .map(Node)
+    }
+
+    /// The child of `v` whose edge starts with character `c`, if any.
+    pub fn child(&self, v: Node, c: T) -> Option<Node> {
+        let depth = self.depth(v);
+        self.children(v).find(|&u| self.letter(u, depth) == c)
+    }
+
+    /// The character at offset `i` of the path label of `v`, where
+    /// `i < depth(v)`.
+    pub fn letter(&self, v: Node, i: u64) -> T {
+        debug_assert!(i < self.depth(v), "{} is out of range", i);
+        let (s, _) = self.range(v);
+        self.source.char_at(self.source.get_sa(s) + i)
+    }
+
+    /// The suffix link of `v`: the node whose path label is that of `v`
+    /// without its first character.
+    ///
+    /// Returns `None` for the root.
+    pub fn suffix_link(&self, v: Node) -> Option<Node> {
+        if v == self.root() {
+            return None;
+        }
+        let (s, e) = self.range(v);
+        if self.is_leaf(v) {
+            if self.source.get_sa(s) + 1 == self.source.len() {
+                // the leaf of the terminator
+                return Some(self.root());
+            }
+            return Some(self.leaf(self.source.psi(s)));
+        }
+        let x = self.leaf(self.source.psi(s));
+        let y = self.leaf(self.source.psi(e - 1));
+        Some(self.lca(x, y))
+    }
+
+    /// The lowest common ancestor of `u` and `v`.
+    pub fn lca(&self, u: Node, v: Node) -> Node {
+        let (x, y) = if u <= v { (u.0, v.0) } else { (v.0, u.0) };
+        if x == y || self.bp.find_close(x) > y {
+            return Node(x);
+        }
+        Node(self.bp.enclose(self.bp.rmq(x, y) + 1).unwrap())
+    }
+}
+
+impl<T> HeapSize for SuffixTree<'_, T> {
+    fn heap_size(&self) -> usize {
+        self.bp.heap_size() + self.leaves.heap_size() + self.depths.heap_size()
+    }
+}
@@ -0,0 +1,156 @@
+//! Sequences over small alphabets, stored as one bitvector per character.
+use crate::sequence::{PackedSymbols, Sequence};
+
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use vers_vecs::{BitVec, RsVec};
+
+/// The largest number of characters besides the terminator.
+const MAX_CHARACTERS: u64 = 8;
+
+/// A sequence with a bitvector for each character that marks its
+/// positions, for the BWT of texts over an alphabet of at most eight
+/// characters, such as DNA with ambiguity codes.
+///
+/// Rank of a character is a single rank on its bitvector, where a wavelet
+/// matrix needs one for each of its levels. The terminator, which only
+/// occurs a few times, has no bitvector; its positions are kept separately.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SymbolVectors {
+    // the bitvector of character c is at c - 1
+    vectors: Vec<RsVec>,
+    // the positions of the terminators, in order
+    zeros: Vec<usize>,
+    len: usize,
+}
+
+impl SymbolVectors {
+    // The number of terminators in [0, i).
+    fn rank_zero(&self, i: usize) -> usize {
+        self.zeros.partition_point(|&p| p < i)
+    }
+}
+
+impl Sequence for SymbolVectors {
+    fn from_symbols(symbols: &PackedSymbols, alphabet_size: u64) -> Self {
+        assert!(
+            alphabet_size <= MAX_CHARACTERS + 1,
+            "symbol vectors hold at most {} characters besides the terminator, not {}",
+            MAX_CHARACTERS,
+            alphabet_size - 1
+        );
+        let len = symbols.len();
+        let mut bits = vec![BitVec::from_zeros(len); alphabet_size as usize - 1];
+        let mut zeros = Vec::new();
+        for (i, c) in symbols.iter().enumerate() {
+            if c == 0 {
+                zeros.push(i);
+            } else {
+                bits[c as usize - 1].set_unchecked(i, 1);
+            }
+        }
+
+        SymbolVectors {
+            vectors: bits.into_iter().map(RsVec::from_bit_vec).collect(),
+            zeros,
+            len,
+        }
+    }
+
+    #[cfg(all(feature = "std", feature = "serde"))]
+    fn alphabet_size(&self) -> u64 {
+        self.vectors.len() as u64 + 1
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, i: usize) -> u64 {
+        self.vectors
+            .iter()
+            .position(|vector| vector.get_unchecked(i) == 1)
+            .map_or(0, |c| c as u64 + 1)
+    }
+
+    fn rank(&self, i: usize, c: u64) -> usize {
+        match c {
+            0 => self.rank_zero(i),
+            c => self
+                .vectors
+                .get(c as usize - 1)
+                .map_or(0, |vector| vector.rank1(i)),
+        }
+    }
+
+    fn select(&self, k: usize, c: u64) -> usize {
+        match c {
+            0 => self.zeros[k],
+            c => self.vectors[c as usize - 1].select1(k),
+        }
+    }
+
+    fn interval_symbols(&self, s: usize, e: usize) -> Vec<(u64, u64)> {
+        self.vectors
+            .iter()
+            .enumerate()
+            .map(|(c, vector)| (c as u64 + 1, (vector.rank1(e) - vector.rank1(s)) as u64))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    fn heap_size(&self) -> usize {
+        self.vectors.iter().map(RsVec::heap_size).sum::<usize>()
+            + self.vectors.capacity() * core::mem::size_of::<RsVec>()
+            + self.zeros.capacity() * core::mem::size_of::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vers_vecs::WaveletMatrix;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_against_wavelet_matrix() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut symbols = (0..70000)
+            .map(|_| rng.gen_range(1, 9))
+            .collect::<Vec<u64>>();
+        for i in [0, 12345, 65536, 69999] {
+            symbols[i] = 0;
+        }
+        let packed = PackedSymbols::pack(symbols.iter().copied(), 9);
+        let sv = SymbolVectors::from_symbols(&packed, 9);
+        let wm = WaveletMatrix::from_symbols(&packed, 9);
+        for (i, &c) in symbols.iter().enumerate().step_by(3) {
+            assert_eq!(sv.get(i), c);
+        }
+        for c in 0..10 {
+            for i in (0..=symbols.len()).step_by(97) {
+                assert_eq!(
+                    sv.rank(i, c),
+                    Sequence::rank(&wm, i, c),
+                    "rank({}, {})",
+                    i,
+                    c
+                );
+            }
+        }
+        for c in 0..9 {
+            let count = Sequence::rank(&wm, symbols.len(), c);
+            for k in (0..count).step_by(101) {
+                assert_eq!(sv.select(k, c), Sequence::select(&wm, k, c));
+            }
+        }
+        for _ in 0..100 {
+            let s = rng.gen_range(0, symbols.len());
+            let e = rng.gen_range(s, symbols.len() + 1);
+            assert_eq!(sv.interval_symbols(s, e), wm.interval_symbols(s, e));
+        }
+    }
+}
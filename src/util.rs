@@ -1,5 +1,29 @@
+use vers_vecs::EliasFanoVec;
+
 pub fn log2(x: u64) -> u64 {
-    ((std::mem::size_of::<u64>() * 8) as u64) - u64::from(x.leading_zeros()) - 1
+    ((core::mem::size_of::<u64>() * 8) as u64) - u64::from(x.leading_zeros()) - 1
+}
+
+/// Whether two Elias-Fano vectors hold the same values, as they don't
+/// implement `PartialEq`.
+pub(crate) fn elias_fano_eq(a: &EliasFanoVec, b: &EliasFanoVec) -> bool {
+    a.len() == b.len() && (0..a.len()).all(|i| a.get_unchecked(i) == b.get_unchecked(i))
+}
+
+/// Hint to the CPU that the cache line holding `value` will be read soon.
+///
+/// This does nothing on architectures without a stable prefetch intrinsic.
+#[inline(always)]
+pub(crate) fn prefetch<T>(value: &T) {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: prefetching is a hint and never faults, and SSE is part of
+    // the x86-64 baseline.
+    unsafe {
+        use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(value as *const T as *const i8);
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = value;
 }
 
 #[cfg(test)]
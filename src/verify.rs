@@ -0,0 +1,113 @@
+//! Checking the internal consistency of an index.
+use crate::suffix_array::sample::SuffixArraySamples;
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// The result of checking the internal invariants of an index with
+/// `verify`.
+///
+/// An index that passes may still be damaged in rows or samples that
+/// weren't checked, but one that fails certainly is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    rows_checked: u64,
+    samples_checked: u64,
+    problems: Vec<String>,
+}
+
+impl VerifyReport {
+    /// Whether no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// A description of each problem found.
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+
+    /// The number of rows whose LF-mapping was checked.
+    pub fn rows_checked(&self) -> u64 {
+        self.rows_checked
+    }
+
+    /// The number of rows whose suffix array value was recovered from the
+    /// samples and checked. This is 0 for an index without locate support.
+    pub fn samples_checked(&self) -> u64 {
+        self.samples_checked
+    }
+
+    pub(crate) fn problem(&mut self, problem: String) {
+        self.problems.push(problem);
+    }
+
+    pub(crate) fn row_checked(&mut self) {
+        self.rows_checked += 1;
+    }
+
+    pub(crate) fn sample_checked(&mut self) {
+        self.samples_checked += 1;
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "checked {} rows and {} samples: ",
+            self.rows_checked, self.samples_checked
+        )?;
+        if self.is_ok() {
+            return write!(f, "no problems found");
+        }
+        write!(f, "{} problems found", self.problems.len())?;
+        for problem in &self.problems {
+            write!(f, "\n- {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+/// Access to the suffix array samples of a backend, if it has any, for
+/// checking them.
+pub(crate) trait VerifySamples {
+    /// Whether there are samples to check.
+    fn has_samples(&self) -> bool;
+
+    /// The suffix array value at row `i`, if it was sampled.
+    fn sample_at(&self, i: u64) -> Option<u64>;
+
+    /// The sampled text position at or after `p`, with its row.
+    fn inverse_sample(&self, p: u64) -> (u64, u64);
+}
+
+// An index without locate support has no samples, and these are never
+// called.
+impl VerifySamples for () {
+    fn has_samples(&self) -> bool {
+        false
+    }
+
+    fn sample_at(&self, _i: u64) -> Option<u64> {
+        None
+    }
+
+    fn inverse_sample(&self, _p: u64) -> (u64, u64) {
+        (0, 0)
+    }
+}
+
+impl<S: SuffixArraySamples> VerifySamples for S {
+    fn has_samples(&self) -> bool {
+        true
+    }
+
+    fn sample_at(&self, i: u64) -> Option<u64> {
+        self.get(i)
+    }
+
+    fn inverse_sample(&self, p: u64) -> (u64, u64) {
+        self.get_inverse(p)
+    }
+}
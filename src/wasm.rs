@@ -0,0 +1,91 @@
+//! JavaScript bindings for WebAssembly, built with `wasm-bindgen`.
+//!
+//! This exports a single class, `FMIndex`, an index of a text of bytes with
+//! locate support. Texts, patterns and saved indexes are passed as
+//! `Uint8Array`s. Counts and positions are JavaScript numbers, which are
+//! exact for texts of up to 2<sup>53</sup> bytes.
+//!
+//! An index is usually built ahead of time, saved with
+//! [`save`](crate::FMIndexWithLocate::save), and shipped to the browser as
+//! a file to be loaded with `FMIndex.load`:
+//!
+//! ```js
+//! const response = await fetch("index.fmi");
+//! const index = FMIndex.load(new Uint8Array(await response.arrayBuffer()));
+//! const encoder = new TextEncoder();
+//! const positions = index.locate(encoder.encode("needle"));
+//! ```
+use crate::character::{check_level, check_text};
+use crate::converter::IdConverter;
+use crate::frontend::FMIndexWithLocate;
+
+use alloc::vec::Vec;
+use wasm_bindgen::prelude::*;
+
+/// An index of a text of bytes with locate support, exported to JavaScript
+/// as `FMIndex`.
+#[wasm_bindgen(js_name = FMIndex)]
+pub struct WasmFMIndex(FMIndexWithLocate<u8, IdConverter>);
+
+#[wasm_bindgen(js_class = FMIndex)]
+impl WasmFMIndex {
+    /// Build an index of `text`, sampling the suffix array at `level` as in
+    /// [`FMIndexWithLocate::new`].
+    ///
+    /// Throws if the text can't be indexed, for instance because it's
+    /// empty, or if `level` is too large for it.
+    #[wasm_bindgen(constructor)]
+    pub fn new(text: &[u8], level: u32) -> Result<WasmFMIndex, JsError> {
+        let converter = IdConverter::new::<u8>();
+        check_text(text, &converter)?;
+        check_level(text, level as usize)?;
+        Ok(WasmFMIndex(FMIndexWithLocate::new(
+            text,
+            converter,
+            level as usize,
+        )))
+    }
+
+    /// Load an index saved by [`save`](Self::save), or by
+    /// [`FMIndexWithLocate::save`] for an index of bytes with an
+    /// [`IdConverter`].
+    pub fn load(bytes: &[u8]) -> Result<WasmFMIndex, JsError> {
+        Ok(WasmFMIndex(FMIndexWithLocate::load(bytes)?))
+    }
+
+    /// Save the index as bytes.
+    pub fn save(&self) -> Result<Vec<u8>, JsError> {
+        let mut bytes = Vec::new();
+        self.0.save(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// The length of the indexed text, without the terminator.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> f64 {
+        self.0.text_len() as f64
+    }
+
+    /// The number of occurrences of `pattern`.
+    pub fn count(&self, pattern: &[u8]) -> f64 {
+        self.0.search(pattern).count() as f64
+    }
+
+    /// The positions of all occurrences of `pattern`, in no particular
+    /// order.
+    pub fn locate(&self, pattern: &[u8]) -> Vec<f64> {
+        let search = self.0.search(pattern);
+        let mut positions = Vec::with_capacity(search.count() as usize);
+        search.for_each_position(|position| positions.push(position as f64));
+        positions
+    }
+
+    /// The bytes of the text from `start` up to `end`, both clamped to the
+    /// length of the text.
+    pub fn extract(&self, start: f64, end: f64) -> Vec<u8> {
+        let len = self.0.text_len();
+        let end = (end as u64).min(len);
+        let start = (start as u64).min(end);
+        self.0.extract(start, end)
+    }
+}
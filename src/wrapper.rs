@@ -2,41 +2,561 @@
 // the functionality used by the frontend.
 // This makes the implementation of the frontend more regular.
 
-use crate::backend::{HasPosition, SearchIndexBackend};
+use crate::backend::{HasPosition, SearchIndexBackend, SizeBreakdown};
+use crate::character::Character;
 use crate::converter::Converter;
+use crate::error::Error;
+#[cfg(feature = "std")]
+use crate::instrument::{QueryRecord, QueryStats, Recorder};
+use crate::kmer::KmerTable;
+use crate::lcp::LcpArray;
+use crate::occ::OccTable;
+#[cfg(all(feature = "std", feature = "serde"))]
+use crate::persist::{SectionReader, SectionWriter, Sections};
+#[cfg(feature = "cache")]
+use crate::query_cache::QueryCache;
+use crate::seed::Anchor;
+use crate::snapshot::SearchSnapshot;
+use crate::stats::IndexStats;
+use crate::suffix_tree::{SuffixTree, TreeSource};
+use crate::util::prefetch;
 use crate::HeapSize;
 
-pub(crate) struct SearchIndexWrapper<B>(B)
+use alloc::{vec, vec::Vec};
+use num_traits::Zero;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(all(feature = "std", feature = "serde"))]
+use std::io;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct SearchIndexWrapper<B>
 where
-    B: SearchIndexBackend;
+    B: SearchIndexBackend,
+{
+    backend: B,
+    kmers: Option<KmerTable>,
+    // rebuilt on demand, so it's neither serialized nor saved
+    #[cfg_attr(feature = "serde", serde(skip))]
+    occ: Option<OccTable>,
+    #[cfg(feature = "cache")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cache: Option<QueryCache>,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    recorder: Option<Recorder>,
+}
 
 pub(crate) struct SearchWrapper<'a, B>
 where
     B: SearchIndexBackend,
 {
-    backend: &'a B,
+    index: &'a SearchIndexWrapper<B>,
     s: u64,
     e: u64,
     pattern: Vec<B::T>,
 }
 
+// A clone starts out with an empty query cache.
+impl<B> Clone for SearchIndexWrapper<B>
+where
+    B: SearchIndexBackend + Clone,
+{
+    fn clone(&self) -> Self {
+        SearchIndexWrapper {
+            backend: self.backend.clone(),
+            kmers: self.kmers.clone(),
+            occ: self.occ.clone(),
+            #[cfg(feature = "cache")]
+            cache: self.cache.as_ref().map(|cache| {
+                let (capacity, locate) = cache.config();
+                QueryCache::new(capacity, locate)
+            }),
+            #[cfg(feature = "std")]
+            recorder: self.recorder.clone(),
+        }
+    }
+}
+
+// Indexes are equal if they would be saved the same, so the tables that are
+// rebuilt on demand, the cache and the recorded queries aren't compared.
+impl<B> PartialEq for SearchIndexWrapper<B>
+where
+    B: SearchIndexBackend + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.backend == other.backend && self.kmers == other.kmers
+    }
+}
+
+impl<'a, B> Clone for SearchWrapper<'a, B>
+where
+    B: SearchIndexBackend,
+{
+    fn clone(&self) -> Self {
+        SearchWrapper {
+            index: self.index,
+            s: self.s,
+            e: self.e,
+            pattern: self.pattern.clone(),
+        }
+    }
+}
+
+impl<'a, B> core::fmt::Debug for SearchWrapper<'a, B>
+where
+    B: SearchIndexBackend,
+    B::T: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Search")
+            .field("pattern", &self.pattern)
+            .field("range", &(self.s..self.e))
+            .finish()
+    }
+}
+
 impl<B> SearchIndexWrapper<B>
 where
     B: SearchIndexBackend + HeapSize,
 {
     pub(crate) fn new(backend: B) -> Self {
-        SearchIndexWrapper(backend)
+        SearchIndexWrapper {
+            backend,
+            kmers: None,
+            occ: None,
+            #[cfg(feature = "cache")]
+            cache: None,
+            #[cfg(feature = "std")]
+            recorder: None,
+        }
+    }
+
+    /// Precompute the intervals of all patterns of length `k`.
+    pub(crate) fn with_kmer_table(mut self, k: usize) -> Self {
+        self.kmers = Some(KmerTable::new(&self.backend, k));
+        self
+    }
+
+    /// Build an occurrence table with a checkpoint every `k` positions,
+    /// used instead of the backend to count the occurrences of characters.
+    ///
+    /// Panics if the alphabet has more than 15 characters.
+    pub(crate) fn with_occ_table(mut self, k: usize) -> Self {
+        self.occ = Some(OccTable::new(&self.backend, k));
+        self
+    }
+
+    /// Cache the intervals of the `capacity` most recently searched
+    /// patterns, and their locate results as well if `locate` is set.
+    #[cfg(feature = "cache")]
+    pub(crate) fn with_query_cache(mut self, capacity: usize, locate: bool) -> Self {
+        self.cache = Some(QueryCache::new(capacity, locate));
+        self
+    }
+
+    /// The capacity of the query cache, if there is one, and whether it
+    /// holds locate results.
+    #[cfg(feature = "cache")]
+    pub(crate) fn query_cache_config(&self) -> Option<(usize, bool)> {
+        self.cache.as_ref().map(QueryCache::config)
+    }
+
+    /// Record the time and work of every search and locate.
+    #[cfg(feature = "std")]
+    pub(crate) fn with_instrumentation(mut self) -> Self {
+        self.recorder = Some(Recorder::default());
+        self
+    }
+
+    /// The queries recorded so far, if the index is instrumented.
+    #[cfg(feature = "std")]
+    pub(crate) fn query_stats(&self) -> Option<QueryStats> {
+        self.recorder.as_ref().map(Recorder::stats)
+    }
+
+    /// Forget the queries recorded so far.
+    #[cfg(feature = "std")]
+    pub(crate) fn reset_query_stats(&self) {
+        if let Some(recorder) = &self.recorder {
+            recorder.reset();
+        }
+    }
+
+    /// Rebuild the index from its backend with `build`, keeping the k-mer
+    /// table, the occurrence table, the query cache settings and the
+    /// recorded queries.
+    ///
+    /// The new backend may be of another type, such as one with suffix
+    /// array samples added.
+    pub(crate) fn rebuild<B2>(self, build: impl FnOnce(B) -> B2) -> SearchIndexWrapper<B2>
+    where
+        B2: SearchIndexBackend + HeapSize,
+    {
+        let kmer_len = self.kmer_len();
+        let occ_interval = self.occ_interval();
+        #[cfg(feature = "cache")]
+        let query_cache = self.query_cache_config();
+        let mut index = SearchIndexWrapper::new(build(self.backend));
+        if let Some(k) = kmer_len {
+            index = index.with_kmer_table(k);
+        }
+        if let Some(k) = occ_interval {
+            index = index.with_occ_table(k);
+        }
+        #[cfg(feature = "cache")]
+        if let Some((capacity, locate)) = query_cache {
+            index = index.with_query_cache(capacity, locate);
+        }
+        #[cfg(feature = "std")]
+        {
+            index.recorder = self.recorder;
+        }
+        index
+    }
+
+    /// The checkpoint interval of the occurrence table, if there is one.
+    pub(crate) fn occ_interval(&self) -> Option<usize> {
+        self.occ.as_ref().map(|occ| occ.k())
+    }
+
+    /// The pattern length of the k-mer table, if there is one.
+    pub(crate) fn kmer_len(&self) -> Option<usize> {
+        self.kmers.as_ref().map(|kmers| kmers.k())
     }
 
     /// Search for a pattern in the text.
     ///
     /// Return a [`Search`] object with information about the search
     /// result.
-    pub(crate) fn search<K>(&self, pattern: K) -> SearchWrapper<B>
+    pub(crate) fn search<K>(&self, pattern: K) -> SearchWrapper<'_, B>
     where
         K: AsRef<[B::T]>,
     {
-        SearchWrapper::new(&self.0).search(pattern)
+        let pattern = pattern.as_ref();
+        #[cfg(feature = "std")]
+        if let Some(recorder) = &self.recorder {
+            return self.search_recorded(recorder, pattern);
+        }
+        self.search_unrecorded(pattern)
+    }
+
+    #[cfg(feature = "std")]
+    fn search_recorded(&self, recorder: &Recorder, pattern: &[B::T]) -> SearchWrapper<'_, B> {
+        let start = Instant::now();
+        let search = self.search_unrecorded(pattern);
+        let time = start.elapsed();
+        recorder.record(QueryRecord::Search {
+            pattern_len: pattern.len(),
+            steps: self.search_steps(pattern),
+            occurrences: search.e - search.s,
+            time,
+        });
+        search
+    }
+
+    fn search_unrecorded(&self, pattern: &[B::T]) -> SearchWrapper<'_, B> {
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &self.cache {
+            let (s, e) = cache.interval(pattern, || {
+                let search = self.search_with_min(pattern, 1);
+                (search.s, search.e)
+            });
+            return SearchWrapper {
+                index: self,
+                s,
+                e,
+                pattern: pattern.to_vec(),
+            };
+        }
+        self.search_with_min(pattern, 1)
+    }
+
+    // The number of backward search steps a search for `pattern` takes,
+    // found by repeating it one character at a time.
+    #[cfg(feature = "std")]
+    fn search_steps(&self, pattern: &[B::T]) -> u64 {
+        let (mut s, mut e, split) = self.initial_interval(pattern);
+        let mut steps = 0;
+        for i in (0..split).rev() {
+            if s == e {
+                break;
+            }
+            (s, e) = self.search_interval(&pattern[i..i + 1], s, e, 1);
+            steps += 1;
+        }
+        steps
+    }
+
+    // Like `search`, but give up once fewer than `min` occurrences are
+    // left, in which case the result only bounds the count from above.
+    fn search_with_min<K>(&self, pattern: K, min: u64) -> SearchWrapper<'_, B>
+    where
+        K: AsRef<[B::T]>,
+    {
+        let pattern = pattern.as_ref();
+        let (s, e, split) = self.initial_interval(pattern);
+        let (prefix, suffix) = pattern.split_at(split);
+        let search = SearchWrapper {
+            index: self,
+            s,
+            e,
+            pattern: suffix.to_vec(),
+        };
+        search.search_with_min(prefix, min)
+    }
+
+    // The interval to start the search for `pattern` from, taken from the
+    // k-mer table if there is one, and the length of the prefix of
+    // `pattern` that is left to search for.
+    fn initial_interval(&self, pattern: &[B::T]) -> (u64, u64, usize) {
+        if let Some(kmers) = &self.kmers {
+            if pattern.len() >= kmers.k() {
+                let split = pattern.len() - kmers.k();
+                if let Some((s, e)) = kmers.get(&pattern[split..], self.backend.get_converter()) {
+                    return (s, e, split);
+                }
+            }
+        }
+        (0, self.backend.len(), pattern.len())
+    }
+
+    /// Count the number of occurrences of each of `patterns`.
+    pub(crate) fn count_many<K>(&self, patterns: &[K]) -> Vec<u64>
+    where
+        K: AsRef<[B::T]>,
+    {
+        patterns
+            .iter()
+            .map(|pattern| self.search(pattern).count())
+            .collect()
+    }
+
+    /// Count the number of occurrences of each of `patterns`, advancing
+    /// the searches for all of them one character at a time.
+    ///
+    /// The pending searches are kept sorted by the start of their interval,
+    /// so that consecutive rank queries land close together in the BWT.
+    /// As the LF-mapping preserves the order of the positions of a
+    /// character, regrouping the searches by the character of each step
+    /// keeps them sorted, which takes linear time.
+    pub(crate) fn count_many_lockstep<K>(&self, patterns: &[K]) -> Vec<u64>
+    where
+        K: AsRef<[B::T]>,
+    {
+        let converter = self.backend.get_converter();
+        let mut counts = vec![0; patterns.len()];
+        let mut pending = Vec::with_capacity(patterns.len());
+        for (i, pattern) in patterns.iter().enumerate() {
+            let (s, e, left) = self.initial_interval(pattern.as_ref());
+            if left == 0 || s == e {
+                counts[i] = self.backend.live_count(s, e);
+            } else {
+                pending.push(Pending {
+                    s,
+                    e,
+                    pattern: i,
+                    left,
+                    c: 0,
+                });
+            }
+        }
+        pending.sort_unstable_by_key(|p| p.s);
+
+        let alphabet_size = converter.len() as usize;
+        let mut next = Vec::with_capacity(pending.len());
+        while !pending.is_empty() {
+            for j in 0..pending.len() {
+                // the patterns are read in random order, so fetch them ahead
+                if let Some(ahead) = pending.get(j + 2 * PREFETCH_DISTANCE) {
+                    prefetch(&patterns[ahead.pattern]);
+                }
+                if let Some(ahead) = pending.get(j + PREFETCH_DISTANCE) {
+                    prefetch(&patterns[ahead.pattern].as_ref()[ahead.left - 1]);
+                }
+                let p = &mut pending[j];
+                p.left -= 1;
+                let c = &patterns[p.pattern].as_ref()[p.left..p.left + 1];
+                (p.s, p.e) = self.search_interval(c, p.s, p.e, 1);
+                p.c = converter.convert(c[0]).into() as usize;
+                if p.left == 0 || p.s == p.e {
+                    counts[p.pattern] = self.backend.live_count(p.s, p.e);
+                }
+            }
+            pending.retain(|p| p.left > 0 && p.s < p.e);
+
+            if alphabet_size > pending.len() {
+                pending.sort_unstable_by_key(|p| p.s);
+                continue;
+            }
+            // a counting sort by character, which is stable
+            let mut offsets = vec![0; alphabet_size + 1];
+            for p in &pending {
+                offsets[p.c + 1] += 1;
+            }
+            for c in 1..=alphabet_size {
+                offsets[c] += offsets[c - 1];
+            }
+            next.clear();
+            next.resize(pending.len(), Pending::default());
+            for p in &pending {
+                next[offsets[p.c]] = *p;
+                offsets[p.c] += 1;
+            }
+            core::mem::swap(&mut pending, &mut next);
+        }
+        counts
+    }
+
+    /// Whether each of `patterns` occurs at least `n` times.
+    ///
+    /// The search for a pattern stops as soon as fewer than `n` of its
+    /// occurrences are left.
+    pub(crate) fn count_many_at_least<K>(&self, patterns: &[K], n: u64) -> Vec<bool>
+    where
+        K: AsRef<[B::T]>,
+    {
+        patterns
+            .iter()
+            .map(|pattern| self.search_with_min(pattern, n).count_at_least(n))
+            .collect()
+    }
+
+    /// Count the number of occurrences of each of `patterns`, distributing
+    /// the patterns over the rayon thread pool.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn count_many_parallel<K>(&self, patterns: &[K]) -> Vec<u64>
+    where
+        K: AsRef<[B::T]> + Sync,
+        B: Sync,
+        B::T: Sync,
+    {
+        use rayon::prelude::*;
+        patterns
+            .par_iter()
+            .map(|pattern| self.search(pattern).count())
+            .collect()
+    }
+
+    /// Find the anchors of `query` of at least `min_len` characters, by
+    /// backward search restarting before each mismatch.
+    pub(crate) fn seeds(&self, query: &[B::T], min_len: usize) -> Vec<Anchor> {
+        let converter = self.backend.get_converter();
+        let min_len = min_len.max(1);
+        let mut anchors = Vec::new();
+        let mut end = query.len();
+        while end >= min_len {
+            let (mut s, mut e) = (0, self.backend.len());
+            let mut start = end;
+            while start > 0 && converter.contains(query[start - 1]) {
+                let (s2, e2) = self.search_interval(&query[start - 1..start], s, e, 1);
+                if s2 == e2 {
+                    break;
+                }
+                (s, e) = (s2, e2);
+                start -= 1;
+            }
+            if end - start >= min_len {
+                anchors.push(Anchor::new(start, end - start, (s, e)));
+            }
+            if start == 0 {
+                break;
+            }
+            end = start - 1;
+        }
+        anchors.reverse();
+        anchors
+    }
+
+    /// Find the anchors of each of `reads` like `seeds`, leaving out those
+    /// with more than `max_hits` occurrences, advancing the searches for
+    /// all reads one character at a time.
+    ///
+    /// Before each step the pending searches are sorted by the start of
+    /// their interval, as in `count_many_lockstep`, so that consecutive
+    /// rank queries land close together in the BWT.
+    pub(crate) fn seed_reads<K>(
+        &self,
+        reads: &[K],
+        min_len: usize,
+        max_hits: u64,
+    ) -> Vec<Vec<Anchor>>
+    where
+        K: AsRef<[B::T]>,
+    {
+        let converter = self.backend.get_converter();
+        let n = self.backend.len();
+        let min_len = min_len.max(1);
+        let mut anchors = vec![Vec::new(); reads.len()];
+        let mut pending = reads
+            .iter()
+            .enumerate()
+            .filter(|(_, read)| read.as_ref().len() >= min_len)
+            .map(|(read, query)| {
+                let end = query.as_ref().len();
+                PendingSeed {
+                    s: 0,
+                    e: n,
+                    read,
+                    start: end,
+                    end,
+                }
+            })
+            .collect::<Vec<_>>();
+        while !pending.is_empty() {
+            pending.sort_unstable_by_key(|p| p.s);
+            for p in &mut pending {
+                let read = reads[p.read].as_ref();
+                if p.start > 0 && converter.contains(read[p.start - 1]) {
+                    let (s, e) = self.search_interval(&read[p.start - 1..p.start], p.s, p.e, 1);
+                    if s < e {
+                        (p.s, p.e) = (s, e);
+                        p.start -= 1;
+                        continue;
+                    }
+                }
+                // the anchor ending at `end` can't be extended further
+                if p.end - p.start >= min_len && p.e - p.s <= max_hits {
+                    anchors[p.read].push(Anchor::new(p.start, p.end - p.start, (p.s, p.e)));
+                }
+                if p.start == 0 {
+                    p.end = 0;
+                } else {
+                    p.end = p.start - 1;
+                    p.start = p.end;
+                    (p.s, p.e) = (0, n);
+                }
+            }
+            pending.retain(|p| p.end >= min_len);
+        }
+        for anchors in &mut anchors {
+            anchors.reverse();
+        }
+        anchors
+    }
+
+    /// Find the anchors of each of `reads` like `seed_reads`, distributing
+    /// chunks of reads over the rayon thread pool.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn seed_reads_parallel<K>(
+        &self,
+        reads: &[K],
+        min_len: usize,
+        max_hits: u64,
+    ) -> Vec<Vec<Anchor>>
+    where
+        K: AsRef<[B::T]> + Sync,
+        B: Sync,
+        B::T: Sync,
+    {
+        use rayon::prelude::*;
+        reads
+            .par_chunks(SEED_CHUNK)
+            .flat_map_iter(|chunk| self.seed_reads(chunk, min_len, max_hits))
+            .collect()
     }
 
     /// Get the length of the text in the index.
@@ -44,86 +564,428 @@ where
     /// Note that this includes an ending \0 (terminator) character
     /// so will be one more than the length of the text passed in.
     pub(crate) fn len(&self) -> u64 {
-        self.0.len()
+        self.backend.len()
+    }
+
+    /// An empty search result for `pattern`, for patterns with characters
+    /// outside the converter's alphabet, which can't occur in the text.
+    pub(crate) fn search_absent<K>(&self, pattern: K) -> SearchWrapper<'_, B>
+    where
+        K: AsRef<[B::T]>,
+    {
+        SearchWrapper {
+            index: self,
+            s: 0,
+            e: 0,
+            pattern: pattern.as_ref().to_vec(),
+        }
+    }
+
+    /// The search result captured by `snapshot`.
+    ///
+    /// Panics if its interval is out of range for this index.
+    pub(crate) fn resume(&self, snapshot: &SearchSnapshot<B::T>) -> SearchWrapper<'_, B> {
+        let (s, e) = snapshot.range();
+        assert!(
+            s <= e && e <= self.backend.len(),
+            "the snapshot's interval [{}, {}) is out of range for this index",
+            s,
+            e
+        );
+        SearchWrapper {
+            index: self,
+            s,
+            e,
+            pattern: snapshot.pattern().to_vec(),
+        }
+    }
+
+    pub(crate) fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut B {
+        // the results of earlier queries may no longer hold
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+        &mut self.backend
+    }
+
+    pub(crate) fn into_backend(self) -> B {
+        self.backend
     }
 
     pub(crate) fn heap_size(&self) -> usize {
-        B::heap_size(&self.0)
+        B::heap_size(&self.backend)
+            + self.kmers.as_ref().map_or(0, |kmers| kmers.heap_size())
+            + self.occ.as_ref().map_or(0, |occ| occ.heap_size())
+            + self.cache_heap_size()
+            + self.recorder_heap_size()
+    }
+
+    fn cache_heap_size(&self) -> usize {
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &self.cache {
+            return cache.heap_size();
+        }
+        0
+    }
+
+    fn recorder_heap_size(&self) -> usize {
+        #[cfg(feature = "std")]
+        if let Some(recorder) = &self.recorder {
+            return recorder.heap_size();
+        }
+        0
+    }
+
+    pub(crate) fn size_breakdown(&self) -> SizeBreakdown {
+        let mut breakdown = B::size_breakdown(&self.backend);
+        if let Some(kmers) = &self.kmers {
+            breakdown = breakdown.add("kmers", kmers.heap_size());
+        }
+        if let Some(occ) = &self.occ {
+            breakdown = breakdown.add("occ", occ.heap_size());
+        }
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &self.cache {
+            breakdown = breakdown.add("query_cache", cache.heap_size());
+        }
+        #[cfg(feature = "std")]
+        if let Some(recorder) = &self.recorder {
+            breakdown = breakdown.add("query_records", recorder.heap_size());
+        }
+        breakdown
+    }
+
+    /// Get the Burrows-Wheeler transform of the text.
+    ///
+    /// The characters are converted back into the original alphabet.
+    pub(crate) fn bwt(&self) -> Vec<B::T> {
+        let converter = self.backend.get_converter();
+        (0..self.backend.len())
+            .map(|i| converter.convert_inv(self.backend.get_l(i)))
+            .collect()
+    }
+
+    /// Compute statistics about the text and its alphabet.
+    pub(crate) fn stats(&self) -> IndexStats<B::T> {
+        IndexStats::new(&self.backend)
     }
 }
 
-impl<'a, B> SearchWrapper<'a, B>
+#[cfg(all(feature = "std", feature = "serde"))]
+impl<B> Sections for SearchIndexWrapper<B>
+where
+    B: SearchIndexBackend + Sections,
+{
+    fn save_sections<W: io::Write>(&self, out: &mut SectionWriter<W>) -> io::Result<()> {
+        self.backend.save_sections(out)?;
+        out.write(&self.kmers)
+    }
+
+    fn load_sections<R: io::Read>(input: &mut SectionReader<R>) -> io::Result<Self> {
+        Ok(SearchIndexWrapper {
+            backend: B::load_sections(input)?,
+            kmers: input.read()?,
+            occ: None,
+            #[cfg(feature = "cache")]
+            cache: None,
+            #[cfg(feature = "std")]
+            recorder: None,
+        })
+    }
+
+    fn sampling_level(&self) -> Option<usize> {
+        self.backend.sampling_level()
+    }
+}
+
+impl<B> SearchIndexWrapper<B>
+where
+    B: SearchIndexBackend + HasPosition,
+{
+    /// Reconstruct the full suffix array from the sampled one.
+    pub(crate) fn suffix_array(&self) -> Vec<u64> {
+        (0..self.backend.len())
+            .map(|i| self.backend.get_sa(i))
+            .collect()
+    }
+
+    /// Get the character at text position `pos`.
+    pub(crate) fn char_at(&self, pos: u64) -> B::T {
+        debug_assert!(pos < self.backend.len(), "{} is out of range", pos);
+        let (q, i) = self.backend.get_isa_sample(pos + 1);
+        BackwardIterator::new(&self.backend, i)
+            .nth((q - pos - 1) as usize)
+            .unwrap()
+    }
+
+    /// Get the characters at text positions `start..end`.
+    pub(crate) fn extract(&self, start: u64, end: u64) -> Vec<B::T> {
+        debug_assert!(start <= end, "{} is after {}", start, end);
+        debug_assert!(end <= self.backend.len(), "{} is out of range", end);
+        // walk back from the nearest sample at or after the end
+        let (q, i) = self.backend.get_isa_sample(end);
+        let mut result = BackwardIterator::new(&self.backend, i)
+            .skip((q - end) as usize)
+            .take((end - start) as usize)
+            .collect::<Vec<_>>();
+        result.reverse();
+        result
+    }
+
+    /// Build the LCP array of the text.
+    ///
+    /// The text itself is recovered from the BWT and the suffix array.
+    pub(crate) fn lcp_array(&self) -> LcpArray {
+        let sa = self.suffix_array();
+        let n = sa.len() as u64;
+        let mut text = vec![B::T::zero(); n as usize];
+        for (i, &p) in sa.iter().enumerate() {
+            text[((p + n - 1) % n) as usize] = self.backend.get_l(i as u64);
+        }
+        LcpArray::new(&text, &sa)
+    }
+
+    /// Build a suffix tree of the text, on top of this index.
+    pub(crate) fn suffix_tree(&self) -> SuffixTree<'_, B::T> {
+        SuffixTree::new(self, &self.lcp_array())
+    }
+}
+
+impl<B> TreeSource<B::T> for SearchIndexWrapper<B>
+where
+    B: HasPosition,
+{
+    fn len(&self) -> u64 {
+        self.backend.len()
+    }
+
+    fn get_sa(&self, i: u64) -> u64 {
+        self.backend.get_sa(i)
+    }
+
+    fn psi(&self, i: u64) -> u64 {
+        self.backend.fl_map(i)
+    }
+
+    fn char_at(&self, pos: u64) -> B::T {
+        SearchIndexWrapper::char_at(self, pos)
+    }
+}
+
+// How many searches ahead `count_many_lockstep` fetches the patterns.
+const PREFETCH_DISTANCE: usize = 8;
+
+// The number of reads each task of `seed_reads_parallel` seeds in
+// lockstep.
+#[cfg(feature = "rayon")]
+const SEED_CHUNK: usize = 1024;
+
+// The search for the anchors of a read in `seed_reads`.
+struct PendingSeed {
+    s: u64,
+    e: u64,
+    // the index of the read, and the part of it matched so far
+    read: usize,
+    start: usize,
+    end: usize,
+}
+
+// A search in `count_many_lockstep`.
+#[derive(Clone, Copy, Default)]
+struct Pending {
+    s: u64,
+    e: u64,
+    // the index of the pattern and the length of its prefix left
+    pattern: usize,
+    left: usize,
+    // the converted character of the last step
+    c: usize,
+}
+
+impl<B> SearchIndexWrapper<B>
 where
     B: SearchIndexBackend,
 {
-    fn new(backend: &'a B) -> Self {
-        let e = backend.len();
-        SearchWrapper {
-            backend,
-            s: 0,
-            e,
-            pattern: vec![],
+    /// Check that all characters of `pattern` are in the alphabet, as
+    /// searching for it panics otherwise.
+    pub(crate) fn check_pattern(&self, pattern: &[B::T]) -> Result<(), Error> {
+        Ok(self.backend.get_converter().validate(pattern)?)
+    }
+
+    // Narrow down `[s, e)` with the occurrence table if there is one.
+    fn search_interval(&self, pattern: &[B::T], s: u64, e: u64, min: u64) -> (u64, u64) {
+        match &self.occ {
+            Some(occ) => occ.search_interval(self.backend.get_converter(), pattern, s, e, min),
+            None => self.backend.search_interval(pattern, s, e, min),
         }
     }
+}
 
+impl<'a, B> SearchWrapper<'a, B>
+where
+    B: SearchIndexBackend,
+{
     /// Search in the current search result, refining it.
     ///
     /// This adds a prefix `pattern` to the existing pattern, and
     /// looks for those expanded patterns in the text.
     pub(crate) fn search<K: AsRef<[B::T]>>(&self, pattern: K) -> Self {
-        // TODO: move this loop into backend to avoid dispatch overhead
-        let mut s = self.s;
-        let mut e = self.e;
-        let mut pattern = pattern.as_ref().to_vec();
-        for &c in pattern.iter().rev() {
-            s = self.backend.lf_map2(c, s);
-            e = self.backend.lf_map2(c, e);
-            if s == e {
-                break;
-            }
+        self.search_with_min(pattern, 1)
+    }
+
+    /// Check that all characters of `pattern` are in the alphabet.
+    pub(crate) fn check_pattern(&self, pattern: &[B::T]) -> Result<(), Error> {
+        self.index.check_pattern(pattern)
+    }
+
+    /// Check that `i` is an offset into the search result.
+    pub(crate) fn check_offset(&self, i: u64) -> Result<(), Error> {
+        let len = self.e - self.s;
+        if i < len {
+            Ok(())
+        } else {
+            Err(Error::OffsetOutOfRange { offset: i, len })
         }
+    }
+
+    // Like `search`, but give up once fewer than `min` occurrences are
+    // left, in which case the result only bounds the count from above.
+    fn search_with_min<K: AsRef<[B::T]>>(&self, pattern: K, min: u64) -> Self {
+        let mut pattern = pattern.as_ref().to_vec();
+        let (s, e) = self.search_interval(&pattern, self.s, self.e, min.max(1));
         pattern.extend_from_slice(&self.pattern);
 
         SearchWrapper {
-            backend: self.backend,
+            index: self.index,
             s,
             e,
             pattern,
         }
     }
 
-    #[cfg(test)]
+    fn search_interval(&self, pattern: &[B::T], s: u64, e: u64, min: u64) -> (u64, u64) {
+        self.index.search_interval(pattern, s, e, min)
+    }
+
+    /// List the characters that follow the pattern in the text, together
+    /// with the number of occurrences of the pattern extended by that
+    /// character.
+    ///
+    /// Terminator characters are not included.
+    pub(crate) fn extensions(&self) -> Vec<(B::T, u64)> {
+        let converter = self.index.backend.get_converter();
+        let n = self.index.backend.len();
+        let mut result = Vec::new();
+        if self.s == self.e {
+            return result;
+        }
+        for c in 1..converter.len() {
+            let c = converter.convert_inv(B::T::from_u64(c));
+            let s = self.index.backend.lf_map2(c, 0);
+            let e = self.index.backend.lf_map2(c, n);
+            if s == e {
+                continue;
+            }
+            let (s, e) = self.search_interval(&self.pattern, s, e, 1);
+            if s < e {
+                result.push((c, e - s));
+            }
+        }
+        result
+    }
+
+    /// List the characters that precede the pattern in the text, together
+    /// with the number of occurrences of the pattern extended by that
+    /// character.
+    ///
+    /// Terminator characters are not included.
+    pub(crate) fn left_extensions(&self) -> Vec<(B::T, u64)> {
+        let converter = self.index.backend.get_converter();
+        self.index
+            .backend
+            .interval_symbols(self.s, self.e)
+            .into_iter()
+            .map(|(c, count)| (converter.convert_inv(c), count))
+            .collect()
+    }
+
+    /// The interval `[s, e)` of the suffix array matching the pattern.
     pub(crate) fn get_range(&self) -> (u64, u64) {
         (self.s, self.e)
     }
 
+    /// A snapshot of the search result, detached from the index.
+    pub(crate) fn snapshot(&self) -> SearchSnapshot<B::T> {
+        SearchSnapshot::new(self.pattern.clone(), (self.s, self.e))
+    }
+
+    pub(crate) fn backend(&self) -> &'a B {
+        &self.index.backend
+    }
+
+    /// The pattern searched for.
+    pub(crate) fn pattern(&self) -> &[B::T] {
+        &self.pattern
+    }
+
+    /// The suffix array range matched by the pattern.
+    pub(crate) fn range(&self) -> (u64, u64) {
+        (self.s, self.e)
+    }
+
+    /// The length of the pattern searched for.
+    pub(crate) fn pattern_len(&self) -> u64 {
+        self.pattern.len() as u64
+    }
+
     /// Count the number of occurrences.
+    ///
+    /// Occurrences in texts marked as deleted are not counted.
     pub(crate) fn count(&self) -> u64 {
-        self.e - self.s
+        self.index.backend.live_count(self.s, self.e)
+    }
+
+    /// Whether the pattern occurs at least `n` times.
+    pub(crate) fn count_at_least(&self, n: u64) -> bool {
+        self.e - self.s >= n && self.count() >= n
     }
 
     /// Get an iterator that goes backwards through the text, producing
     /// [`Character`].
     pub(crate) fn iter_backward(&self, i: u64) -> impl Iterator<Item = B::T> + use<'a, B> {
-        let m = self.count();
-
-        debug_assert!(m > 0, "cannot iterate from empty search result");
-        debug_assert!(i < m, "{} is out of range", i);
-
-        debug_assert!(i < self.backend.len());
-        BackwardIteratorWrapper::new(self.backend, self.s + i)
+        BackwardIterator::new(self.backend(), self.row(i))
     }
 
-    // Get an iterator that goes forwards through the text, producing
+    /// Get an iterator that goes forwards through the text, producing
     /// [`Character`].
     pub(crate) fn iter_forward(&self, i: u64) -> impl Iterator<Item = B::T> + use<'a, B> {
-        let m = self.count();
+        ForwardIterator::new(self.backend(), self.row(i))
+    }
+
+    /// Like `iter_backward`, but end at the start of the text.
+    pub(crate) fn iter_backward_in_text(&self, i: u64) -> impl Iterator<Item = B::T> + use<'a, B> {
+        BackwardIterator::in_text(self.backend(), self.row(i))
+    }
+
+    /// Like `iter_forward`, but end at the terminator.
+    pub(crate) fn iter_forward_in_text(&self, i: u64) -> impl Iterator<Item = B::T> + use<'a, B> {
+        ForwardIterator::in_text(self.backend(), self.row(i))
+    }
+
+    // The BWT row of occurrence `i`.
+    fn row(&self, i: u64) -> u64 {
+        let m = self.e - self.s;
 
         debug_assert!(m > 0, "cannot iterate from empty search result");
         debug_assert!(i < m, "{} is out of range", i);
-        debug_assert!(i < self.backend.len());
+        debug_assert!(i < self.index.backend.len());
 
-        ForwardIteratorWrapper::new(self.backend, self.s + i)
+        self.s + i
     }
 }
 
@@ -131,54 +993,192 @@ impl<B> SearchWrapper<'_, B>
 where
     B: SearchIndexBackend + HasPosition,
 {
+    // Iterate over the occurrences that are not in deleted texts, as pairs
+    // of the offset in the search result and the text position.
+    fn occurrences(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.index
+            .backend
+            .get_sa_range(self.s, self.e, &self.pattern)
+            .into_iter()
+            .enumerate()
+            .map(|(i, position)| (i as u64, position))
+            .filter(|&(_, position)| !self.index.backend.is_deleted(position))
+    }
+
     /// List the position of all occurrences.
     pub(crate) fn locate(&self) -> Vec<u64> {
-        let mut results: Vec<u64> = Vec::with_capacity((self.e - self.s) as usize);
-        for k in self.s..self.e {
-            results.push(self.backend.get_sa(k));
+        #[cfg(feature = "std")]
+        if let Some(recorder) = &self.index.recorder {
+            return self.locate_recorded(recorder);
+        }
+        self.locate_unrecorded()
+    }
+
+    #[cfg(feature = "std")]
+    fn locate_recorded(&self, recorder: &Recorder) -> Vec<u64> {
+        let start = Instant::now();
+        let positions = self.locate_unrecorded();
+        let time = start.elapsed();
+        let (walk_steps, max_walk) = (self.s..self.e)
+            .map(|i| self.index.backend.get_sa_with_steps(i).1)
+            .fold((0, 0), |(total, max), steps| {
+                (total + steps, max.max(steps))
+            });
+        recorder.record(QueryRecord::Locate {
+            occurrences: self.e - self.s,
+            walk_steps,
+            max_walk,
+            time,
+        });
+        positions
+    }
+
+    fn locate_unrecorded(&self) -> Vec<u64> {
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &self.index.cache {
+            return cache.locate(self.s, self.e, || self.locate_uncached());
         }
+        self.locate_uncached()
+    }
+
+    fn locate_uncached(&self) -> Vec<u64> {
+        let mut results: Vec<u64> = Vec::with_capacity((self.e - self.s) as usize);
+        results.extend(self.occurrences().map(|(_, position)| position));
         results
     }
+
+    /// Fold `f` over the positions of the occurrences that are not in
+    /// deleted texts, in no particular order, without collecting them.
+    pub(crate) fn fold_positions<A, F>(&self, init: A, mut f: F) -> A
+    where
+        F: FnMut(A, u64) -> A,
+    {
+        let backend = &self.index.backend;
+        backend.fold_sa(self.s, self.e, &self.pattern, init, |acc, position| {
+            if backend.is_deleted(position) {
+                acc
+            } else {
+                f(acc, position)
+            }
+        })
+    }
+
+    /// Iterate over the occurrences that are not in deleted texts, as the
+    /// offset in the search result, the text position and, for multi-text
+    /// indexes, the id of the text containing it.
+    pub(crate) fn locate_each(&self) -> impl Iterator<Item = (u64, u64, Option<u64>)> + '_ {
+        self.occurrences()
+            .map(|(i, position)| (i, position, self.index.backend.doc_id(position)))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<B> SearchWrapper<'_, B>
+where
+    B: SearchIndexBackend + HasPosition + Sync,
+{
+    /// List the position of all occurrences, resolving them on the rayon
+    /// thread pool. The order is the same as that of `locate`.
+    pub(crate) fn locate_parallel(&self) -> Vec<u64> {
+        use rayon::prelude::*;
+        self.index
+            .backend
+            .get_sa_range_parallel(self.s, self.e, &self.pattern)
+            .into_par_iter()
+            .filter(|&position| !self.index.backend.is_deleted(position))
+            .collect()
+    }
 }
 
 /// An iterator that goes backwards through the text, producing [`Character`].
-pub(crate) struct BackwardIteratorWrapper<'a, B: SearchIndexBackend> {
+///
+/// Every index walks backwards through the text with this iterator, by
+/// repeated LF-mapping. It optionally ends at the start of the text instead
+/// of wrapping around.
+pub(crate) struct BackwardIterator<'a, B: SearchIndexBackend> {
     backend: &'a B,
     i: u64,
+    in_text: bool,
+    done: bool,
 }
 
-impl<'a, B: SearchIndexBackend> BackwardIteratorWrapper<'a, B> {
+impl<'a, B: SearchIndexBackend> BackwardIterator<'a, B> {
     pub(crate) fn new(backend: &'a B, i: u64) -> Self {
-        BackwardIteratorWrapper { backend, i }
+        BackwardIterator {
+            backend,
+            i,
+            in_text: false,
+            done: false,
+        }
+    }
+
+    /// Like `new`, but end at the start of the text.
+    pub(crate) fn in_text(backend: &'a B, i: u64) -> Self {
+        BackwardIterator {
+            in_text: true,
+            ..BackwardIterator::new(backend, i)
+        }
     }
 }
 
-impl<B: SearchIndexBackend> Iterator for BackwardIteratorWrapper<'_, B> {
+impl<B: SearchIndexBackend> Iterator for BackwardIterator<'_, B> {
     type Item = B::T;
     fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
         let c = self.backend.get_l(self.i);
+        if self.in_text && c.is_zero() {
+            self.done = true;
+            return None;
+        }
         self.i = self.backend.lf_map(self.i);
         Some(self.backend.get_converter().convert_inv(c))
     }
 }
 
 /// An iterator that goes forwards through the text, producing [`Character`].
-pub(crate) struct ForwardIteratorWrapper<'a, B: SearchIndexBackend> {
+///
+/// The counterpart of [`BackwardIterator`], by repeated inverse LF-mapping.
+/// It optionally ends at the terminator instead of wrapping around.
+pub(crate) struct ForwardIterator<'a, B: SearchIndexBackend> {
     backend: &'a B,
     i: u64,
+    in_text: bool,
+    done: bool,
 }
 
-impl<'a, B: SearchIndexBackend> ForwardIteratorWrapper<'a, B> {
+impl<'a, B: SearchIndexBackend> ForwardIterator<'a, B> {
     pub(crate) fn new(backend: &'a B, i: u64) -> Self {
-        ForwardIteratorWrapper { backend, i }
+        ForwardIterator {
+            backend,
+            i,
+            in_text: false,
+            done: false,
+        }
+    }
+
+    /// Like `new`, but end at the terminator.
+    pub(crate) fn in_text(backend: &'a B, i: u64) -> Self {
+        ForwardIterator {
+            in_text: true,
+            ..ForwardIterator::new(backend, i)
+        }
     }
 }
 
-impl<B: SearchIndexBackend> Iterator for ForwardIteratorWrapper<'_, B> {
+impl<B: SearchIndexBackend> Iterator for ForwardIterator<'_, B> {
     type Item = B::T;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
         let c = self.backend.get_f(self.i);
+        if self.in_text && c.is_zero() {
+            self.done = true;
+            return None;
+        }
         self.i = self.backend.fl_map(self.i);
         Some(self.backend.get_converter().convert_inv(c))
     }
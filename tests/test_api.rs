@@ -76,3 +76,717 @@ fn test_heap_size_trait_rlfm_index_count_only() {
     // any result will do for this test
     assert!(size(&index) > 0);
 }
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_indexes_are_send_sync() {
+    use fm_index::{
+        converter::RangeConverter, AutoFMIndexWithLocate, MultiTextFMIndexWithLocate, RIndex,
+        SlimFMIndex,
+    };
+
+    assert_send_sync::<FMIndex<u8, RangeConverter<u8>>>();
+    assert_send_sync::<FMIndexWithLocate<u8, RangeConverter<u8>>>();
+    assert_send_sync::<RLFMIndexWithLocate<u8, RangeConverter<u8>>>();
+    assert_send_sync::<SlimFMIndex<u8, RangeConverter<u8>>>();
+    #[cfg(feature = "std")]
+    {
+        use fm_index::{DiskFMIndex, MappedFMIndex};
+        assert_send_sync::<DiskFMIndex<u8, RangeConverter<u8>>>();
+        assert_send_sync::<MappedFMIndex<u8, RangeConverter<u8>>>();
+    }
+    assert_send_sync::<RIndex<u8, RangeConverter<u8>>>();
+    assert_send_sync::<MultiTextFMIndexWithLocate<u8, RangeConverter<u8>>>();
+    assert_send_sync::<AutoFMIndexWithLocate<u8, RangeConverter<u8>>>();
+}
+
+#[test]
+fn test_search_results_are_send_sync() {
+    use fm_index::{
+        converter::RangeConverter, AutoFMIndexSearchWithLocate, FMIndexSearch,
+        MultiTextFMIndexSearch, RLFMIndexSearch,
+    };
+
+    // so that a search result can be held across an `.await`
+    assert_send_sync::<FMIndexSearch<'static, u8, RangeConverter<u8>>>();
+    assert_send_sync::<RLFMIndexSearch<'static, u8, RangeConverter<u8>>>();
+    assert_send_sync::<MultiTextFMIndexSearch<'static, u8, RangeConverter<u8>>>();
+    assert_send_sync::<AutoFMIndexSearchWithLocate<'static, u8, RangeConverter<u8>>>();
+}
+
+#[test]
+fn test_shared_between_threads() {
+    use std::sync::Arc;
+
+    let text = "mississippi".repeat(100).into_bytes();
+    let index = Arc::new(FMIndexWithLocate::new(text, IdConverter::new::<u8>(), 2));
+    let handles = ["i", "ssi", "ppi", "z"]
+        .into_iter()
+        .map(|pattern| {
+            let index = Arc::clone(&index);
+            std::thread::spawn(move || {
+                let search = index.search(pattern);
+                (search.count(), search.locate().len() as u64)
+            })
+        })
+        .collect::<Vec<_>>();
+    let counts = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(counts, vec![(400, 400), (200, 200), (100, 100), (0, 0)]);
+}
+
+#[test]
+fn test_count_many() {
+    let text = "mississippi".as_bytes().to_vec();
+    let index = FMIndex::new(text.clone(), IdConverter::new::<u8>());
+    let patterns = ["i", "ssi", "ppi", "z", ""];
+    let expected = patterns
+        .iter()
+        .map(|pattern| index.search(pattern).count())
+        .collect::<Vec<_>>();
+    assert_eq!(expected, vec![4, 2, 1, 0, 12]);
+    assert_eq!(index.count_many(&patterns), expected);
+
+    let index = RLFMIndexWithLocate::new(text, IdConverter::new::<u8>(), 2);
+    assert_eq!(index.count_many(&patterns), expected);
+    #[cfg(feature = "rayon")]
+    assert_eq!(index.count_many_parallel(&patterns), expected);
+}
+
+#[test]
+fn test_count_many_lockstep() {
+    use fm_index::converter::RangeConverter;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let text = (0..5000)
+        .map(|_| b"abcd"[rng.gen_range(0, 4)])
+        .collect::<Vec<u8>>();
+    let mut patterns = (0..500)
+        .map(|_| {
+            let start = rng.gen_range(0, text.len());
+            let end = (start + rng.gen_range(0, 12)).min(text.len());
+            text[start..end].to_vec()
+        })
+        .collect::<Vec<_>>();
+    patterns.push(b"dddddddddd".to_vec());
+    patterns.push(b"abcz".to_vec());
+
+    let index = FMIndex::new(text.clone(), RangeConverter::new(b'a', b'z'));
+    let expected = index.count_many(&patterns);
+    // a large alphabet compared to the number of patterns
+    assert_eq!(index.count_many_lockstep(&patterns), expected);
+    assert_eq!(index.count_many_lockstep(&patterns[..10]), expected[..10]);
+    assert_eq!(index.count_many_lockstep::<Vec<u8>>(&[]), Vec::<u64>::new());
+
+    let index = FMIndex::new(text.clone(), RangeConverter::new(b'a', b'd'))
+        .with_kmer_table(3)
+        .with_occ_table(64);
+    assert_eq!(index.count_many_lockstep(&patterns[..500]), expected[..500]);
+
+    let index = RLFMIndexWithLocate::new(text, IdConverter::new::<u8>(), 2);
+    assert_eq!(index.count_many_lockstep(&patterns), expected);
+
+    // occurrences in deleted texts aren't counted
+    let mut index = fm_index::MultiTextFMIndex::from_texts(
+        vec![b"abcab".to_vec(), b"abxab".to_vec()],
+        RangeConverter::new(b'a', b'z'),
+    );
+    index.delete_doc(1);
+    let patterns = [&b"ab"[..], b"bxa", b"ca", b"b", b""];
+    assert_eq!(
+        index.count_many_lockstep(&patterns),
+        index.count_many(&patterns)
+    );
+    assert_eq!(index.count_many_lockstep(&patterns), vec![2, 0, 1, 2, 6]);
+}
+
+#[test]
+fn test_fold_positions() {
+    use fm_index::{converter::RangeConverter, CSAIndexWithLocate, RIndex, SearchWithLocate};
+
+    fn check<'a, S: SearchWithLocate<'a, u8>>(search: S) {
+        let mut expected = search.locate();
+        expected.sort_unstable();
+        let mut positions = search.fold_positions(Vec::new(), |mut positions, p| {
+            positions.push(p);
+            positions
+        });
+        positions.sort_unstable();
+        assert_eq!(positions, expected);
+        let mut count = 0;
+        search.for_each_position(|_| count += 1);
+        assert_eq!(count, search.count());
+    }
+
+    let text = b"mississippi".repeat(40);
+    let converter = RangeConverter::new(b'a', b'z');
+    let fm_index = FMIndexWithLocate::new(text.clone(), converter.clone(), 2);
+    let rlfm_index = RLFMIndexWithLocate::new(text.clone(), converter.clone(), 2);
+    let csa_index = CSAIndexWithLocate::new(text.clone(), converter.clone(), 2);
+    let r_index = RIndex::new(text, converter);
+    for pattern in ["i", "ssi", "mississippim", "x", ""] {
+        check(fm_index.search(pattern));
+        check(rlfm_index.search(pattern));
+        check(csa_index.search(pattern));
+        check(r_index.search(pattern));
+    }
+}
+
+#[test]
+fn test_clone_and_eq() {
+    use fm_index::{
+        converter::RangeConverter, CSAIndexWithLocate, MultiTextFMIndexWithLocate, RIndex,
+    };
+
+    fn check<I: Clone + PartialEq + std::fmt::Debug>(index: I, other: I) {
+        assert_eq!(index.clone(), index);
+        assert_ne!(index, other);
+        assert!(!format!("{:?}", index).is_empty());
+    }
+
+    let text = b"mississippi".to_vec();
+    let other = b"missouri".to_vec();
+    let converter = RangeConverter::new(b'a', b'z');
+    let new = |text: &Vec<u8>| FMIndexWithLocate::new(text.clone(), converter.clone(), 2);
+    check(new(&text), new(&other));
+    check(
+        RLFMIndex::new(text.clone(), converter.clone()),
+        RLFMIndex::new(other.clone(), converter.clone()),
+    );
+    check(
+        CSAIndexWithLocate::new(text.clone(), converter.clone(), 2),
+        CSAIndexWithLocate::new(other.clone(), converter.clone(), 2),
+    );
+    check(
+        RIndex::new(text.clone(), converter.clone()),
+        RIndex::new(other.clone(), converter.clone()),
+    );
+    check(
+        MultiTextFMIndexWithLocate::new(b"ab\0cd\0".to_vec(), converter.clone(), 2),
+        MultiTextFMIndexWithLocate::new(b"ab\0ce\0".to_vec(), converter.clone(), 2),
+    );
+    // the sampling level is part of the index
+    assert_ne!(
+        new(&text),
+        FMIndexWithLocate::new(text.clone(), converter.clone(), 1)
+    );
+
+    let index = new(&text);
+    let search = index.search("ss");
+    let clone = search.clone();
+    assert_eq!(clone.locate(), search.locate());
+    assert_eq!(clone.search("i").count(), 2);
+    assert!(format!("{:?}", search).contains("range"));
+}
+
+#[test]
+fn test_str_text_and_patterns() {
+    let converter = IdConverter::new::<u8>();
+    let index = FMIndexWithLocate::new("mississippi", converter.clone(), 2);
+    assert_eq!(
+        index,
+        FMIndexWithLocate::new(String::from("mississippi"), converter.clone(), 2)
+    );
+    assert_eq!(
+        index,
+        FMIndexWithLocate::new(&b"mississippi"[..], converter.clone(), 2)
+    );
+    assert_eq!(
+        index,
+        FMIndexWithLocate::new(b"mississippi".to_vec(), converter.clone(), 2)
+    );
+
+    let pattern = String::from("ssi");
+    assert_eq!(index.search(&pattern).count(), 2);
+    assert_eq!(index.search("ssi").search("m").count(), 0);
+    assert!(RLFMIndex::try_new("", converter).is_err());
+}
+
+#[test]
+fn test_borrowed_text() {
+    use fm_index::IntoText;
+    use std::borrow::Cow;
+
+    let converter = IdConverter::new::<u8>();
+    let index = FMIndexWithLocate::new("mississippi", converter.clone(), 2);
+
+    // a terminated text is borrowed as it is
+    let terminated = b"mississippi\0".to_vec();
+    assert!(matches!((&terminated).into_text(), Cow::Borrowed(_)));
+    assert_eq!(
+        index,
+        FMIndexWithLocate::new(&terminated, converter.clone(), 2)
+    );
+    assert_eq!(
+        index,
+        FMIndexWithLocate::new(&terminated[..], converter.clone(), 2)
+    );
+
+    let text = String::from("mississippi");
+    assert_eq!(index, FMIndexWithLocate::new(&text, converter.clone(), 2));
+    assert_eq!(
+        index,
+        FMIndexWithLocate::new(b"mississippi", converter.clone(), 2)
+    );
+    assert_eq!(
+        index,
+        FMIndexWithLocate::new(*b"mississippi", converter.clone(), 2)
+    );
+    assert_eq!(
+        index,
+        FMIndexWithLocate::new(Cow::Borrowed(text.as_bytes()), converter.clone(), 2)
+    );
+    assert_eq!(
+        index,
+        FMIndexWithLocate::new(text.into_bytes().into_boxed_slice(), converter, 2)
+    );
+}
+
+#[test]
+fn test_search_accessors() {
+    use fm_index::{converter::RangeConverter, AutoFMIndex, Search};
+
+    fn check<'a, S: Search<'a, u8>>(search: S) {
+        assert_eq!(search.pattern(), b"ssi");
+        let (s, e) = search.range();
+        assert_eq!(e - s, search.count());
+        assert_eq!(search.index_len(), 12);
+        let refined = search.search("i");
+        assert_eq!(refined.pattern(), b"issi");
+        let (rs, re) = refined.range();
+        assert_eq!(re - rs, 2);
+        let extended = search.extend_backward(b'i');
+        assert_eq!(extended.pattern(), b"issi");
+        assert_eq!(extended.range(), refined.range());
+    }
+
+    let converter = RangeConverter::new(b'a', b'z');
+    let index = FMIndex::new("mississippi", converter.clone());
+    check(index.search("ssi"));
+    check(SearchIndex::search(&index, "ssi"));
+    let auto = AutoFMIndex::new("mississippi", converter);
+    check(auto.search("ssi"));
+    assert_eq!(auto.search("ssi").range(), index.search("ssi").range());
+}
+
+#[test]
+fn test_extend_backward() {
+    use fm_index::{converter::RangeConverter, AutoFMIndex};
+
+    let converter = RangeConverter::new(b'a', b'z');
+    let index = FMIndex::new("mississippi", converter.clone());
+    let rlfm_index = RLFMIndex::new("mississippi", converter.clone());
+    let auto = AutoFMIndex::new("mississippi", converter);
+    let pattern = b"ssissip";
+    let mut search = index.search("");
+    let mut rlfm_search = rlfm_index.search("");
+    let mut auto_search = auto.search("");
+    for (i, &c) in pattern.iter().enumerate().rev() {
+        search = search.extend_backward(c);
+        rlfm_search = rlfm_search.extend_backward(c);
+        auto_search = auto_search.extend_backward(c);
+        assert_eq!(search.pattern(), &pattern[i..]);
+        assert_eq!(search.range(), index.search(&pattern[i..]).range());
+        assert_eq!(rlfm_search.count(), search.count());
+        assert_eq!(auto_search.range(), search.range());
+    }
+    assert_eq!(search.count(), 1);
+    assert_eq!(search.extend_backward(b'm').count(), 0);
+}
+
+#[test]
+fn test_owned_search() {
+    use fm_index::{converter::RangeConverter, AutoFMIndex, OwnedSearch};
+    use std::sync::Arc;
+
+    type Index = FMIndexWithLocate<u8, RangeConverter<u8>>;
+
+    fn find(index: &Arc<Index>, pattern: &str) -> OwnedSearch<Index, u8> {
+        index.search_owned(pattern)
+    }
+
+    let index = Arc::new(FMIndexWithLocate::new(
+        "mississippi",
+        RangeConverter::new(b'a', b'z'),
+        2,
+    ));
+    let search = find(&index, "ssi");
+    assert_eq!(search.pattern(), b"ssi");
+    let handle = std::thread::spawn(move || {
+        let mut positions = search.get().locate();
+        positions.sort_unstable();
+        (positions, search.search("i").get().count())
+    });
+    assert_eq!(handle.join().unwrap(), (vec![2, 5], 2));
+
+    let snapshot = find(&index, "ss").into_snapshot();
+    assert_eq!(index.resume_owned(snapshot).get().count(), 2);
+
+    let auto = Arc::new(AutoFMIndex::new(
+        "mississippi",
+        RangeConverter::new(b'a', b'z'),
+    ));
+    assert_eq!(auto.search_owned("i").get().count(), 4);
+}
+
+#[test]
+fn test_dyn_search_index() {
+    use fm_index::{
+        converter::RangeConverter, AutoFMIndexWithLocate, CSAIndex, DynSearchIndex, RIndex,
+    };
+
+    let text = "mississippi";
+    let converter = RangeConverter::new(b'a', b'z');
+    let indexes: Vec<Box<dyn DynSearchIndex<u8>>> = vec![
+        Box::new(FMIndex::new(text, converter.clone())),
+        Box::new(RLFMIndexWithLocate::new(text, converter.clone(), 2)),
+        Box::new(CSAIndex::new(text, converter.clone())),
+        Box::new(RIndex::new(text, converter.clone())),
+        Box::new(AutoFMIndexWithLocate::new(text, converter, 1)),
+    ];
+    for index in &indexes {
+        assert_eq!(index.len(), 12);
+        assert_eq!(index.count(b"ssi"), 2);
+        let search = index.search(b"ssi");
+        assert_eq!(search.pattern(), b"ssi");
+        let refined = search.search(b"i");
+        assert_eq!(refined.pattern(), b"issi");
+        assert_eq!(refined.count(), 2);
+        assert_eq!(search.extend_backward(b'i').pattern(), b"issi");
+        assert_eq!(search.snapshot().pattern(), b"ssi");
+        match index.locate(b"ssi") {
+            Some(mut positions) => {
+                assert!(index.supports_locate());
+                positions.sort_unstable();
+                assert_eq!(positions, vec![2, 5]);
+            }
+            None => assert!(!index.supports_locate()),
+        }
+    }
+    assert_eq!(
+        indexes
+            .iter()
+            .filter(|index| index.supports_locate())
+            .count(),
+        3
+    );
+}
+
+#[test]
+fn test_text_len() {
+    use fm_index::{converter::RangeConverter, AutoFMIndex, DynSearchIndex, MultiTextFMIndex};
+
+    let converter = RangeConverter::new(b'a', b'z');
+    let index = FMIndexWithLocate::new("mississippi", converter.clone(), 2);
+    assert_eq!(index.len(), 12);
+    assert_eq!(index.text_len(), 11);
+    assert_eq!(SearchIndex::text_len(&index), 11);
+    assert_eq!(DynSearchIndex::text_len(&index), 11);
+    assert!(index
+        .search("i")
+        .locate()
+        .iter()
+        .all(|&p| p < index.text_len()));
+    // a terminator in the text isn't counted twice
+    assert_eq!(
+        FMIndex::new("mississippi\0", converter.clone()).text_len(),
+        11
+    );
+
+    let multi = MultiTextFMIndex::new("ab\0cde\0", converter.clone());
+    assert_eq!(multi.text_len(), 6);
+    assert_eq!(AutoFMIndex::new("mississippi", converter).text_len(), 11);
+}
+
+#[test]
+fn test_checked_queries() {
+    use fm_index::{converter::RangeConverter, AutoFMIndexWithLocate, Error, RIndex};
+
+    let converter = RangeConverter::new(b'a', b'z');
+    let index = FMIndexWithLocate::new("mississippi", converter.clone(), 2);
+    let out_of_range = Error::CharacterOutOfRange {
+        position: 1,
+        character: u64::from(b'A'),
+    };
+    assert_eq!(index.try_search("sA").err(), Some(out_of_range.clone()));
+    assert_eq!(index.try_locate("sA"), Err(out_of_range.clone()));
+    let mut positions = index.try_locate("ssi").unwrap();
+    positions.sort_unstable();
+    assert_eq!(positions, vec![2, 5]);
+
+    let search = index.try_search("ssi").unwrap();
+    assert_eq!(search.try_search("iA").err(), Some(out_of_range));
+    assert_eq!(search.try_search("i").unwrap().count(), 2);
+    assert!(search.try_iter_backward(1).is_ok());
+    assert!(search.try_iter_forward(1).is_ok());
+    let err = Error::OffsetOutOfRange { offset: 2, len: 2 };
+    assert_eq!(search.try_iter_backward(2).err(), Some(err.clone()));
+    assert_eq!(search.try_iter_forward(2).err(), Some(err));
+
+    let r_index = RIndex::new("mississippi", converter.clone());
+    assert!(r_index.try_locate("~").is_err());
+    let auto = AutoFMIndexWithLocate::new("mississippi", converter, 2);
+    assert!(auto.try_locate("~").is_err());
+    assert_eq!(auto.try_locate("ssi").unwrap().len(), 2);
+    let search = auto.try_search("p").unwrap();
+    assert!(search.try_search("Z").is_err());
+    assert!(search.try_iter_forward(2).is_err());
+    assert_eq!(
+        search
+            .try_iter_forward(0)
+            .unwrap()
+            .take(2)
+            .collect::<Vec<_>>()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_iter_in_text() {
+    use fm_index::{converter::RangeConverter, AutoFMIndex, MultiTextFMIndexWithLocate};
+
+    let converter = RangeConverter::new(b'a', b'z');
+    let index = FMIndexWithLocate::new("mississippi", converter.clone(), 2);
+    let search = index.search("ssi");
+    for i in 0..search.count() {
+        let mut before = search.iter_backward_in_text(i).collect::<Vec<_>>();
+        before.reverse();
+        let after = search.iter_forward_in_text(i).collect::<Vec<_>>();
+        let position = search.locate()[i as usize] as usize;
+        assert_eq!(before, b"mississippi"[..position]);
+        assert_eq!(after, b"mississippi"[position..]);
+    }
+
+    let multi = MultiTextFMIndexWithLocate::new("ab\0cde\0fg\0", converter.clone(), 2);
+    let search = multi.search("de");
+    assert_eq!(search.iter_backward_in_text(0).collect::<Vec<_>>(), b"c");
+    assert_eq!(search.iter_forward_in_text(0).collect::<Vec<_>>(), b"de");
+
+    let auto = AutoFMIndex::new("mississippi", converter);
+    let search = auto.search("m");
+    assert_eq!(search.iter_backward_in_text(0).count(), 0);
+    assert_eq!(search.iter_forward_in_text(0).count(), 11);
+}
+
+#[test]
+fn test_iter_range() {
+    use fm_index::{converter::RangeConverter, RIndex};
+
+    let text = "mississippi";
+    let converter = RangeConverter::new(b'a', b'z');
+    let index = FMIndexWithLocate::new(text, converter.clone(), 2);
+    let iter = index.iter_range(2, 5);
+    assert_eq!(iter.len(), 5);
+    assert_eq!(iter.collect::<Vec<_>>(), b"ssiss");
+    assert_eq!(index.iter_range(0, 4).rev().collect::<Vec<_>>(), b"ssim");
+    assert_eq!(
+        index.iter_range(0, 4).map(char::from).collect::<String>(),
+        "miss"
+    );
+    assert_eq!(index.iter_range(11, 0).len(), 0);
+
+    let r_index = RIndex::new(text, converter);
+    let mut iter = r_index.iter_range(8, 3);
+    assert_eq!(iter.next(), Some(b'p'));
+    assert_eq!(iter.next_back(), Some(b'i'));
+    assert_eq!(iter.collect::<Vec<_>>(), b"p");
+}
+
+#[test]
+fn test_search_index_builder() {
+    use fm_index::{converter::RangeConverter, Error, IndexKind, SearchIndexBuilder};
+
+    let builder = SearchIndexBuilder::with_converter(RangeConverter::new(b'a', b'z'));
+    let indexes = vec![
+        builder.clone().build("mississippi"),
+        builder.clone().sampling_level(1).build("mississippi"),
+        builder.clone().kind(IndexKind::RLFM).build("mississippi"),
+        builder
+            .clone()
+            .kind(IndexKind::FM)
+            .sampling_level(0)
+            .build("mississippi"),
+    ];
+    for index in &indexes {
+        assert_eq!(index.count(b"ssi"), 2);
+        assert_eq!(index.text_len(), 11);
+    }
+    assert_eq!(
+        indexes
+            .iter()
+            .map(|i| i.supports_locate())
+            .collect::<Vec<_>>(),
+        vec![false, true, false, true]
+    );
+
+    let dna = SearchIndexBuilder::with_converter(RangeConverter::new(b'a', b'd'))
+        .sampling_level(2)
+        .build("abcdabcd");
+    let mut positions = dna.locate(b"bc").unwrap();
+    positions.sort_unstable();
+    assert_eq!(positions, vec![1, 5]);
+
+    assert_eq!(builder.clone().try_build("").err(), Some(Error::EmptyText));
+    assert!(builder.try_build("mississippi").is_ok());
+}
+
+#[test]
+fn test_dyn_search_iter() {
+    use fm_index::{converter::RangeConverter, DynSearchIndex, MultiTextFMIndex};
+
+    let converter = RangeConverter::new(b'a', b'z');
+    let index: Box<dyn DynSearchIndex<u8>> =
+        Box::new(FMIndexWithLocate::new("mississippi", converter.clone(), 2));
+    let search = index.search(b"ssi");
+    let positions = search.locate().unwrap();
+    for i in 0..search.count() {
+        let position = positions[i as usize] as usize;
+        let mut before = search.iter_backward(i).collect::<Vec<_>>();
+        before.reverse();
+        assert_eq!(before, b"mississippi"[..position]);
+        let after = search.iter_forward(i).collect::<Vec<_>>();
+        assert_eq!(after, b"mississippi"[position..]);
+    }
+
+    let multi: Box<dyn DynSearchIndex<u8>> =
+        Box::new(MultiTextFMIndex::new("ab\0cde\0fg\0", converter));
+    let search = multi.search(b"d");
+    assert_eq!(search.iter_backward(0).collect::<Vec<_>>(), b"c");
+    assert_eq!(search.iter_forward(0).collect::<Vec<_>>(), b"de");
+}
+
+#[test]
+fn test_count_at_least() {
+    let text = "mississippi".as_bytes().to_vec();
+    let index = FMIndex::new(text.clone(), IdConverter::new::<u8>());
+    let search = index.search("ssi");
+    assert!(search.count_at_least(0));
+    assert!(search.count_at_least(2));
+    assert!(!search.count_at_least(3));
+    assert!(search.is_present());
+    assert!(!index.search("pis").is_present());
+
+    let patterns = ["i", "ssi", "ppi", "z", "", "mississippi", "sissi"];
+    for n in 0..6 {
+        let expected = patterns
+            .iter()
+            .map(|pattern| index.search(pattern).count() >= n)
+            .collect::<Vec<_>>();
+        assert_eq!(index.count_many_at_least(&patterns, n), expected);
+
+        let rlfm_index = RLFMIndexWithLocate::new(text.clone(), IdConverter::new::<u8>(), 2);
+        assert_eq!(rlfm_index.count_many_at_least(&patterns, n), expected);
+
+        let kmer_index = FMIndex::new(text.clone(), IdConverter::new::<u8>()).with_kmer_table(2);
+        assert_eq!(kmer_index.count_many_at_least(&patterns, n), expected);
+    }
+}
+
+#[test]
+fn test_size_breakdown() {
+    use fm_index::{MultiTextFMIndexWithLocate, SizeBreakdown};
+
+    fn names(breakdown: &SizeBreakdown) -> Vec<&str> {
+        breakdown
+            .components()
+            .iter()
+            .map(|&(name, _)| name)
+            .collect()
+    }
+
+    let text = "mississippi".repeat(100).into_bytes();
+    let index = FMIndexWithLocate::new(text.clone(), IdConverter::new::<u8>(), 2);
+    let breakdown = index.size_breakdown();
+    assert_eq!(names(&breakdown), vec!["bwt", "cs", "suffix_array"]);
+    assert_eq!(breakdown.total(), index.heap_size());
+    let sparse = FMIndexWithLocate::new(text.clone(), IdConverter::new::<u8>(), 4);
+    assert!(sparse.size_breakdown().get("suffix_array") < breakdown.get("suffix_array"));
+    assert_eq!(sparse.size_breakdown().get("bwt"), breakdown.get("bwt"));
+
+    let index = FMIndex::new(text.clone(), IdConverter::new::<u8>()).with_kmer_table(1);
+    let breakdown = index.size_breakdown();
+    assert_eq!(names(&breakdown), vec!["bwt", "cs", "kmers"]);
+    assert_eq!(breakdown.total(), index.heap_size());
+
+    let index = RLFMIndexWithLocate::new(text.clone(), IdConverter::new::<u8>(), 2);
+    let breakdown = index.size_breakdown();
+    assert_eq!(names(&breakdown), vec!["bwt", "runs", "cs", "suffix_array"]);
+    assert_eq!(breakdown.total(), index.heap_size());
+
+    let mut texts = text.clone();
+    texts.push(0);
+    texts.extend_from_slice(b"banana\0");
+    let index = MultiTextFMIndexWithLocate::new(texts, IdConverter::new::<u8>(), 2);
+    let breakdown = index.size_breakdown();
+    assert!(breakdown.get("documents").unwrap() > 0);
+    assert_eq!(breakdown.total(), index.heap_size());
+    let report = breakdown.to_string();
+    let total = index.heap_size().to_string();
+    assert_eq!(
+        report
+            .lines()
+            .last()
+            .unwrap()
+            .split_whitespace()
+            .collect::<Vec<_>>(),
+        vec!["total", &total, "bytes"]
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_query_stats() {
+    use fm_index::QueryRecord;
+
+    let text = "mississippi".repeat(10).into_bytes();
+    let index = FMIndexWithLocate::new(text.clone(), IdConverter::new::<u8>(), 2);
+    assert_eq!(index.query_stats(), None);
+
+    let index = index.with_instrumentation();
+    assert_eq!(index.search("ssi").count(), 20);
+    assert_eq!(index.search("xssi").count(), 0);
+    let mut positions = index.search("ppi").locate();
+    positions.sort();
+    assert_eq!(positions, (0..10).map(|i| 11 * i + 8).collect::<Vec<_>>());
+
+    let stats = index.query_stats().unwrap();
+    assert_eq!(stats.search_count(), 3);
+    assert_eq!(stats.locate_count(), 1);
+    // "xssi" stops after the "x", when nothing is left
+    assert_eq!(stats.search_steps(), 3 + 4 + 3);
+    assert_eq!(stats.located(), 10);
+    assert!(stats.walk_steps() >= stats.max_walk());
+    assert!(stats.mean_walk() <= stats.max_walk() as f64);
+    match &stats.records()[1] {
+        QueryRecord::Search {
+            pattern_len,
+            steps,
+            occurrences,
+            ..
+        } => assert_eq!((*pattern_len, *steps, *occurrences), (4, 4, 0)),
+        record => panic!("unexpected record {:?}", record),
+    }
+    assert!(stats.to_string().starts_with("search"));
+
+    index.reset_query_stats();
+    assert_eq!(index.query_stats().unwrap().records(), &[]);
+
+    // all positions are sampled, so there is nothing to walk
+    let index =
+        FMIndexWithLocate::new(text.clone(), IdConverter::new::<u8>(), 0).with_instrumentation();
+    index.search("ssi").locate();
+    let stats = index.query_stats().unwrap();
+    assert_eq!((stats.walk_steps(), stats.max_walk()), (0, 0));
+
+    // characters found in the k-mer table take no steps
+    let index = FMIndex::new(text, IdConverter::new::<u8>())
+        .with_kmer_table(2)
+        .with_instrumentation();
+    index.search("ssi");
+    assert_eq!(index.query_stats().unwrap().search_steps(), 1);
+}
@@ -0,0 +1,132 @@
+#![cfg(feature = "arrow")]
+
+use std::sync::Arc;
+
+use arrow_array::{
+    Array, BinaryArray, Int32Array, LargeStringArray, RecordBatch, StringArray, StringViewArray,
+};
+use fm_index::converter::IdConverter;
+use fm_index::{ArrowColumnBuilder, Error};
+
+fn sorted(mut rows: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    rows.sort();
+    rows
+}
+
+#[test]
+fn test_arrow_rows() {
+    let mut builder = ArrowColumnBuilder::with_converter(IdConverter::new::<u8>());
+    builder
+        .push_array(&StringArray::from(vec![
+            Some("apple"),
+            None,
+            Some("banana"),
+        ]))
+        .unwrap();
+    builder
+        .push_array(&BinaryArray::from(vec![&b"cherry"[..], b"", b"grape"]))
+        .unwrap();
+    builder
+        .push_array(&LargeStringArray::from(vec!["pineapple"]))
+        .unwrap();
+    builder
+        .push_array(&StringViewArray::from(vec![Some("papaya"), None, Some("")]))
+        .unwrap();
+    assert_eq!(builder.rows(), 10);
+
+    let index = builder.build_with_locate(1).unwrap();
+    // the empty rows at the end are left out
+    assert_eq!(index.doc_count(), 8);
+    assert_eq!(
+        sorted(index.search("ap").locate_in_docs()),
+        vec![(0, 0), (5, 2), (6, 4), (7, 1)]
+    );
+    assert_eq!(index.search("an").count(), 2);
+
+    let mut builder = ArrowColumnBuilder::with_converter(IdConverter::new::<u8>());
+    builder
+        .push_array(&StringArray::from(vec!["apple", "banana"]))
+        .unwrap();
+    assert_eq!(builder.build().unwrap().search("an").count(), 2);
+}
+
+#[test]
+fn test_arrow_errors() {
+    let mut builder = ArrowColumnBuilder::with_converter(IdConverter::new::<u8>());
+    builder.push_array(&StringArray::from(vec!["a"])).unwrap();
+    // a failed push adds no rows
+    assert!(builder
+        .push_array(&BinaryArray::from(vec![&b"b"[..], b"c\0"]))
+        .is_err());
+    assert!(builder.push_array(&Int32Array::from(vec![1])).is_err());
+    assert_eq!(builder.rows(), 1);
+    let batch = RecordBatch::try_from_iter([(
+        "text",
+        Arc::new(StringArray::from(vec!["b"])) as Arc<dyn Array>,
+    )])
+    .unwrap();
+    assert!(builder.push_column(&batch, "missing").is_err());
+    builder.push_column(&batch, "text").unwrap();
+    assert_eq!(builder.rows(), 2);
+    assert_eq!(builder.build().unwrap().doc_count(), 2);
+
+    let mut builder = ArrowColumnBuilder::with_converter(IdConverter::new::<u8>());
+    builder
+        .push_array(&StringArray::from(vec![None, Some("")]))
+        .unwrap();
+    assert_eq!(builder.build().err(), Some(Error::EmptyText));
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn test_parquet() {
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+    use std::fs::File;
+
+    let ids = Arc::new(Int32Array::from(vec![1, 2, 3])) as Arc<dyn Array>;
+    let titles = Arc::new(StringArray::from(vec![
+        Some("The Hobbit"),
+        None,
+        Some("The Silmarillion"),
+    ])) as Arc<dyn Array>;
+    // a column whose name starts with that of the indexed one
+    let subtitles = Arc::new(StringArray::from(vec!["x", "y", "z"])) as Arc<dyn Array>;
+    let batch = RecordBatch::try_from_iter([("id", ids), ("title", titles), ("titles", subtitles)])
+        .unwrap();
+
+    let path = std::env::temp_dir().join(format!("fm-index-arrow-{}", std::process::id()));
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_max_row_group_size(2)
+        .build();
+    let mut writer = ArrowWriter::try_new(
+        File::create(&path).unwrap(),
+        batch.schema(),
+        Some(properties),
+    )
+    .unwrap();
+    writer.write(&batch).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let mut builder = ArrowColumnBuilder::with_converter(IdConverter::new::<u8>());
+    builder
+        .push_parquet(File::open(&path).unwrap(), "title")
+        .unwrap();
+    assert_eq!(builder.rows(), 6);
+    assert!(builder
+        .push_parquet(File::open(&path).unwrap(), "missing")
+        .is_err());
+    assert!(builder
+        .push_parquet(File::open(&path).unwrap(), "id")
+        .is_err());
+    std::fs::remove_file(&path).unwrap();
+    let index = builder.build_with_locate(0).unwrap();
+    assert_eq!(
+        sorted(index.search("The ").locate_in_docs()),
+        vec![(0, 0), (2, 0), (3, 0), (5, 0)]
+    );
+    assert_eq!(index.search("x").count(), 0);
+}
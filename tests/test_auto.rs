@@ -0,0 +1,77 @@
+use fm_index::converter::RangeConverter;
+use fm_index::{
+    AutoFMIndex, AutoFMIndexWithLocate, FMIndexWithLocate, IndexKind, Search, SearchIndex,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[test]
+fn test_choose() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let random = (0..10000)
+        .map(|_| rng.gen_range(b'a', b'z' + 1))
+        .collect::<Vec<u8>>();
+    let repetitive = b"the quick brown fox jumps over the lazy dog".repeat(200);
+    let dna = (0..10000)
+        .map(|_| b"ABCD"[rng.gen_range(0, 4)])
+        .collect::<Vec<u8>>();
+
+    let converter = RangeConverter::new(b' ', b'z');
+    assert_eq!(IndexKind::choose(&random, &converter), IndexKind::FM);
+    assert_eq!(IndexKind::choose(&repetitive, &converter), IndexKind::RLFM);
+    assert_eq!(
+        IndexKind::choose(&dna, &RangeConverter::new(b'A', b'D')),
+        IndexKind::DNA
+    );
+    assert_eq!(
+        AutoFMIndex::new(repetitive, converter).kind(),
+        IndexKind::RLFM
+    );
+}
+
+#[test]
+fn test_search() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let random = (0..5000)
+        .map(|_| rng.gen_range(b'a', b'z' + 1))
+        .collect::<Vec<u8>>();
+    let repetitive = b"the quick brown fox jumps over the lazy dog".repeat(100);
+    for (text, kind) in [(random, IndexKind::FM), (repetitive, IndexKind::RLFM)] {
+        let converter = RangeConverter::new(b' ', b'z');
+        let index = AutoFMIndexWithLocate::new(text.clone(), converter.clone(), 2);
+        let fm_index = FMIndexWithLocate::new(text.clone(), converter, 2);
+        assert_eq!(index.kind(), kind);
+        assert_eq!(index.len(), fm_index.len());
+        assert_eq!(index.bwt(), fm_index.bwt());
+        for pattern in ["o", "the", "fox j", "qu", "zz"] {
+            let search = index.search(pattern);
+            let fm_search = fm_index.search(pattern);
+            assert_eq!(search.count(), fm_search.count());
+            assert_eq!(search.extensions(), fm_search.extensions());
+            let mut positions = search.locate();
+            positions.sort();
+            let mut fm_positions = fm_search.locate();
+            fm_positions.sort();
+            assert_eq!(positions, fm_positions);
+            for m in search.matches().take(5) {
+                let start = m.position() as usize;
+                assert_eq!(
+                    m.iter_forward().take(pattern.len()).collect::<Vec<_>>(),
+                    text[start..start + pattern.len()]
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_search_index() {
+    fn count<I: SearchIndex<u8>>(index: &I, pattern: &str) -> u64 {
+        index.search(pattern).count()
+    }
+    let text = b"ABCDABCDDCBA".to_vec();
+    let index = AutoFMIndex::new(text, RangeConverter::new(b'A', b'D'));
+    assert_eq!(index.kind(), IndexKind::DNA);
+    assert_eq!(count(&index, "ABCD"), 2);
+    assert_eq!(count(&index, "DC"), 1);
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "capi")]
+
+use fm_index::capi::*;
+use std::ffi::CString;
+use std::ptr;
+
+#[test]
+fn test_capi() {
+    let text = b"mississippi";
+    unsafe {
+        let index = fm_index_new(text.as_ptr(), text.len(), 2);
+        assert!(!index.is_null());
+        assert_eq!(fm_index_len(index), 12);
+        assert_eq!(fm_index_count(index, b"ss".as_ptr(), 2), 2);
+        assert_eq!(fm_index_count(index, b"z".as_ptr(), 1), 0);
+        assert_eq!(fm_index_count(index, ptr::null(), 0), 12);
+
+        let mut out = [0u64; 4];
+        let count = fm_index_locate(index, b"i".as_ptr(), 1, out.as_mut_ptr(), out.len());
+        assert_eq!(count, 4);
+        out.sort();
+        assert_eq!(out, [1, 4, 7, 10]);
+
+        let mut out = [u64::MAX; 2];
+        let count = fm_index_locate(index, b"i".as_ptr(), 1, out.as_mut_ptr(), 1);
+        assert_eq!(count, 4);
+        assert!([1, 4, 7, 10].contains(&out[0]));
+        assert_eq!(out[1], u64::MAX);
+        assert_eq!(
+            fm_index_locate(index, b"i".as_ptr(), 1, ptr::null_mut(), 0),
+            4
+        );
+
+        let mut data = ptr::null_mut();
+        let mut len = 0;
+        assert_eq!(fm_index_save_bytes(index, &mut data, &mut len), 0);
+        let copy = fm_index_load_bytes(data, len);
+        fm_index_bytes_free(data, len);
+        assert!(!copy.is_null());
+        assert_eq!(fm_index_count(copy, b"ssi".as_ptr(), 3), 2);
+        fm_index_free(copy);
+
+        let file = std::env::temp_dir().join(format!("fm-index-capi-{}", std::process::id()));
+        let path = CString::new(file.to_str().unwrap()).unwrap();
+        assert_eq!(fm_index_save(index, path.as_ptr()), 0);
+        let loaded = fm_index_load(path.as_ptr());
+        assert!(!loaded.is_null());
+        assert_eq!(fm_index_count(loaded, b"p".as_ptr(), 1), 2);
+        fm_index_free(loaded);
+        std::fs::remove_file(&file).unwrap();
+
+        fm_index_free(index);
+        fm_index_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn test_capi_errors() {
+    unsafe {
+        assert!(fm_index_new(ptr::null(), 0, 0).is_null());
+        assert!(fm_index_new(b"a\0b".as_ptr(), 3, 0).is_null());
+        assert!(fm_index_new(b"abc".as_ptr(), 3, 2).is_null());
+        assert!(fm_index_load_bytes(b"garbage".as_ptr(), 7).is_null());
+        let path = CString::new("/nonexistent/fm_index/index").unwrap();
+        assert!(fm_index_load(path.as_ptr()).is_null());
+        let index = fm_index_new(b"abc".as_ptr(), 3, 0);
+        assert_eq!(fm_index_save(index, path.as_ptr()), -1);
+        fm_index_free(index);
+    }
+}
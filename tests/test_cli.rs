@@ -0,0 +1,55 @@
+#![cfg(feature = "cli")]
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fm-index-cli-{}-{}", std::process::id(), name))
+}
+
+fn fm_index(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fm-index"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(args: &[&str]) -> String {
+    let output = fm_index(args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_cli() {
+    let text = temp_path("text");
+    let index = temp_path("index");
+    std::fs::write(&text, "mississippi").unwrap();
+    let (text_arg, index_arg) = (text.to_str().unwrap(), index.to_str().unwrap());
+
+    stdout(&["build", text_arg, "-o", index_arg, "--level", "1"]);
+    assert_eq!(stdout(&["count", index_arg, "ssi", "z", "i"]), "2\n0\n4\n");
+    assert_eq!(stdout(&["locate", index_arg, "i"]), "1\n4\n7\n10\n");
+    assert_eq!(
+        stdout(&["locate", index_arg, "ss", "--context", "2"]),
+        "2\tmi[ss]is\n5\tsi[ss]ip\n"
+    );
+    assert_eq!(stdout(&["extract", index_arg, "2", "6"]), "ssis");
+    let stats = stdout(&["stats", index_arg]);
+    assert!(stats.contains("text length:     11\n"), "{}", stats);
+    assert!(stats.contains("sampling level:  1\n"), "{}", stats);
+    assert!(stats.contains("distinct bytes:  4\n"), "{}", stats);
+
+    let output = fm_index(&["extract", index_arg, "5", "50"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("out of range"));
+
+    std::fs::remove_file(&text).unwrap();
+    std::fs::remove_file(&index).unwrap();
+    let output = fm_index(&["count", index_arg, "i"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,290 @@
+use fm_index::converter::{
+    AmbiguityPolicy, CaseFoldConverter, Converter, DNAConverter, DenseConverter,
+    FrequencyConverter, IdConverter, InvalidBase, InvalidCharacter, ProteinConverter,
+    RangeConverter, SequenceConverter,
+};
+use fm_index::{DNAFMIndex, FMIndex, FMIndexWithLocate, MultiTextFMIndexWithLocate};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[test]
+fn test_frequency_converter() {
+    let text = b"abracadabra".to_vec();
+    let converter = FrequencyConverter::from_text(&text);
+    // a: 5, b: 2, r: 2, c: 1, d: 1, and one code for absent characters
+    assert_eq!(converter.len(), 7);
+    let codes = b"abrcd"
+        .iter()
+        .map(|&c| converter.convert(c))
+        .collect::<Vec<_>>();
+    assert_eq!(codes, vec![1, 2, 3, 4, 5]);
+    for c in 1..6 {
+        assert_eq!(converter.convert(converter.convert_inv(c)), c);
+    }
+    assert_eq!(converter.convert(b'z'), 6);
+    assert_eq!(
+        converter.convert(b'z'),
+        converter.convert(converter.convert_inv(6))
+    );
+    assert_eq!(converter.convert(0), 0);
+
+    let index = FMIndexWithLocate::new(text, converter, 2);
+    let mut positions = index.search("abra").locate();
+    positions.sort();
+    assert_eq!(positions, vec![0, 7]);
+    assert_eq!(index.search("ra").count(), 2);
+    assert_eq!(index.search("rz").count(), 0);
+    assert_eq!(
+        index.search("a").extensions(),
+        vec![(b'b', 2), (b'c', 1), (b'd', 1)]
+    );
+}
+
+#[test]
+fn test_frequency_converter_full_alphabet() {
+    let text = (1..=255u8).collect::<Vec<_>>();
+    let converter = FrequencyConverter::from_text(&text);
+    assert_eq!(converter.len(), 256);
+    let index = FMIndex::new(text, converter);
+    assert_eq!(index.search([7, 8, 9]).count(), 1);
+}
+
+#[test]
+fn test_frequency_converter_sparse() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let tokens = (0..300)
+        .map(|_| rng.gen_range(1, u32::MAX))
+        .collect::<Vec<_>>();
+    let text = (0..20000)
+        .map(|_| tokens[rng.gen_range(0, tokens.len())])
+        .collect::<Vec<_>>();
+    let converter = FrequencyConverter::from_text(&text);
+    assert_eq!(converter.len(), 302);
+    let index = FMIndex::new(text.clone(), converter);
+    for _ in 0..100 {
+        let start = rng.gen_range(0, text.len() - 3);
+        let pattern = &text[start..start + 3];
+        let expected = text.windows(3).filter(|w| w == &pattern).count();
+        assert_eq!(index.search(pattern).count(), expected as u64);
+    }
+}
+
+#[test]
+fn test_dense_converter() {
+    let text = b"the quick brown fox jumps over the lazy dog".to_vec();
+    let converter = DenseConverter::from_text(&text);
+    // 26 letters and the space, and one code for absent characters
+    assert_eq!(converter.len(), 29);
+    assert_eq!(converter.convert(b' '), 1);
+    assert_eq!(converter.convert(b'a'), 2);
+    assert_eq!(converter.convert(b'z'), 27);
+    assert_eq!(converter.convert(b'!'), 28);
+    for c in 1..28 {
+        assert_eq!(converter.convert(converter.convert_inv(c)), c);
+    }
+
+    let index = FMIndexWithLocate::new(text.clone(), converter, 2);
+    let fm_index = FMIndexWithLocate::new(text, RangeConverter::new(b' ', b'z'), 2);
+    assert_eq!(index.bwt(), fm_index.bwt());
+    for pattern in ["the", "o", "fox!", "X", "dog"] {
+        assert_eq!(
+            index.search(pattern).locate(),
+            fm_index.search(pattern).locate()
+        );
+    }
+}
+
+#[test]
+fn test_dna_converter() {
+    let converter = DNAConverter::new(AmbiguityPolicy::Error);
+    assert_eq!(converter.len(), 5);
+    assert_eq!(converter.prepare(b"acGT").unwrap(), b"ACGT");
+    assert_eq!(
+        converter.prepare(b"ACNGT"),
+        Err(InvalidBase {
+            position: 2,
+            base: b'N'
+        })
+    );
+    let text = converter.prepare(b"GATTACAgattaca").unwrap();
+    let index = DNAFMIndex::new(text, converter);
+    assert_eq!(index.search("TTA").count(), 2);
+    assert_eq!(index.search("tta").count(), 2);
+    assert_eq!(index.search("ACAG").count(), 1);
+}
+
+#[test]
+fn test_dna_converter_symbol() {
+    let converter = DNAConverter::new(AmbiguityPolicy::Symbol);
+    assert_eq!(converter.len(), 6);
+    assert_eq!(converter.prepare(b"ACRYnT").unwrap(), b"ACNNNT");
+    assert_eq!(
+        converter.prepare(b"AC GT"),
+        Err(InvalidBase {
+            position: 2,
+            base: b' '
+        })
+    );
+    let text = converter.prepare(b"ACGNNACGTN").unwrap();
+    let index = FMIndex::new(text, converter);
+    assert_eq!(index.search("ACG").count(), 2);
+    assert_eq!(index.search("GN").count(), 1);
+    assert_eq!(index.search("NN").count(), 1);
+}
+
+#[test]
+fn test_dna_converter_split() {
+    let converter = DNAConverter::new(AmbiguityPolicy::Split);
+    assert_eq!(
+        converter.prepare(b"NACGNNNTTGN").unwrap(),
+        b"ACG\0TTG\0".to_vec()
+    );
+    let text = converter.prepare(b"ACGTNNACGT").unwrap();
+    let index = MultiTextFMIndexWithLocate::new(text, converter, 1);
+    let mut positions = index.search("ACGT").locate();
+    positions.sort();
+    assert_eq!(positions, vec![0, 5]);
+    assert_eq!(index.search("TA").count(), 0);
+}
+
+#[test]
+fn test_protein_converter() {
+    let converter = ProteinConverter::new();
+    assert_eq!(converter.len(), 28);
+    assert_eq!(converter.convert(b'*'), 1);
+    assert_eq!(converter.convert(b'A'), 2);
+    assert_eq!(converter.convert(b'y'), 26);
+    for c in 1..28 {
+        assert_eq!(converter.convert(converter.convert_inv(c)), c);
+    }
+
+    let text = b"MKTAYIAKQRQISFVKSHFSRQ*MKTAYW*".to_vec();
+    let index = FMIndexWithLocate::new(text, converter, 1);
+    let mut positions = index.search("MKTAY").locate();
+    positions.sort();
+    assert_eq!(positions, vec![0, 23]);
+    assert_eq!(index.search("q*").count(), 1);
+    assert_eq!(index.search("Y").extensions(), vec![(b'I', 1), (b'W', 1)]);
+}
+
+#[test]
+fn test_case_fold_converter() {
+    let text = b"The cat sat on THE Mat, then the CAT ran".to_vec();
+    let converter = CaseFoldConverter::new(DenseConverter::from_text(&text.to_ascii_lowercase()));
+    assert!(converter.len() < DenseConverter::from_text(&text).len());
+    assert_eq!(converter.convert(b'T'), converter.convert(b't'));
+    assert_eq!(converter.convert_inv(converter.convert(b'M')), b'm');
+
+    let index = FMIndexWithLocate::new(text, converter, 2);
+    let mut positions = index.search("the").locate();
+    positions.sort();
+    assert_eq!(positions, vec![0, 15, 24, 29]);
+    assert_eq!(index.search("CAT").count(), 2);
+    assert_eq!(index.search("at").count(), 4);
+    assert_eq!(index.search("mAT,").count(), 1);
+}
+
+#[test]
+fn test_then() {
+    let text = b"Banana BANDANA bandana".to_vec();
+    // fold case, restrict to the printable range, then remap by frequency
+    let restrict =
+        CaseFoldConverter::new(IdConverter::new::<u8>()).then(RangeConverter::new(b' ', b'z'));
+    let restricted = text
+        .iter()
+        .map(|&c| restrict.convert(c))
+        .collect::<Vec<_>>();
+    let converter = restrict.then(FrequencyConverter::from_text(&restricted));
+    // a, n, b, space, d, and one code for absent characters
+    assert_eq!(converter.len(), 7);
+    assert_eq!(
+        converter.name(),
+        "Then<Then<CaseFoldConverter<IdConverter>, RangeConverter>, FrequencyConverter>"
+    );
+    assert_eq!(converter.convert(b'A'), 1);
+    assert_eq!(converter.convert(b'a'), 1);
+    assert_eq!(converter.convert_inv(1), b'a');
+
+    let index = FMIndexWithLocate::new(text, converter, 1);
+    let mut positions = index.search("ANA").locate();
+    positions.sort();
+    assert_eq!(positions, vec![1, 3, 11, 19]);
+    assert_eq!(index.search("band").count(), 2);
+    assert_eq!(index.search("nab").count(), 0);
+}
+
+#[test]
+fn test_validate() {
+    let converter = RangeConverter::new(b'a', b'z');
+    assert_eq!(converter.validate(b"hello\0world"), Ok(()));
+    let err = converter.validate(b"hello World").unwrap_err();
+    assert_eq!(
+        err,
+        InvalidCharacter {
+            position: 5,
+            character: b' '
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "character 32 at position 5 is not in the converter's alphabet"
+    );
+
+    let converter = DenseConverter::from_text(b"banana");
+    assert!(converter.contains(b'n'));
+    assert!(!converter.contains(b'c'));
+    assert!(converter.validate(b"cabana").is_err());
+
+    let converter =
+        CaseFoldConverter::new(IdConverter::new::<u8>()).then(RangeConverter::new(b'a', b'z'));
+    assert_eq!(converter.validate(b"HelloWorld"), Ok(()));
+    assert_eq!(
+        converter.validate(b"Hello!"),
+        Err(InvalidCharacter {
+            position: 5,
+            character: b'!'
+        })
+    );
+
+    assert!(DNAConverter::new(AmbiguityPolicy::Error)
+        .validate(b"acgtN")
+        .is_err());
+    assert!(DNAConverter::new(AmbiguityPolicy::Symbol)
+        .validate(b"acgtN")
+        .is_ok());
+    assert!(ProteinConverter::new().validate(b"MKTAY*").is_ok());
+    assert!(ProteinConverter::new().validate(b"MK-AY").is_err());
+}
+
+#[test]
+#[should_panic(expected = "character 33 at position 5 is not in the converter's alphabet")]
+fn test_invalid_text() {
+    FMIndex::new(b"hello!".to_vec(), RangeConverter::new(b'a', b'z'));
+}
+
+#[test]
+fn test_sequence_converter() {
+    let text = b"el chico llama a la chica en la calle".to_vec();
+    let converter = SequenceConverter::from_text(&text, &[b"ch", b"ll", b"rr"]);
+    let encoded = converter.encode(&text);
+    assert_eq!(encoded.len(), text.len() - 4);
+    assert_eq!(converter.decode(&encoded), text);
+    assert_eq!(converter.encode(b"chi"), vec![256, b'i' as u32]);
+    assert_eq!(
+        converter.encode(b"call"),
+        vec![b'c' as u32, b'a' as u32, 257]
+    );
+
+    let index = FMIndexWithLocate::new(encoded, converter.clone(), 1);
+    let search = index.search(converter.encode(b"chic"));
+    let mut positions = search.locate();
+    positions.sort();
+    assert_eq!(positions, vec![3, 18]);
+    // c doesn't match the start of ch, nor l that of ll
+    assert_eq!(index.search(converter.encode(b"c")).count(), 3);
+    assert_eq!(index.search(converter.encode(b"l")).count(), 3);
+    assert_eq!(index.search(converter.encode(b"ll")).count(), 2);
+    // rr doesn't occur in the text
+    assert_eq!(index.search(converter.encode(b"rr")).count(), 0);
+    assert_eq!(converter.decode(&index.extract(3, 7)), b"chico".to_vec());
+}
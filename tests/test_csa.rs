@@ -0,0 +1,63 @@
+use fm_index::{converter::RangeConverter, CSAIndex, CSAIndexWithLocate, FMIndexWithLocate};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[test]
+fn test_count() {
+    let text = "mississippi".to_string().into_bytes();
+    let ans = vec![
+        ("m", 1),
+        ("mi", 1),
+        ("i", 4),
+        ("iss", 2),
+        ("ss", 2),
+        ("p", 2),
+        ("ppi", 1),
+        ("z", 0),
+        ("pps", 0),
+    ];
+    let csa = CSAIndex::new(text, RangeConverter::new(b'a', b'z'));
+    for (pattern, expected) in ans {
+        assert_eq!(csa.search(pattern).count(), expected, "pattern {}", pattern);
+    }
+}
+
+#[test]
+fn test_locate() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let text = (0..1000)
+        .map(|_| rng.gen_range(b'a', b'e'))
+        .collect::<Vec<_>>();
+    let csa = CSAIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'd'), 2);
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'd'), 2);
+    for _ in 0..100 {
+        let s = rng.gen_range(0, text.len() - 6);
+        let pattern = &text[s..s + rng.gen_range(1, 6)];
+        let mut res = csa.search(pattern).locate();
+        res.sort();
+        let mut expected = fm_index.search(pattern).locate();
+        expected.sort();
+        assert_eq!(res, expected);
+    }
+}
+
+#[test]
+fn test_iter() {
+    let text = "mississippi".to_string().into_bytes();
+    let csa = CSAIndex::new(text, RangeConverter::new(b'a', b'z'));
+    let search = csa.search("ssip");
+    let next_seq = search.iter_forward(0).take(6).collect::<Vec<_>>();
+    assert_eq!(next_seq, b"ssippi");
+    let prev_seq = search.iter_backward(0).take(3).collect::<Vec<_>>();
+    assert_eq!(prev_seq, b"iss");
+}
+
+#[test]
+fn test_char_at() {
+    let text = "mississippi".to_string().into_bytes();
+    let csa = CSAIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'z'), 2);
+    for (i, &c) in text.iter().enumerate() {
+        assert_eq!(csa.char_at(i as u64), c);
+    }
+    assert_eq!(csa.bwt(), b"ipssm\0pissii".to_owned());
+}
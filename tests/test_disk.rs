@@ -0,0 +1,165 @@
+#![cfg(feature = "std")]
+
+use fm_index::converter::RangeConverter;
+use fm_index::{DiskFMIndex, FMIndexWithLocate, HeapSize, MappedFMIndex};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fm-index-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn test_search() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let text = (0..20000)
+        .map(|_| b"acgt"[rng.gen::<usize>() % 4])
+        .collect::<Vec<u8>>();
+    let path = temp_path("search");
+    DiskFMIndex::create(&path, text.clone(), &RangeConverter::new(b'a', b't'), 3).unwrap();
+    // a cache of 4 pages, much smaller than the index
+    let index = DiskFMIndex::open(&path, RangeConverter::new(b'a', b't'), 4).unwrap();
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b't'), 3);
+
+    assert_eq!(index.len(), fm_index.len());
+    for _ in 0..50 {
+        let start = rng.gen::<usize>() % text.len();
+        let end = (start + 1 + rng.gen::<usize>() % 10).min(text.len());
+        let pattern = &text[start..end];
+        let search = index.search(pattern);
+        let fm_search = fm_index.search(pattern);
+        assert_eq!(search.count(), fm_search.count());
+        assert_eq!(search.locate(), fm_search.locate());
+        assert_eq!(
+            search.iter_forward(0).take(8).collect::<Vec<_>>(),
+            fm_search.iter_forward(0).take(8).collect::<Vec<_>>()
+        );
+    }
+    assert_eq!(index.search("").count(), index.len());
+    assert_eq!(index.char_at(1234), text[1234]);
+    assert_eq!(index.bwt(), fm_index.bwt());
+
+    drop(index);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_hot_samples() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let text = (0..5000)
+        .map(|_| b"acgt"[rng.gen::<usize>() % 4])
+        .collect::<Vec<u8>>();
+    let path = temp_path("hot");
+    DiskFMIndex::create(&path, text.clone(), &RangeConverter::new(b'a', b't'), 1).unwrap();
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b't'), 1);
+    let cold = DiskFMIndex::open(&path, RangeConverter::new(b'a', b't'), 4).unwrap();
+    let cold_size = cold.heap_size();
+    for threshold in [0, 3, 100] {
+        let index = DiskFMIndex::open(&path, RangeConverter::new(b'a', b't'), 4)
+            .unwrap()
+            .with_hot_samples(4, threshold);
+        assert!(index.heap_size() > cold_size);
+        for pattern in ["a", "acg", "ttga", "gattaca"] {
+            assert_eq!(
+                index.search(pattern).locate(),
+                fm_index.search(pattern).locate(),
+                "pattern \"{}\", threshold {}",
+                pattern,
+                threshold
+            );
+        }
+    }
+
+    drop(cold);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_mapped() {
+    let mut rng = StdRng::seed_from_u64(2);
+    // long enough for two superblocks of the rank directory
+    let text = (0..70000)
+        .map(|_| b"acgt"[rng.gen::<usize>() % 4])
+        .collect::<Vec<u8>>();
+    let path = temp_path("mapped");
+    DiskFMIndex::create(&path, text.clone(), &RangeConverter::new(b'a', b't'), 3).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let index = MappedFMIndex::new(bytes, RangeConverter::new(b'a', b't')).unwrap();
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b't'), 3);
+
+    // nothing but the character counts is held on the heap
+    assert!(index.heap_size() < 1024);
+    assert_eq!(index.len(), fm_index.len());
+    for _ in 0..50 {
+        let start = rng.gen::<usize>() % text.len();
+        let end = (start + 1 + rng.gen::<usize>() % 10).min(text.len());
+        let pattern = &text[start..end];
+        let search = index.search(pattern);
+        let fm_search = fm_index.search(pattern);
+        assert_eq!(search.count(), fm_search.count());
+        assert_eq!(search.locate(), fm_search.locate());
+    }
+    assert_eq!(index.char_at(67890), text[67890]);
+
+    let err = MappedFMIndex::new(
+        b"not an index at all, not at all",
+        RangeConverter::new(b'a', b't'),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_open_mmap() {
+    let text = "mississippi".to_string().into_bytes();
+    let path = temp_path("mmap");
+    DiskFMIndex::create(&path, text, &RangeConverter::new(b'a', b'z'), 1).unwrap();
+    let index =
+        unsafe { MappedFMIndex::open_mmap(&path, RangeConverter::new(b'a', b'z')) }.unwrap();
+    // two maps of the same file
+    let other =
+        unsafe { MappedFMIndex::open_mmap(&path, RangeConverter::new(b'a', b'z')) }.unwrap();
+    let mut positions = index.search("ssi").locate();
+    positions.sort();
+    assert_eq!(positions, vec![2, 5]);
+    assert_eq!(other.search("i").count(), 4);
+    assert!(index.heap_size() < 1024);
+    drop((index, other));
+    std::fs::remove_file(&path).unwrap();
+
+    let err = unsafe { MappedFMIndex::open_mmap(&path, RangeConverter::new(b'a', b'z')) }
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_invalid() {
+    let path = temp_path("invalid");
+    let text = "mississippi".to_string().into_bytes();
+    DiskFMIndex::create(&path, text, &RangeConverter::new(b'a', b'z'), 2).unwrap();
+    let err = DiskFMIndex::open(&path, RangeConverter::new(b'a', b'c'), 4)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes.truncate(bytes.len() - 8);
+    std::fs::write(&path, &bytes).unwrap();
+    let err = DiskFMIndex::open(&path, RangeConverter::new(b'a', b'z'), 4)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    std::fs::write(&path, b"not an index at all, not at all").unwrap();
+    let err = DiskFMIndex::open(&path, RangeConverter::new(b'a', b'z'), 4)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,95 @@
+use fm_index::converter::{AmbiguityPolicy, DNAConverter, RangeConverter};
+use fm_index::{
+    DNAFMIndex, DNAFMIndexWithLocate, FMDIndex, FMIndex, FMIndexWithLocate, HeapSize, Strand,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn random_text(rng: &mut StdRng, len: usize) -> Vec<u8> {
+    (0..len).map(|_| b"ABCD"[rng.gen::<usize>() % 4]).collect()
+}
+
+#[test]
+fn test_count() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let text = random_text(&mut rng, 100000);
+    let index = DNAFMIndex::new(text.clone(), RangeConverter::new(b'A', b'D'));
+    let fm_index = FMIndex::new(text.clone(), RangeConverter::new(b'A', b'D'));
+    for _ in 0..200 {
+        let start = rng.gen::<usize>() % text.len();
+        let end = (start + 1 + rng.gen::<usize>() % 12).min(text.len());
+        let pattern = &text[start..end];
+        assert_eq!(
+            index.search(pattern).count(),
+            fm_index.search(pattern).count()
+        );
+        assert_eq!(
+            index.search(pattern).extensions(),
+            fm_index.search(pattern).extensions()
+        );
+    }
+    assert_eq!(index.bwt(), fm_index.bwt());
+    assert!(index.heap_size() < fm_index.heap_size());
+}
+
+#[test]
+fn test_locate() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let text = random_text(&mut rng, 5000);
+    let index = DNAFMIndexWithLocate::new(text.clone(), RangeConverter::new(b'A', b'D'), 2);
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'A', b'D'), 2);
+    for pattern in ["A", "CAB", "DDA", "ABCDA"] {
+        let search = index.search(pattern);
+        let fm_search = fm_index.search(pattern);
+        assert_eq!(search.locate(), fm_search.locate());
+        for i in 0..search.count().min(10) {
+            assert_eq!(
+                search.iter_forward(i).take(10).collect::<Vec<_>>(),
+                fm_search.iter_forward(i).take(10).collect::<Vec<_>>()
+            );
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_large_alphabet() {
+    DNAFMIndex::new(b"ACGT".to_vec(), RangeConverter::new(b'A', b'T'));
+}
+
+#[test]
+fn test_search_both_strands() {
+    let index = DNAFMIndexWithLocate::new(
+        b"ACCGTTAG".to_vec(),
+        DNAConverter::new(AmbiguityPolicy::Error),
+        1,
+    );
+    // the reverse complement of AAC is GTT
+    assert_eq!(index.search_both_strands("AAC"), vec![(3, Strand::Reverse)]);
+    assert_eq!(
+        index.search_both_strands("CG"),
+        vec![(2, Strand::Forward), (2, Strand::Reverse)]
+    );
+    assert_eq!(
+        index.search_both_strands("TA"),
+        vec![(5, Strand::Forward), (5, Strand::Reverse)]
+    );
+    assert_eq!(index.search_both_strands("CGT"), vec![(2, Strand::Forward)]);
+    assert_eq!(index.search_both_strands("GGT"), vec![(0, Strand::Reverse)]);
+    assert_eq!(index.search_both_strands("GGGG"), vec![]);
+
+    let mut rng = StdRng::seed_from_u64(2);
+    let text = (0..3000)
+        .map(|_| b"ACGT"[rng.gen::<usize>() % 4])
+        .collect::<Vec<_>>();
+    let index =
+        DNAFMIndexWithLocate::new(text.clone(), DNAConverter::new(AmbiguityPolicy::Error), 2);
+    let fmd_index = FMDIndex::new(text.clone(), RangeConverter::new(b'A', b'T'), 2);
+    for _ in 0..100 {
+        let start = rng.gen_range(0, text.len() - 8);
+        let pattern = &text[start..start + rng.gen_range(1, 8)];
+        let mut expected = fmd_index.search(pattern).locate();
+        expected.sort_unstable();
+        assert_eq!(index.search_both_strands(pattern), expected);
+    }
+}
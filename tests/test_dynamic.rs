@@ -0,0 +1,117 @@
+#![cfg(feature = "std")]
+
+use fm_index::converter::RangeConverter;
+use fm_index::{DynamicFMIndex, Error};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::sync::Arc;
+
+fn naive_locate(docs: &[(u64, Vec<u8>)], pattern: &[u8]) -> Vec<(u64, u64)> {
+    let mut result = Vec::new();
+    for (doc_id, text) in docs {
+        for (i, w) in text.windows(pattern.len()).enumerate() {
+            if w == pattern {
+                result.push((*doc_id, i as u64));
+            }
+        }
+    }
+    result
+}
+
+fn check(index: &DynamicFMIndex<u8, RangeConverter<u8>>, docs: &[(u64, Vec<u8>)]) {
+    for pattern in ["a", "ab", "bca", "ccc", "abcab"] {
+        let pattern = pattern.as_bytes();
+        let mut positions = index.locate(pattern);
+        positions.sort();
+        assert_eq!(positions, naive_locate(docs, pattern));
+        assert_eq!(index.count(pattern), positions.len() as u64);
+    }
+    assert_eq!(index.doc_count(), docs.len() as u64);
+}
+
+#[test]
+fn test_insert_delete_compact() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let index = DynamicFMIndex::new(RangeConverter::new(b'a', b'c'), 2);
+    let mut docs = Vec::new();
+    for _ in 0..20 {
+        let len = 1 + rng.gen::<usize>() % 60;
+        let text = (0..len)
+            .map(|_| b"abc"[rng.gen::<usize>() % 3])
+            .collect::<Vec<u8>>();
+        let doc_id = index.insert_doc(text.clone()).unwrap();
+        docs.push((doc_id, text));
+    }
+    assert_eq!(index.segment_count(), 20);
+    check(&index, &docs);
+
+    for doc_id in [3, 7, 8, 19] {
+        assert!(index.delete_doc(doc_id));
+        docs.retain(|(id, _)| *id != doc_id);
+    }
+    assert!(!index.delete_doc(3));
+    assert!(index.is_doc_deleted(7));
+    check(&index, &docs);
+
+    index.compact();
+    assert_eq!(index.segment_count(), 1);
+    check(&index, &docs);
+
+    let doc_id = index.insert_doc(b"abcabc".to_vec()).unwrap();
+    assert_eq!(doc_id, 20);
+    docs.push((doc_id, b"abcabc".to_vec()));
+    assert!(index.delete_doc(0));
+    docs.remove(0);
+    assert_eq!(index.segment_count(), 2);
+    check(&index, &docs);
+    index.compact();
+    assert_eq!(index.segment_count(), 1);
+    check(&index, &docs);
+}
+
+#[test]
+fn test_background_compaction() {
+    let index = Arc::new(DynamicFMIndex::new(RangeConverter::new(b'a', b'z'), 1));
+    for _ in 0..10 {
+        index.insert_doc(b"mississippi".to_vec()).unwrap();
+    }
+    index.delete_doc(4);
+    let handle = index.compact_in_background();
+    // searches and insertions continue during compaction
+    let doc_id = index.insert_doc(b"missouri".to_vec()).unwrap();
+    assert!(index.count("ssi") >= 18);
+    handle.join().unwrap();
+
+    assert_eq!(index.count("ssi"), 18);
+    assert_eq!(index.count("mis"), 10);
+    assert!(index.locate("sou").contains(&(doc_id, 3)));
+    assert_eq!(index.doc_count(), 10);
+}
+
+#[test]
+fn test_invalid_docs() {
+    let index = DynamicFMIndex::new(RangeConverter::new(b'a', b'c'), 2);
+    // no text has the id yet, so it stays usable
+    assert!(!index.delete_doc(0));
+    assert_eq!(index.insert_doc(b"abc".to_vec()), Ok(0));
+    assert!(!index.is_doc_deleted(0));
+    assert!(!index.delete_doc(1));
+
+    assert_eq!(index.insert_doc(Vec::new()), Err(Error::EmptyText));
+    assert_eq!(
+        index.insert_doc(b"ab\0c".to_vec()),
+        Err(Error::TerminatorInText { position: 2 })
+    );
+    assert_eq!(
+        index.insert_doc(b"abd".to_vec()),
+        Err(Error::CharacterOutOfRange {
+            position: 2,
+            character: u64::from(b'd')
+        })
+    );
+    // a final terminator is allowed
+    assert_eq!(index.insert_doc(b"ca\0".to_vec()), Ok(1));
+    assert_eq!(index.doc_count(), 2);
+    assert_eq!(index.count("a"), 2);
+    assert_eq!(index.locate("ca"), vec![(1, 0)]);
+}
@@ -0,0 +1,156 @@
+use fm_index::{converter::RangeConverter, Error, FMDIndex, FMIndexWithLocate, Strand};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn reverse_complement(text: &[u8]) -> Vec<u8> {
+    text.iter()
+        .rev()
+        .map(|&c| match c {
+            b'A' => b'T',
+            b'C' => b'G',
+            b'G' => b'C',
+            b'T' => b'A',
+            c => c,
+        })
+        .collect()
+}
+
+fn generate_dna(rng: &mut StdRng, size: usize) -> Vec<u8> {
+    (0..size).map(|_| b"ACGT"[rng.gen_range(0, 4)]).collect()
+}
+
+#[test]
+fn test_small() {
+    let text = b"ACGTTGCA".to_vec();
+    let index = FMDIndex::new(text, RangeConverter::new(b'A', b'T'), 1);
+    assert_eq!(index.text_len(), 8);
+    assert_eq!(index.len(), 18);
+
+    let search = index.search("GTT");
+    assert_eq!(search.count(), 1);
+    assert_eq!(search.locate(), vec![(2, Strand::Forward)]);
+
+    // "TGC" occurs forward at 4, and as the reverse complement of "GCA" at 5
+    let mut res = index.search("TGC").locate();
+    res.sort();
+    assert_eq!(res, vec![(4, Strand::Forward), (5, Strand::Reverse)]);
+}
+
+#[test]
+fn test_both_strands() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let text = generate_dna(&mut rng, 500);
+    let index = FMDIndex::new(text.clone(), RangeConverter::new(b'A', b'T'), 2);
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'A', b'T'), 2);
+    for _ in 0..100 {
+        let m = rng.gen_range(1, 8);
+        let pattern = generate_dna(&mut rng, m);
+        let rc = reverse_complement(&pattern);
+
+        let mut expected = fm_index
+            .search(&pattern)
+            .locate()
+            .into_iter()
+            .map(|p| (p, Strand::Forward))
+            .chain(
+                fm_index
+                    .search(&rc)
+                    .locate()
+                    .into_iter()
+                    .map(|p| (p, Strand::Reverse)),
+            )
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        let search = index.search(&pattern);
+        let mut res = search.locate();
+        res.sort();
+        assert_eq!(res, expected, "pattern {:?}", pattern);
+
+        if search.count() > 0 {
+            let range = search.reverse_complement_range();
+            assert_eq!(range, index.search(&rc).forward_range());
+        }
+    }
+}
+
+#[test]
+fn test_extend_forward() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let text = generate_dna(&mut rng, 500);
+    let index = FMDIndex::new(text.clone(), RangeConverter::new(b'A', b'T'), 2);
+    for _ in 0..100 {
+        let s = rng.gen_range(0, text.len() - 10);
+        let pattern = &text[s..s + rng.gen_range(2, 10)];
+        let mut search = index.search(&pattern[..1]);
+        for &c in &pattern[1..] {
+            search = search.extend_forward(c);
+        }
+        let expected = index.search(pattern);
+        assert_eq!(search.forward_range(), expected.forward_range());
+        assert_eq!(
+            search.reverse_complement_range(),
+            expected.reverse_complement_range()
+        );
+        let mut res = search.locate();
+        res.sort();
+        let mut expected = expected.locate();
+        expected.sort();
+        assert_eq!(res, expected);
+    }
+}
+
+#[test]
+fn test_extend_outside_alphabet() {
+    let text = b"ACGTACGGTC".to_vec();
+    let index = FMDIndex::new(text, RangeConverter::new(b'A', b'T'), 2);
+    let search = index.search("AC");
+    assert_eq!(search.count(), 4);
+
+    let search = search.extend_backward(b'Z');
+    assert_eq!(search.count(), 0);
+    assert!(search.locate().is_empty());
+    assert_eq!(search.extend_backward(b'A').count(), 0);
+    assert_eq!(index.search("AC").extend_forward(b'Z').count(), 0);
+    assert_eq!(index.search("ZAC").count(), 0);
+}
+
+#[test]
+fn test_try_new() {
+    let converter = RangeConverter::new(b'A', b'T');
+    let index = FMDIndex::try_new(b"ACGTTG".to_vec(), converter.clone(), 2).unwrap();
+    assert_eq!(index.len(), 14);
+    // a final terminator isn't part of the text
+    let terminated = FMDIndex::try_new(b"ACGTTG\0".to_vec(), converter.clone(), 2).unwrap();
+    assert_eq!(terminated, index);
+    assert_eq!(terminated.text_len(), 6);
+
+    assert_eq!(
+        FMDIndex::try_new(Vec::new(), converter.clone(), 0).err(),
+        Some(Error::EmptyText)
+    );
+    // a \0 would pair the wrong parts of the strands
+    assert_eq!(
+        FMDIndex::try_new(b"AC\0GT".to_vec(), converter.clone(), 0).err(),
+        Some(Error::TerminatorInText { position: 2 })
+    );
+    assert_eq!(
+        FMDIndex::try_new(b"ACGU".to_vec(), converter.clone(), 0).err(),
+        Some(Error::CharacterOutOfRange {
+            position: 3,
+            character: u64::from(b'U')
+        })
+    );
+    // the rate must be less than the size of both strands
+    assert!(FMDIndex::try_new(b"ACG".to_vec(), converter.clone(), 2).is_ok());
+    assert_eq!(
+        FMDIndex::try_new(b"ACG".to_vec(), converter, 3).err(),
+        Some(Error::InvalidLevel { level: 3, len: 8 })
+    );
+}
+
+#[test]
+#[should_panic(expected = "the text contains a \\0 other than a final terminator, at position 2")]
+fn test_new_terminator() {
+    FMDIndex::new(b"AC\0GT".to_vec(), RangeConverter::new(b'A', b'T'), 0);
+}
@@ -1,4 +1,8 @@
-use fm_index::{converter::RangeConverter, FMIndex, FMIndexWithLocate};
+use fm_index::{
+    converter::{AmbiguityPolicy, DNAConverter, IdConverter, RangeConverter},
+    Error, FMIndex, FMIndexWithLocate, HeapSize, Highlighter, IndexStats, MultiTextFMIndex,
+    MultiTextFMIndexWithLocate, RLFMIndexWithLocate, TextOrderSampledArray,
+};
 
 #[test]
 fn test_small() {
@@ -36,6 +40,79 @@ fn test_small() {
     }
 }
 
+#[test]
+fn test_try_new() {
+    let converter = RangeConverter::new(b'a', b'z');
+    let index = FMIndex::try_new(b"mississippi".to_vec(), converter.clone()).unwrap();
+    assert_eq!(index.search("ssi").count(), 2);
+    assert!(FMIndex::try_new(b"mississippi\0".to_vec(), converter.clone()).is_ok());
+    assert!(MultiTextFMIndex::try_new(b"miss\0issippi\0".to_vec(), converter.clone()).is_ok());
+
+    assert_eq!(
+        FMIndex::try_new(vec![], converter.clone()).err(),
+        Some(Error::EmptyText)
+    );
+    assert_eq!(
+        FMIndexWithLocate::try_new(vec![0], converter.clone(), 0).err(),
+        Some(Error::EmptyText)
+    );
+    assert_eq!(
+        FMIndexWithLocate::try_new(b"miss\0\0".to_vec(), converter.clone(), 2).err(),
+        Some(Error::TerminatorInText { position: 4 })
+    );
+    // a search could match across an inner terminator, which `new` allows
+    assert_eq!(
+        FMIndexWithLocate::try_new(b"\0\0ab".to_vec(), IdConverter::new::<u8>(), 0).err(),
+        Some(Error::TerminatorInText { position: 0 })
+    );
+    assert_eq!(
+        FMIndex::try_new(b"miss\0issippi\0".to_vec(), converter.clone()).err(),
+        Some(Error::TerminatorInText { position: 4 })
+    );
+    assert_eq!(
+        MultiTextFMIndexWithLocate::try_new(b"miss\0ippi\0\0".to_vec(), converter.clone(), 0).err(),
+        Some(Error::TerminatorInText { position: 9 })
+    );
+    let err = MultiTextFMIndexWithLocate::try_new(b"miss\0Issippi".to_vec(), converter.clone(), 2)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        Error::CharacterOutOfRange {
+            position: 5,
+            character: u64::from(b'I')
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "character 73 at position 5 is not in the converter's alphabet"
+    );
+
+    // 12 entries with the terminator, so the rate 2^level must be below 12
+    let text = b"mississippi".to_vec();
+    assert!(FMIndexWithLocate::try_new(text.clone(), converter.clone(), 3).is_ok());
+    assert_eq!(
+        FMIndexWithLocate::try_new(text.clone(), converter.clone(), 4).err(),
+        Some(Error::InvalidLevel { level: 4, len: 12 })
+    );
+    assert_eq!(
+        RLFMIndexWithLocate::try_new(text, converter, 64).err(),
+        Some(Error::InvalidLevel { level: 64, len: 12 })
+    );
+}
+
+#[test]
+#[should_panic(expected = "sampling level 4 is too large for a text of length 12")]
+fn test_new_invalid_level() {
+    FMIndexWithLocate::new(b"mississippi".to_vec(), RangeConverter::new(b'a', b'z'), 4);
+}
+
+#[test]
+#[should_panic(expected = "the text is empty")]
+fn test_new_empty() {
+    FMIndex::new(vec![], RangeConverter::new(b'a', b'z'));
+}
+
 #[test]
 fn test_small_contain_null() {
     let text = "miss\0issippi\0".to_string().into_bytes();
@@ -105,3 +182,545 @@ fn test_iter_forward() {
     let next_seq = search.iter_forward(0).take(10).collect::<Vec<_>>();
     assert_eq!(next_seq, b"sit amet, ".to_owned());
 }
+
+#[test]
+fn test_bwt() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    assert_eq!(index.bwt(), b"ipssm\0pissii".to_owned());
+}
+
+#[test]
+fn test_stats() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text.clone(), RangeConverter::new(b'a', b'z'));
+    let stats = index.stats();
+    assert_eq!(stats.text_len(), 11);
+    assert_eq!(stats.alphabet_size(), 27);
+    assert_eq!(
+        stats.frequencies(),
+        &[(b'i', 4), (b'm', 1), (b'p', 2), (b's', 4)]
+    );
+    // ipssm\0pissii
+    assert_eq!(stats.runs(), 8);
+    for k in 0..=IndexStats::<u8>::MAX_ORDER {
+        let entropy = stats.entropy(k).unwrap();
+        assert!(
+            (entropy - naive_entropy(&text, k)).abs() < 1e-9,
+            "k = {}",
+            k
+        );
+    }
+    assert_eq!(stats.entropy(IndexStats::<u8>::MAX_ORDER + 1), None);
+
+    let text = "the quick brown fox jumps over the lazy dog and the lazy cat"
+        .to_string()
+        .into_bytes();
+    let index = RLFMIndexWithLocate::new(text.clone(), RangeConverter::new(b' ', b'z'), 2);
+    let stats = index.stats();
+    assert_eq!(stats.runs(), {
+        let mut bwt = index.bwt();
+        bwt.dedup();
+        bwt.len() as u64 - 1
+    });
+    for k in 0..=IndexStats::<u8>::MAX_ORDER {
+        let entropy = stats.entropy(k).unwrap();
+        assert!(
+            (entropy - naive_entropy(&text, k)).abs() < 1e-9,
+            "k = {}",
+            k
+        );
+    }
+}
+
+// The order-k entropy of the characters preceding each context of k
+// characters, counting only contexts that lie within the text.
+fn naive_entropy(text: &[u8], k: usize) -> f64 {
+    let mut contexts = std::collections::BTreeMap::<&[u8], Vec<u8>>::new();
+    for j in 0..text.len() {
+        if j + k < text.len() {
+            contexts
+                .entry(&text[j + 1..j + 1 + k])
+                .or_default()
+                .push(text[j]);
+        }
+    }
+    let mut bits = 0.0;
+    for chars in contexts.values() {
+        let mut counts = std::collections::BTreeMap::<u8, f64>::new();
+        for &c in chars {
+            *counts.entry(c).or_default() += 1.0;
+        }
+        let total = chars.len() as f64;
+        bits += counts
+            .values()
+            .map(|&count| count * (total / count).log2())
+            .sum::<f64>();
+    }
+    bits / text.len() as f64
+}
+
+#[test]
+fn test_suffix_array() {
+    let text = "mississippi".to_string().into_bytes();
+    let ans = vec![11, 10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2];
+    for level in 0..3 {
+        let index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'z'), level);
+        assert_eq!(index.suffix_array(), ans);
+    }
+}
+
+#[test]
+fn test_lcp_array() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    let lcp = index.lcp_array();
+    let ans = vec![0, 0, 1, 1, 4, 0, 0, 1, 0, 2, 1, 3];
+    assert_eq!(lcp.len(), 12);
+    for (i, a) in ans.into_iter().enumerate() {
+        assert_eq!(lcp.lcp(i as u64), a);
+    }
+    // "sippi", "sissippi", "ssippi" and "ssissippi" share only "s".
+    assert_eq!(lcp.range_min(9, 12), 1);
+}
+
+#[test]
+fn test_extensions() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    assert_eq!(index.search("i").extensions(), vec![(b'p', 1), (b's', 2)]);
+    assert_eq!(index.search("ss").extensions(), vec![(b'i', 2)]);
+    assert_eq!(index.search("pi").extensions(), vec![]);
+    assert_eq!(index.search("z").extensions(), vec![]);
+    assert_eq!(
+        index.search("").extensions(),
+        vec![(b'i', 4), (b'm', 1), (b'p', 2), (b's', 4)]
+    );
+}
+
+#[test]
+fn test_left_extensions() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    assert_eq!(
+        index.search("i").left_extensions(),
+        vec![(b'm', 1), (b'p', 1), (b's', 2)]
+    );
+    assert_eq!(index.search("ss").left_extensions(), vec![(b'i', 2)]);
+    assert_eq!(index.search("m").left_extensions(), vec![]);
+    assert_eq!(index.search("z").left_extensions(), vec![]);
+}
+
+#[test]
+fn test_char_at() {
+    let text = "mississippi".to_string().into_bytes();
+    for level in 0..3 {
+        let index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'z'), level);
+        for (i, &c) in text.iter().enumerate() {
+            assert_eq!(index.char_at(i as u64), c);
+        }
+        assert_eq!(index.char_at(text.len() as u64), 0);
+    }
+}
+
+#[test]
+fn test_matches() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    let search = index.search("ss");
+    let mut matches = search.matches().collect::<Vec<_>>();
+    matches.sort_by_key(|m| m.position());
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].position(), 2);
+    assert_eq!(matches[1].position(), 5);
+    for m in &matches {
+        assert_eq!(m.pattern_len(), 2);
+        assert_eq!(m.doc_id(), None);
+        assert_eq!(m.iter_forward().take(3).collect::<Vec<_>>(), b"ssi");
+    }
+    assert_eq!(
+        matches[0].iter_backward().take(2).collect::<Vec<_>>(),
+        b"im"
+    );
+}
+
+#[test]
+fn test_highlight() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    let search = index.search("ss");
+    let mut matches = search.matches().collect::<Vec<_>>();
+    matches.sort_by_key(|m| m.position());
+
+    let highlighter = Highlighter::new(3, 4);
+    let highlighted = matches[0].highlight(&highlighter);
+    assert_eq!(highlighted.before(), b"mi");
+    assert_eq!(highlighted.matched(), b"ss");
+    assert_eq!(highlighted.after(), b"issi");
+    assert_eq!(highlighted.to_string(), "mi[ss]issi");
+    assert_eq!(
+        matches[1].highlight(&highlighter).to_string(),
+        "ssi[ss]ippi"
+    );
+
+    let highlighter = Highlighter::new(1, 0).markers("<b>", "</b>");
+    assert_eq!(matches[1].highlight(&highlighter).to_string(), "i<b>ss</b>");
+    let highlighter = Highlighter::new(0, 1).ansi();
+    assert_eq!(
+        matches[0].highlight(&highlighter).to_string(),
+        "\x1b[1;31mss\x1b[0mi"
+    );
+    assert_eq!(
+        matches[0].highlight(&highlighter).render(|text| text
+            .iter()
+            .map(|&c| (c as char).to_ascii_uppercase())
+            .collect()),
+        "\x1b[1;31mSS\x1b[0mI"
+    );
+}
+
+#[test]
+fn test_kmer_table() {
+    let text = "GATTACAGATTACACATTAGGACCAGTTACAGAT"
+        .to_string()
+        .into_bytes();
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'A', b'T'), 1);
+    let with_table =
+        FMIndexWithLocate::new(text, RangeConverter::new(b'A', b'T'), 1).with_kmer_table(3);
+
+    for pattern in [
+        "", "A", "GA", "GAT", "TTA", "ATTACA", "ACAGATT", "CAT", "GGG", "CATTAGGA", "B",
+    ] {
+        let expected = fm_index.search(pattern);
+        let actual = with_table.search(pattern);
+        assert_eq!(expected.count(), actual.count(), "pattern {:?}", pattern);
+        let (mut expected, mut actual) = (expected.locate(), actual.locate());
+        expected.sort();
+        actual.sort();
+        assert_eq!(expected, actual, "pattern {:?}", pattern);
+    }
+
+    // refining a search result does not use the table
+    let search = with_table.search("TACA").search("AT");
+    assert_eq!(search.count(), 2);
+}
+
+#[test]
+fn test_occ_table() {
+    let text = "GATTACAGATTACACATTAGGACCAGTTACAGAT".repeat(20).into_bytes();
+    let fm_index =
+        FMIndexWithLocate::new(text.clone(), DNAConverter::new(AmbiguityPolicy::Error), 1);
+
+    for k in [1, 16, 64, 100, 1000] {
+        let with_table =
+            FMIndexWithLocate::new(text.clone(), DNAConverter::new(AmbiguityPolicy::Error), 1)
+                .with_occ_table(k);
+        for pattern in [
+            "", "A", "GA", "GAT", "TTA", "ATTACA", "ACAGATT", "CAT", "GGG", "CATTAGGA",
+        ] {
+            let expected = fm_index.search(pattern);
+            let actual = with_table.search(pattern);
+            assert_eq!(
+                expected.count(),
+                actual.count(),
+                "k {} pattern {:?}",
+                k,
+                pattern
+            );
+            assert_eq!(expected.extensions(), actual.extensions());
+            assert_eq!(
+                expected.search("T").count(),
+                actual.search("T").count(),
+                "k {} pattern {:?}",
+                k,
+                pattern
+            );
+            let (mut expected, mut actual) = (expected.locate(), actual.locate());
+            expected.sort();
+            actual.sort();
+            assert_eq!(expected, actual, "k {} pattern {:?}", k, pattern);
+        }
+        assert!(with_table.size_breakdown().get("occ").is_some());
+    }
+
+    let both = FMIndex::new(text.clone(), DNAConverter::new(AmbiguityPolicy::Error))
+        .with_kmer_table(2)
+        .with_occ_table(32);
+    for pattern in ["A", "GAT", "ACAGATT", "CATTAGGA"] {
+        assert_eq!(
+            both.search(pattern).count(),
+            fm_index.search(pattern).count()
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "an occurrence table holds at most 15 characters")]
+fn test_occ_table_large_alphabet() {
+    FMIndex::new(b"mississippi".to_vec(), RangeConverter::new(b'a', b'z')).with_occ_table(64);
+}
+
+#[test]
+fn test_kmer_table_large_alphabet() {
+    let text = vec![1u32, 300, 70000, 65536, 300, 70000];
+    let index = FMIndex::new(text, RangeConverter::new(1u32, 1 << 17)).with_kmer_table(1);
+    assert_eq!(index.search([300, 70000]).count(), 2);
+}
+
+#[test]
+#[should_panic(expected = "a k-mer table holds at most 16777216 patterns, not 65536^4")]
+fn test_kmer_table_overflow() {
+    let text = vec![1u32, 300, 65535, 300];
+    FMIndex::new(text, RangeConverter::new(1u32, 1 << 16)).with_kmer_table(4);
+}
+
+#[test]
+fn test_text_order_sampling() {
+    let text = "mississippi".to_string().into_bytes();
+    let suffix_array =
+        FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'z'), 0).suffix_array();
+    for level in 0..3 {
+        let fm_index = FMIndexWithLocate::<_, _, TextOrderSampledArray>::with_sampling(
+            text.clone(),
+            RangeConverter::new(b'a', b'z'),
+            level,
+        );
+        assert_eq!(fm_index.suffix_array(), suffix_array, "level {}", level);
+        for (i, &c) in text.iter().enumerate() {
+            assert_eq!(fm_index.char_at(i as u64), c);
+        }
+
+        let rlfm_index = RLFMIndexWithLocate::<_, _, TextOrderSampledArray>::with_sampling(
+            text.clone(),
+            RangeConverter::new(b'a', b'z'),
+            level,
+        );
+        let mut res = rlfm_index.search("ssi").locate();
+        res.sort();
+        assert_eq!(res, vec![2, 5]);
+    }
+
+    let multi_text_index =
+        MultiTextFMIndexWithLocate::<_, _, _, TextOrderSampledArray>::with_sampling(
+            "miss\0issippi\0".to_string().into_bytes(),
+            RangeConverter::new(b'a', b'z'),
+            1,
+        );
+    let mut res = multi_text_index.search("iss").locate_in_docs();
+    res.sort();
+    assert_eq!(res, vec![(0, 1), (1, 0)]);
+}
+
+#[test]
+fn test_merge() {
+    let a = "mississippi".to_string().into_bytes();
+    let b = "missouri sassafras".to_string().into_bytes();
+    let merged = FMIndex::merge(
+        FMIndex::new(a.clone(), RangeConverter::new(b' ', b'z')),
+        FMIndex::new(b.clone(), RangeConverter::new(b' ', b'z')),
+    );
+    let mut text = a;
+    text.push(0);
+    text.extend(b);
+    let fm_index = FMIndex::new(text, RangeConverter::new(b' ', b'z'));
+
+    assert_eq!(merged.len(), fm_index.len());
+    assert_eq!(merged.bwt(), fm_index.bwt());
+    for pattern in ["s", "ss", "issi", "mis", "as", "pimi", "i m"] {
+        assert_eq!(
+            merged.search(pattern).count(),
+            fm_index.search(pattern).count(),
+            "pattern {:?}",
+            pattern
+        );
+    }
+}
+
+#[test]
+fn test_add_samples() {
+    let text = "mississippi".to_string().into_bytes();
+    let converter = RangeConverter::new(b'a', b'z');
+    let fm_index = FMIndex::new(text.clone(), converter.clone()).with_kmer_table(2);
+    let with_locate = fm_index.add_samples(1);
+    assert_eq!(
+        with_locate,
+        FMIndexWithLocate::new(text, converter, 1).with_kmer_table(2)
+    );
+    let mut positions = with_locate.search("ssi").locate();
+    positions.sort();
+    assert_eq!(positions, vec![2, 5]);
+}
+
+#[test]
+fn test_resample() {
+    let text = "mississippi".to_string().into_bytes();
+    let converter = RangeConverter::new(b'a', b'z');
+    let fm_index = FMIndexWithLocate::new(text.clone(), converter.clone(), 1);
+    let coarser = fm_index.resample(3);
+    assert_eq!(
+        coarser,
+        FMIndexWithLocate::new(text.clone(), converter.clone(), 3)
+    );
+    let finer = coarser.resample_rate(3);
+    assert_eq!(
+        finer,
+        FMIndexWithLocate::<_, _>::with_sampling_rate(text, converter, 3)
+    );
+    let mut positions = finer.search("ssi").locate();
+    positions.sort();
+    assert_eq!(positions, vec![2, 5]);
+}
+
+#[test]
+fn test_from_str() {
+    let text = "naïve café, naïf résumé";
+    let index: FMIndex<u32, RangeConverter<u32>> = text.parse().unwrap();
+    assert_eq!(index.len(), text.chars().count() as u64 + 1);
+    assert_eq!(index.search_str("naï").count(), 2);
+    assert_eq!(index.search_str("é").count(), 3);
+    assert_eq!(index.search_str("ï").count(), 2);
+    // characters outside the range of the text
+    assert_eq!(index.search_str("ü").count(), 0);
+    assert_eq!(index.search_str("A").count(), 0);
+    // the UTF-8 encodings of é and ï share a first byte, which the byte
+    // index matches but the code point index doesn't
+    let byte_index = FMIndex::new(text.as_bytes().to_vec(), RangeConverter::new(b' ', 0xff));
+    assert_eq!(byte_index.search(&"é".as_bytes()[..1]).count(), 5);
+
+    let index = FMIndexWithLocate::from_str(text, 2);
+    let mut positions = index.search_str("é").locate();
+    positions.sort();
+    assert_eq!(positions, vec![9, 18, 22]);
+    assert_eq!(index.search_str("€").locate(), Vec::<u64>::new());
+
+    assert_eq!(
+        "".parse::<FMIndex<u32, RangeConverter<u32>>>().err(),
+        Some(Error::EmptyText)
+    );
+    assert_eq!(
+        "\0\0".parse::<FMIndex<u32, RangeConverter<u32>>>().err(),
+        Some(Error::TerminatorInText { position: 0 })
+    );
+    // positions count characters
+    assert_eq!(
+        "né\0e".parse::<FMIndex<u32, RangeConverter<u32>>>().err(),
+        Some(Error::TerminatorInText { position: 2 })
+    );
+    assert!("né\0".parse::<FMIndex<u32, RangeConverter<u32>>>().is_ok());
+}
+
+#[test]
+fn test_from_str_normalized() {
+    // compose e with a combining acute accent
+    let compose = |s: &str| s.replace("e\u{301}", "é");
+    let text = "cafe\u{301} and café";
+    let index = FMIndexWithLocate::from_str_normalized(text, 1, compose);
+    assert_eq!(index.len(), 14);
+    let mut positions = index.search_str("café").locate();
+    positions.sort();
+    assert_eq!(positions, vec![0, 9]);
+    assert_eq!(index.search_str("cafe\u{301}").count(), 2);
+    assert_eq!(index.search_str("cafe").count(), 0);
+
+    // without normalization, the encodings don't match each other
+    let index = FMIndexWithLocate::from_str(text, 1);
+    assert_eq!(index.search_str("café").count(), 1);
+    assert_eq!(index.search_str("cafe\u{301}").count(), 1);
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[test]
+fn test_from_str_nfc() {
+    use fm_index::converter::{Nfc, Nfkc};
+
+    let index = FMIndex::from_str_normalized("Å and A\u{30a} and Å", Nfc);
+    assert_eq!(index.search_str("Å").count(), 3);
+    assert_eq!(index.search_str("A\u{30a}").count(), 3);
+    assert_eq!(index.search_str("A").count(), 0);
+
+    // compatibility equivalence only holds with NFKC
+    let index = FMIndex::from_str_normalized("ﬁne and fine", Nfc);
+    assert_eq!(index.search_str("fi").count(), 1);
+    let index = FMIndex::from_str_normalized("ﬁne and fine", Nfkc);
+    assert_eq!(index.search_str("fi").count(), 2);
+    assert_eq!(index.search_str("ﬁ").count(), 2);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_locate_parallel() {
+    let text = "mississippi".repeat(1000).into_bytes();
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'z'), 3);
+    let rlfm_index = RLFMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 3);
+
+    for pattern in ["i", "ssi", "ppim", "z"] {
+        let search = fm_index.search(pattern);
+        assert_eq!(search.locate_parallel(), search.locate());
+        let search = rlfm_index.search(pattern);
+        assert_eq!(search.locate_parallel(), search.locate());
+    }
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_query_cache() {
+    let text = "mississippi".repeat(100).into_bytes();
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'z'), 2);
+    let cached =
+        FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2).with_locate_cache(2);
+    let size = cached.heap_size();
+
+    // more distinct patterns than the capacity, each searched twice
+    for pattern in ["ssi", "i", "ssi", "ppim", "z", "", "i", "ssi", "z"] {
+        for _ in 0..2 {
+            let expected = fm_index.search(pattern);
+            let actual = cached.search(pattern);
+            assert_eq!(actual.count(), expected.count(), "pattern {:?}", pattern);
+            assert_eq!(actual.locate(), expected.locate(), "pattern {:?}", pattern);
+            assert_eq!(
+                actual.search("s").locate(),
+                expected.search("s").locate(),
+                "pattern {:?}",
+                pattern
+            );
+        }
+    }
+    assert!(cached.heap_size() > size);
+    assert!(cached.size_breakdown().get("query_cache").is_some());
+
+    let cached =
+        FMIndex::new(b"banana".to_vec(), RangeConverter::new(b'a', b'z')).with_query_cache(0);
+    assert_eq!(cached.search("ana").count(), 2);
+    assert_eq!(cached.search("ana").count(), 2);
+}
+
+#[test]
+fn test_verify() {
+    let text = "mississippi".repeat(500).into_bytes();
+    let converter = RangeConverter::new(b'a', b'z');
+
+    let report = FMIndex::new(text.clone(), converter.clone()).verify();
+    assert!(report.is_ok(), "{}", report);
+    assert_eq!(report.rows_checked(), 917);
+    assert_eq!(report.samples_checked(), 0);
+
+    #[cfg(all(feature = "std", feature = "serde"))]
+    {
+        let fm_index = FMIndexWithLocate::new(text.clone(), converter.clone(), 3);
+        let mut bytes = Vec::new();
+        fm_index.save(&mut bytes).unwrap();
+        let report = FMIndexWithLocate::<u8, RangeConverter<u8>>::load(&bytes[..])
+            .unwrap()
+            .verify();
+        assert!(report.is_ok(), "{}", report);
+        assert!(report.samples_checked() > 0);
+        assert_eq!(
+            report.to_string(),
+            "checked 917 rows and 128 samples: no problems found"
+        );
+    }
+
+    let fm_index =
+        FMIndexWithLocate::<_, _, TextOrderSampledArray>::with_sampling(text, converter, 4);
+    assert!(fm_index.verify().is_ok());
+}
@@ -0,0 +1,43 @@
+use fm_index::{
+    converter::RangeConverter, FMIndex, FMIndexWithLocate, HeapSize, HuffmanFMIndex,
+    HuffmanFMIndexWithLocate,
+};
+
+const TEXT: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat.";
+
+#[test]
+fn test_count() {
+    let text = TEXT.repeat(200).into_bytes();
+    let index = HuffmanFMIndex::new(text.clone(), RangeConverter::new(b' ', b'~'));
+    let fm_index = FMIndex::new(text, RangeConverter::new(b' ', b'~'));
+    for pattern in ["a", "ut", "dolor", "m ", "ex ea", "xyz", ""] {
+        assert_eq!(
+            index.search(pattern).count(),
+            fm_index.search(pattern).count(),
+            "pattern \"{}\"",
+            pattern
+        );
+        assert_eq!(
+            index.search(pattern).extensions(),
+            fm_index.search(pattern).extensions()
+        );
+    }
+    assert!(index.heap_size() < fm_index.heap_size());
+}
+
+#[test]
+fn test_locate() {
+    let text = TEXT.to_string().into_bytes();
+    let index = HuffmanFMIndexWithLocate::new(text.clone(), RangeConverter::new(b' ', b'~'), 2);
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b' ', b'~'), 2);
+    for pattern in ["a", "ut", "dolor", "m ", "ex ea"] {
+        assert_eq!(
+            index.search(pattern).locate(),
+            fm_index.search(pattern).locate()
+        );
+    }
+    assert_eq!(index.bwt(), fm_index.bwt());
+    for (i, &c) in text.iter().enumerate() {
+        assert_eq!(index.char_at(i as u64), c);
+    }
+}
@@ -1,6 +1,6 @@
 use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use fm_index::{converter::IdConverter, MultiTextFMIndexWithLocate};
+use fm_index::{converter::IdConverter, Error, MultiTextFMIndex, MultiTextFMIndexWithLocate};
 
 #[test]
 fn test_search_count() {
@@ -74,3 +74,295 @@ fn generate_text_random(text_size: usize, alphabet_size: u8) -> Vec<u8> {
         .map(|_| rng.gen::<u8>() % alphabet_size)
         .collect::<Vec<_>>()
 }
+
+#[test]
+fn test_char_at() {
+    let text = generate_text_random(256, 8);
+    let fm_index = MultiTextFMIndexWithLocate::new(text.clone(), IdConverter::new::<u8>(), 2);
+    for (i, &c) in text.iter().enumerate() {
+        assert_eq!(fm_index.char_at(i as u64), c, "i = {}", i);
+    }
+}
+
+#[test]
+fn test_matches_doc_id() {
+    let text = "foo\0bar\0foobar\0".to_string().into_bytes();
+    let fm_index = MultiTextFMIndexWithLocate::new(text, IdConverter::new::<u8>(), 1);
+    let search = fm_index.search("bar");
+    let mut actual = search
+        .matches()
+        .map(|m| (m.position(), m.doc_id()))
+        .collect::<Vec<_>>();
+    actual.sort();
+    assert_eq!(actual, vec![(4, Some(1)), (11, Some(2))]);
+}
+
+#[test]
+fn test_from_texts() {
+    let texts = vec![b"foo".to_vec(), b"bar".to_vec(), b"foobar".to_vec()];
+    let fm_index = MultiTextFMIndexWithLocate::from_texts(texts, IdConverter::new::<u8>(), 1);
+    assert_eq!(fm_index.doc_count(), 3);
+    assert_eq!(fm_index.search("foo").count(), 2);
+
+    let mut actual = fm_index
+        .search("bar")
+        .locate()
+        .into_iter()
+        .map(|p| fm_index.doc_position(p))
+        .collect::<Vec<_>>();
+    actual.sort();
+    assert_eq!(actual, vec![(1, 0), (2, 3)]);
+
+    assert_eq!(fm_index.doc_id(0), 0);
+    assert_eq!(fm_index.doc_id(3), 0);
+    assert_eq!(fm_index.doc_id(4), 1);
+    assert_eq!(fm_index.doc_id(14), 2);
+}
+
+#[test]
+fn test_try_from_texts() {
+    let texts = vec![b"foo".to_vec(), b"bar".to_vec()];
+    let fm_index =
+        MultiTextFMIndex::try_from_texts(texts.clone(), IdConverter::new::<u8>()).unwrap();
+    assert_eq!(fm_index.doc_count(), 2);
+
+    // a \0 would move the texts after it to the wrong ids
+    let texts = vec![b"a\0b".to_vec(), b"c".to_vec()];
+    assert_eq!(
+        MultiTextFMIndexWithLocate::try_from_texts(texts.clone(), IdConverter::new::<u8>(), 0)
+            .err(),
+        Some(Error::TerminatorInText { position: 1 })
+    );
+    let texts = vec![b"ab".to_vec(), b"c\0".to_vec()];
+    assert_eq!(
+        MultiTextFMIndex::try_from_texts(texts, IdConverter::new::<u8>()).err(),
+        Some(Error::TerminatorInText { position: 4 })
+    );
+    assert_eq!(
+        MultiTextFMIndex::try_from_texts(Vec::<Vec<u8>>::new(), IdConverter::new::<u8>()).err(),
+        Some(Error::EmptyText)
+    );
+    assert_eq!(
+        MultiTextFMIndexWithLocate::try_from_texts(
+            vec![b"ab".to_vec()],
+            IdConverter::new::<u8>(),
+            2
+        )
+        .err(),
+        Some(Error::InvalidLevel { level: 2, len: 3 })
+    );
+}
+
+#[test]
+#[should_panic(expected = "the text contains a \\0 other than a final terminator, at position 1")]
+fn test_from_texts_terminator() {
+    MultiTextFMIndex::from_texts(
+        vec![b"a\0b".to_vec(), b"c".to_vec()],
+        IdConverter::new::<u8>(),
+    );
+}
+
+#[test]
+fn test_docs_with_tf() {
+    let texts = vec![b"abab".to_vec(), b"cd".to_vec(), b"ab".to_vec()];
+    let fm_index = MultiTextFMIndexWithLocate::from_texts(texts, IdConverter::new::<u8>(), 1);
+    assert_eq!(fm_index.search("ab").docs_with_tf(), vec![(0, 2), (2, 1)]);
+    assert_eq!(fm_index.search("d").docs_with_tf(), vec![(1, 1)]);
+    assert_eq!(fm_index.search("x").docs_with_tf(), vec![]);
+}
+
+// With a power of two texts, the last text id fills the bit width of the
+// document array, past which the search for the next text must not go.
+#[test]
+fn test_docs_with_tf_power_of_two_texts() {
+    let texts = vec![b"ab".to_vec(), b"bab".to_vec()];
+    let fm_index = MultiTextFMIndexWithLocate::from_texts(texts, IdConverter::new::<u8>(), 1);
+    assert_eq!(fm_index.search("b").docs_with_tf(), vec![(0, 1), (1, 2)]);
+    assert_eq!(fm_index.search("ba").docs_with_tf(), vec![(1, 1)]);
+    assert_eq!(fm_index.search("").docs_with_tf(), vec![(0, 3), (1, 4)]);
+
+    let texts = vec![
+        b"ab".to_vec(),
+        b"cd".to_vec(),
+        b"abc".to_vec(),
+        b"dab".to_vec(),
+    ];
+    let fm_index = MultiTextFMIndexWithLocate::from_texts(texts, IdConverter::new::<u8>(), 1);
+    assert_eq!(
+        fm_index.search("ab").docs_with_tf(),
+        vec![(0, 1), (2, 1), (3, 1)]
+    );
+    assert_eq!(fm_index.search("d").docs_with_tf(), vec![(1, 1), (3, 1)]);
+    assert_eq!(fm_index.search("da").docs_with_tf(), vec![(3, 1)]);
+}
+
+#[test]
+fn test_top_docs() {
+    let texts = vec![
+        b"ab".to_vec(),
+        b"ababab".to_vec(),
+        b"cd".to_vec(),
+        b"abab".to_vec(),
+        b"ab".to_vec(),
+    ];
+    let fm_index = MultiTextFMIndexWithLocate::from_texts(texts, IdConverter::new::<u8>(), 1);
+    let search = fm_index.search("ab");
+    assert_eq!(search.top_docs(2), vec![(1, 3), (3, 2)]);
+    assert_eq!(search.top_docs(3), vec![(1, 3), (3, 2), (0, 1)]);
+    assert_eq!(search.top_docs(10), vec![(1, 3), (3, 2), (0, 1), (4, 1)]);
+    assert_eq!(search.top_docs(0), vec![]);
+}
+
+#[test]
+fn test_locate_in_docs() {
+    let texts = vec![b"abab".to_vec(), b"cd".to_vec(), b"xab".to_vec()];
+    let fm_index = MultiTextFMIndexWithLocate::from_texts(texts, IdConverter::new::<u8>(), 2);
+    let mut actual = fm_index.search("ab").locate_in_docs();
+    actual.sort();
+    assert_eq!(actual, vec![(0, 0), (0, 2), (2, 1)]);
+}
+
+#[test]
+fn test_metadata() {
+    let docs = vec![
+        (b"foo".to_vec(), "first"),
+        (b"bar".to_vec(), "second"),
+        (b"foobar".to_vec(), "third"),
+    ];
+    let fm_index =
+        MultiTextFMIndexWithLocate::from_texts_with_metadata(docs, IdConverter::new::<u8>(), 1);
+    assert_eq!(fm_index.metadata(1), Some(&"second"));
+    assert_eq!(fm_index.metadata(3), None);
+
+    let search = fm_index.search("bar");
+    let mut actual = search
+        .matches_with_metadata()
+        .map(|(m, metadata)| (m.position(), metadata.copied()))
+        .collect::<Vec<_>>();
+    actual.sort();
+    assert_eq!(actual, vec![(4, Some("second")), (11, Some("third"))]);
+}
+
+#[test]
+#[should_panic(expected = "sampling level 3 is too large for a text of length 8")]
+fn test_metadata_invalid_level() {
+    let docs = vec![(b"foo".to_vec(), 1), (b"bar".to_vec(), 2)];
+    MultiTextFMIndexWithLocate::from_texts_with_metadata(docs, IdConverter::new::<u8>(), 3);
+}
+
+#[test]
+fn test_metadata_absent() {
+    let texts = vec![b"foo".to_vec(), b"bar".to_vec()];
+    let fm_index = MultiTextFMIndexWithLocate::from_texts(texts, IdConverter::new::<u8>(), 1);
+    assert_eq!(fm_index.metadata(0), None);
+}
+
+#[test]
+fn test_delete_doc() {
+    let docs = vec![
+        (b"abab".to_vec(), 'a'),
+        (b"cd".to_vec(), 'b'),
+        (b"xab".to_vec(), 'c'),
+    ];
+    let mut fm_index =
+        MultiTextFMIndexWithLocate::from_texts_with_metadata(docs, IdConverter::new::<u8>(), 2);
+    assert_eq!(fm_index.search("ab").count(), 3);
+
+    fm_index.delete_doc(0);
+    assert!(fm_index.is_doc_deleted(0));
+    assert_eq!(fm_index.deleted_count(), 1);
+    let search = fm_index.search("ab");
+    assert_eq!(search.count(), 1);
+    assert_eq!(search.locate(), vec![9]);
+    assert_eq!(
+        search.fold_positions(vec![], |mut v, p| {
+            v.push(p);
+            v
+        }),
+        vec![9]
+    );
+    assert_eq!(search.docs_with_tf(), vec![(2, 1)]);
+    assert_eq!(search.matches().count(), 1);
+
+    let fm_index = fm_index.purge();
+    assert_eq!(fm_index.doc_count(), 2);
+    assert_eq!(fm_index.deleted_count(), 0);
+    assert_eq!(fm_index.metadata(1), Some(&'c'));
+    assert_eq!(fm_index.search("ab").locate_in_docs(), vec![(1, 1)]);
+    assert_eq!(fm_index.search("cd").count(), 1);
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_delete_doc_clears_query_cache() {
+    let texts = vec![b"abab".to_vec(), b"cd".to_vec(), b"xab".to_vec()];
+    let mut fm_index = MultiTextFMIndexWithLocate::from_texts(texts, IdConverter::new::<u8>(), 2)
+        .with_locate_cache(8);
+    let mut positions = fm_index.search("ab").locate();
+    positions.sort();
+    assert_eq!(positions, vec![0, 2, 9]);
+
+    fm_index.delete_doc(0);
+    assert_eq!(fm_index.search("ab").locate(), vec![9]);
+    let fm_index = fm_index.purge();
+    assert_eq!(fm_index.search("ab").locate(), vec![4]);
+    assert_eq!(fm_index.search("ab").locate(), vec![4]);
+}
+
+#[test]
+fn test_delete_doc_count_only() {
+    let texts = vec![b"abab".to_vec(), b"cd".to_vec(), b"xab".to_vec()];
+    let mut fm_index = MultiTextFMIndex::from_texts(texts, IdConverter::new::<u8>());
+    fm_index.delete_doc(2);
+    assert_eq!(fm_index.search("ab").count(), 2);
+    let fm_index = fm_index.purge();
+    assert_eq!(fm_index.doc_count(), 2);
+    assert_eq!(fm_index.search("ab").count(), 2);
+    assert_eq!(fm_index.search("x").count(), 0);
+}
+
+#[test]
+fn test_docs_with_tf_count_only() {
+    let text_size = 1024;
+    let text = generate_text_random(text_size, 4);
+    let fm_index = MultiTextFMIndex::new(text.clone(), IdConverter::new::<u8>());
+    let fm_index_locate = MultiTextFMIndexWithLocate::new(text, IdConverter::new::<u8>(), 2);
+    for pattern in [&[1u8][..], &[1, 2], &[3, 3, 1]] {
+        let docs = fm_index.search(pattern).docs_with_tf();
+        let mut expected = fm_index_locate
+            .search(pattern)
+            .locate()
+            .into_iter()
+            .map(|p| fm_index_locate.doc_id(p))
+            .collect::<Vec<_>>();
+        expected.sort();
+        expected.dedup();
+        assert_eq!(docs.iter().map(|(d, _)| *d).collect::<Vec<_>>(), expected);
+        assert_eq!(
+            docs.iter().map(|(_, tf)| tf).sum::<u64>(),
+            fm_index.search(pattern).count()
+        );
+    }
+}
+
+#[test]
+fn test_doc_bitmap() {
+    let texts = vec![
+        b"apple".to_vec(),
+        b"banana".to_vec(),
+        b"cherry".to_vec(),
+        b"pineapple".to_vec(),
+    ];
+    let fm_index = MultiTextFMIndex::from_texts(texts, IdConverter::new::<u8>());
+    let apple = fm_index.search("apple").doc_bitmap();
+    let an = fm_index.search("an").doc_bitmap();
+    let e = fm_index.search("e").doc_bitmap();
+    assert_eq!(apple.len(), 4);
+    assert_eq!(apple.iter().collect::<Vec<_>>(), vec![0, 3]);
+    assert!(apple.contains(3));
+    assert!(!apple.contains(1));
+    assert_eq!(an.iter().collect::<Vec<_>>(), vec![1]);
+    assert_eq!((&apple | &an).iter().collect::<Vec<_>>(), vec![0, 1, 3]);
+    assert_eq!((&e & &apple).count(), 2);
+    assert_eq!((&e - &apple).iter().collect::<Vec<_>>(), vec![2]);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "python")]
+
+// Embeds the interpreter to check the bindings as Python code sees them.
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+fn run(code: &std::ffi::CStr) {
+    Python::initialize();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(fm_index::python::fm_index)(py);
+        // importable, for pickling
+        py.import("sys")
+            .and_then(|sys| sys.getattr("modules"))
+            .and_then(|modules| modules.set_item("fm_index", &module))
+            .unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("fm_index", module).unwrap();
+        if let Err(err) = py.run(code, Some(&globals), None) {
+            err.display(py);
+            panic!("{}", err);
+        }
+    });
+}
+
+#[test]
+fn test_python() {
+    run(cr#"
+FMIndex = fm_index.FMIndex
+index = FMIndex(b"mississippi", level=2)
+assert len(index) == 11
+assert index.count(b"ssi") == 2
+assert index.count(b"z") == 0
+assert sorted(index.locate(b"i")) == [1, 4, 7, 10]
+assert index.extract(2, 6) == b"ssis"
+assert index.extract(9, 100) == b"pi"
+
+# any contiguous buffer of bytes
+assert index.count(bytearray(b"ss")) == 2
+assert index.count(memoryview(b"xssx")[1:3]) == 2
+assert FMIndex(bytearray(b"banana")).count(b"ana") == 2
+
+copy = FMIndex.from_bytes(index.to_bytes())
+assert sorted(copy.locate(b"ss")) == [2, 5]
+
+import pickle
+assert pickle.loads(pickle.dumps(index)).count(b"p") == 2
+
+for text in [b"", b"ab\0\0"]:
+    try:
+        FMIndex(text)
+    except ValueError:
+        pass
+    else:
+        raise AssertionError(text)
+try:
+    FMIndex(b"abc", level=2)
+except ValueError:
+    pass
+else:
+    raise AssertionError("level too large")
+try:
+    FMIndex.from_bytes(b"garbage")
+except OSError:
+    pass
+else:
+    raise AssertionError("loaded garbage")
+"#);
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "testing")]
+
+use fm_index::converter::RangeConverter;
+use fm_index::{
+    CSAIndexWithLocate, DNAFMIndexWithLocate, FMIndex, FMIndexWithLocate, HuffmanFMIndexWithLocate,
+    Mismatch, RIndex, RLFMIndexWithLocate, ReferenceIndex, SearchIndex, SearchIndexWithLocate,
+    SlimFMIndex, SmallFMIndexWithLocate,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn random_text(rng: &mut StdRng, len: usize) -> Vec<u8> {
+    (0..len).map(|_| b"abcd"[rng.gen::<usize>() % 4]).collect()
+}
+
+// every pattern of up to three characters, some of them \0
+fn patterns() -> Vec<Vec<u8>> {
+    let mut patterns = vec![vec![]];
+    for len in 1..=3 {
+        let mut next = Vec::new();
+        for pattern in patterns.iter().filter(|p| p.len() == len - 1) {
+            for &c in b"\0abcd" {
+                let mut pattern = pattern.clone();
+                pattern.push(c);
+                next.push(pattern);
+            }
+        }
+        patterns.extend(next);
+    }
+    patterns
+}
+
+fn check_count(reference: &ReferenceIndex<u8>, index: &impl SearchIndex<u8>) {
+    for pattern in patterns() {
+        if let Err(mismatch) = reference.check_count(index, &pattern) {
+            panic!("{}", mismatch);
+        }
+    }
+}
+
+fn check_locate(reference: &ReferenceIndex<u8>, index: &impl SearchIndexWithLocate<u8>) {
+    for pattern in patterns() {
+        if let Err(mismatch) = reference.check_locate(index, &pattern) {
+            panic!("{}", mismatch);
+        }
+    }
+}
+
+#[test]
+fn test_backends() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for len in [1, 2, 10, 300] {
+        let text = random_text(&mut rng, len);
+        let converter = RangeConverter::new(b'a', b'd');
+        let reference = ReferenceIndex::new(text.clone());
+        // the sampling rate must be less than the length of the text
+        let level = if len < 10 { 0 } else { 2 };
+
+        check_count(&reference, &FMIndex::new(text.clone(), converter.clone()));
+        check_count(
+            &reference,
+            &SlimFMIndex::new(text.clone(), converter.clone()),
+        );
+
+        let index = FMIndexWithLocate::new(text.clone(), converter.clone(), level);
+        check_locate(&reference, &index);
+        for start in 0..len as u64 {
+            let end = rng.gen_range(start, len as u64 + 1);
+            reference
+                .check_extract(start, end, |start, end| index.extract(start, end))
+                .unwrap();
+        }
+        check_locate(
+            &reference,
+            &HuffmanFMIndexWithLocate::new(text.clone(), converter.clone(), level),
+        );
+        check_locate(
+            &reference,
+            &DNAFMIndexWithLocate::new(text.clone(), converter.clone(), level),
+        );
+        check_locate(
+            &reference,
+            &SmallFMIndexWithLocate::new(text.clone(), converter.clone(), level),
+        );
+        check_locate(
+            &reference,
+            &RLFMIndexWithLocate::new(text.clone(), converter.clone(), level),
+        );
+        check_locate(
+            &reference,
+            &CSAIndexWithLocate::new(text.clone(), converter.clone(), level),
+        );
+        check_locate(&reference, &RIndex::new(text, converter));
+    }
+}
+
+#[test]
+fn test_reference() {
+    let reference = ReferenceIndex::new("mississippi");
+    assert_eq!(reference, ReferenceIndex::new("mississippi\0"));
+    assert_eq!(reference.len(), 12);
+    assert_eq!(reference.text_len(), 11);
+    assert_eq!(reference.count("ssi"), 2);
+    assert_eq!(reference.locate("i"), vec![1, 4, 7, 10]);
+    assert_eq!(reference.locate("i\0m"), vec![10]);
+    assert_eq!(reference.count(""), 12);
+    assert_eq!(reference.extract(2, 5), b"ssi");
+}
+
+#[test]
+fn test_mismatch() {
+    let converter = RangeConverter::new(b'a', b'z');
+    let reference = ReferenceIndex::new("mississippi");
+    let index = FMIndexWithLocate::new("mississippi", converter.clone(), 2);
+    let other = FMIndexWithLocate::new("missouri", converter, 2);
+
+    assert_eq!(
+        reference.check_count(&other, "ssi"),
+        Err(Mismatch::Len {
+            expected: 12,
+            actual: 9
+        })
+    );
+    assert!(reference
+        .check_extract(0, 4, |start, end| other.extract(start, end))
+        .is_ok());
+    let mismatch = reference
+        .check_extract(3, 6, |start, end| other.extract(start, end))
+        .unwrap_err();
+    assert_eq!(
+        mismatch.to_string(),
+        "[115, 111, 117] was extracted from 3..6, but the reference has [115, 105, 115]"
+    );
+    assert!(reference.check_locate(&index, "ssi").is_ok());
+}
@@ -0,0 +1,73 @@
+use fm_index::{converter::RangeConverter, FMIndexWithLocate, HeapSize, RIndex};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[test]
+fn test_locate() {
+    let text = "mississippi".to_string().into_bytes();
+    let ans = vec![
+        ("m", vec![0]),
+        ("mi", vec![0]),
+        ("i", vec![1, 4, 7, 10]),
+        ("iss", vec![1, 4]),
+        ("ss", vec![2, 5]),
+        ("p", vec![8, 9]),
+        ("ppi", vec![8]),
+        ("z", vec![]),
+        ("pps", vec![]),
+    ];
+    let index = RIndex::new(text, RangeConverter::new(b'a', b'z'));
+    for (pattern, positions) in ans {
+        let search = index.search(pattern);
+        assert_eq!(search.count(), positions.len() as u64);
+        let mut res = search.locate();
+        res.sort();
+        assert_eq!(res, positions, "pattern \"{}\"", pattern);
+    }
+}
+
+#[test]
+fn test_search_refined() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = RIndex::new(text, RangeConverter::new(b'a', b'z'));
+    let mut res = index.search("ssi").search("i").locate();
+    res.sort();
+    assert_eq!(res, vec![1, 4]);
+}
+
+#[test]
+fn test_suffix_array() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = RIndex::new(text.clone(), RangeConverter::new(b'a', b'z'));
+    assert_eq!(
+        index.suffix_array(),
+        vec![11, 10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2]
+    );
+    for (i, &c) in text.iter().enumerate() {
+        assert_eq!(index.char_at(i as u64), c);
+    }
+}
+
+#[test]
+fn test_repetitive_text() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let base = (0..200)
+        .map(|_| rng.gen_range(b'a', b'e'))
+        .collect::<Vec<_>>();
+    let mut text = Vec::new();
+    for _ in 0..50 {
+        let mut copy = base.clone();
+        let i = rng.gen_range(0, copy.len());
+        copy[i] = rng.gen_range(b'a', b'e');
+        text.extend(copy);
+    }
+    let index = RIndex::new(text.clone(), RangeConverter::new(b'a', b'd'));
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'd'), 2);
+    for _ in 0..100 {
+        let s = rng.gen_range(0, text.len() - 8);
+        let pattern = &text[s..s + rng.gen_range(1, 8)];
+        let expected = fm_index.search(pattern).locate();
+        assert_eq!(index.search(pattern).locate(), expected);
+    }
+    assert!(index.heap_size() < fm_index.heap_size());
+}
@@ -1,4 +1,4 @@
-use fm_index::{converter::RangeConverter, RLFMIndex, RLFMIndexWithLocate};
+use fm_index::{converter::RangeConverter, FMIndex, HeapSize, RLFMIndex, RLFMIndexWithLocate};
 
 #[test]
 fn test_count() {
@@ -80,3 +80,60 @@ fn test_iter_forward() {
     let next_seq = search.iter_forward(0).take(10).collect::<Vec<_>>();
     assert_eq!(next_seq, b"sit amet, ".to_owned());
 }
+
+#[test]
+fn test_bwt() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = RLFMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    assert_eq!(index.bwt(), b"ipssm\0pissii".to_owned());
+}
+
+#[test]
+fn test_suffix_array() {
+    let text = "mississippi".to_string().into_bytes();
+    let ans = vec![11, 10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2];
+    let index = RLFMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    assert_eq!(index.suffix_array(), ans);
+}
+
+#[test]
+fn test_extensions() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = RLFMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    assert_eq!(index.search("i").extensions(), vec![(b'p', 1), (b's', 2)]);
+    assert_eq!(index.search("s").extensions(), vec![(b'i', 2), (b's', 2)]);
+}
+
+#[test]
+fn test_left_extensions() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = RLFMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    assert_eq!(
+        index.search("i").left_extensions(),
+        vec![(b'm', 1), (b'p', 1), (b's', 2)]
+    );
+    assert_eq!(
+        index.search("s").left_extensions(),
+        vec![(b'i', 2), (b's', 2)]
+    );
+    assert_eq!(index.search("m").left_extensions(), vec![]);
+}
+
+#[test]
+fn test_char_at() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = RLFMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'z'), 2);
+    for (i, &c) in text.iter().enumerate() {
+        assert_eq!(index.char_at(i as u64), c);
+    }
+}
+
+#[test]
+fn test_repetitive_text() {
+    let text = "Lorem ipsum dolor sit amet, ".repeat(1000).into_bytes();
+    let rlfmi = RLFMIndex::new(text.clone(), RangeConverter::new(b' ', b'~'));
+    let fm_index = FMIndex::new(text, RangeConverter::new(b' ', b'~'));
+    assert_eq!(rlfmi.search("sit amet").count(), 1000);
+    assert_eq!(rlfmi.search("amet, Lorem").count(), 999);
+    assert!(rlfmi.heap_size() * 2 < fm_index.heap_size());
+}
@@ -0,0 +1,569 @@
+#![cfg(all(feature = "std", feature = "serde"))]
+
+use fm_index::converter::{Converter, RangeConverter};
+use fm_index::{
+    ChecksumMismatch, DNAFMIndexWithLocate, FMIndex, FMIndexWithLocate, HeapSize,
+    HuffmanFMIndexWithLocate, IndexInfo, LazySamples, MultiTextFMIndexWithLocate, RLFMIndex,
+    RLFMIndexWithLocate, SearchSnapshot, SlimFMIndex, SplitPaths, TextOrderSampledArray,
+};
+
+use std::io;
+use std::path::PathBuf;
+use xxhash_rust::xxh3::xxh3_64;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fm-index-save-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn test_save_load_fm_index() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    assert_eq!(&bytes[..8], b"FMINDEX\0");
+
+    let loaded = FMIndex::<u8, RangeConverter<u8>>::load(&bytes[..]).unwrap();
+    assert_eq!(loaded.bwt(), index.bwt());
+    assert_eq!(loaded.search("ssi").count(), 2);
+}
+
+#[test]
+fn test_save_load_sequences() {
+    // the BWT is saved as its symbols and its sequence rebuilt on load
+    let text = "mississippi".to_string().into_bytes();
+    let converter = RangeConverter::new(b'a', b'z');
+
+    let index = SlimFMIndex::new(text.clone(), converter.clone());
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    let loaded = SlimFMIndex::<u8, RangeConverter<u8>>::load(&bytes[..]).unwrap();
+    assert_eq!(loaded.search("ssi").count(), 2);
+
+    let index = HuffmanFMIndexWithLocate::new(text.clone(), converter.clone(), 1);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    let loaded = HuffmanFMIndexWithLocate::<u8, RangeConverter<u8>>::load(&bytes[..]).unwrap();
+    assert_eq!(loaded.search("ssi").locate(), index.search("ssi").locate());
+
+    let text = b"DABBADCA".to_vec();
+    let index = DNAFMIndexWithLocate::new(text, RangeConverter::new(b'A', b'D'), 1);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    let loaded = DNAFMIndexWithLocate::<u8, RangeConverter<u8>>::load(&bytes[..]).unwrap();
+    assert_eq!(loaded.search("BA").locate(), index.search("BA").locate());
+}
+
+#[test]
+fn test_save_load_sampling_rate() {
+    let text = "mississippi".to_string().into_bytes();
+    let converter = RangeConverter::new(b'a', b'z');
+    let index = FMIndexWithLocate::<_, _>::with_sampling_rate(text.clone(), converter.clone(), 3);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+
+    let loaded = FMIndexWithLocate::<u8, RangeConverter<u8>>::load(&bytes[..]).unwrap();
+    assert_eq!(loaded, index);
+    let mut positions = loaded.search("ssi").locate();
+    positions.sort();
+    assert_eq!(positions, vec![2, 5]);
+
+    // the rate is the first field of the samples
+    let (start, _) = components(&bytes, "FMIndexWithLocate<u8>")[3];
+    assert_eq!(bytes[start..start + 8], 3u64.to_le_bytes());
+
+    // a rate that is a power of two is saved the same as the sampling level
+    let by_level = FMIndexWithLocate::new(text.clone(), converter.clone(), 2);
+    let by_rate = FMIndexWithLocate::<_, _>::with_sampling_rate(text, converter, 4);
+    let mut level_bytes = Vec::new();
+    by_level.save(&mut level_bytes).unwrap();
+    let mut rate_bytes = Vec::new();
+    by_rate.save(&mut rate_bytes).unwrap();
+    assert_eq!(level_bytes, rate_bytes);
+}
+
+#[test]
+fn test_save_load_with_locate() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = RLFMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+
+    let loaded =
+        RLFMIndexWithLocate::<u8, RangeConverter<u8>>::load(io::Cursor::new(bytes)).unwrap();
+    let mut positions = loaded.search("ssi").locate();
+    positions.sort();
+    assert_eq!(positions, vec![2, 5]);
+    assert_eq!(loaded.suffix_array(), index.suffix_array());
+}
+
+#[test]
+fn test_save_load_multi_text() {
+    let texts = vec![b"banana".to_vec(), b"bandana".to_vec()];
+    let index = MultiTextFMIndexWithLocate::from_texts(texts, RangeConverter::new(b'a', b'z'), 1);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+
+    let loaded = MultiTextFMIndexWithLocate::<u8, RangeConverter<u8>>::load(&bytes[..]).unwrap();
+    assert_eq!(loaded.doc_count(), 2);
+    let mut docs = loaded.search("ana").locate_in_docs();
+    docs.sort();
+    assert_eq!(docs, vec![(0, 1), (0, 3), (1, 4)]);
+}
+
+#[test]
+fn test_load_wrong_kind() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text.clone(), RangeConverter::new(b'a', b'z'));
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+
+    let err = RLFMIndex::<u8, RangeConverter<u8>>::load(&bytes[..])
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "expected a saved RLFMIndex<u8>, found FMIndex<u8>"
+    );
+
+    let err = FMIndexWithLocate::<u16, RangeConverter<u16>>::load(&bytes[..])
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_load_unsupported_version() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    bytes[8..12].copy_from_slice(&99u32.to_le_bytes());
+
+    let err = FMIndex::<u8, RangeConverter<u8>>::load(&bytes[..])
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "unsupported format version 99, expected at most 1"
+    );
+}
+
+#[test]
+fn test_load_invalid() {
+    let err = FMIndex::<u8, RangeConverter<u8>>::load(&b"not an index"[..])
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    bytes.truncate(bytes.len() - 4);
+    let err = FMIndex::<u8, RangeConverter<u8>>::load(&bytes[..])
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+// The length of the header of a saved index of the given kind, up to and
+// including the flags.
+fn prefix_len(kind: &str) -> usize {
+    8 + 4 + 1 + kind.len() + 1
+}
+
+// The offset of the first component of a saved index, after the metadata.
+fn header_len(bytes: &[u8], kind: &str) -> usize {
+    let offset = prefix_len(kind);
+    let len = u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+    offset + 8 + len as usize + 8
+}
+
+#[test]
+fn test_save_components() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 1);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+
+    // the BWT, the character counts, the converter, the suffix array
+    // samples and the k-mer table
+    let components = components(&bytes, "FMIndexWithLocate<u8>");
+    assert_eq!(components.len(), 5);
+    let end = components[4].1;
+    assert_eq!(end + 8, bytes.len());
+}
+
+// The start and end of each component of a saved index, not counting its
+// length and checksum.
+fn components(bytes: &[u8], kind: &str) -> Vec<(usize, usize)> {
+    let mut offset = header_len(bytes, kind);
+    let mut result = Vec::new();
+    while offset < bytes.len() {
+        let len = u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        result.push((offset + 8, offset + 8 + len as usize));
+        offset += 8 + len as usize + 8;
+    }
+    result
+}
+
+#[test]
+fn test_load_truncated_component() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    // claim the BWT is a byte longer than it is
+    let offset = header_len(&bytes, "FMIndex<u8>");
+    let len = u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+    bytes[offset..offset + 8].copy_from_slice(&(len + 1).to_le_bytes());
+
+    let err = FMIndex::<u8, RangeConverter<u8>>::load(&bytes[..])
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_load_checksum_mismatch() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 1);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    let kind = "FMIndexWithLocate<u8>";
+
+    for component in [0, 3] {
+        let mut corrupt = bytes.clone();
+        let (start, end) = components(&bytes, kind)[component];
+        assert!(end > start);
+        corrupt[end - 1] ^= 0x10;
+
+        let err = FMIndexWithLocate::<u8, RangeConverter<u8>>::load(&corrupt[..])
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let mismatch = err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<ChecksumMismatch>())
+            .unwrap();
+        assert_eq!(mismatch, &ChecksumMismatch { component });
+    }
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn test_save_compressed() {
+    let text = "mississippi".repeat(1000).into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    let mut compressed = Vec::new();
+    index.save_compressed(&mut compressed, 3).unwrap();
+    assert!(compressed.len() < bytes.len());
+
+    let loaded = FMIndexWithLocate::<u8, RangeConverter<u8>>::load(&compressed[..]).unwrap();
+    assert_eq!(loaded.suffix_array(), index.suffix_array());
+
+    // the metadata isn't compressed
+    let info = IndexInfo::peek(&compressed[..]).unwrap();
+    assert!(info.compressed());
+    assert_eq!(info.text_len(), 11000);
+}
+
+#[cfg(not(feature = "compression"))]
+#[test]
+fn test_load_compressed_without_feature() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    bytes[prefix_len("FMIndex<u8>") - 1] = 1;
+
+    let err = FMIndex::<u8, RangeConverter<u8>>::load(&bytes[..])
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "the index is compressed, which requires the compression feature"
+    );
+}
+
+#[test]
+fn test_load_file_lazy() {
+    let text = "mississippi".repeat(100).into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    let path = temp_path("lazy");
+    index
+        .save(io::BufWriter::new(std::fs::File::create(&path).unwrap()))
+        .unwrap();
+
+    let loaded =
+        FMIndexWithLocate::<u8, RangeConverter<u8>, LazySamples>::load_file(&path).unwrap();
+    let size = loaded.heap_size();
+    assert!(size < index.heap_size());
+    // counting doesn't need the samples
+    assert_eq!(loaded.search("ssi").count(), 200);
+    assert_eq!(loaded.heap_size(), size);
+
+    let mut positions = loaded.search("ssi").locate();
+    positions.sort();
+    assert_eq!(positions[..2], [2, 5]);
+    assert_eq!(loaded.heap_size(), index.heap_size());
+    assert_eq!(loaded.suffix_array(), index.suffix_array());
+
+    // loading from a reader loads the samples right away
+    let bytes = std::fs::read(&path).unwrap();
+    let loaded =
+        FMIndexWithLocate::<u8, RangeConverter<u8>, LazySamples>::load(&bytes[..]).unwrap();
+    assert_eq!(loaded.heap_size(), index.heap_size());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_load_file_lazy_text_order() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = RLFMIndexWithLocate::<_, _, TextOrderSampledArray>::with_sampling(
+        text,
+        RangeConverter::new(b'a', b'z'),
+        1,
+    );
+    let path = temp_path("lazy-text-order");
+    index.save(std::fs::File::create(&path).unwrap()).unwrap();
+
+    let loaded = RLFMIndexWithLocate::<u8, RangeConverter<u8>, LazySamples<TextOrderSampledArray>>::load_file(&path).unwrap();
+    let mut positions = loaded.search("ssi").locate();
+    positions.sort();
+    assert_eq!(positions, vec![2, 5]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "failed to load the suffix array samples")]
+fn test_load_file_lazy_corrupt() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 1);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    // corrupt the suffix array samples, which aren't read until locate
+    let (_, end) = components(&bytes, "FMIndexWithLocate<u8>")[3];
+    bytes[end - 1] ^= 0x10;
+    let path = temp_path("lazy-corrupt");
+    std::fs::write(&path, &bytes).unwrap();
+
+    let loaded =
+        FMIndexWithLocate::<u8, RangeConverter<u8>, LazySamples>::load_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.search("ssi").count(), 2);
+    loaded.search("ssi").locate();
+}
+
+#[test]
+fn test_peek() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    // peeking doesn't read the components
+    let metadata_end = header_len(&bytes, "FMIndexWithLocate<u8>");
+
+    let info = IndexInfo::peek(&bytes[..metadata_end]).unwrap();
+    assert_eq!(info.format_version(), 1);
+    assert_eq!(info.kind(), "FMIndexWithLocate<u8>");
+    assert!(!info.compressed());
+    assert_eq!(info.character(), "u8");
+    assert_eq!(info.converter(), "RangeConverter");
+    let converter: RangeConverter<u8> = info.converter_config().unwrap();
+    assert_eq!(Converter::<u8>::len(&converter), 27);
+    assert_eq!(info.sampling_level(), Some(2));
+    assert_eq!(info.text_len(), 11);
+    assert_eq!(info.alphabet_size(), 27);
+    assert_eq!(info.crate_version(), env!("CARGO_PKG_VERSION"));
+
+    let mut bytes = Vec::new();
+    FMIndex::new(b"banana".to_vec(), RangeConverter::new(b'a', b'z'))
+        .save(&mut bytes)
+        .unwrap();
+    let info = IndexInfo::peek(&bytes[..]).unwrap();
+    assert_eq!(info.kind(), "FMIndex<u8>");
+    assert_eq!(info.sampling_level(), None);
+    assert_eq!(info.text_len(), 6);
+}
+
+// The XXH3 hash of the components of a saved index. This leaves out the
+// metadata, which records the crate version.
+fn components_digest(bytes: &[u8], kind: &str) -> u64 {
+    xxh3_64(&bytes[header_len(bytes, kind)..])
+}
+
+#[test]
+fn test_save_deterministic() {
+    // The encoding doesn't depend on the platform, so these digests must
+    // be the same on every target, whatever its endianness or pointer width.
+    let text = "mississippi".repeat(10).into_bytes();
+
+    let index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'z'), 2);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    let mut again = Vec::new();
+    index.save(&mut again).unwrap();
+    assert_eq!(bytes, again);
+    assert_eq!(
+        components_digest(&bytes, "FMIndexWithLocate<u8>"),
+        0x2cc93d30c3a3a9fc
+    );
+
+    let mut bytes = Vec::new();
+    RLFMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'z'), 1)
+        .save(&mut bytes)
+        .unwrap();
+    assert_eq!(
+        components_digest(&bytes, "RLFMIndexWithLocate<u8>"),
+        0x417cb30bc6898b94
+    );
+
+    let texts = vec![b"banana".to_vec(), b"bandana".to_vec()];
+    let mut bytes = Vec::new();
+    MultiTextFMIndexWithLocate::from_texts(texts, RangeConverter::new(b'a', b'z'), 1)
+        .save(&mut bytes)
+        .unwrap();
+    assert_eq!(
+        components_digest(&bytes, "MultiTextFMIndexWithLocate<u8>"),
+        0xd04f41a474286e54
+    );
+
+    let text = text.into_iter().map(|c| c as u16 * 100).collect::<Vec<_>>();
+    let mut bytes = Vec::new();
+    FMIndex::new(text, RangeConverter::new(9700u16, 12200))
+        .save(&mut bytes)
+        .unwrap();
+    assert_eq!(
+        components_digest(&bytes, "FMIndex<u16>"),
+        0x54389ebc0ffff8c3
+    );
+}
+
+#[test]
+fn test_search_snapshot() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 1);
+    let search = index.search("ssi");
+    let snapshot = search.snapshot();
+    assert_eq!(snapshot.pattern(), b"ssi");
+
+    let bytes = bincode::serialize(&snapshot).unwrap();
+    let snapshot: SearchSnapshot<u8> = bincode::deserialize(&bytes).unwrap();
+    let resumed = index.resume(&snapshot);
+    assert_eq!(resumed.count(), 2);
+    assert_eq!(resumed.locate(), search.locate());
+    assert_eq!(resumed.search("i").count(), 2);
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn test_resume_out_of_range() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndex::new(text, RangeConverter::new(b'a', b'z'));
+    let long = FMIndex::new(
+        "mississippi".repeat(10).into_bytes(),
+        RangeConverter::new(b'a', b'z'),
+    );
+    index.resume(&long.search("ssi").snapshot());
+}
+
+#[test]
+fn test_serialize_match() {
+    let texts = vec![b"banana".to_vec(), b"bandana".to_vec()];
+    let index = MultiTextFMIndexWithLocate::from_texts(texts, RangeConverter::new(b'a', b'z'), 1);
+    let search = index.search("dan");
+    let m = search.matches().next().unwrap();
+
+    let bytes = bincode::serialize(&m).unwrap();
+    let (position, pattern_len, doc_id): (u64, u64, Option<u64>) =
+        bincode::deserialize(&bytes).unwrap();
+    assert_eq!(position, m.position());
+    assert_eq!(pattern_len, 3);
+    assert_eq!(doc_id, Some(1));
+}
+
+#[test]
+fn test_save_split() {
+    let text = "mississippi".repeat(100).into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    let paths = SplitPaths::new(temp_path("split-core")).samples(temp_path("split-samples"));
+    index.save_split(&paths).unwrap();
+
+    let loaded = FMIndexWithLocate::<u8, RangeConverter<u8>>::load_split(&paths).unwrap();
+    assert_eq!(loaded.suffix_array(), index.suffix_array());
+
+    let lazy =
+        FMIndexWithLocate::<u8, RangeConverter<u8>, LazySamples>::load_split(&paths).unwrap();
+    assert!(lazy.heap_size() < index.heap_size());
+    assert_eq!(lazy.search("ssi").count(), 200);
+    assert_eq!(lazy.search("ssi").locate(), index.search("ssi").locate());
+
+    // the core file has the metadata, and can't be loaded on its own
+    let core = std::fs::read(temp_path("split-core")).unwrap();
+    assert_eq!(IndexInfo::peek(&core[..]).unwrap().text_len(), 1100);
+    let err = FMIndexWithLocate::<u8, RangeConverter<u8>>::load(&core[..])
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // nor without the samples file
+    let err = FMIndexWithLocate::<u8, RangeConverter<u8>>::load_split(&SplitPaths::new(temp_path(
+        "split-core",
+    )))
+    .err()
+    .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "the index was split into the parts core, samples, but files for the parts core were given"
+    );
+
+    // nor with the parts swapped
+    let swapped = SplitPaths::new(temp_path("split-samples")).samples(temp_path("split-core"));
+    let err = FMIndexWithLocate::<u8, RangeConverter<u8>>::load_split(&swapped)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // nor with the samples of another index
+    let other = FMIndexWithLocate::new(b"banana".to_vec(), RangeConverter::new(b'a', b'z'), 2);
+    let other_paths = SplitPaths::new(temp_path("split-other")).samples(temp_path("split-samples"));
+    other.save_split(&other_paths).unwrap();
+    let err = FMIndexWithLocate::<u8, RangeConverter<u8>>::load_split(&paths)
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "the parts belong to different indexes");
+
+    for name in ["split-core", "split-samples", "split-other"] {
+        std::fs::remove_file(temp_path(name)).unwrap();
+    }
+}
+
+#[test]
+fn test_save_split_documents() {
+    let texts = vec![b"banana".to_vec(), b"bandana".to_vec()];
+    let index = MultiTextFMIndexWithLocate::from_texts(texts, RangeConverter::new(b'a', b'z'), 1);
+    let paths = SplitPaths::new(temp_path("split-docs-core"))
+        .samples(temp_path("split-docs-samples"))
+        .documents(temp_path("split-docs-documents"));
+    index.save_split(&paths).unwrap();
+
+    let loaded = MultiTextFMIndexWithLocate::<u8, RangeConverter<u8>>::load_split(&paths).unwrap();
+    let mut docs = loaded.search("ana").locate_in_docs();
+    docs.sort();
+    assert_eq!(docs, vec![(0, 1), (0, 3), (1, 4)]);
+
+    for name in [
+        "split-docs-core",
+        "split-docs-samples",
+        "split-docs-documents",
+    ] {
+        std::fs::remove_file(temp_path(name)).unwrap();
+    }
+}
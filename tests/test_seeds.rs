@@ -0,0 +1,129 @@
+use fm_index::converter::RangeConverter;
+use fm_index::{Anchor, FMIndex, FMIndexWithLocate, RLFMIndexWithLocate};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn naive_locate(text: &[u8], pattern: &[u8]) -> Vec<u64> {
+    text.windows(pattern.len())
+        .enumerate()
+        .filter(|(_, w)| *w == pattern)
+        .map(|(i, _)| i as u64)
+        .collect()
+}
+
+#[test]
+fn test_seeds() {
+    let index = FMIndexWithLocate::new("mississippi", RangeConverter::new(b'a', b'z'), 2);
+    // "ippi" matches up to the end, "m" is a mismatch, "iss" matches
+    let query = b"issmippi";
+    let anchors = index.seeds(query, 2);
+    assert_eq!(
+        anchors
+            .iter()
+            .map(|a| (a.query_offset(), a.query_len(), a.count()))
+            .collect::<Vec<_>>(),
+        vec![(0, 3, 2), (4, 4, 1)]
+    );
+    assert_eq!(anchors[1].query_range(), 4..8);
+
+    let search = index.resume(&anchors[0].snapshot(query));
+    assert_eq!(search.count(), 2);
+    let mut positions = search.locate();
+    positions.sort_unstable();
+    assert_eq!(positions, vec![1, 4]);
+
+    // shorter anchors are left out
+    assert_eq!(index.seeds(query, 4).len(), 1);
+    assert_eq!(index.seeds(query, 9), Vec::<Anchor>::new());
+    assert_eq!(index.seeds(b"", 1), Vec::<Anchor>::new());
+    // characters outside the alphabet are mismatches
+    assert_eq!(
+        index
+            .seeds(b"ssi0ppi", 1)
+            .iter()
+            .map(|a| a.query_range())
+            .collect::<Vec<_>>(),
+        vec![0..3, 4..7]
+    );
+}
+
+#[test]
+fn test_seeds_random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let text = (0..2000)
+        .map(|_| b"acgt"[rng.gen::<usize>() % 4])
+        .collect::<Vec<_>>();
+    let converter = RangeConverter::new(b'a', b't');
+    let index = FMIndex::new(text.clone(), converter.clone());
+    let rl_index = RLFMIndexWithLocate::new(text.clone(), converter, 2);
+    for _ in 0..50 {
+        let start = rng.gen_range(0, text.len() - 100);
+        let mut query = text[start..start + 100].to_vec();
+        for _ in 0..5 {
+            let i = rng.gen_range(0, query.len());
+            query[i] = b"acgt"[rng.gen::<usize>() % 4];
+        }
+        let anchors = index.seeds(&query, 8);
+        assert_eq!(rl_index.seeds(&query, 8), anchors);
+        let mut end = 0;
+        for anchor in &anchors {
+            assert!(anchor.query_len() >= 8);
+            assert!(anchor.query_offset() >= end);
+            end = anchor.query_offset() + anchor.query_len();
+            let pattern = &query[anchor.query_range()];
+            assert_eq!(anchor.count(), naive_locate(&text, pattern).len() as u64);
+            // the anchor can't be extended to the left
+            if anchor.query_offset() > 0 {
+                let longer = &query[anchor.query_offset() - 1..end];
+                assert!(naive_locate(&text, longer).is_empty());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_seed_reads() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let text = (0..5000)
+        .map(|_| b"acgt"[rng.gen::<usize>() % 4])
+        .collect::<Vec<_>>();
+    let index = FMIndex::new(text.clone(), RangeConverter::new(b'a', b't'));
+    let mut reads = (0..300)
+        .map(|_| {
+            let start = rng.gen_range(0, text.len() - 60);
+            let mut read = text[start..start + rng.gen_range(0, 60)].to_vec();
+            if !read.is_empty() {
+                let i = rng.gen_range(0, read.len());
+                read[i] = b"acgtx"[rng.gen::<usize>() % 5];
+            }
+            read
+        })
+        .collect::<Vec<_>>();
+    reads.push(b"acgtacgt".to_vec());
+
+    for (min_len, max_hits) in [(1, u64::MAX), (6, 3), (12, 1)] {
+        let expected = reads
+            .iter()
+            .map(|read| {
+                index
+                    .seeds(read, min_len)
+                    .into_iter()
+                    .filter(|anchor| anchor.count() <= max_hits)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(index.seed_reads(&reads, min_len, max_hits), expected);
+        #[cfg(feature = "rayon")]
+        assert_eq!(
+            index.seed_reads_parallel(&reads, min_len, max_hits),
+            expected
+        );
+    }
+    // a single character is too repetitive
+    assert_eq!(index.seed_reads(&["a"], 1, 100), vec![vec![]]);
+    assert_eq!(index.seed_reads(&["a"], 1, 5000)[0].len(), 1);
+    assert_eq!(
+        index.seed_reads::<Vec<u8>>(&[], 1, 1),
+        Vec::<Vec<Anchor>>::new()
+    );
+}
@@ -0,0 +1,63 @@
+use fm_index::converter::RangeConverter;
+use fm_index::{FMIndex, FMIndexWithLocate, ShardedIndex};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn naive_count(text: &[u8], pattern: &[u8]) -> u64 {
+    text.windows(pattern.len())
+        .filter(|w| *w == pattern)
+        .count() as u64
+}
+
+#[test]
+fn test_locate() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let texts = [300, 1000, 5, 500]
+        .iter()
+        .map(|&n| {
+            (0..n)
+                .map(|_| b"abc"[rng.gen::<usize>() % 3])
+                .collect::<Vec<u8>>()
+        })
+        .collect::<Vec<_>>();
+    let index = ShardedIndex::new(
+        texts
+            .iter()
+            .map(|text| FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'c'), 2))
+            .collect(),
+    );
+    assert_eq!(index.shards().len(), 4);
+    assert_eq!(index.len(), 1809);
+    assert_eq!(index.shard_offset(2), 1302);
+    assert_eq!(index.shard_position(1302), (2, 0));
+    assert_eq!(index.shard_position(1307), (2, 5));
+    assert_eq!(index.shard_position(1309), (3, 1));
+
+    for pattern in ["a", "ab", "cab", "abcabc", "bbbb"] {
+        let pattern = pattern.as_bytes();
+        let expected = texts.iter().map(|t| naive_count(t, pattern)).sum::<u64>();
+        assert_eq!(index.count(pattern), expected);
+        #[cfg(feature = "std")]
+        assert_eq!(index.count_parallel(pattern), expected);
+
+        let positions = index.locate(pattern);
+        assert_eq!(positions.len() as u64, expected);
+        for &pos in &positions {
+            let (s, p) = index.shard_position(pos);
+            assert!(texts[s][p as usize..].starts_with(pattern));
+        }
+        #[cfg(feature = "std")]
+        assert_eq!(index.locate_parallel(pattern), positions);
+    }
+}
+
+#[test]
+fn test_count_only() {
+    let index = ShardedIndex::new(vec![
+        FMIndex::new(b"mississippi".to_vec(), RangeConverter::new(b'a', b'z')),
+        FMIndex::new(b"missouri".to_vec(), RangeConverter::new(b'a', b'z')),
+    ]);
+    assert_eq!(index.count("iss"), 3);
+    #[cfg(feature = "std")]
+    assert_eq!(index.count_parallel("mis"), 2);
+}
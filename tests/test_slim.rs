@@ -0,0 +1,47 @@
+use fm_index::{converter::RangeConverter, FMIndex, HeapSize, SlimFMIndex};
+
+const TEXT: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat.";
+
+#[test]
+fn test_count() {
+    let text = TEXT.repeat(200).into_bytes();
+    let index = SlimFMIndex::new(text.clone(), RangeConverter::new(b' ', b'~'));
+    let fm_index = FMIndex::new(text, RangeConverter::new(b' ', b'~'));
+    for pattern in ["a", "ut", "dolor", "m ", "ex ea", "xyz", ""] {
+        assert_eq!(
+            index.search(pattern).count(),
+            fm_index.search(pattern).count(),
+            "pattern \"{}\"",
+            pattern
+        );
+        assert_eq!(
+            index.search(pattern).extensions(),
+            fm_index.search(pattern).extensions()
+        );
+        assert_eq!(
+            index.search(pattern).left_extensions(),
+            fm_index.search(pattern).left_extensions()
+        );
+    }
+    assert_eq!(index.bwt(), fm_index.bwt());
+    assert!(index.heap_size() < fm_index.heap_size());
+}
+
+#[test]
+fn test_iter() {
+    let text = TEXT.to_string().into_bytes();
+    let index = SlimFMIndex::new(text.clone(), RangeConverter::new(b' ', b'~'));
+    let fm_index = FMIndex::new(text, RangeConverter::new(b' ', b'~'));
+    let search = index.search("dolor");
+    let fm_search = fm_index.search("dolor");
+    for i in 0..search.count() {
+        assert_eq!(
+            search.iter_forward(i).take(20).collect::<Vec<_>>(),
+            fm_search.iter_forward(i).take(20).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            search.iter_backward(i).take(20).collect::<Vec<_>>(),
+            fm_search.iter_backward(i).take(20).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use fm_index::converter::RangeConverter;
+use fm_index::{FMIndex, FMIndexWithLocate, HeapSize, SmallFMIndex, SmallFMIndexWithLocate};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn random_text(rng: &mut StdRng, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|_| b"ABCDEFGH"[rng.gen::<usize>() % 8])
+        .collect()
+}
+
+#[test]
+fn test_count() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let text = random_text(&mut rng, 100000);
+    let index = SmallFMIndex::new(text.clone(), RangeConverter::new(b'A', b'H'));
+    let fm_index = FMIndex::new(text.clone(), RangeConverter::new(b'A', b'H'));
+    for _ in 0..200 {
+        let start = rng.gen::<usize>() % text.len();
+        let end = (start + 1 + rng.gen::<usize>() % 12).min(text.len());
+        let pattern = &text[start..end];
+        assert_eq!(
+            index.search(pattern).count(),
+            fm_index.search(pattern).count()
+        );
+        assert_eq!(
+            index.search(pattern).extensions(),
+            fm_index.search(pattern).extensions()
+        );
+    }
+    assert_eq!(index.bwt(), fm_index.bwt());
+    assert!(index.heap_size() > 0);
+}
+
+#[test]
+fn test_locate() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let text = random_text(&mut rng, 5000);
+    let index = SmallFMIndexWithLocate::new(text.clone(), RangeConverter::new(b'A', b'H'), 2);
+    let fm_index = FMIndexWithLocate::new(text.clone(), RangeConverter::new(b'A', b'H'), 2);
+    for pattern in ["A", "CAB", "HGA", "ABCDE"] {
+        let search = index.search(pattern);
+        let fm_search = fm_index.search(pattern);
+        assert_eq!(search.locate(), fm_search.locate());
+        for i in 0..search.count().min(10) {
+            assert_eq!(
+                search.iter_forward(i).take(10).collect::<Vec<_>>(),
+                fm_search.iter_forward(i).take(10).collect::<Vec<_>>()
+            );
+        }
+    }
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+#[test]
+fn test_save_load() {
+    let text = b"ABHGCHEABFD".to_vec();
+    let index = SmallFMIndexWithLocate::new(text, RangeConverter::new(b'A', b'H'), 1);
+    let mut bytes = Vec::new();
+    index.save(&mut bytes).unwrap();
+    let loaded =
+        SmallFMIndexWithLocate::<u8, RangeConverter<u8>>::load(&mut bytes.as_slice()).unwrap();
+    assert_eq!(loaded.search("AB").locate(), index.search("AB").locate());
+}
+
+#[test]
+#[should_panic]
+fn test_large_alphabet() {
+    SmallFMIndex::new(b"ACGT".to_vec(), RangeConverter::new(b'A', b'T'));
+}
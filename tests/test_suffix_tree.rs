@@ -0,0 +1,133 @@
+use fm_index::converter::RangeConverter;
+use fm_index::{CSAIndexWithLocate, FMIndexWithLocate, Node, SuffixTree};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn label(tree: &SuffixTree<'_, u8>, v: Node) -> Vec<u8> {
+    (0..tree.depth(v)).map(|i| tree.letter(v, i)).collect()
+}
+
+fn descend(tree: &SuffixTree<'_, u8>, pattern: &[u8]) -> Option<Node> {
+    let mut v = tree.root();
+    let mut i = 0;
+    while (tree.depth(v) as usize) < pattern.len() {
+        v = tree.child(v, pattern[tree.depth(v) as usize])?;
+        while i < pattern.len().min(tree.depth(v) as usize) {
+            if tree.letter(v, i as u64) != pattern[i] {
+                return None;
+            }
+            i += 1;
+        }
+    }
+    Some(v)
+}
+
+#[test]
+fn test_small() {
+    let text = "mississippi".to_string().into_bytes();
+    let index = FMIndexWithLocate::new(text, RangeConverter::new(b'a', b'z'), 2);
+    let tree = index.suffix_tree();
+
+    let root = tree.root();
+    assert_eq!(tree.range(root), (0, 12));
+    assert_eq!(tree.depth(root), 0);
+    assert_eq!(tree.parent(root), None);
+    assert_eq!(tree.suffix_link(root), None);
+    // 12 leaves and the internal nodes i, issi, p, s, si, ssi
+    assert_eq!(tree.node_count(), 19);
+
+    let letters = tree
+        .children(root)
+        .map(|v| tree.letter(v, 0))
+        .collect::<Vec<_>>();
+    assert_eq!(letters, b"\0imps".to_vec());
+
+    let ssi = descend(&tree, b"ssi").unwrap();
+    assert!(!tree.is_leaf(ssi));
+    assert_eq!(tree.depth(ssi), 3);
+    assert_eq!(tree.count(ssi), 2);
+    assert_eq!(label(&tree, tree.parent(ssi).unwrap()), b"s");
+
+    let si = tree.suffix_link(ssi).unwrap();
+    assert_eq!(label(&tree, si), b"si");
+    let i = tree.suffix_link(si).unwrap();
+    assert_eq!(label(&tree, i), b"i");
+    assert_eq!(tree.suffix_link(i), Some(root));
+
+    let leaf = descend(&tree, b"ppi").unwrap();
+    assert!(tree.is_leaf(leaf));
+    assert_eq!(label(&tree, leaf), b"ppi\0");
+    assert_eq!(label(&tree, tree.suffix_link(leaf).unwrap()), b"pi\0");
+    assert_eq!(
+        tree.lca(leaf, descend(&tree, b"pi").unwrap()),
+        descend(&tree, b"p").unwrap()
+    );
+
+    assert_eq!(tree.child(root, b'z'), None);
+    assert_eq!(descend(&tree, b"sp"), None);
+}
+
+#[test]
+fn test_random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let text = (0..500)
+        .map(|_| b"ab"[rng.gen::<usize>() % 2])
+        .collect::<Vec<_>>();
+    let index = CSAIndexWithLocate::new(text.clone(), RangeConverter::new(b'a', b'b'), 2);
+    let tree = index.suffix_tree();
+    let suffix_array = index.suffix_array();
+
+    let mut terminated = text.clone();
+    terminated.push(0);
+    let suffix = |i: u64| &terminated[suffix_array[i as usize] as usize..];
+
+    // every node: its path label is the longest common prefix of its suffixes
+    let mut stack = vec![tree.root()];
+    let mut nodes = 0;
+    while let Some(v) = stack.pop() {
+        nodes += 1;
+        let (s, e) = tree.range(v);
+        let depth = tree.depth(v) as usize;
+        let first = suffix(s);
+        let last = suffix(e - 1);
+        let lcp = first.iter().zip(last).take_while(|(a, b)| a == b).count();
+        if tree.is_leaf(v) {
+            assert_eq!(depth, first.len());
+        } else {
+            assert_eq!(depth, lcp);
+        }
+        assert_eq!(label(&tree, v), first[..depth].to_vec());
+
+        if let Some(u) = tree.suffix_link(v) {
+            assert_eq!(label(&tree, u), first[1..depth].to_vec());
+        }
+        for u in tree.children(v) {
+            assert_eq!(tree.parent(u), Some(v));
+            assert!(tree.depth(u) > tree.depth(v));
+            stack.push(u);
+        }
+    }
+    assert_eq!(nodes, tree.node_count());
+
+    for _ in 0..200 {
+        let i = rng.gen::<u64>() % index.len();
+        let j = rng.gen::<u64>() % index.len();
+        let v = tree.lca(tree.leaf(i), tree.leaf(j));
+        let lcp = suffix(i)
+            .iter()
+            .zip(suffix(j))
+            .take_while(|(a, b)| a == b)
+            .count();
+        if i == j {
+            assert_eq!(v, tree.leaf(i));
+        } else {
+            assert_eq!(tree.depth(v) as usize, lcp);
+        }
+
+        let start = rng.gen::<usize>() % text.len();
+        let end = (start + 1 + rng.gen::<usize>() % 12).min(text.len());
+        let pattern = &text[start..end];
+        let v = descend(&tree, pattern).unwrap();
+        assert_eq!(tree.count(v), index.search(pattern).count());
+    }
+}
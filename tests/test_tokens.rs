@@ -0,0 +1,66 @@
+use fm_index::converter::DenseConverter;
+use fm_index::FMIndexWithLocate;
+
+// A toy tokenizer with sparse token ids, as produced by real tokenizers.
+const VOCABULARY: [(&str, u32); 8] = [
+    ("the", 464),
+    ("cat", 9246),
+    ("sat", 3332),
+    ("on", 319),
+    ("mat", 2603),
+    ("dog", 3290),
+    ("ran", 4966),
+    (".", 13),
+];
+
+fn tokenize(text: &str) -> Vec<u32> {
+    text.split(' ')
+        .map(|word| VOCABULARY.iter().find(|(w, _)| *w == word).unwrap().1)
+        .collect()
+}
+
+fn detokenize(tokens: &[u32]) -> String {
+    tokens
+        .iter()
+        .map(|&t| VOCABULARY.iter().find(|(_, id)| *id == t).unwrap().0)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn test_extract() {
+    let text = tokenize("the cat sat on the mat . the dog ran on the mat .");
+    let index = FMIndexWithLocate::new(text.clone(), DenseConverter::from_text(&text), 2);
+
+    let search = index.search(tokenize("on the mat"));
+    assert_eq!(search.count(), 2);
+    let mut positions = search.locate();
+    positions.sort();
+    assert_eq!(positions, vec![3, 10]);
+
+    assert_eq!(index.extract(0, text.len() as u64), text);
+    assert_eq!(index.extract(7, 10), tokenize("the dog ran"));
+    assert_eq!(index.extract(4, 4), Vec::<u32>::new());
+    assert_eq!(index.extract_with(1, 3, detokenize), "cat sat");
+
+    let mut snippets = search
+        .matches()
+        .map(|m| m.snippet(2, 1, detokenize))
+        .collect::<Vec<_>>();
+    snippets.sort();
+    assert_eq!(
+        snippets,
+        vec!["cat sat on the mat .", "dog ran on the mat ."]
+    );
+    // the context stops at the start and the end of the text
+    let search = index.search(tokenize("the cat"));
+    let m = search.matches().next().unwrap();
+    assert_eq!(m.context(3, 1), tokenize("the cat sat"));
+    let search = index.search(tokenize("mat ."));
+    let mut contexts = search
+        .matches()
+        .map(|m| detokenize(&m.context(0, 5)))
+        .collect::<Vec<_>>();
+    contexts.sort();
+    assert_eq!(contexts, vec!["mat .", "mat . the dog ran on the"]);
+}
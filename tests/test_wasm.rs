@@ -0,0 +1,35 @@
+#![cfg(feature = "wasm")]
+
+// The bindings are plain Rust functions on other targets, so this checks
+// them natively. Errors are JavaScript values, which only exist in wasm, so
+// only the successful calls are checked.
+
+use fm_index::converter::IdConverter;
+use fm_index::wasm::WasmFMIndex;
+use fm_index::FMIndexWithLocate;
+
+#[test]
+fn test_wasm() {
+    let index = WasmFMIndex::new(b"mississippi", 2).unwrap();
+    assert_eq!(index.length(), 11.0);
+    assert_eq!(index.count(b"ss"), 2.0);
+    assert_eq!(index.count(b"z"), 0.0);
+    let mut positions = index.locate(b"i");
+    positions.sort_by(f64::total_cmp);
+    assert_eq!(positions, vec![1.0, 4.0, 7.0, 10.0]);
+    assert_eq!(index.extract(2.0, 6.0), b"ssis");
+    assert_eq!(index.extract(9.0, 100.0), b"pi");
+    assert_eq!(index.extract(50.0, 100.0), b"");
+
+    let bytes = index.save().unwrap();
+    let loaded = WasmFMIndex::load(&bytes).unwrap();
+    assert_eq!(loaded.count(b"ssi"), 2.0);
+
+    // an index saved from Rust loads in the bindings
+    let mut bytes = Vec::new();
+    FMIndexWithLocate::new("banana", IdConverter::new::<u8>(), 1)
+        .save(&mut bytes)
+        .unwrap();
+    let loaded = WasmFMIndex::load(&bytes).unwrap();
+    assert_eq!(loaded.locate(b"nan"), vec![2.0]);
+}